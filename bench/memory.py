@@ -0,0 +1,70 @@
+"""
+Peak memory of reading a large body with `bytes()` versus `read_buffer()`.
+
+Each mode runs in a fresh process so its peak RSS is measured in isolation.
+
+    python memory.py [size_in_mb]
+"""
+
+import multiprocessing
+import resource
+import sys
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import rnet
+
+CHUNK = b"\0" * (1024 * 1024)
+
+
+class Handler(BaseHTTPRequestHandler):
+    def do_GET(self):
+        size = int(self.path.strip("/"))
+        self.send_response(200)
+        self.send_header("Content-Length", str(size * len(CHUNK)))
+        self.end_headers()
+        for _ in range(size):
+            self.wfile.write(CHUNK)
+
+    def log_message(self, *args):
+        pass
+
+
+def peak_rss_mb():
+    # `ru_maxrss` is in kilobytes on Linux and bytes on macOS.
+    rss = resource.getrusage(resource.RUSAGE_SELF).ru_maxrss
+    return rss / (1024 * 1024 if sys.platform == "darwin" else 1024)
+
+
+def run(mode, url, result):
+    client = rnet.BlockingClient()
+    baseline = peak_rss_mb()
+    response = client.get(url)
+    if mode == "bytes":
+        body = response.bytes()
+        total = len(body)
+    else:
+        body = response.read_buffer()
+        total = memoryview(body).nbytes
+    result.put((mode, total, peak_rss_mb() - baseline))
+
+
+def main():
+    size = int(sys.argv[1]) if len(sys.argv) > 1 else 100
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    url = f"http://127.0.0.1:{server.server_address[1]}/{size}"
+
+    result = multiprocessing.Queue()
+    for mode in ("bytes", "read_buffer"):
+        process = multiprocessing.Process(target=run, args=(mode, url, result))
+        process.start()
+        mode, total, peak = result.get()
+        process.join()
+        print(f"{mode:>12}: {total / 2**20:.0f} MB body, peak RSS +{peak:.0f} MB")
+
+    server.shutdown()
+
+
+if __name__ == "__main__":
+    main()
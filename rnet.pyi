@@ -7,6 +7,7 @@
 
 import datetime
 import ipaddress
+import os
 import typing
 from enum import Enum, auto
 from typing import (
@@ -19,43 +20,191 @@ from typing import (
     TypedDict,
     Unpack,
     NotRequired,
+    Literal,
+    Callable,
+    Sequence,
 )
 from pathlib import Path
 
+IpAddress = Union[str, ipaddress.IPv4Address, ipaddress.IPv6Address]
+LocalAddress = Union[IpAddress, Tuple[Optional[IpAddress], Optional[IpAddress]]]
+r"""
+A local IP address to bind to, or a pair of an IPv4 and an IPv6 address of which the
+one matching the family of the resolved target is used.
+"""
+Timeout = Union[float, Tuple[Optional[float], Optional[float]]]
+r"""
+A total timeout in seconds, or a `(connect, read)` pair of timeouts in seconds, where
+either may be `None`.
+"""
+ClientCert = Union[str, os.PathLike[str], Tuple[Union[str, os.PathLike[str]], Union[str, os.PathLike[str]]]]
+r"""
+The path of a PEM file with a client certificate and its PKCS #8 key, or a
+`(cert, key)` pair of paths.
+"""
+
+class TlsConfig(TypedDict, closed=True):
+    r"""
+    Low-level TLS settings for `Client(tls_config=...)`.
+
+    Curve names are `"X25519"`, `"X25519MLKEM768"`, `"X25519Kyber768Draft00"`, `"P-256"`,
+    `"P-384"`, `"P-521"`, `"P-224"`, `"ffdhe2048"` and `"ffdhe3072"`. Signature algorithms and
+    ciphers use BoringSSL names, as a list or a colon-separated string.
+    """
+
+    curves: NotRequired[List[str]]
+    sigalgs: NotRequired[Union[str, List[str]]]
+    cipher_list: NotRequired[Union[str, List[str]]]
+    cert_compression_algorithm: NotRequired[List[Literal["brotli", "zlib", "zstd"]]]
+    delegated_credentials: NotRequired[str]
+    grease_enabled: NotRequired[bool]
+    permute_extensions: NotRequired[bool]
+    enable_ech_grease: NotRequired[bool]
+    session_ticket: NotRequired[bool]
+    pre_shared_key: NotRequired[bool]
+    psk_skip_session_ticket: NotRequired[bool]
+    psk_dhe_ke: NotRequired[bool]
+    renegotiation: NotRequired[bool]
+    enable_ocsp_stapling: NotRequired[bool]
+    enable_signed_cert_timestamps: NotRequired[bool]
+    record_size_limit: NotRequired[int]
+    key_shares_limit: NotRequired[int]
+
+class ClientParams(TypedDict, closed=True):
+    impersonate: NotRequired[Union[Impersonate, ImpersonateOption, Literal["random"]]]
+    user_agent: NotRequired[str]
+    default_headers: NotRequired[Union[Dict[str, str], HeaderMap, List[Tuple[str, str]]]]
+    headers_order: NotRequired[List[str]]
+    referer: NotRequired[bool]
+    allow_redirects: NotRequired[bool]
+    max_redirects: NotRequired[int]
+    cookie_store: NotRequired[bool]
+    cookie_provider: NotRequired[CookieJar]
+    lookup_ip_strategy: NotRequired[LookupIpStrategy]
+    timeout: NotRequired[Timeout]
+    connect_timeout: NotRequired[float]
+    read_timeout: NotRequired[float]
+    no_keepalive: NotRequired[bool]
+    tcp_keepalive: NotRequired[int]
+    pool_idle_timeout: NotRequired[float]
+    pool_max_idle_per_host: NotRequired[int]
+    pool_max_size: NotRequired[int]
+    http1_only: NotRequired[bool]
+    http2_only: NotRequired[bool]
+    https_only: NotRequired[bool]
+    preserve_header_case: NotRequired[bool]
+    tcp_nodelay: NotRequired[bool]
+    http2_max_retry_count: NotRequired[int]
+    verify: NotRequired[Union[bool, str, os.PathLike[str]]]
+    cert: NotRequired[ClientCert]
+    tls_info: NotRequired[bool]
+    min_tls_version: NotRequired[TlsVersion]
+    max_tls_version: NotRequired[TlsVersion]
+    tls_config: NotRequired[TlsConfig]
+    cache: NotRequired[Union[bool, Cache]]
+    cache_dir: NotRequired[Union[str, os.PathLike[str]]]
+    transport: NotRequired[MockTransport]
+    record: NotRequired[Union[str, os.PathLike[str]]]
+    replay: NotRequired[Union[str, os.PathLike[str]]]
+    websocket_defaults: NotRequired[WebSocketParams]
+    request_id_header: NotRequired[str]
+    max_response_size: NotRequired[int]
+    max_header_size: NotRequired[int]
+    auth: NotRequired[OAuth2ClientCredentials]
+    no_proxy: NotRequired[bool]
+    trust_env: NotRequired[bool]
+    proxies: NotRequired[List[Proxy]]
+    local_address: NotRequired[LocalAddress]
+    interface: NotRequired[str]
+    gzip: NotRequired[bool]
+    brotli: NotRequired[bool]
+    deflate: NotRequired[bool]
+    zstd: NotRequired[bool]
+
+class UpdateClientParams(TypedDict, closed=True):
+    impersonate: NotRequired[Union[Impersonate, ImpersonateOption, Literal["random"]]]
+    headers: NotRequired[Union[Dict[str, str], HeaderMap, List[Tuple[str, str]]]]
+    headers_order: NotRequired[List[str]]
+    proxies: NotRequired[List[Proxy]]
+    local_address: NotRequired[LocalAddress]
+    interface: NotRequired[str]
+    allow_redirects: NotRequired[bool]
+    max_redirects: NotRequired[int]
+    cookie_store: NotRequired[bool]
+    cookie_provider: NotRequired[CookieJar]
+    timeout: NotRequired[Timeout]
+    connect_timeout: NotRequired[float]
+    read_timeout: NotRequired[float]
+    pool_idle_timeout: NotRequired[float]
+    pool_max_idle_per_host: NotRequired[int]
+    pool_max_size: NotRequired[int]
+    gzip: NotRequired[bool]
+    brotli: NotRequired[bool]
+    deflate: NotRequired[bool]
+    zstd: NotRequired[bool]
+
 class RequestParams(TypedDict, closed=True):
+    impersonate: NotRequired[Union[Impersonate, ImpersonateOption, Literal["random"]]]
     proxy: NotRequired[Union[str, Proxy]]
-    local_address: NotRequired[Union[ipaddress.IPv4Address, ipaddress.IPv6Address]]
+    local_address: NotRequired[LocalAddress]
     interface: NotRequired[str]
-    timeout: NotRequired[int]
-    read_timeout: NotRequired[int]
+    timeout: NotRequired[Timeout]
+    read_timeout: NotRequired[float]
     version: NotRequired[Version]
-    headers: NotRequired[Union[Dict[str, str], HeaderMap]]
+    version_fallback: NotRequired[bool]
+    decompress: NotRequired[bool]
+    range: NotRequired[Tuple[int, Optional[int]]]
+    headers: NotRequired[Union[Dict[str, str], HeaderMap, List[Tuple[str, str]]]]
+    headers_order: NotRequired[List[str]]
     cookies: NotRequired[Dict[str, str]]
     allow_redirects: NotRequired[bool]
     max_redirects: NotRequired[int]
+    follow_meta_refresh: NotRequired[bool]
+    max_response_size: NotRequired[int]
+    prefetch_body: NotRequired[bool]
+    https_only: NotRequired[bool]
+    allow_insecure_redirects: NotRequired[bool]
     auth: NotRequired[str]
     bearer_auth: NotRequired[str]
     basic_auth: NotRequired[Tuple[str, Optional[str]]]
-    query: NotRequired[List[Tuple[str, str]]]
-    form: NotRequired[List[Tuple[str, str]]]
+    query: NotRequired[Union[Dict[str, str], List[Tuple[str, str]]]]
+    params: NotRequired[Union[Dict[str, str], List[Tuple[str, str]]]]
+    form: NotRequired[Union[Dict[str, str], List[Tuple[str, str]]]]
     json: NotRequired[Dict[str, Any]]
     body: NotRequired[
         Union[
             str,
             bytes,
+            os.PathLike[str],
+            typing.BinaryIO,
+            typing.AsyncGenerator[bytes, str],
+            typing.Generator[bytes, str],
+        ]
+    ]
+    data: NotRequired[
+        Union[
+            Dict[str, str],
+            List[Tuple[str, str]],
+            str,
+            bytes,
+            os.PathLike[str],
+            typing.BinaryIO,
             typing.AsyncGenerator[bytes, str],
             typing.Generator[bytes, str],
         ]
     ]
     multipart: NotRequired[Multipart]
+    on_upload_progress: NotRequired[Callable[[int, Optional[int]], Any]]
 
 class WebSocketParams(TypedDict, closed=True):
     proxy: NotRequired[Union[str, Proxy]]
-    local_address: NotRequired[Union[str, ipaddress.IPv4Address, ipaddress.IPv6Address]]
+    local_address: NotRequired[LocalAddress]
     interface: NotRequired[str]
-    headers: NotRequired[Dict[str, bytes]]
+    headers: NotRequired[Union[Dict[str, str], HeaderMap, List[Tuple[str, str]]]]
     cookies: NotRequired[Dict[str, str]]
-    protocols: NotRequired[List[str]]
+    protocols: NotRequired[Union[str, typing.Iterable[str]]]
+    origin: NotRequired[str]
+    user_agent: NotRequired[str]
     use_http2: NotRequired[bool]
     auth: NotRequired[str]
     bearer_auth: NotRequired[str]
@@ -67,15 +216,20 @@ class WebSocketParams(TypedDict, closed=True):
     max_message_size: NotRequired[int]
     max_frame_size: NotRequired[int]
     accept_unmasked_frames: NotRequired[bool]
+    max_recv_queue: NotRequired[int]
+    auto_reconnect: NotRequired[bool]
+    reconnect_max_attempts: NotRequired[int]
+    reconnect_backoff: NotRequired[float]
+    on_reconnect: NotRequired[Callable[[int], Any]]
+    reconnect_queue_sends: NotRequired[bool]
 
 class ProxyParams(TypedDict, total=False, closed=True):
-    scheme: NotRequired[str]
-    host: NotRequired[str]
-    port: NotRequired[int]
     username: NotRequired[str]
     password: NotRequired[str]
-    rdns: NotRequired[bool]
-    headers: NotRequired[Dict[str, str]]
+    custom_http_auth: NotRequired[str]
+    custom_http_headers: NotRequired[Union[Dict[str, str], HeaderMap, List[Tuple[str, str]]]]
+    exclusion: NotRequired[str]
+    matches: NotRequired[Union[str, List[str]]]
 
 class BlockingClient:
     r"""
@@ -92,46 +246,14 @@ class BlockingClient:
     """
     def __new__(
         cls,
-        impersonate: Optional[Union[Impersonate, ImpersonateOption]] = None,
-        user_agent: Optional[str] = None,
-        default_headers: Optional[Dict[str, bytes]] = None,
-        headers_order: Optional[List[str]] = None,
-        referer: Optional[bool] = None,
-        allow_redirects: Optional[bool] = None,
-        max_redirects: Optional[int] = None,
-        cookie_store: Optional[bool] = None,
-        lookup_ip_strategy: Optional[LookupIpStrategy] = None,
-        timeout: Optional[int] = None,
-        connect_timeout: Optional[int] = None,
-        read_timeout: Optional[int] = None,
-        no_keepalive: Optional[bool] = None,
-        tcp_keepalive: Optional[int] = None,
-        pool_idle_timeout: Optional[int] = None,
-        pool_max_idle_per_host: Optional[int] = None,
-        pool_max_size: Optional[int] = None,
-        http1_only: Optional[bool] = None,
-        http2_only: Optional[bool] = None,
-        https_only: Optional[bool] = None,
-        tcp_nodelay: Optional[bool] = None,
-        http2_max_retry_count: Optional[int] = None,
-        verify: Optional[Union[bool, Path]] = None,
-        tls_info: Optional[bool] = None,
-        min_tls_version: Optional[TlsVersion] = None,
-        max_tls_version: Optional[TlsVersion] = None,
-        no_proxy: Optional[bool] = None,
-        proxies: Optional[List[Proxy]] = None,
-        local_address: Optional[
-            Union[str, ipaddress.IPv4Address, ipaddress.IPv6Address]
-        ] = None,
-        interface: Optional[str] = None,
-        gzip: Optional[bool] = None,
-        brotli: Optional[bool] = None,
-        deflate: Optional[bool] = None,
-        zstd: Optional[bool] = None,
+        **kwargs: Unpack[ClientParams],
     ) -> BlockingClient:
         r"""
         Creates a new BlockingClient instance.
 
+        Unknown keyword arguments raise `TypeError`; set the environment variable
+        `RNET_ALLOW_UNKNOWN_KWARGS=1` to ignore them instead.
+
         # Arguments
 
         * `**kwargs` - Optional request parameters as a dictionary.
@@ -153,7 +275,7 @@ class BlockingClient:
         ```
         """
 
-    def get_cookies(self, url: str) -> Optional[bytes]:
+    def get_cookies(self, url: Union[str, Url]) -> Optional[bytes]:
         r"""
         Returns the cookies for the given URL.
 
@@ -162,7 +284,7 @@ class BlockingClient:
         * `url` - The URL to get the cookies for.
         """
 
-    def set_cookie(self, url: str, cookie: Cookie) -> None:
+    def set_cookie(self, url: Union[str, Url], cookie: Cookie) -> None:
         r"""
         Sets the cookies for the given URL.
 
@@ -180,7 +302,7 @@ class BlockingClient:
         ```
         """
 
-    def remove_cookie(self, url: str, name: str) -> None:
+    def remove_cookie(self, url: Union[str, Url], name: str) -> None:
         r"""
         Removes the cookie with the given name for the given URL.
 
@@ -202,20 +324,44 @@ class BlockingClient:
         Clears the cookies for the given URL.
         """
 
+    @property
+    def cookie_jar(self) -> Optional[CookieJar]:
+        r"""
+        The jar the client stores cookies in, or `None` if it doesn't store any.
+
+        Assigning a `CookieJar` makes the client store cookies in it, and assigning `None`
+        stops it from storing cookies. Either one rebuilds the client like `update`.
+        """
+
+    @cookie_jar.setter
+    def cookie_jar(self, jar: Optional[CookieJar]) -> None: ...
+
+    def token_info(self) -> Optional[Dict[str, Any]]:
+        r"""
+        Returns the OAuth2 token the client has cached, as a dict with its `access_token`,
+        `token_type`, `scope` and the seconds it `expires_in` (`None` if the server didn't
+        say), or `None` if the client has no `auth` or hasn't fetched a token yet.
+        """
+
+    def mount(
+        self,
+        matcher: Union[str, Callable[[str, str], bool]],
+        handler: Callable[[str, str, HeaderMap, bytes], Tuple[int, Union[Dict[str, str], HeaderMap, List[Tuple[str, str]]], Union[bytes, str]]],
+    ) -> None:
+        r"""
+        Answers the requests `matcher` matches with `handler` instead of sending them. See
+        `Client.mount`.
+        """
+
     def update(
         self,
-        impersonate: Optional[Union[Impersonate, ImpersonateOption]] = None,
-        headers: Optional[Union[Dict[str, str], HeaderMap]] = None,
-        headers_order: Optional[List[str]] = None,
-        proxies: Optional[List[Proxy]] = None,
-        local_address: Optional[
-            Union[ipaddress.IPv4Address, ipaddress.IPv6Address]
-        ] = None,
-        interface: Optional[str] = None,
+        **kwargs: Unpack[UpdateClientParams],
     ) -> None:
         r"""
         Updates the client with the given parameters.
 
+        Takes the same options as `Client.update`.
+
         # Examples
 
         ```python
@@ -230,10 +376,34 @@ class BlockingClient:
         ```
         """
 
+    def __repr__(self) -> str:
+        r"""
+        Summarizes the client's user agent, impersonation, cookie, redirect and
+        compression settings and how many proxies it has. Proxy URLs and their
+        credentials are left out.
+        """
+
+    def close(self) -> None:
+        r"""
+        Closes the client, dropping its connection pool.
+
+        Requests already in flight complete normally; any further use of the
+        client raises a `RuntimeError`.
+
+        # Examples
+
+        ```python
+        import rnet
+
+        client = rnet.BlockingClient()
+        client.close()
+        ```
+        """
+
     def request(
         self,
-        method: Method,
-        url: str,
+        method: Union[Method, str],
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> BlockingResponse:
         r"""
@@ -255,12 +425,139 @@ class BlockingClient:
         ```
         """
 
+    def head_ok(
+        self,
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> bool:
+        r"""
+        Checks whether the URL exists with a `HEAD` request.
+        See `Client.head_ok`.
+        """
+
+    def content_length(
+        self,
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> Optional[int]:
+        r"""
+        Returns the size of the body at the URL without downloading it.
+        See `Client.content_length`.
+        """
+
+    def download(
+        self,
+        url: Union[str, Url],
+        path: Union[str, os.PathLike[str]],
+        resume: bool = True,
+        **kwargs: Unpack[RequestParams],
+    ) -> int:
+        r"""
+        Downloads the URL into `path` and returns the size of the file.
+
+        With `resume`, an existing file is kept and only the remaining bytes are requested
+        with a `Range` header. If the server ignores the range, the file is replaced with
+        the full body.
+
+        # Examples
+
+        ```python
+        import rnet
+
+        client = rnet.BlockingClient()
+        size = client.download("https://example.com/large.iso", "large.iso")
+        ```
+        """
+
+    def map(
+        self,
+        requests: List[
+            Union[
+                Tuple[Union[Method, str], str],
+                Tuple[Union[Method, str], str, RequestParams],
+            ]
+        ],
+        concurrency: int = 50,
+        return_exceptions: bool = False,
+        on_progress: Optional[Callable[[int, int], Any]] = None,
+    ) -> List[Union[BlockingResponse, Exception]]:
+        r"""
+        Sends a batch of requests with at most `concurrency` in flight and returns the
+        responses in the order of `requests`.
+
+        Each request is a `(method, url)` or `(method, url, kwargs)` tuple. Unless
+        `return_exceptions` is set, the first error is raised; otherwise it takes the place
+        of the response. `on_progress(completed, total)` is called as requests finish.
+
+        # Examples
+
+        ```python
+        import rnet
+        from rnet import Method
+
+        client = rnet.BlockingClient()
+        responses = client.map([(Method.GET, f"https://example.com/{i}") for i in range(500)])
+        ```
+        """
+
+    def get_many(
+        self,
+        urls: List[str],
+        concurrency: int = 50,
+        **kwargs: Unpack[RequestParams],
+    ) -> List[Union[BlockingResponse, Exception]]:
+        r"""
+        Sends a `GET` request to each of `urls` with at most `concurrency` in flight and
+        returns the responses in the order of `urls`. `kwargs` apply to every request.
+
+        A failed request doesn't fail the batch: its exception takes the place of the
+        response.
+
+        # Examples
+
+        ```python
+        import rnet
+
+        client = rnet.BlockingClient()
+        results = client.get_many([f"https://example.com/{i}" for i in range(500)], timeout=10)
+        failed = [r for r in results if isinstance(r, Exception)]
+        ```
+        """
+
+    def request_many(
+        self,
+        requests: List[
+            Union[
+                Tuple[Union[Method, str], str],
+                Tuple[Union[Method, str], str, RequestParams],
+            ]
+        ],
+        concurrency: int = 50,
+    ) -> List[Union[BlockingResponse, Exception]]:
+        r"""
+        Like `map` with `return_exceptions=True`: the exception of a failed request takes
+        the place of its response instead of failing the batch.
+        """
+
+    def resolve(
+        self, host: str
+    ) -> List[Union[ipaddress.IPv4Address, ipaddress.IPv6Address]]:
+        r"""
+        Resolves `host` the way the client does before connecting, with its resolver and
+        `lookup_ip_strategy`, and returns the addresses in the order they are tried.
+
+        Raises `DNSResolverError` if the name doesn't resolve.
+        """
+
     def websocket(
-        self, url: str, **kwargs: Unpack[WebSocketParams]
+        self, url: Union[str, Url], **kwargs: Unpack[WebSocketParams]
     ) -> BlockingWebSocket:
         r"""
         Sends a WebSocket request.
 
+        With `auto_reconnect=True`, `on_reconnect` must be a plain function: a coroutine
+        function raises `TypeError`, as there is no event loop to await it on.
+
         # Examples
 
         ```python
@@ -281,7 +578,7 @@ class BlockingClient:
 
     def trace(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> BlockingResponse:
         r"""
@@ -305,7 +602,7 @@ class BlockingClient:
 
     def options(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> BlockingResponse:
         r"""
@@ -329,7 +626,7 @@ class BlockingClient:
 
     def head(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> BlockingResponse:
         r"""
@@ -353,7 +650,7 @@ class BlockingClient:
 
     def delete(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> BlockingResponse:
         r"""
@@ -377,7 +674,7 @@ class BlockingClient:
 
     def patch(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> BlockingResponse:
         r"""
@@ -401,7 +698,7 @@ class BlockingClient:
 
     def put(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> BlockingResponse:
         r"""
@@ -425,7 +722,7 @@ class BlockingClient:
 
     def post(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> BlockingResponse:
         r"""
@@ -449,7 +746,7 @@ class BlockingClient:
 
     def get(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> BlockingResponse:
         r"""
@@ -476,7 +773,7 @@ class BlockingResponse:
     A blocking response from a request.
     """
 
-    url: str
+    url: Url
     r"""
     Returns the URL of the response.
     """
@@ -484,6 +781,27 @@ class BlockingResponse:
     r"""
     Returns whether the response is successful.
     """
+    is_informational: bool
+    r"""
+    Returns whether the response is informational (1xx).
+    """
+    is_redirect: bool
+    r"""
+    Returns whether the response redirects: a 301, 302, 303, 307 or 308 status with a
+    `Location` header.
+    """
+    is_permanent_redirect: bool
+    r"""
+    Returns whether the response is a permanent redirect (301 or 308) with a `Location`.
+    """
+    is_client_error: bool
+    r"""
+    Returns whether the response is a client error (4xx).
+    """
+    is_server_error: bool
+    r"""
+    Returns whether the response is a server error (5xx).
+    """
     status: int
     r"""
     Returns the status code as integer of the response.
@@ -507,15 +825,106 @@ class BlockingResponse:
     content_length: int
     r"""
     Returns the content length of the response.
+
+    This is the length of the body as received, so it's `0` for a body the client
+    decoded, and the encoded length for one left encoded with `decompress=False`.
+    """
+    content_encoding: Optional[str]
+    r"""
+    Returns the `Content-Encoding` the body is still encoded with, or `None` if it isn't
+    or the client decoded it.
+    """
+    content_range: Optional[Tuple[int, int, Optional[int]]]
+    r"""
+    Returns the `Content-Range` of a `206 Partial Content` response as
+    `(start, end, total)`, with `end` inclusive and `total` `None` if unknown.
     """
     remote_addr: Optional[SocketAddr]
     r"""
     Returns the remote address of the response.
     """
+    bytes_sent: int
+    r"""
+    Returns an estimate of the bytes sent for the request: the size of the request head as
+    HTTP/1.1 text plus the body. It isn't measured on the wire, so it leaves out TLS and
+    HTTP/2 framing and header compression.
+    """
+    bytes_received: int
+    r"""
+    Returns an estimate of the bytes received so far: the size of the response head as
+    HTTP/1.1 text plus the body read, after decompression. Like `bytes_sent`, it isn't
+    measured on the wire.
+    """
+    from_cache: bool
+    r"""
+    Returns whether the response was served from the client's cache, either because the
+    stored copy was fresh or because the server confirmed it with `304 Not Modified`.
+    """
+    connection_reused: Optional[bool]
+    r"""
+    Returns whether the request was sent over a connection taken from the client's pool.
+
+    This is inferred from the connections the client's pool opened while the request was
+    in flight, since the pool doesn't report it. It's `None` when that can't be told, because
+    a connection was opened while other requests were in flight, and for responses that
+    didn't come from the network through a `Client`. Impersonated and `without_proxy`
+    requests use pools of their own, which are tracked separately.
+    """
+    connection_id: Optional[int]
+    r"""
+    Returns an opaque id of the connection the response came over, the same for every
+    response over one pooled connection, or `None` when it can't be told which connection
+    that was, such as when several were opened to the same address.
+    """
+    timing: Optional[dict[str, Optional[float]]]
+    r"""
+    Returns how long the phases of the request took, in seconds, like curl's
+    `--write-out` timings, or `None` if the response didn't come from the network.
+
+    - `dns`: resolving the host.
+    - `connect`: opening the connection once the host was resolved, including
+      the TLS handshake, which isn't timed on its own.
+    - `tls`: always `None`, as the TLS handshake is counted in `connect`.
+    - `time_to_first_byte`: from sending the request to receiving the response head.
+    - `total`: from sending the request to reading the body to its end, `None`
+      until it's read.
+
+    `dns` and `connect` are `None` when the request went over a pooled
+    connection, and `dns` is when the host is an IP address. After redirects,
+    the timing is the one of the last request.
+    """
+    history: list[str]
+    r"""
+    Returns the URLs the request was redirected from, in order, including meta refreshes
+    followed with `follow_meta_refresh=True`. The final URL is `url`.
+    """
+    request_id: Optional[str]
+    r"""
+    Returns the id of the request if the client has a `request_id_header`: the server's
+    if it answered with a different one in that header, otherwise the one sent.
+    """
+    client_request_id: Optional[str]
+    r"""
+    Returns the id sent in the client's `request_id_header`.
+    """
     encoding: str
     r"""
-    Encoding to decode with when accessing text.
+    Encoding to decode with when accessing text, or the one detected from the body once
+    it has been read with `text(detect_encoding=True)` or `text(autodetect=True)`.
     """
+    @staticmethod
+    def build(
+        status: int = 200,
+        headers: Optional[Union[Dict[str, str], HeaderMap, List[Tuple[str, str]]]] = None,
+        body: Optional[Union[str, bytes]] = None,
+        url: str = "http://test",
+        version: Version = Version.HTTP_11,
+    ) -> BlockingResponse:
+        r"""
+        Builds a response in memory, without a request, for testing code that handles
+        responses. Its body, headers and cookies behave like those of a real response.
+        """
+
     def __enter__(self) -> BlockingResponse: ...
     def __exit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> None: ...
     def peer_certificate(self) -> Optional[bytes]:
@@ -523,18 +932,53 @@ class BlockingResponse:
         Returns the TLS peer certificate of the response.
         """
 
-    def text(self) -> str:
+    def text(
+        self,
+        errors: Literal["strict", "replace", "ignore"] = "replace",
+        detect_encoding: bool = False,
+        autodetect: bool = False,
+        encoding: Optional[Literal["auto", "detect"]] = None,
+    ) -> str:
         r"""
         Returns the text content of the response.
+
+        # Arguments
+
+        * `errors` - How malformed bytes are handled, as in `bytes.decode`: replaced with
+          U+FFFD, dropped, or raised as a `DecodingError` with `"strict"`.
+        * `detect_encoding` - Detect the charset from a byte order mark or the statistics of
+          the first 64 KB of the body, instead of trusting the `Content-Type`, which pages
+          sometimes get wrong. The detected charset is then reported by `encoding`.
+        * `autodetect` - Detect the charset as with `detect_encoding`, but only if the
+          `Content-Type` doesn't declare one, e.g. for plain text or CSV from legacy
+          systems. Without it, such bodies are decoded as UTF-8.
+        * `encoding` - The same choice as a string: `"detect"` for `detect_encoding=True`
+          and `"auto"` for `autodetect=True`. Use `text_with_charset` to decode with a
+          given charset.
+        """
+
+    def detect_encoding(self) -> Tuple[str, bool]:
+        r"""
+        Guesses the charset of the body from its byte order mark, or else from the
+        statistics of its first 64 KB, entirely in Rust. The sampled bytes are left in
+        place, so the body can still be read afterwards.
+
+        Returns the name of the charset, as reported by `encoding`, and whether the
+        guess is confident; an unconfident guess is likely to be wrong.
         """
 
-    def text_with_charset(self, encoding: str) -> str:
+    def text_with_charset(
+        self,
+        encoding: str,
+        errors: Literal["strict", "replace", "ignore"] = "replace",
+    ) -> str:
         r"""
         Returns the text content of the response with a specific charset.
 
         # Arguments
 
         * `encoding` - The default encoding to use if the charset is not specified.
+        * `errors` - How malformed bytes are handled, as in `text`.
         """
 
     def json(self) -> Dict[str, Any]:
@@ -542,19 +986,59 @@ class BlockingResponse:
         Returns the JSON content of the response.
         """
 
+    def save(self, path: Union[str, os.PathLike[str]]) -> int:
+        r"""
+        Writes the body into the file at `path` as it arrives, without holding it
+        in memory, replacing the file. Returns the size of the file.
+
+        The body is written to `path` with `.tmp` appended and renamed over `path` once
+        it's complete, so a failed read leaves any file already there untouched.
+        """
+
+    def save_json(self, path: Union[str, os.PathLike[str]]) -> int:
+        r"""
+        Parses the body as JSON and writes it into the file at `path` indented by
+        two spaces, keeping the order of object keys, replacing the file. Returns
+        the size of the file.
+        """
+
     def bytes(self) -> bytes:
         r"""
         Returns the bytes content of the response.
         """
 
-    def stream(self) -> BlockingStreamer:
+    def read_buffer(self) -> BytesBuffer:
+        r"""
+        Returns the body as a read-only `BytesBuffer` without copying it into `bytes`.
+
+        See `Response.read_buffer` for the lifetime semantics.
+        """
+
+    def stream(self, zero_copy: bool = False) -> BlockingStreamer:
         r"""
         Convert the response into a `Stream` of `Bytes` from the body.
+
+        Each chunk is awaited for at most the request's `read_timeout`, if it has one,
+        after which `TimeoutError` is raised.
+
+        # Arguments
+
+        * `zero_copy` - Yield each chunk as a `BytesBuffer` instead of `bytes`.
+        """
+
+    def parts(self) -> BlockingParts:
+        r"""
+        Iterates over the `(headers, body)` parts of a `multipart/*` response.
+        See `Response.parts`.
         """
 
     def close(self) -> None:
         r"""
         Closes the response connection.
+
+        A response dropped with its body neither read nor closed emits a
+        `ResourceWarning`, as its connection isn't returned to the pool until then.
+        See `strict_resources`.
         """
 
 class BlockingStreamer:
@@ -564,13 +1048,45 @@ class BlockingStreamer:
     Used for streaming response content.
     Employed in the `stream` method of the `Response` class.
     Utilized in an asynchronous for loop in Python.
+
+    A stream has a single consumer at a time: calling `__next__` while another
+    thread is already reading raises a `RuntimeError` instead of blocking.
     """
 
     def __iter__(self) -> BlockingStreamer: ...
     def __next__(self) -> Any: ...
+    def read(self, n: int) -> bytes:
+        r"""
+        Reads exactly `n` bytes, or fewer if the stream ends first.
+        See `Streamer.read`.
+        """
+
+    def readexactly(self, n: int) -> bytes:
+        r"""
+        Reads exactly `n` bytes, raising `BodyError` if the stream ends first.
+        See `Streamer.readexactly`.
+        """
+
+    def read_all(self) -> bytes:
+        r"""
+        Reads the rest of the stream into a single buffer.
+        See `Streamer.read_all`.
+        """
+
     def __enter__(self) -> BlockingStreamer: ...
     def __exit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> None: ...
 
+class BlockingParts:
+    r"""
+    A blocking iterator over the `(headers, body)` parts of a `multipart/*` response.
+    Implemented in the `parts` method of the `BlockingResponse` class.
+    """
+
+    def __iter__(self) -> BlockingParts: ...
+    def __next__(self) -> Tuple[HeaderMap, bytes]: ...
+    def __enter__(self) -> BlockingParts: ...
+    def __exit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> None: ...
+
 class BlockingWebSocket:
     r"""
     A blocking WebSocket response.
@@ -608,13 +1124,70 @@ class BlockingWebSocket:
     r"""
     Returns the WebSocket protocol.
     """
+    origin: Optional[str]
+    r"""
+    Returns the `Origin` header the handshake was sent with.
+    """
+    user_agent: Optional[str]
+    r"""
+    Returns the `User-Agent` header the handshake was sent with, if set for the request.
+    """
+    messages_sent: int
+    r"""
+    Returns the number of messages sent with `send` and `send_all`, across
+    reconnects.
+    """
+    messages_received: int
+    r"""
+    Returns the number of messages received, across reconnects.
+    """
+    bytes_sent: int
+    r"""
+    Returns the size of the payloads of the messages sent, without framing.
+    """
+    bytes_received: int
+    r"""
+    Returns the size of the payloads of the messages received, without framing.
+    """
+    pending_bytes: int
+    r"""
+    Returns the size of the payloads of the messages being sent that haven't
+    been written to the connection yet, including those waiting for another
+    send to finish. A slow peer makes it grow.
+
+    These counters are read without waiting for pending sends or receives.
+    """
+    max_message_size: int
+    r"""
+    Returns the largest message the connection accepts, as set with
+    `max_message_size` or 64 MiB by default.
+    """
+    max_frame_size: int
+    r"""
+    Returns the largest frame the connection accepts, as set with
+    `max_frame_size` or 16 MiB by default.
+    """
     def __iter__(self) -> BlockingWebSocket: ...
     def __next__(self) -> Message: ...
     def __enter__(self) -> BlockingWebSocket: ...
     def __exit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> None: ...
-    def recv(self) -> Optional[Message]:
+    def recv(self, timeout: Optional[float] = None) -> Optional[Message]:
         r"""
-        Receives a message from the WebSocket.
+        Receives a message from the WebSocket, blocking until one arrives.
+
+        Iterating the WebSocket receives messages the same way until it's closed:
+
+        ```python
+        with client.websocket("wss://echo.websocket.org") as ws:
+            ws.send(rnet.Message.from_text("hello"))
+            for message in ws:
+                print(message.data)
+        ```
+
+        # Arguments
+
+        * `timeout` - The number of seconds to wait before raising `TimeoutError`. The
+          WebSocket stays usable after a timeout.
         """
 
     def send(self, message: Message) -> None:
@@ -626,6 +1199,20 @@ class BlockingWebSocket:
         * `message` - The message to send.
         """
 
+    def send_all(self, messages: typing.Iterable[Message]) -> int:
+        r"""
+        Sends every message of an iterable, flushing once at the end, and returns how
+        many were sent.
+
+        If sending fails, the raised exception has a `sent` attribute with the number of
+        messages known to be sent, which is 0: messages count as sent once the final flush
+        succeeds, as any of them may still have been buffered when the error happened.
+
+        # Arguments
+
+        * `messages` - The messages to send.
+        """
+
     def close(
         self,
         code: Optional[int] = None,
@@ -634,6 +1221,9 @@ class BlockingWebSocket:
         r"""
         Closes the WebSocket connection.
 
+        Pending `recv` and `send` calls from other tasks are interrupted and
+        raise a disconnect error; pending iteration stops.
+
         # Arguments
 
         * `code` - An optional close code.
@@ -675,46 +1265,57 @@ class Client:
     """
     def __new__(
         cls,
-        impersonate: Optional[Union[Impersonate, ImpersonateOption]] = None,
-        user_agent: Optional[str] = None,
-        default_headers: Optional[Dict[str, bytes]] = None,
-        headers_order: Optional[List[str]] = None,
-        referer: Optional[bool] = None,
-        allow_redirects: Optional[bool] = None,
-        max_redirects: Optional[int] = None,
-        cookie_store: Optional[bool] = None,
-        lookup_ip_strategy: Optional[LookupIpStrategy] = None,
-        timeout: Optional[int] = None,
-        connect_timeout: Optional[int] = None,
-        read_timeout: Optional[int] = None,
-        no_keepalive: Optional[bool] = None,
-        tcp_keepalive: Optional[int] = None,
-        pool_idle_timeout: Optional[int] = None,
-        pool_max_idle_per_host: Optional[int] = None,
-        pool_max_size: Optional[int] = None,
-        http1_only: Optional[bool] = None,
-        http2_only: Optional[bool] = None,
-        https_only: Optional[bool] = None,
-        tcp_nodelay: Optional[bool] = None,
-        http2_max_retry_count: Optional[int] = None,
-        verify: Optional[Union[bool, Path]] = None,
-        tls_info: Optional[bool] = None,
-        min_tls_version: Optional[TlsVersion] = None,
-        max_tls_version: Optional[TlsVersion] = None,
-        no_proxy: Optional[bool] = None,
-        proxies: Optional[List[Proxy]] = None,
-        local_address: Optional[
-            Union[str, ipaddress.IPv4Address, ipaddress.IPv6Address]
-        ] = None,
-        interface: Optional[str] = None,
-        gzip: Optional[bool] = None,
-        brotli: Optional[bool] = None,
-        deflate: Optional[bool] = None,
-        zstd: Optional[bool] = None,
+        **kwargs: Unpack[ClientParams],
     ) -> Client:
         r"""
         Creates a new Client instance.
 
+        Unknown keyword arguments raise `TypeError`; set the environment variable
+        `RNET_ALLOW_UNKNOWN_KWARGS=1` to ignore them instead.
+
+        `tls_config` applies the given settings on top of wreq's TLS defaults. The TLS
+        values of an impersonation preset cannot be read back to merge them with, so
+        `tls_config` raises `ValueError` when combined with `impersonate`, whether at
+        creation, through `update` or per request. `verify`, `min_tls_version` and
+        `max_tls_version` still apply on top.
+
+        `verify` and `cert` work as in requests: `verify` is a bool or the path of a CA
+        bundle, and `cert` is the path of a PEM file with the client certificate and its
+        PKCS #8 key, or a `(cert, key)` pair of paths. With a `cert` and no CA bundle, the
+        server is verified against the same bundled Mozilla roots as without a `cert`.
+
+        With `request_id_header="x-request-id"`, every request is sent with a fresh UUIDv4
+        in that header, unless the request sets the header itself. The id is exposed as
+        `response.request_id`, or the server's id if it echoes a different one back.
+
+        `max_response_size` caps response bodies, counted after decompression so that a
+        compression bomb can't inflate into memory: reading past it raises
+        `ResponseTooLargeError` and closes the connection. Requests can set their own
+        `max_response_size`. `max_header_size` (at least 8192) caps HTTP/1 response heads;
+        HTTP/2 keeps the limit the impersonated browser advertises.
+
+        `record` and `replay` take a cassette file, for tests that shouldn't depend on the
+        network. With `record`, the response to every request is written to the file,
+        replacing it, with bodies decoded and kept as text when they are UTF-8. With
+        `replay`, requests are answered from the file instead, matched by method, URL and
+        body; identical requests get their responses in recorded order, the last one
+        repeating once they run out, and a request that wasn't recorded raises
+        `RequestError`. Recording reads every body in full before returning the response.
+
+        With `auth=OAuth2ClientCredentials(...)`, requests are sent with a bearer token from
+        the token endpoint, fetched by the client itself when first needed and again before
+        it expires. Only requests to the credentials' `base_urls` carry the token. A request refused with `401` gets a new token and is retried once, unless
+        its body is a stream. Requests that set their own `Authorization` header, through
+        `headers`, `auth`, `bearer_auth` or `basic_auth`, are sent as they are.
+
+        Socket options other than `tcp_keepalive`, `tcp_nodelay`, `local_address` and
+        `interface`, such as `TCP_USER_TIMEOUT` or `SO_MARK`, can't be set: wreq creates
+        its sockets inside its connector and hands out only the finished connection. For
+        policy routing, bind to a source address with `local_address` and match it with an
+        `ip rule from ...` instead of a firewall mark; `read_timeout` bounds stalled reads.
+        Likewise, `local_address` binds an IP address only: the source port is always
+        picked by the OS, so firewall rules can't rely on a fixed port range.
+
         # Examples
 
         ```python
@@ -730,7 +1331,7 @@ class Client:
         ```
         """
 
-    def get_cookies(self, url: str) -> Optional[bytes]:
+    def get_cookies(self, url: Union[str, Url]) -> Optional[bytes]:
         r"""
         Returns the cookies for the given URL.
 
@@ -753,7 +1354,7 @@ class Client:
         ```
         """
 
-    def set_cookie(self, url: str, cookie: Cookie) -> None:
+    def set_cookie(self, url: Union[str, Url], cookie: Cookie) -> None:
         r"""
         Sets the cookies for the given URL.
 
@@ -771,7 +1372,7 @@ class Client:
         ```
         """
 
-    def remove_cookie(self, url: str, name: str) -> None:
+    def remove_cookie(self, url: Union[str, Url], name: str) -> None:
         r"""
         Removes the cookie with the given name for the given URL.
 
@@ -793,28 +1394,38 @@ class Client:
         Clears the cookies for the given URL.
         """
 
-    def update(
+    @property
+    def cookie_jar(self) -> Optional[CookieJar]:
+        r"""
+        The jar the client stores cookies in, or `None` if it doesn't store any.
+
+        Assigning a `CookieJar` makes the client store cookies in it, and assigning `None`
+        stops it from storing cookies. Either one rebuilds the client like `update`.
+        """
+
+    @cookie_jar.setter
+    def cookie_jar(self, jar: Optional[CookieJar]) -> None: ...
+
+    def token_info(self) -> Optional[Dict[str, Any]]:
+        r"""
+        Returns the OAuth2 token the client has cached, as a dict with its `access_token`,
+        `token_type`, `scope` and the seconds it `expires_in` (`None` if the server didn't
+        say), or `None` if the client has no `auth` or hasn't fetched a token yet.
+        """
+
+    def mount(
         self,
-        impersonate: Optional[Union[Impersonate, ImpersonateOption]] = None,
-        headers: Optional[Union[Dict[str, str], HeaderMap]] = None,
-        headers_order: Optional[List[str]] = None,
-        proxies: Optional[List[Proxy]] = None,
-        local_address: Optional[
-            Union[ipaddress.IPv4Address, ipaddress.IPv6Address]
-        ] = None,
-        interface: Optional[str] = None,
+        matcher: Union[str, Callable[[str, str], bool]],
+        handler: Callable[[str, str, HeaderMap, bytes], Tuple[int, Union[Dict[str, str], HeaderMap, List[Tuple[str, str]]], Union[bytes, str]]],
     ) -> None:
         r"""
-        Updates the client with the given parameters.
+        Answers the requests `matcher` matches with `handler` instead of sending them.
 
-        # Arguments
-
-        * `impersonate` - The impersonation settings for the request.
-        * `headers` - The headers to use for the request.
-        * `headers_order` - The order of the headers to use for the request.
-        * `proxies` - The proxy to use for the request.
-        * `local_address` - The local IP address to bind to.
-        * `interface` - The interface to bind to.
+        The matcher is either a URL prefix, or a callable called as `matcher(method, url)`
+        that returns whether it matches. The handler is called like a `MockTransport`
+        handler. Mounts are tried in the order they were added, ahead of the client's
+        `transport`; requests that none matches go to the transport, or over the network
+        if the client has none.
 
         # Examples
 
@@ -822,23 +1433,219 @@ class Client:
         import rnet
 
         client = rnet.Client()
-        client.update(
-           impersonate=rnet.Impersonate.Firefox135,
+        client.mount(
+            "https://api.example.com/users",
+            lambda method, url, headers, body: (200, {}, b'[]'),
+        )
+        client.mount(
+            lambda method, url: method == "DELETE",
+            lambda method, url, headers, body: (403, {}, b""),
+        )
+        ```
+        """
+
+    def update(
+        self,
+        **kwargs: Unpack[UpdateClientParams],
+    ) -> None:
+        r"""
+        Updates the client with the given parameters.
+
+        # Arguments
+
+        * `impersonate` - The impersonation settings for the request.
+        * `headers` - The headers to use for the request.
+        * `headers_order` - The order of the headers to use for the request.
+        * `proxies` - The proxy to use for the request.
+        * `local_address` - The local IP address to bind to, or an `(ipv4, ipv6)` pair.
+          The source port is always picked by the OS. A link-local IPv6 address keeps its
+          zone, as in `"fe80::1%eth0"`, and is bound on the interface the zone names unless
+          `interface` is set (Linux, Android and Apple platforms only). That binds IPv4
+          connections to the interface too, so a zone can't be paired with an IPv4 address.
+        * `interface` - The network interface to bind to, e.g. `"eth0"` or `"en0"`. On Linux,
+          Android and Apple platforms the socket is bound to the device itself, so traffic
+          always leaves through it. Elsewhere (Windows, the BSDs) the interface name is
+          resolved to its primary IPv4/IPv6 addresses and used as `local_address`; this only
+          picks the source address, and the OS routing table still decides which interface
+          the packets leave through. On those platforms `interface` overrides `local_address`.
+        * `allow_redirects` - Whether to follow redirects.
+        * `max_redirects` - The maximum number of redirects to follow.
+        * `cookie_store` - Whether to store cookies.
+        * `cookie_provider` - The `CookieJar` to store cookies in, which other clients may share.
+        * `timeout` - The total timeout of a request, in seconds, or a `(connect, read)`
+          pair, where either may be `None`. Fractions such as `0.5` are allowed, here and
+          in the other timeouts.
+        * `connect_timeout` - The connect timeout, in seconds.
+        * `read_timeout` - The read timeout, in seconds. It bounds the wait for the response
+          and then for each chunk of its body, so a stream that stalls raises
+          `TimeoutError` however long the whole body takes.
+        * `pool_idle_timeout` - How long idle connections are kept alive, in seconds.
+        * `pool_max_idle_per_host` - The maximum number of idle connections per host.
+        * `pool_max_size` - The maximum number of connections in the pool.
+        * `gzip`, `brotli`, `deflate`, `zstd` - Whether to accept each encoding.
+
+        The impersonation, headers, proxies, local address, interface and redirect options
+        are changed in place. The other options can't be changed on a live client, so setting
+        any of them rebuilds it from the options it was created and updated with. The rebuilt
+        client keeps the cookie jar and the current headers, and starts with an empty
+        connection pool. Requests already in flight finish on the old client. Turning
+        `cookie_store` off and back on brings back the cookies stored before.
+
+        # Examples
+
+        ```python
+        import rnet
+
+        client = rnet.Client()
+        client.update(
+           impersonate=rnet.Impersonate.Firefox135,
            headers={"X-My-Header": "value"},
            proxies=[rnet.Proxy.all("http://proxy.example.com:8080")],
         )
         ```
         """
 
+    def __repr__(self) -> str:
+        r"""
+        Summarizes the client's user agent, impersonation, cookie, redirect and
+        compression settings and how many proxies it has. Proxy URLs and their
+        credentials are left out.
+        """
+
+    def close(self) -> None:
+        r"""
+        Closes the client, dropping its connection pool.
+
+        Requests already in flight complete normally; any further use of the
+        client raises a `RuntimeError`.
+
+        # Examples
+
+        ```python
+        import rnet
+
+        client = rnet.Client()
+        client.close()
+        ```
+        """
+
+    async def tls_fingerprint(self) -> TlsFingerprint:
+        r"""
+        Returns the TLS fingerprint the client presents with its current settings.
+
+        The fingerprint is computed from a real `ClientHello`, captured by
+        connecting to a short-lived listener on `localhost`, so it reflects the
+        effective impersonation and TLS options. Proxies, whether configured or
+        read from the environment, are bypassed for the capture. Profiles that
+        shuffle their extensions (e.g. recent Chrome) produce a different JA3 on
+        every call; compare the cipher list instead when checking such profiles.
+
+        # Examples
+
+        ```python
+        import rnet
+        import asyncio
+
+        async def main():
+            client = rnet.Client(impersonate=rnet.Impersonate.Firefox136)
+            fingerprint = await client.tls_fingerprint()
+            print(fingerprint.ja3, fingerprint.ja3_hash)
+
+        asyncio.run(main())
+        ```
+        """
+
+    def connection(self) -> Connection:
+        r"""
+        Returns a `Connection` that pins subsequent requests to the same
+        underlying connection.
+
+        It has a connection pool of its own, so other requests of the client
+        can't take its connection. Requests made through it are sent one at a
+        time: each waits until the body of the previous response is read,
+        closed or dropped. A new connection is only opened if the server closed
+        the last one, or a body was dropped before it was read to its end.
+
+        # Examples
+
+        ```python
+        import rnet
+        import asyncio
+
+        async def main():
+            client = rnet.Client()
+            async with client.connection() as conn:
+                login = await conn.post("https://example.com/login", form=[("user", "rnet")])
+                await login.text()
+                resp = await conn.get("https://example.com/profile")
+                print(await resp.text())
+
+        asyncio.run(main())
+        ```
+        """
+
     async def request(
         self,
-        method: Method,
-        url: str,
+        method: Union[Method, str],
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> Response:
         r"""
         Sends a request with the given method and URL.
 
+        The method is a `Method` or, for any other verb, a string such as `"SEARCH"`,
+        which is sent verbatim, case included; `ValueError` is raised if it isn't a
+        valid token.
+
+        Only one of `json`, `form`, `body` and `multipart` may be given, since each
+        defines the whole body; passing more raises `BuilderError` naming them.
+
+        Cancelling the awaiting task aborts the in-flight request and closes its
+        connection.
+
+        Passing `impersonate` overrides the client's profile for this request only;
+        `impersonate="random"` picks a new random profile on every call. Idle
+        pooled connections are still shared across profiles, so create the client
+        with `no_keepalive=True` when each request must carry its own fingerprint.
+
+        `headers_order` likewise overrides the client's header order for this request
+        only. `headers` given as a list of `(name, value)` pairs keep every value of a
+        repeated name. The client's header order, from `headers_order` or the impersonated
+        profile, still places the names it lists; the other headers follow in the order of
+        the list. The values of a repeated name are sent together, where the name first
+        appears.
+
+        For code moving from requests, `params` is accepted for `query`, and `data` for
+        `form` if it's a dict or a list of pairs and for `body` otherwise. Giving both
+        spellings of an option raises `TypeError` unless they give the same value.
+
+        With `follow_meta_refresh=True`, a successful HTML response whose first 16 KB hold
+        a `<meta http-equiv="refresh" content="0; url=...">` is followed like a redirect:
+        with a `GET`, within the same `max_redirects` and only if redirects are allowed,
+        without credentials on another host, and recorded in `Response.history`. Other
+        content types are returned without touching the body.
+
+        A response that may be stored in the client's `cache` is read in full before it's
+        returned. With `prefetch_body=False` it's returned as soon as its headers arrive
+        and isn't stored; this can't be combined with `follow_meta_refresh=True`.
+
+        Redirects from `https` to `http` raise `RedirectError` unless
+        `allow_insecure_redirects=True`. `https_only=True` rejects a non-`https` URL with
+        `BuilderError` and any redirect away from `https` with `RedirectError`; it can only
+        tighten a client created with `https_only=True`, not lift it.
+
+        A `body` given as a path (`pathlib.Path` or another `os.PathLike`) is opened right
+        away and streamed from the file. A path to a regular file, or a seekable binary
+        file object, is sent with a `Content-Length` of the bytes left from its current
+        position; the file must not change size before it's sent. Other streams use
+        chunked transfer encoding.
+
+        `on_upload_progress(sent, total)` is called as the body is sent, with the bytes sent
+        so far and the body's size, or `None` if it isn't known up front. It runs on a
+        worker thread, at most once per 64 KB plus once at the end. An exception it raises
+        fails the request, which raises that exception. The body is then sent as a stream,
+        so a `307` or `308` redirect can't resend it.
+
         # Examples
 
         ```python
@@ -855,9 +1662,174 @@ class Client:
         ```
         """
 
+    async def head_ok(
+        self,
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> bool:
+        r"""
+        Checks whether the URL exists with a `HEAD` request, without downloading it.
+
+        Returns `True` if the final response has a `2xx` status. Redirects are followed
+        unless `allow_redirects=False` is passed, and other statuses return `False`
+        rather than raising. Connection errors and timeouts still raise, since they
+        don't tell whether the URL exists.
+
+        # Examples
+
+        ```python
+        if await client.head_ok("https://example.com/file.zip"):
+            print("available")
+        ```
+        """
+
+    async def content_length(
+        self,
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> Optional[int]:
+        r"""
+        Returns the size of the body at the URL without downloading it, or `None` if the
+        server doesn't tell.
+
+        A `HEAD` request is tried first. If it fails, whether with an error or a non-`2xx`
+        status, or has no `Content-Length`, the size is taken from a `GET` of the first byte: the complete
+        length in `Content-Range`, or the `Content-Length` if the server ignores the
+        range. Neither body is read. Redirects are followed unless `allow_redirects=False`
+        is passed.
+
+        # Examples
+
+        ```python
+        size = await client.content_length("https://example.com/file.zip")
+        ```
+        """
+
+    async def download(
+        self,
+        url: Union[str, Url],
+        path: Union[str, os.PathLike[str]],
+        resume: bool = True,
+        **kwargs: Unpack[RequestParams],
+    ) -> int:
+        r"""
+        Downloads the URL into `path` and returns the size of the file.
+
+        With `resume`, an existing file is kept and only the remaining bytes are requested
+        with a `Range` header (`range=(size, None)`). A `206 Partial Content` answer is
+        appended to the file; if the server ignores the range, the file is replaced with
+        the full body, and a `416` for a file that is already complete returns its size.
+
+        # Examples
+
+        ```python
+        import rnet
+
+        client = rnet.Client()
+        size = await client.download("https://example.com/large.iso", "large.iso")
+        ```
+        """
+
+    async def gather(
+        self,
+        requests: List[
+            Union[
+                Tuple[Union[Method, str], str],
+                Tuple[Union[Method, str], str, RequestParams],
+            ]
+        ],
+        concurrency: int = 50,
+        return_exceptions: bool = False,
+        on_progress: Optional[Callable[[int, int], Any]] = None,
+    ) -> List[Union[Response, Exception]]:
+        r"""
+        Sends a batch of requests with at most `concurrency` in flight and returns the
+        responses in the order of `requests`.
+
+        Each request is a `(method, url)` or `(method, url, kwargs)` tuple. A new request
+        starts as soon as one finishes, so a slow response doesn't hold back the others.
+        Unless `return_exceptions` is set, the first error is raised and the remaining
+        requests are cancelled; otherwise it takes the place of the response.
+        `on_progress(completed, total)` is called as requests finish.
+
+        # Examples
+
+        ```python
+        import rnet
+        from rnet import Method
+
+        client = rnet.Client()
+        responses = await client.gather(
+            [(Method.GET, f"https://example.com/{i}", {"timeout": 10}) for i in range(500)],
+            concurrency=50,
+            return_exceptions=True,
+        )
+        ```
+        """
+
+    async def get_many(
+        self,
+        urls: List[str],
+        concurrency: int = 50,
+        **kwargs: Unpack[RequestParams],
+    ) -> List[Union[Response, Exception]]:
+        r"""
+        Sends a `GET` request to each of `urls` with at most `concurrency` in flight and
+        returns the responses in the order of `urls`. `kwargs` apply to every request.
+
+        A failed request doesn't fail the batch: its exception takes the place of the
+        response.
+
+        # Examples
+
+        ```python
+        import rnet
+
+        client = rnet.Client()
+        results = await client.get_many([f"https://example.com/{i}" for i in range(500)], timeout=10)
+        failed = [r for r in results if isinstance(r, Exception)]
+        ```
+        """
+
+    async def request_many(
+        self,
+        requests: List[
+            Union[
+                Tuple[Union[Method, str], str],
+                Tuple[Union[Method, str], str, RequestParams],
+            ]
+        ],
+        concurrency: int = 50,
+    ) -> List[Union[Response, Exception]]:
+        r"""
+        Like `gather` with `return_exceptions=True`: the exception of a failed request takes
+        the place of its response instead of failing the batch.
+        """
+
+    async def resolve(
+        self, host: str
+    ) -> List[Union[ipaddress.IPv4Address, ipaddress.IPv6Address]]:
+        r"""
+        Resolves `host` the way the client does before connecting, with its resolver and
+        `lookup_ip_strategy`, and returns the addresses in the order they are tried. The
+        resolver, and its cache, are shared with the requests of the client, which makes
+        this useful to tell DNS problems apart from others.
+
+        Raises `DNSResolverError` if the name doesn't resolve.
+
+        # Examples
+
+        ```python
+        import rnet
+
+        client = rnet.Client(lookup_ip_strategy=rnet.LookupIpStrategy.Ipv4Only)
+        print(await client.resolve("example.com"))
+        ```
+        """
+
     async def websocket(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[WebSocketParams],
     ) -> WebSocket:
         r"""
@@ -877,13 +1849,92 @@ class Client:
             print("Received:", message.data)
             await ws.close()
 
+        asyncio.run(main())
+        ```
+
+        # Reconnecting
+
+        With `auto_reconnect=True`, a network error or a close frame from the server while
+        receiving re-runs the handshake (with the same protocols, headers and
+        authentication) instead of raising or ending the iteration. Attempts
+        wait `reconnect_backoff` seconds, doubled after each failure, and the error is
+        raised once `reconnect_max_attempts` have failed. After reconnecting,
+        `on_reconnect(attempt)` is called, and awaited if it returns a coroutine, so
+        subscriptions can be renewed. Messages sent while reconnecting wait for the new
+        connection, or raise if `reconnect_queue_sends=False`. A send that fails with a
+        network error reconnects the same way and sends the message once more.
+
+        ```python
+        async def resubscribe(attempt):
+            await ws.send(rnet.Message.from_text('{"op": "subscribe"}'))
+
+        ws = await client.websocket(url, auto_reconnect=True, on_reconnect=resubscribe)
+        ```
+
+        # Receive queue
+
+        By default, frames are read from the connection only while `recv()` is waiting.
+        With `max_recv_queue=n`, a background task reads up to `n` messages ahead. When
+        the queue is full it stops reading, so the server is slowed down by TCP
+        backpressure instead of memory growing without bound.
+
+        Pings from the server are answered as frames are read. While the queue is full
+        (or, without a queue, while nothing is receiving) pings go unanswered, and a server
+        with a strict keepalive timeout may close the connection. Keep consuming messages,
+        or size the queue for bursts.
+
+        # Compression
+
+        The permessage-deflate extension isn't supported: the handshake doesn't offer it
+        and compressed frames are rejected, so don't add a `Sec-WebSocket-Extensions`
+        header of your own. Messages are always sent and received uncompressed.
+
+        # Defaults
+
+        Options shared by every WebSocket of a client can be given once as
+        `Client(websocket_defaults={...})`. Options passed to `websocket()` take precedence,
+        and headers are merged by name.
+
+        ```python
+        client = rnet.Client(websocket_defaults={"max_message_size": 1 << 20, "origin": "https://example.com"})
+        ws = await client.websocket(url, protocols=["v2"])
+        ```
+        """
+
+    async def connect_tunnel(
+        self,
+        proxy: Union[str, Proxy],
+        target_host: str,
+        target_port: int,
+    ) -> Tunnel:
+        r"""
+        Opens a raw TCP tunnel to `target_host:target_port` through an HTTP proxy
+        using a `CONNECT` request.
+
+        The proxy must handle plain HTTP requests (a URL string, `Proxy.http` or
+        `Proxy.all`).
+
+        # Examples
+
+        ```python
+        import rnet
+        import asyncio
+
+        async def main():
+            client = rnet.Client()
+            async with await client.connect_tunnel(
+                "http://proxy.example.com:8080", "example.com", 25
+            ) as tunnel:
+                print(await tunnel.read())
+                await tunnel.write(b"QUIT\r\n")
+
         asyncio.run(main())
         ```
         """
 
     async def trace(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> Response:
         r"""
@@ -907,7 +1958,7 @@ class Client:
 
     async def options(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> Response:
         r"""
@@ -931,7 +1982,7 @@ class Client:
 
     async def patch(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> Response:
         r"""
@@ -955,7 +2006,7 @@ class Client:
 
     async def delete(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> Response:
         r"""
@@ -979,7 +2030,7 @@ class Client:
 
     async def put(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> Response:
         r"""
@@ -1003,7 +2054,7 @@ class Client:
 
     async def post(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> Response:
         r"""
@@ -1027,7 +2078,7 @@ class Client:
 
     async def head(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> Response:
         r"""
@@ -1051,7 +2102,7 @@ class Client:
 
     async def get(
         self,
-        url: str,
+        url: Union[str, Url],
         **kwargs: Unpack[RequestParams],
     ) -> Response:
         r"""
@@ -1137,6 +2188,52 @@ class Cookie:
     def __str__(self) -> str: ...
     def __repr__(self) -> str: ...
 
+class CookieJar:
+    r"""
+    A cookie store that can be shared between clients.
+
+    Clients created with the same jar as their `cookie_provider` send and store the same
+    cookies. The jar is locked internally, so it can be used from any thread. A client
+    given a jar stores cookies unless it's created with `cookie_store=False`.
+
+    # Examples
+
+    ```python
+    import rnet
+
+    desktop = rnet.Client(impersonate=rnet.Impersonate.Chrome133, cookie_store=True)
+    mobile = rnet.Client(
+        impersonate=rnet.Impersonate.SafariIos17_4_1,
+        cookie_provider=desktop.cookie_jar,
+    )
+    ```
+    """
+
+    def __new__(cls) -> CookieJar:
+        r"""
+        Creates a new, empty cookie jar.
+        """
+
+    def get_cookies(self, url: str) -> Optional[bytes]:
+        r"""
+        Returns the cookies for the given URL.
+        """
+
+    def set_cookie(self, url: str, cookie: Cookie) -> None:
+        r"""
+        Sets the cookie for the given URL.
+        """
+
+    def remove_cookie(self, url: str, name: str) -> None:
+        r"""
+        Removes the cookie with the given name for the given URL.
+        """
+
+    def clear(self) -> None:
+        r"""
+        Clears all the cookies.
+        """
+
 class HeaderMap:
     r"""
     A HTTP header map.
@@ -1180,6 +2277,15 @@ class HeaderMap:
         key. Returns `None` if there are no values associated with the key.
         """
 
+    def get_str(self, key: str, errors: str = "replace") -> Optional[str]:
+        r"""
+        Returns the first value associated with the key decoded as UTF-8.
+
+        Servers sometimes send Latin-1 or raw bytes in header values; `errors` decides
+        how invalid bytes are handled, as in `bytes.decode` (`"strict"`, `"replace"`,
+        `"ignore"`, ...).
+        """
+
     def get_all(self, key: str) -> HeaderMapValuesIter:
         r"""
         Returns a view of all values associated with a key.
@@ -1269,11 +2375,47 @@ class ImpersonateOption:
         with random settings for browser type and operating system options.
         """
 
+    @staticmethod
+    def random_for(browser: Union[Impersonate, str]) -> ImpersonateOption:
+        r"""
+        Creates an impersonation option for a browser on an operating system it runs on.
+
+        Combinations a real user could not have, such as Safari on Windows, are never picked.
+
+        Args:
+            browser (Union[Impersonate, str]): A profile, or a browser family such as
+                `"chrome"`, `"edge"`, `"firefox"`, `"safari"`, `"opera"` or `"okhttp"`
+                to pick a random profile of that family
+
+        Raises:
+            ValueError: If the browser family is unknown.
+
+        Examples:
+            ```python
+            from rnet import ImpersonateOption, Impersonate
+
+            option = ImpersonateOption.random_for("safari")
+            option = ImpersonateOption.random_for(Impersonate.FirefoxAndroid135)
+            ```
+        """
+
 class Message:
     r"""
     A WebSocket message.
     """
 
+    type: MessageType
+    r"""
+    Returns the type of the message.
+
+    ```python
+    match message.type:
+        case rnet.MessageType.Text:
+            handle(message.text)
+        case rnet.MessageType.Close:
+            return
+    ```
+    """
     data: Optional[bytes]
     r"""
     Returns the data of the message as bytes.
@@ -1379,47 +2521,158 @@ class Multipart:
     A multipart form for a request.
     """
 
-    def __new__(cls, *parts) -> Multipart:
+    def __new__(
+        cls,
+        *parts: Part,
+        content_type: Optional[str] = None,
+        boundary: Optional[str] = None,
+    ) -> Multipart:
         r"""
         Creates a new multipart form.
-        """
 
-class Part:
-    r"""
-    A part of a multipart form.
-    """
+        # Arguments
+        - `parts` - The parts of the form.
+        - `content_type` - The media type of the form, e.g. `"multipart/related"`.
+          Defaults to `"multipart/form-data"`, or `"multipart/mixed"` when the form
+          is nested inside a `Part`.
+        - `boundary` - An explicit boundary instead of a random one. It must be 1 to
+          70 characters of letters, digits and `'()+_,-./:=? `, and must not end
+          with a space; otherwise `ValueError` is raised. A form with an explicit
+          boundary is sent without a `Content-Length`.
+
+        The form sets the `Content-Type` of the request, so a request that also
+        passes `Content-Type` in `headers` raises `BuilderError`.
 
-    def __new__(
-        cls,
-        name: str,
-        value: Union[
-            str,
-            bytes,
-            Path,
+        # Examples
+
+        ```python
+        import rnet
+
+        form = rnet.Multipart(
+            rnet.Part("metadata", '{"name": "a.txt"}', mime="application/json",
+                      headers={"Content-ID": "<meta>"}),
+            rnet.Part("file", b"hello", mime="text/plain"),
+            content_type="multipart/related",
+            boundary="rnet-boundary",
+        )
+        ```
+        """
+
+    @classmethod
+    def from_dict(
+        cls,
+        fields: Dict[str, Union[str, bytes, Part]],
+        content_type: Optional[str] = None,
+        boundary: Optional[str] = None,
+    ) -> Multipart:
+        r"""
+        Creates a multipart form from a dict of field names to values.
+
+        Strings become text parts and bytes become binary parts. A `Part` is added
+        as it is, under the name of its key. Any other value raises `TypeError`.
+
+        # Arguments
+        - `fields` - The fields of the form, in order.
+        - `content_type` - As for `Multipart()`.
+        - `boundary` - As for `Multipart()`.
+
+        # Examples
+
+        ```python
+        import rnet
+
+        form = rnet.Multipart.from_dict({
+            "name": "rnet",
+            "data": b"\x00\x01",
+            "file": rnet.Part("ignored", b"hello", filename="a.txt", mime="text/plain"),
+        })
+        ```
+        """
+
+class Part:
+    r"""
+    A part of a multipart form.
+    """
+
+    def __new__(
+        cls,
+        name: str,
+        value: Union[
+            str,
+            bytes,
+            os.PathLike[str],
+            typing.BinaryIO,
             typing.AsyncGenerator[bytes, str],
             typing.Generator[bytes, str],
+            Multipart,
         ],
         filename: Optional[str] = None,
         mime: Optional[str] = None,
+        headers: Optional[Union[Dict[str, str], HeaderMap, List[Tuple[str, str]]]] = None,
     ) -> Part:
         r"""
         Creates a new part.
 
         # Arguments
         - `name` - The name of the part.
-        - `value` - The value of the part, either text, bytes, a file path, or a async or sync stream.
+        - `value` - The value of the part, either text, bytes, a file path (`os.PathLike`), a
+          file-like object with `read(size)`, a async or sync stream, or a nested `Multipart` form.
+        - `filename` - The filename of the part.
+        - `mime` - The MIME type of the part.
+        - `headers` - Extra headers for the part, e.g. `Content-ID` or
+          `Content-Transfer-Encoding`. Names and values are validated as for request
+          headers. `Content-Type` may be given here instead of with `mime`, but not with
+          both. `Content-Disposition` is always written from `name` and `filename`, and
+          custom parameters for it aren't supported, so passing it raises `ValueError`.
+        """
+
+    @staticmethod
+    def from_stream(
+        name: str,
+        stream: Union[
+            typing.BinaryIO,
+            typing.AsyncGenerator[bytes, str],
+            typing.Generator[bytes, str],
+        ],
+        length: Optional[int] = None,
+        filename: Optional[str] = None,
+        mime: Optional[str] = None,
+        headers: Optional[Union[Dict[str, str], HeaderMap, List[Tuple[str, str]]]] = None,
+    ) -> Part:
+        r"""
+        Creates a new part whose value is read from a sync or async iterator of bytes.
+
+        # Arguments
+        - `name` - The name of the part.
+        - `stream` - A file-like object with `read(size)`, or an iterator yielding the chunks
+          of the value. File-like objects are read on a worker thread.
+        - `length` - The total length of the value, if known. When every part of the
+          form has a known length the form is sent with a `Content-Length`.
         - `filename` - The filename of the part.
         - `mime` - The MIME type of the part.
+        - `headers` - Extra headers for the part, as for `Part()`.
         """
 
 class Proxy:
     r"""
     A proxy server for a request.
     Supports HTTP, HTTPS, SOCKS4, SOCKS4a, SOCKS5, and SOCKS5h protocols.
+
+    A client tries its proxies in order and uses the first one that applies to a request,
+    so proxies limited with `matches` route some hosts through their own proxy:
+
+    ```python
+    import rnet
+
+    client = rnet.Client(proxies=[
+        rnet.Proxy.http("http://p1:8080", matches="*.google.com"),
+        rnet.Proxy.all("socks5://p2:1080", exclusion="localhost"),
+    ])
+    ```
     """
 
     @staticmethod
-    def http(**kwargs: Unpack[ProxyParams]) -> Proxy:
+    def http(url: str, **kwargs: Unpack[ProxyParams]) -> Proxy:
         r"""
         Creates a new HTTP proxy.
 
@@ -1428,11 +2681,17 @@ class Proxy:
         # Arguments
 
         * `url` - The URL of the proxy server.
-        * `username` - Optional username for proxy authentication.
-        * `password` - Optional password for proxy authentication.
+        * `username` - Optional username for proxy authentication, used as is rather than
+          percent-encoded into `url`. Overrides credentials in `url`.
+        * `password` - Optional password for proxy authentication, empty if only `username`
+          is given. SOCKS4 proxies take neither.
         * `custom_http_auth` - Optional custom HTTP proxy authentication header value.
         * `custom_http_headers` - Optional custom HTTP proxy headers.
         * `exclusion` - Optional List of domains to exclude from proxying.
+        * `matches` - Optional host pattern, or list of them, limiting the proxy to those
+          hosts. `*` matches any run of characters, so `"*.google.com"` matches
+          `www.google.com` but not `google.com`. Requests to other hosts try the next proxy
+          of the client, or go direct. Can't be combined with `custom_http_headers`.
 
         # Examples
 
@@ -1444,7 +2703,7 @@ class Proxy:
         """
 
     @staticmethod
-    def https(**kwargs: Unpack[ProxyParams]) -> Proxy:
+    def https(url: str, **kwargs: Unpack[ProxyParams]) -> Proxy:
         r"""
         Creates a new HTTPS proxy.
 
@@ -1453,11 +2712,17 @@ class Proxy:
         # Arguments
 
         * `url` - The URL of the proxy server.
-        * `username` - Optional username for proxy authentication.
-        * `password` - Optional password for proxy authentication.
+        * `username` - Optional username for proxy authentication, used as is rather than
+          percent-encoded into `url`. Overrides credentials in `url`.
+        * `password` - Optional password for proxy authentication, empty if only `username`
+          is given. SOCKS4 proxies take neither.
         * `custom_http_auth` - Optional custom HTTP proxy authentication header value.
         * `custom_http_headers` - Optional custom HTTP proxy headers.
         * `exclusion` - Optional List of domains to exclude from proxying.
+        * `matches` - Optional host pattern, or list of them, limiting the proxy to those
+          hosts. `*` matches any run of characters, so `"*.google.com"` matches
+          `www.google.com` but not `google.com`. Requests to other hosts try the next proxy
+          of the client, or go direct. Can't be combined with `custom_http_headers`.
 
         # Examples
 
@@ -1469,7 +2734,7 @@ class Proxy:
         """
 
     @staticmethod
-    def all(**kwargs: Unpack[ProxyParams]) -> Proxy:
+    def all(url: str, **kwargs: Unpack[ProxyParams]) -> Proxy:
         r"""
         Creates a new proxy for all protocols.
 
@@ -1478,11 +2743,17 @@ class Proxy:
         # Arguments
 
         * `url` - The URL of the proxy server.
-        * `username` - Optional username for proxy authentication.
-        * `password` - Optional password for proxy authentication.
+        * `username` - Optional username for proxy authentication, used as is rather than
+          percent-encoded into `url`. Overrides credentials in `url`.
+        * `password` - Optional password for proxy authentication, empty if only `username`
+          is given. SOCKS4 proxies take neither.
         * `custom_http_auth` - Optional custom HTTP proxy authentication header value.
         * `custom_http_headers` - Optional custom HTTP proxy headers.
         * `exclusion` - Optional List of domains to exclude from proxying.
+        * `matches` - Optional host pattern, or list of them, limiting the proxy to those
+          hosts. `*` matches any run of characters, so `"*.google.com"` matches
+          `www.google.com` but not `google.com`. Requests to other hosts try the next proxy
+          of the client, or go direct. Can't be combined with `custom_http_headers`.
 
         # Examples
 
@@ -1493,6 +2764,94 @@ class Proxy:
         ```
         """
 
+    @staticmethod
+    def from_pac(
+        source: Union[str, os.PathLike[str]],
+        client: Optional[Union[Client, BlockingClient]] = None,
+    ) -> Proxy:
+        r"""
+        Creates a proxy chosen per request by the `FindProxyForURL(url, host)` function of a
+        proxy auto-config (PAC) script.
+
+        # Arguments
+
+        * `source` - The path of the script, or an `http`, `https` or `file` URL to load it
+          from. It is loaded once, when the proxy is created.
+        * `client` - The client to fetch an `http` or `https` script with, so that its TLS
+          and proxy settings apply. A default client is used if not given.
+
+        Fetching a script blocks, so it can't be done while an asyncio event loop is running
+        in the thread; fetch the script with `await client.get(url)` instead and pass it to
+        `from_pac_script`.
+
+        The first proxy the script returns is used: `PROXY` and `HTTP` give an HTTP proxy,
+        `HTTPS` an HTTPS one, `SOCKS` and `SOCKS5` a SOCKS5 one and `SOCKS4` a SOCKS4 one.
+        `DIRECT` sends the request direct. A request the script fails for, or returns
+        neither for, raises `ProxyError` with the script's error instead of going direct.
+        WebSocket and tunnel requests fail to connect instead, and the failure is logged as
+        a warning.
+
+        Only the subset of JavaScript PAC scripts are commonly written in is understood:
+        functions, `var`, `if`/`else` and `return`, the usual operators, a few string
+        methods and the standard helpers, such as `shExpMatch`, `dnsDomainIs` and
+        `isInNet`, apart from the date and time ones. The `url` argument only has the
+        scheme, host and port of the request, not its path. `dnsResolve`, `isResolvable`
+        and `isInNet` look hosts up with the system resolver, blocking while they do.
+
+        # Raises
+
+        * `ValueError` - If the script uses anything else or doesn't define
+          `FindProxyForURL`.
+        * `RuntimeError` - If a script would be fetched while an event loop is running.
+
+        # Examples
+
+        ```python
+        import rnet
+
+        client = rnet.Client(proxies=[rnet.Proxy.from_pac("proxy.pac")])
+        ```
+        """
+
+    @staticmethod
+    def from_pac_script(script: str) -> Proxy:
+        r"""
+        Creates a proxy chosen per request by the `FindProxyForURL(url, host)` function of
+        the PAC script `script`, like `from_pac`.
+
+        # Raises
+
+        * `ValueError` - If the script isn't one `from_pac` understands.
+
+        # Examples
+
+        ```python
+        import rnet
+
+        script = await (await client.get("http://wpad/wpad.dat")).text()
+        client = rnet.Client(proxies=[rnet.Proxy.from_pac_script(script)])
+        ```
+        """
+
+    @staticmethod
+    def find_proxy_for_url(script: str, url: str) -> str:
+        r"""
+        Returns what the `FindProxyForURL(url, host)` function of the PAC script `script`
+        returns for `url`, such as `"PROXY proxy:8080; DIRECT"`.
+
+        # Raises
+
+        * `ValueError` - If the script isn't one `from_pac` understands, or fails.
+
+        # Examples
+
+        ```python
+        import rnet
+
+        print(rnet.Proxy.find_proxy_for_url(script, "http://example.com/"))
+        ```
+        """
+
 class Response:
     r"""
     A response from a request.
@@ -1521,7 +2880,7 @@ class Response:
     ```
     """
 
-    url: str
+    url: Url
     r"""
     Returns the URL of the response.
     """
@@ -1529,6 +2888,27 @@ class Response:
     r"""
     Returns whether the response is successful.
     """
+    is_informational: bool
+    r"""
+    Returns whether the response is informational (1xx).
+    """
+    is_redirect: bool
+    r"""
+    Returns whether the response redirects: a 301, 302, 303, 307 or 308 status with a
+    `Location` header.
+    """
+    is_permanent_redirect: bool
+    r"""
+    Returns whether the response is a permanent redirect (301 or 308) with a `Location`.
+    """
+    is_client_error: bool
+    r"""
+    Returns whether the response is a client error (4xx).
+    """
+    is_server_error: bool
+    r"""
+    Returns whether the response is a server error (5xx).
+    """
     status: int
     r"""
     Returns the status code as integer of the response.
@@ -1552,15 +2932,117 @@ class Response:
     content_length: int
     r"""
     Returns the content length of the response.
+
+    This is the length of the body as received, so it's `0` for a body the client
+    decoded, and the encoded length for one left encoded with `decompress=False`.
+    """
+    content_encoding: Optional[str]
+    r"""
+    Returns the `Content-Encoding` the body is still encoded with, or `None` if it isn't
+    or the client decoded it.
+    """
+    content_range: Optional[Tuple[int, int, Optional[int]]]
+    r"""
+    Returns the `Content-Range` of a `206 Partial Content` response as
+    `(start, end, total)`, with `end` inclusive and `total` `None` if unknown.
     """
     remote_addr: Optional[SocketAddr]
     r"""
     Returns the remote address of the response.
     """
+    bytes_sent: int
+    r"""
+    Returns an estimate of the bytes sent for the request: the size of the request head as
+    HTTP/1.1 text plus the body. It isn't measured on the wire, so it leaves out TLS and
+    HTTP/2 framing and header compression.
+    """
+    bytes_received: int
+    r"""
+    Returns an estimate of the bytes received so far: the size of the response head as
+    HTTP/1.1 text plus the body read, after decompression. Like `bytes_sent`, it isn't
+    measured on the wire.
+    """
+    from_cache: bool
+    r"""
+    Returns whether the response was served from the client's cache, either because the
+    stored copy was fresh or because the server confirmed it with `304 Not Modified`.
+    """
+    connection_reused: Optional[bool]
+    r"""
+    Returns whether the request was sent over a connection taken from the client's pool.
+
+    This is inferred from the connections the client's pool opened while the request was
+    in flight, since the pool doesn't report it. It's `None` when that can't be told, because
+    a connection was opened while other requests were in flight, and for responses that
+    didn't come from the network through a `Client`. Impersonated and `without_proxy`
+    requests use pools of their own, which are tracked separately.
+    """
+    connection_id: Optional[int]
+    r"""
+    Returns an opaque id of the connection the response came over, the same for every
+    response over one pooled connection, or `None` when it can't be told which connection
+    that was, such as when several were opened to the same address.
+    """
+    timing: Optional[dict[str, Optional[float]]]
+    r"""
+    Returns how long the phases of the request took, in seconds, like curl's
+    `--write-out` timings, or `None` if the response didn't come from the network.
+
+    - `dns`: resolving the host.
+    - `connect`: opening the connection once the host was resolved, including
+      the TLS handshake, which isn't timed on its own.
+    - `tls`: always `None`, as the TLS handshake is counted in `connect`.
+    - `time_to_first_byte`: from sending the request to receiving the response head.
+    - `total`: from sending the request to reading the body to its end, `None`
+      until it's read.
+
+    `dns` and `connect` are `None` when the request went over a pooled
+    connection, and `dns` is when the host is an IP address. After redirects,
+    the timing is the one of the last request.
+    """
+    history: list[str]
+    r"""
+    Returns the URLs the request was redirected from, in order, including meta refreshes
+    followed with `follow_meta_refresh=True`. The final URL is `url`.
+    """
+    request_id: Optional[str]
+    r"""
+    Returns the id of the request if the client has a `request_id_header`: the server's
+    if it answered with a different one in that header, otherwise the one sent.
+    """
+    client_request_id: Optional[str]
+    r"""
+    Returns the id sent in the client's `request_id_header`.
+    """
     encoding: str
     r"""
-    Encoding to decode with when accessing text.
+    Encoding to decode with when accessing text, or the one detected from the body once
+    it has been read with `text(detect_encoding=True)` or `text(autodetect=True)`.
     """
+    @staticmethod
+    def build(
+        status: int = 200,
+        headers: Optional[Union[Dict[str, str], HeaderMap, List[Tuple[str, str]]]] = None,
+        body: Optional[Union[str, bytes]] = None,
+        url: str = "http://test",
+        version: Version = Version.HTTP_11,
+    ) -> Response:
+        r"""
+        Builds a response in memory, without a request, for testing code that handles
+        responses. Its body, headers and cookies behave like those of a real response.
+
+        # Examples
+
+        ```python
+        response = rnet.Response.build(
+            status=404,
+            headers={"content-type": "application/json"},
+            body=b'{"error": "not found"}',
+        )
+        assert await response.json() == {"error": "not found"}
+        ```
+        """
+
     def __aenter__(self) -> Any: ...
     def __aexit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> Any: ...
     def peer_certificate(self) -> Optional[bytes]:
@@ -1568,18 +3050,53 @@ class Response:
         Returns the TLS peer certificate of the response.
         """
 
-    async def text(self) -> str:
+    async def text(
+        self,
+        errors: Literal["strict", "replace", "ignore"] = "replace",
+        detect_encoding: bool = False,
+        autodetect: bool = False,
+        encoding: Optional[Literal["auto", "detect"]] = None,
+    ) -> str:
         r"""
         Returns the text content of the response.
+
+        # Arguments
+
+        * `errors` - How malformed bytes are handled, as in `bytes.decode`: replaced with
+          U+FFFD, dropped, or raised as a `DecodingError` with `"strict"`.
+        * `detect_encoding` - Detect the charset from a byte order mark or the statistics of
+          the first 64 KB of the body, instead of trusting the `Content-Type`, which pages
+          sometimes get wrong. The detected charset is then reported by `encoding`.
+        * `autodetect` - Detect the charset as with `detect_encoding`, but only if the
+          `Content-Type` doesn't declare one, e.g. for plain text or CSV from legacy
+          systems. Without it, such bodies are decoded as UTF-8.
+        * `encoding` - The same choice as a string: `"detect"` for `detect_encoding=True`
+          and `"auto"` for `autodetect=True`. Use `text_with_charset` to decode with a
+          given charset.
+        """
+
+    async def detect_encoding(self) -> Tuple[str, bool]:
+        r"""
+        Guesses the charset of the body from its byte order mark, or else from the
+        statistics of its first 64 KB, entirely in Rust. The sampled bytes are left in
+        place, so the body can still be read afterwards.
+
+        Returns the name of the charset, as reported by `encoding`, and whether the
+        guess is confident; an unconfident guess is likely to be wrong.
         """
 
-    async def text_with_charset(self, encoding: str) -> str:
+    async def text_with_charset(
+        self,
+        encoding: str,
+        errors: Literal["strict", "replace", "ignore"] = "replace",
+    ) -> str:
         r"""
         Returns the text content of the response with a specific charset.
 
         # Arguments
 
         * `encoding` - The default encoding to use if the charset is not specified.
+        * `errors` - How malformed bytes are handled, as in `text`.
         """
 
     async def json(self) -> Dict[str, Any]:
@@ -1587,19 +3104,127 @@ class Response:
         Returns the JSON content of the response.
         """
 
+    def json_items(self) -> JsonItems:
+        r"""
+        Iterates over the items of the top-level JSON array of the response, parsing each
+        as soon as it has arrived. Unlike `json()`, the body is never held in memory as a
+        whole, which suits very large arrays.
+
+        Raises `DecodingError` if the root isn't an array or the document is malformed.
+
+        # Examples
+
+        ```python
+        async for record in response.json_items():
+            print(record["id"])
+        ```
+        """
+
+    def events(self) -> Events:
+        r"""
+        Iterates over the Server-Sent Events of a `text/event-stream` response, parsing
+        each as soon as its terminating blank line has arrived.
+
+        Parsing follows the HTML event stream rules: `data` lines are joined with `\n`,
+        comment lines starting with `:` are skipped, an event without data isn't
+        dispatched, and an event cut off by the end of the stream is discarded. The body
+        is decoded as UTF-8 whatever the `Content-Type` says.
+
+        # Examples
+
+        ```python
+        response = await client.post(url, json=payload)
+        async for event in response.events():
+            if event.data == "[DONE]":
+                break
+            print(event.json()["choices"][0]["delta"])
+        ```
+        """
+
+    def parts(self) -> Parts:
+        r"""
+        Iterates over the `(headers, body)` parts of a `multipart/*` response, such as
+        the `multipart/byteranges` answer to a multi-range request or a `multipart/mixed`
+        batch response. Each part is yielded once its closing boundary has arrived.
+
+        The boundary is taken from the `Content-Type`; `DecodingError` is raised if it
+        isn't multipart with a boundary, or if the body is cut off before its closing
+        boundary. The preamble and epilogue are skipped, and lines may end with `\r\n`
+        or a bare `\n`.
+
+        # Examples
+
+        ```python
+        response = await client.get(url, headers={"Range": "bytes=0-99,200-299"})
+        async for headers, body in response.parts():
+            print(headers["Content-Range"], len(body))
+        ```
+        """
+
+    async def save(self, path: Union[str, os.PathLike[str]]) -> int:
+        r"""
+        Writes the body into the file at `path` as it arrives, without holding it
+        in memory, replacing the file. Returns the size of the file.
+
+        The body is written to `path` with `.tmp` appended and renamed over `path` once
+        it's complete, so a failed read leaves any file already there untouched.
+
+        # Examples
+
+        ```python
+        response = await client.get("https://example.com/data.csv")
+        await response.save("data.csv")
+        ```
+        """
+
+    async def save_json(self, path: Union[str, os.PathLike[str]]) -> int:
+        r"""
+        Parses the body as JSON and writes it into the file at `path` indented by
+        two spaces, keeping the order of object keys, replacing the file. Returns
+        the size of the file.
+        """
+
     async def bytes(self) -> bytes:
         r"""
         Returns the bytes content of the response.
         """
 
-    def stream(self) -> Streamer:
+    async def read_buffer(self) -> BytesBuffer:
+        r"""
+        Returns the body as a read-only `BytesBuffer` without copying it into `bytes`.
+
+        `memoryview(buffer)` exposes the body in place, which keeps peak memory at one
+        copy of the body for large downloads. The memory is released once the buffer and
+        every view into it are gone. Use `bytes(buffer)` when an owned copy is needed.
+
+        # Examples
+
+        ```python
+        buffer = await response.read_buffer()
+        view = memoryview(buffer)
+        header = view[:16].tobytes()
+        ```
+        """
+
+    def stream(self, zero_copy: bool = False) -> Streamer:
         r"""
         Convert the response into a `Stream` of `Bytes` from the body.
+
+        Each chunk is awaited for at most the request's `read_timeout`, if it has one,
+        after which `TimeoutError` is raised.
+
+        # Arguments
+
+        * `zero_copy` - Yield each chunk as a `BytesBuffer` instead of `bytes`.
         """
 
     async def close(self) -> None:
         r"""
         Closes the response connection.
+
+        A response dropped with its body neither read nor closed emits a
+        `ResourceWarning`, as its connection isn't returned to the pool until then.
+        See `strict_resources`.
         """
 
 class SocketAddr:
@@ -1611,7 +3236,8 @@ class SocketAddr:
     def __repr__(self) -> str: ...
     def ip(self) -> Union[ipaddress.IPv4Address, ipaddress.IPv6Address]:
         r"""
-        Returns the IP address of the socket address.
+        Returns the IP address of the socket address. A scoped IPv6 address keeps its
+        scope id as its zone.
         """
 
     def port(self) -> int:
@@ -1619,13 +3245,182 @@ class SocketAddr:
         Returns the port number of the socket address.
         """
 
+class Url:
+    r"""
+    A parsed URL.
+
+    Compares and hashes like its string form, so `response.url == "https://..."` works,
+    and `str(url)` gives the URL back. A `Url` can be passed anywhere a request takes a
+    URL string.
+    """
+
+    def __init__(self, url: str) -> None:
+        r"""
+        Parses `url`, which must be absolute.
+        """
+
+    @property
+    def scheme(self) -> str:
+        r"""
+        The scheme, such as `"https"`.
+        """
+
+    @property
+    def host(self) -> Optional[str]:
+        r"""
+        The host, if the URL has one.
+        """
+
+    @property
+    def port(self) -> Optional[int]:
+        r"""
+        The port written in the URL, or `None` if it is omitted or the default port of
+        the scheme.
+        """
+
+    @property
+    def path(self) -> str:
+        r"""
+        The path, which is `"/"` for an empty path of an `http` URL.
+        """
+
+    @property
+    def query(self) -> Optional[str]:
+        r"""
+        The query without its leading `?`, if the URL has one.
+        """
+
+    @property
+    def fragment(self) -> Optional[str]:
+        r"""
+        The fragment without its leading `#`, if the URL has one.
+        """
+
+    def query_params(self) -> Dict[str, str]:
+        r"""
+        Returns the decoded query parameters, in order. When a name is repeated, its last
+        value is kept.
+        """
+
+    def join(self, relative: str) -> Url:
+        r"""
+        Resolves `relative` against this URL, as a link on its page would be.
+        """
+
+    def with_query(
+        self, params: Union[Dict[str, Any], List[Tuple[str, Any]]]
+    ) -> Url:
+        r"""
+        Returns a copy of this URL with its query replaced by `params`. Values are
+        converted with `str()`, and no params removes the query.
+        """
+
+    def with_fragment(self, fragment: Optional[str] = None) -> Url:
+        r"""
+        Returns a copy of this URL with its fragment replaced, or removed if `None`.
+        """
+
+    def __str__(self) -> str: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+
 class StatusCode:
     r"""
     HTTP status code.
+
+    Compares and hashes like its integer value, so `status == 404` works.
+    `str(status)` gives the code with its reason phrase, such as `"404 Not Found"`.
+    The standard codes are class attributes, such as `StatusCode.NOT_FOUND`.
     """
 
+    CONTINUE: StatusCode
+    SWITCHING_PROTOCOLS: StatusCode
+    PROCESSING: StatusCode
+    EARLY_HINTS: StatusCode
+    OK: StatusCode
+    CREATED: StatusCode
+    ACCEPTED: StatusCode
+    NON_AUTHORITATIVE_INFORMATION: StatusCode
+    NO_CONTENT: StatusCode
+    RESET_CONTENT: StatusCode
+    PARTIAL_CONTENT: StatusCode
+    MULTI_STATUS: StatusCode
+    ALREADY_REPORTED: StatusCode
+    IM_USED: StatusCode
+    MULTIPLE_CHOICES: StatusCode
+    MOVED_PERMANENTLY: StatusCode
+    FOUND: StatusCode
+    SEE_OTHER: StatusCode
+    NOT_MODIFIED: StatusCode
+    USE_PROXY: StatusCode
+    TEMPORARY_REDIRECT: StatusCode
+    PERMANENT_REDIRECT: StatusCode
+    BAD_REQUEST: StatusCode
+    UNAUTHORIZED: StatusCode
+    PAYMENT_REQUIRED: StatusCode
+    FORBIDDEN: StatusCode
+    NOT_FOUND: StatusCode
+    METHOD_NOT_ALLOWED: StatusCode
+    NOT_ACCEPTABLE: StatusCode
+    PROXY_AUTHENTICATION_REQUIRED: StatusCode
+    REQUEST_TIMEOUT: StatusCode
+    CONFLICT: StatusCode
+    GONE: StatusCode
+    LENGTH_REQUIRED: StatusCode
+    PRECONDITION_FAILED: StatusCode
+    PAYLOAD_TOO_LARGE: StatusCode
+    URI_TOO_LONG: StatusCode
+    UNSUPPORTED_MEDIA_TYPE: StatusCode
+    RANGE_NOT_SATISFIABLE: StatusCode
+    EXPECTATION_FAILED: StatusCode
+    IM_A_TEAPOT: StatusCode
+    MISDIRECTED_REQUEST: StatusCode
+    UNPROCESSABLE_ENTITY: StatusCode
+    LOCKED: StatusCode
+    FAILED_DEPENDENCY: StatusCode
+    TOO_EARLY: StatusCode
+    UPGRADE_REQUIRED: StatusCode
+    PRECONDITION_REQUIRED: StatusCode
+    TOO_MANY_REQUESTS: StatusCode
+    REQUEST_HEADER_FIELDS_TOO_LARGE: StatusCode
+    UNAVAILABLE_FOR_LEGAL_REASONS: StatusCode
+    INTERNAL_SERVER_ERROR: StatusCode
+    NOT_IMPLEMENTED: StatusCode
+    BAD_GATEWAY: StatusCode
+    SERVICE_UNAVAILABLE: StatusCode
+    GATEWAY_TIMEOUT: StatusCode
+    HTTP_VERSION_NOT_SUPPORTED: StatusCode
+    VARIANT_ALSO_NEGOTIATES: StatusCode
+    INSUFFICIENT_STORAGE: StatusCode
+    LOOP_DETECTED: StatusCode
+    NOT_EXTENDED: StatusCode
+    NETWORK_AUTHENTICATION_REQUIRED: StatusCode
+
+
     def __str__(self) -> str: ...
     def __repr__(self) -> str: ...
+    def __int__(self) -> int: ...
+    def __index__(self) -> int: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: Union[StatusCode, int]) -> bool: ...
+    def __le__(self, other: Union[StatusCode, int]) -> bool: ...
+    def __gt__(self, other: Union[StatusCode, int]) -> bool: ...
+    def __ge__(self, other: Union[StatusCode, int]) -> bool: ...
+    @property
+    def reason(self) -> Optional[str]:
+        r"""
+        The standard reason phrase, such as `"Not Found"`, if the status has one.
+        """
+
+    @property
+    def reason_phrase(self) -> Optional[str]:
+        r"""
+        Alias of `reason`.
+        """
+
     def as_int(self) -> int:
         r"""
         Return the status code as an integer.
@@ -1646,6 +3441,16 @@ class StatusCode:
         Check if status is within 300-399.
         """
 
+    def is_redirect(self) -> bool:
+        r"""
+        Check if status is one that redirects: 301, 302, 303, 307 or 308.
+        """
+
+    def is_permanent_redirect(self) -> bool:
+        r"""
+        Check if status is a permanent redirect: 301 or 308.
+        """
+
     def is_client_error(self) -> bool:
         r"""
         Check if status is within 400-499.
@@ -1656,6 +3461,22 @@ class StatusCode:
         Check if status is within 500-599.
         """
 
+class BytesBuffer:
+    r"""
+    A read-only buffer over bytes received from the network.
+
+    Supports the buffer protocol, so `memoryview(buffer)` reads the data without
+    copying it. The data stays alive while the buffer or any view into it exists.
+    """
+
+    def __len__(self) -> int: ...
+    def __bytes__(self) -> bytes:
+        r"""
+        Copies the data into a new `bytes` object.
+        """
+
+    def __buffer__(self, flags: int) -> memoryview: ...
+
 class Streamer:
     r"""
     A byte stream response.
@@ -1664,6 +3485,13 @@ class Streamer:
     Implemented in the `stream` method of the `Response` class.
     Can be used in an asynchronous for loop in Python.
 
+    A stream has a single consumer at a time: awaiting `__anext__` while another
+    task is already reading raises a `RuntimeError` instead of waiting.
+
+    Cancelling a pending read (e.g. with `asyncio.wait_for`) aborts the download
+    and closes the underlying connection. Later reads raise `BodyError` rather than
+    look like the end of the stream, which would silently truncate the body.
+
     # Examples
 
     ```python
@@ -1693,236 +3521,876 @@ class Streamer:
 
     def __aiter__(self) -> Streamer: ...
     def __anext__(self) -> Any: ...
+    async def read(self, n: int) -> bytes:
+        r"""
+        Reads exactly `n` bytes, or fewer if the stream ends first.
+
+        Chunks are buffered as needed; bytes read past `n` are returned first by
+        the next read or iteration, so reads and `__anext__` can be mixed freely.
+        Returns `b""` once the stream is exhausted or closed. With `zero_copy`,
+        a `BytesBuffer` is returned instead of `bytes`.
+        """
+
+    async def readexactly(self, n: int) -> bytes:
+        r"""
+        Reads exactly `n` bytes, like `read`, for protocols with fixed-size fields.
+
+        Raises `BodyError` if the stream ends first. The bytes that did arrive are
+        left for the next read, so `read_all` still returns them.
+        """
+
+    async def read_all(self) -> bytes:
+        r"""
+        Reads the rest of the stream into a single buffer, including anything
+        left over by an earlier `read`.
+
+        If the download fails, the bytes read so far are kept for the next read.
+        """
+
+    def framed(self, prefix_len: int = 4, big_endian: bool = True) -> Frames:
+        r"""
+        Returns an async iterator over the payloads of the length-prefixed frames in
+        the rest of the stream, reassembled however the frames are split into chunks.
+
+        It reads from this stream, so it can be mixed with `read` and `__anext__`,
+        e.g. to skip a header first.
+
+        # Arguments
+
+        * `prefix_len` - The size of the length prefix of each frame, from 1 to 8 bytes.
+        * `big_endian` - Whether the length prefix is big-endian.
+
+        A stream that ends inside a frame raises `DecodingError` and ends the
+        iteration. Each frame is buffered whole, so a length prefix from an untrusted
+        server can make it buffer up to that many bytes. With `zero_copy`, payloads
+        are `BytesBuffer`s instead of `bytes`.
+
+        # Examples
+
+        ```python
+        async with (await client.get(url)).stream() as streamer:
+            async for payload in streamer.framed(prefix_len=2, big_endian=False):
+                print(payload)
+        ```
+        """
+
     def __aenter__(self) -> Any: ...
     def __aexit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> Any: ...
 
-class WebSocket:
+class Frames:
     r"""
-    A WebSocket response.
+    An asynchronous iterator over the payloads of the length-prefixed frames in a
+    stream. Implemented in the `framed` method of the `Streamer` class.
+
+    Like `Streamer`, it has a single consumer at a time, and cancelling a pending
+    read closes the connection.
     """
 
-    ok: bool
+    def __aiter__(self) -> Frames: ...
+    def __anext__(self) -> Any: ...
+    def __aenter__(self) -> Any: ...
+    def __aexit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> Any: ...
+
+class Event:
     r"""
-    Returns whether the response is successful.
+    A Server-Sent Event, as yielded by `Response.events()`.
     """
-    status: int
+
+    event: str
     r"""
-    Returns the status code as integer of the response.
+    The type of the event, `"message"` unless its `event` field names another.
     """
-    status_code: StatusCode
+    data: str
     r"""
-    Returns the status code of the response.
+    The data of the event, with the values of its `data` lines joined by `\n`.
     """
-    version: Version
+    id: Optional[str]
     r"""
-    Returns the HTTP version of the response.
+    The last event id the stream has set, which carries over to later events, as the
+    `Last-Event-ID` to resume from.
     """
-    headers: HeaderMap
+    retry: Optional[int]
     r"""
-    Returns the headers of the response.
+    The reconnection time in milliseconds set since the previous event, if any.
     """
-    cookies: List[Cookie]
+
+    def json(self) -> Any:
+        r"""
+        Parses the data of the event as JSON, raising `DecodingError` if it isn't.
+        """
+
+class Events:
     r"""
-    Returns the cookies of the response.
+    An asynchronous iterator over the events of a `text/event-stream` response.
+    Implemented in the `events` method of the `Response` class.
+
+    Like `Streamer`, it has a single consumer at a time, and cancelling a pending read
+    closes the connection.
     """
-    remote_addr: Optional[SocketAddr]
+
+    def __aiter__(self) -> Events: ...
+    def __anext__(self) -> Event: ...
+    def __aenter__(self) -> Any: ...
+    def __aexit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> Any: ...
+
+class Parts:
     r"""
-    Returns the remote address of the response.
+    An asynchronous iterator over the `(headers, body)` parts of a `multipart/*` response.
+    Implemented in the `parts` method of the `Response` class.
+
+    Like `Streamer`, it has a single consumer at a time, and cancelling a pending read
+    closes the connection.
     """
-    protocol: Optional[str]
+
+    def __aiter__(self) -> Parts: ...
+    def __anext__(self) -> Tuple[HeaderMap, bytes]: ...
+    def __aenter__(self) -> Any: ...
+    def __aexit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> Any: ...
+
+class JsonItems:
     r"""
-    Returns the WebSocket protocol.
+    An asynchronous iterator over the items of a JSON array streamed from a response.
+    Implemented in the `json_items` method of the `Response` class.
+
+    Like `Streamer`, it has a single consumer at a time, and cancelling a pending read
+    closes the connection.
     """
-    def __aiter__(self) -> WebSocket: ...
+
+    def __aiter__(self) -> JsonItems: ...
     def __anext__(self) -> Any: ...
     def __aenter__(self) -> Any: ...
     def __aexit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> Any: ...
-    async def recv(self) -> Optional[Message]:
-        r"""
-        Receives a message from the WebSocket.
-        """
 
-    async def send(self, message: Message) -> None:
-        r"""
-        Sends a message to the WebSocket.
+class Cache:
+    r"""
+    An HTTP cache (RFC 9111) for the requests of a client.
 
-        # Arguments
+    `GET` responses that carry `Cache-Control: max-age`, `Expires`, `ETag` or
+    `Last-Modified` are stored, unless they are marked `no-store`. Fresh responses are
+    served without contacting the server; stale ones are revalidated with
+    `If-None-Match`/`If-Modified-Since`, and the stored body is returned on
+    `304 Not Modified`. Successful non-`GET` requests drop the entries for their URL.
 
-        * `message` - The message to send.
-        """
+    Entries are keyed on the method and URL, and on the request headers the response
+    names in `Vary`, so each variant is stored separately.
 
-    async def close(
-        self,
-        code: Optional[int] = None,
-        reason: Optional[str] = None,
-    ) -> None:
-        r"""
-        Closes the WebSocket connection.
+    Responses that are stored are read in full before the request returns. A body that
+    turns out larger than the cache while it's read is handed out unstored.
 
-        # Arguments
+    `Client(cache=True)` creates a default cache, and `cache_dir=...` persists it in a
+    directory; pass a `Cache` to configure its size.
 
-        * `code` - An optional close code.
-        * `reason` - An optional reason for closing.
-        """
+    # Examples
 
-class Impersonate(Enum):
-    r"""
-    An impersonate.
+    ```python
+    import rnet
+
+    client = rnet.Client(cache=rnet.Cache(max_size_mb=128))
+    response = await client.get("https://example.com")
+    print(response.from_cache)
+    ```
     """
 
-    Chrome100 = auto()
-    Chrome101 = auto()
-    Chrome104 = auto()
-    Chrome105 = auto()
-    Chrome106 = auto()
-    Chrome107 = auto()
-    Chrome108 = auto()
-    Chrome109 = auto()
-    Chrome110 = auto()
-    Chrome114 = auto()
-    Chrome116 = auto()
-    Chrome117 = auto()
-    Chrome118 = auto()
-    Chrome119 = auto()
-    Chrome120 = auto()
-    Chrome123 = auto()
-    Chrome124 = auto()
-    Chrome126 = auto()
-    Chrome127 = auto()
-    Chrome128 = auto()
-    Chrome129 = auto()
-    Chrome130 = auto()
-    Chrome131 = auto()
-    Chrome132 = auto()
-    Chrome133 = auto()
-    Chrome134 = auto()
-    Chrome135 = auto()
-    Chrome136 = auto()
-    Edge101 = auto()
-    Edge122 = auto()
-    Edge127 = auto()
-    Edge131 = auto()
-    Edge134 = auto()
-    Firefox109 = auto()
-    Firefox117 = auto()
-    Firefox128 = auto()
-    Firefox133 = auto()
-    Firefox135 = auto()
-    FirefoxPrivate135 = auto()
-    FirefoxAndroid135 = auto()
-    Firefox136 = auto()
-    FirefoxPrivate136 = auto()
-    SafariIos17_2 = auto()
-    SafariIos17_4_1 = auto()
-    SafariIos16_5 = auto()
-    Safari15_3 = auto()
-    Safari15_5 = auto()
-    Safari15_6_1 = auto()
-    Safari16 = auto()
-    Safari16_5 = auto()
-    Safari17_0 = auto()
-    Safari17_2_1 = auto()
-    Safari17_4_1 = auto()
-    Safari17_5 = auto()
-    Safari18 = auto()
-    SafariIPad18 = auto()
-    Safari18_2 = auto()
-    Safari18_3 = auto()
-    Safari18_3_1 = auto()
-    SafariIos18_1_1 = auto()
-    OkHttp3_9 = auto()
-    OkHttp3_11 = auto()
-    OkHttp3_13 = auto()
-    OkHttp3_14 = auto()
-    OkHttp4_9 = auto()
-    OkHttp4_10 = auto()
-    OkHttp4_12 = auto()
-    OkHttp5 = auto()
-    Opera116 = auto()
-    Opera117 = auto()
-    Opera118 = auto()
-    Opera119 = auto()
+    def __new__(cls, max_size_mb: int = 64, directory: Optional[Union[str, os.PathLike[str]]] = None) -> Cache:
+        r"""
+        Creates a new cache.
 
-class ImpersonateOS(Enum):
-    r"""
-    An impersonate operating system.
-    """
+        # Arguments
 
-    Windows = auto()
-    MacOS = auto()
-    Linux = auto()
-    Android = auto()
-    IOS = auto()
+        * `max_size_mb` - The size in megabytes after which the least recently stored entries are evicted.
+        * `directory` - A directory to persist entries in; they are kept in memory if omitted.
+        """
 
-class LookupIpStrategy(Enum):
+    def clear(self) -> None:
+        r"""
+        Removes every entry from the cache.
+        """
+
+class MockTransport:
     r"""
-    The lookup ip strategy.
-    """
+    A transport that answers a client's requests with a Python callable instead of the network.
 
-    Ipv4Only = auto()
-    Ipv6Only = auto()
-    Ipv4AndIpv6 = auto()
-    Ipv6thenIpv4 = auto()
-    Ipv4thenIpv6 = auto()
+    The handler is called as `handler(method, url, headers, body)`, where `method` and `url`
+    are strings, `headers` is a `HeaderMap` that includes the client's default headers, and
+    `body` is the request body as `bytes`. It returns a `(status, headers, body)` tuple whose
+    headers are a dict or `HeaderMap` and whose body is `bytes` or `str`. The result is a
+    regular response, so `text()`, `json()`, `stream()` and `headers` work as usual.
 
-class Method(Enum):
-    r"""
-    An HTTP method.
-    """
+    The handler runs synchronously while the request is being sent. Redirects are not
+    followed, and a client `cache` still applies on top of the transport.
 
-    GET = auto()
-    HEAD = auto()
-    POST = auto()
-    PUT = auto()
-    DELETE = auto()
-    OPTIONS = auto()
-    TRACE = auto()
-    PATCH = auto()
+    Handlers added with `Client.mount` answer the requests they match ahead of the
+    transport's handler.
 
-class SameSite(Enum):
-    r"""
-    The Cookie SameSite attribute.
-    """
+    # Examples
 
-    Strict = auto()
-    Lax = auto()
-    Empty = auto()
+    ```python
+    import rnet
 
-class TlsVersion(Enum):
-    r"""
-    The TLS version.
+    def handler(method, url, headers, body):
+        return 200, {"content-type": "application/json"}, b'{"ok": true}'
+
+    client = rnet.Client(transport=rnet.MockTransport(handler))
+    response = await client.get("https://example.com")
+    assert await response.json() == {"ok": True}
+    ```
     """
 
-    TLS_1_0 = auto()
-    TLS_1_1 = auto()
-    TLS_1_2 = auto()
-    TLS_1_3 = auto()
+    def __new__(
+        cls,
+        handler: Callable[[str, str, HeaderMap, bytes], Tuple[int, Union[Dict[str, str], HeaderMap, List[Tuple[str, str]]], Union[bytes, str]]],
+    ) -> MockTransport: ...
 
-class Version(Enum):
+class OAuth2ClientCredentials:
     r"""
-    An HTTP version.
-    """
+    Credentials for the OAuth2 client credentials grant, which a client given them as `auth`
+    uses to authenticate its requests with bearer tokens.
 
-    HTTP_09 = auto()
-    HTTP_10 = auto()
-    HTTP_11 = auto()
-    HTTP_2 = auto()
-    HTTP_3 = auto()
+    Tokens are requested from `token_url` with a form `POST`, sending `client_id` and
+    `client_secret` with basic authentication, form-urlencoded first as RFC 6749 asks,
+    through the client itself (or its mock transport). The token is cached and refreshed 30 seconds before it expires; only one
+    refresh runs at a time, and concurrent requests wait for it. A failed token request
+    raises `StatusError`, and an invalid token response `DecodingError`.
 
-async def delete(
-    url: str,
-    **kwargs: Unpack[RequestParams],
-) -> Response:
-    r"""
-    Shortcut method to quickly make a request.
+    The token is only sent with requests to URLs under one of `base_urls`: with the same
+    scheme, host and port, and a path at or below the base URL's. By default that's the
+    origin of `token_url`, so an API on another host needs its URL listed. Requests to
+    anywhere else are sent without the token.
 
     # Examples
 
     ```python
     import rnet
-    import asyncio
 
-    async def run():
+    auth = rnet.OAuth2ClientCredentials(
+        "https://auth.example.com/oauth/token",
+        "my-id",
+        "my-secret",
+        scope="read",
+        base_urls=["https://api.example.com/"],
+    )
+    client = rnet.Client(auth=auth)
+    response = await client.get("https://api.example.com/items")
+    print(client.token_info())
+    ```
+    """
+
+    def __new__(
+        cls,
+        token_url: Union[str, Url],
+        client_id: str,
+        client_secret: str,
+        scope: Optional[str] = None,
+        base_urls: Optional[Sequence[Union[str, Url]]] = None,
+    ) -> OAuth2ClientCredentials: ...
+
+class TlsFingerprint:
+    r"""
+    The TLS fingerprint presented by a client.
+    """
+
+    ja3: str
+    r"""
+    The JA3 string, e.g. `"771,4865-4866-...,0-23-...,29-23-24,0"`.
+    """
+    ja3_hash: str
+    r"""
+    The MD5 hash of the JA3 string.
+    """
+
+    def __str__(self) -> str: ...
+
+class Connection:
+    r"""
+    A scoped view of a `Client` whose requests reuse a single connection.
+
+    Obtained from `Client.connection()`. A request waits until the body of the
+    previous response is read, closed or dropped, so don't keep an unread
+    response around while sending the next request.
+
+    Its requests can't override `impersonate`: the TLS handshake of the
+    connection is already made. Nor can they turn `decompress` off, which
+    takes a client of its own.
+
+    Leaving its `async with` block closes the connection, and any further
+    request raises `RuntimeError`, as on a closed `Client`.
+    """
+
+    async def request(
+        self,
+        method: Union[Method, str],
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> Response:
+        r"""
+        Sends a request with the given method and URL on the pinned connection.
+        """
+
+    async def get(
+        self,
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> Response:
+        r"""
+        Sends a GET request on the pinned connection.
+        """
+
+    async def head(
+        self,
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> Response:
+        r"""
+        Sends a HEAD request on the pinned connection.
+        """
+
+    async def post(
+        self,
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> Response:
+        r"""
+        Sends a POST request on the pinned connection.
+        """
+
+    async def put(
+        self,
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> Response:
+        r"""
+        Sends a PUT request on the pinned connection.
+        """
+
+    async def delete(
+        self,
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> Response:
+        r"""
+        Sends a DELETE request on the pinned connection.
+        """
+
+    async def patch(
+        self,
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> Response:
+        r"""
+        Sends a PATCH request on the pinned connection.
+        """
+
+    async def options(
+        self,
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> Response:
+        r"""
+        Sends a OPTIONS request on the pinned connection.
+        """
+
+    async def trace(
+        self,
+        url: Union[str, Url],
+        **kwargs: Unpack[RequestParams],
+    ) -> Response:
+        r"""
+        Sends a TRACE request on the pinned connection.
+        """
+
+    def __aenter__(self) -> Any: ...
+    def __aexit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> Any: ...
+
+class Tunnel:
+    r"""
+    A raw, bidirectional byte stream tunneled through an HTTP proxy with `CONNECT`.
+    """
+
+    async def read(self, size: int = 65536) -> bytes:
+        r"""
+        Reads up to `size` bytes from the tunnel.
+
+        Returns empty bytes once the remote end has closed the connection.
+        """
+
+    async def write(self, data: bytes) -> None:
+        r"""
+        Writes all of `data` to the tunnel.
+        """
+
+    async def close(self) -> None:
+        r"""
+        Closes the tunnel.
+        """
+
+    def __aenter__(self) -> Any: ...
+    def __aexit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> Any: ...
+
+class WebSocket:
+    r"""
+    A WebSocket response.
+    """
+
+    ok: bool
+    r"""
+    Returns whether the response is successful.
+    """
+    status: int
+    r"""
+    Returns the status code as integer of the response.
+    """
+    status_code: StatusCode
+    r"""
+    Returns the status code of the response.
+    """
+    version: Version
+    r"""
+    Returns the HTTP version of the response.
+    """
+    headers: HeaderMap
+    r"""
+    Returns the headers of the response.
+    """
+    cookies: List[Cookie]
+    r"""
+    Returns the cookies of the response.
+    """
+    remote_addr: Optional[SocketAddr]
+    r"""
+    Returns the remote address of the response.
+    """
+    protocol: Optional[str]
+    r"""
+    Returns the WebSocket protocol.
+    """
+    origin: Optional[str]
+    r"""
+    Returns the `Origin` header the handshake was sent with.
+    """
+    user_agent: Optional[str]
+    r"""
+    Returns the `User-Agent` header the handshake was sent with, if set for the request.
+    """
+    messages_sent: int
+    r"""
+    Returns the number of messages sent with `send` and `send_all`, across
+    reconnects.
+    """
+    messages_received: int
+    r"""
+    Returns the number of messages received, across reconnects.
+    """
+    bytes_sent: int
+    r"""
+    Returns the size of the payloads of the messages sent, without framing.
+    """
+    bytes_received: int
+    r"""
+    Returns the size of the payloads of the messages received, without framing.
+    """
+    pending_bytes: int
+    r"""
+    Returns the size of the payloads of the messages being sent that haven't
+    been written to the connection yet, including those waiting for another
+    send to finish. A slow peer makes it grow.
+
+    These counters are read without waiting for pending sends or receives.
+    """
+    max_message_size: int
+    r"""
+    Returns the largest message the connection accepts, as set with
+    `max_message_size` or 64 MiB by default.
+    """
+    max_frame_size: int
+    r"""
+    Returns the largest frame the connection accepts, as set with
+    `max_frame_size` or 16 MiB by default.
+    """
+    def __aiter__(self) -> WebSocket: ...
+    def __anext__(self) -> Any: ...
+    def __aenter__(self) -> Any: ...
+    def __aexit__(self, _exc_type: Any, _exc_value: Any, _traceback: Any) -> Any: ...
+    async def recv(self) -> Optional[Message]:
+        r"""
+        Receives a message from the WebSocket.
+        """
+
+    async def send(self, message: Message) -> None:
+        r"""
+        Sends a message to the WebSocket.
+
+        # Arguments
+
+        * `message` - The message to send.
+        """
+
+    async def send_all(self, messages: typing.Iterable[Message]) -> int:
+        r"""
+        Sends every message of an iterable, flushing once at the end, and returns how
+        many were sent.
+
+        If sending fails, the raised exception has a `sent` attribute with the number of
+        messages known to be sent, which is 0: messages count as sent once the final flush
+        succeeds, as any of them may still have been buffered when the error happened.
+
+        # Arguments
+
+        * `messages` - The messages to send.
+        """
+
+    async def close(
+        self,
+        code: Optional[int] = None,
+        reason: Optional[str] = None,
+    ) -> None:
+        r"""
+        Closes the WebSocket connection.
+
+        Pending `recv` and `send` calls from other tasks are interrupted and
+        raise a disconnect error; pending iteration stops.
+
+        # Arguments
+
+        * `code` - An optional close code.
+        * `reason` - An optional reason for closing.
+        """
+
+class Impersonate(Enum):
+    r"""
+    An impersonate.
+    """
+
+    Chrome100 = auto()
+    Chrome101 = auto()
+    Chrome104 = auto()
+    Chrome105 = auto()
+    Chrome106 = auto()
+    Chrome107 = auto()
+    Chrome108 = auto()
+    Chrome109 = auto()
+    Chrome110 = auto()
+    Chrome114 = auto()
+    Chrome116 = auto()
+    Chrome117 = auto()
+    Chrome118 = auto()
+    Chrome119 = auto()
+    Chrome120 = auto()
+    Chrome123 = auto()
+    Chrome124 = auto()
+    Chrome126 = auto()
+    Chrome127 = auto()
+    Chrome128 = auto()
+    Chrome129 = auto()
+    Chrome130 = auto()
+    Chrome131 = auto()
+    Chrome132 = auto()
+    Chrome133 = auto()
+    Chrome134 = auto()
+    Chrome135 = auto()
+    Chrome136 = auto()
+    Edge101 = auto()
+    Edge122 = auto()
+    Edge127 = auto()
+    Edge131 = auto()
+    Edge134 = auto()
+    Firefox109 = auto()
+    Firefox117 = auto()
+    Firefox128 = auto()
+    Firefox133 = auto()
+    Firefox135 = auto()
+    FirefoxPrivate135 = auto()
+    FirefoxAndroid135 = auto()
+    Firefox136 = auto()
+    FirefoxPrivate136 = auto()
+    SafariIos17_2 = auto()
+    SafariIos17_4_1 = auto()
+    SafariIos16_5 = auto()
+    Safari15_3 = auto()
+    Safari15_5 = auto()
+    Safari15_6_1 = auto()
+    Safari16 = auto()
+    Safari16_5 = auto()
+    Safari17_0 = auto()
+    Safari17_2_1 = auto()
+    Safari17_4_1 = auto()
+    Safari17_5 = auto()
+    Safari18 = auto()
+    SafariIPad18 = auto()
+    Safari18_2 = auto()
+    Safari18_3 = auto()
+    Safari18_3_1 = auto()
+    SafariIos18_1_1 = auto()
+    OkHttp3_9 = auto()
+    OkHttp3_11 = auto()
+    OkHttp3_13 = auto()
+    OkHttp3_14 = auto()
+    OkHttp4_9 = auto()
+    OkHttp4_10 = auto()
+    OkHttp4_12 = auto()
+    OkHttp5 = auto()
+    Opera116 = auto()
+    Opera117 = auto()
+    Opera118 = auto()
+    Opera119 = auto()
+
+    @staticmethod
+    def all() -> List[Impersonate]:
+        r"""
+        Returns all available impersonation profiles.
+
+        # Examples
+
+        ```python
+        import random
+        import rnet
+
+        client = rnet.Client(impersonate=random.choice(rnet.Impersonate.all()))
+        ```
+        """
+
+    name: str
+    r"""
+    The name of the profile, e.g. `"Chrome136"`.
+    """
+    browser: str
+    r"""
+    The browser family of the profile: `"chrome"`, `"edge"`, `"firefox"`,
+    `"safari"`, `"okhttp"` or `"opera"`.
+    """
+    version: str
+    r"""
+    The browser version of the profile, e.g. `"136"` or `"17.4.1"`.
+    """
+    supported_os: List[ImpersonateOS]
+    r"""
+    The operating systems the profile's browser runs on, e.g. `[ImpersonateOS.IOS]`
+    for `SafariIos17_4_1`. `ImpersonateOption.random_for` picks one of them.
+    """
+
+    def __str__(self) -> str: ...
+
+class ImpersonateOS(Enum):
+    r"""
+    An impersonate operating system.
+    """
+
+    Windows = auto()
+    MacOS = auto()
+    Linux = auto()
+    Android = auto()
+    IOS = auto()
+
+    @staticmethod
+    def random() -> ImpersonateOS:
+        r"""
+        Returns a random operating system.
+        """
+
+class LookupIpStrategy(Enum):
+    r"""
+    The lookup ip strategy.
+
+    When a host resolves to both IPv4 and IPv6 addresses, connections race the two
+    families ("happy eyeballs"): the second family is tried if the first hasn't
+    connected within 300ms, and whichever connects first is used, as reported by
+    `Response.remote_addr`. The family of the first resolved address is preferred.
+
+    That delay is fixed by the underlying connector and can't be configured. The
+    `Ipv6thenIpv4` and `Ipv4thenIpv6` strategies only resolve the second family when the
+    first has no records, so there is nothing to fall back to; on networks where one
+    family is unreliable, use `Ipv4AndIpv6`, or `Ipv4Only`/`Ipv6Only` to avoid waiting
+    on it at all.
+    """
+
+    Ipv4Only = auto()
+    Ipv6Only = auto()
+    Ipv4AndIpv6 = auto()
+    Ipv6thenIpv4 = auto()
+    Ipv4thenIpv6 = auto()
+
+class MessageType(Enum):
+    r"""
+    The type of a WebSocket message.
+    """
+
+    Text = auto()
+    Binary = auto()
+    Ping = auto()
+    Pong = auto()
+    Close = auto()
+
+class Method(Enum):
+    r"""
+    An HTTP method, including the common WebDAV ones.
+
+    Requests also take the method as a string, for verbs that aren't listed here.
+    """
+
+    GET = auto()
+    HEAD = auto()
+    POST = auto()
+    PUT = auto()
+    DELETE = auto()
+    OPTIONS = auto()
+    TRACE = auto()
+    PATCH = auto()
+    PROPFIND = auto()
+    PROPPATCH = auto()
+    MKCOL = auto()
+    COPY = auto()
+    MOVE = auto()
+    LOCK = auto()
+    UNLOCK = auto()
+    REPORT = auto()
+
+    @staticmethod
+    def from_str(value: str) -> Method:
+        r"""
+        Parses a method name such as `"GET"` or `"PROPFIND"` (case-insensitive).
+
+        Raises `ValueError` if the string is not one of the listed methods; pass
+        extension methods to requests as strings instead.
+        """
+
+    def __str__(self) -> str: ...
+
+class SameSite(Enum):
+    r"""
+    The Cookie SameSite attribute.
+    """
+
+    Strict = auto()
+    Lax = auto()
+    Empty = auto()
+
+class TlsVersion(Enum):
+    r"""
+    The TLS version.
+    """
+
+    TLS_1_0 = auto()
+    TLS_1_1 = auto()
+    TLS_1_2 = auto()
+    TLS_1_3 = auto()
+
+class Version(Enum):
+    r"""
+    An HTTP version.
+    """
+
+    HTTP_09 = auto()
+    HTTP_10 = auto()
+    HTTP_11 = auto()
+    HTTP_2 = auto()
+    HTTP_3 = auto()
+
+    @staticmethod
+    def from_str(value: str) -> Version:
+        r"""
+        Parses a version string such as `"HTTP/1.1"` or `"HTTP/2"` (case-insensitive).
+
+        Raises `ValueError` if the string is not a known HTTP version.
+        """
+
+    def __str__(self) -> str: ...
+
+def set_log_level(level: Optional[Literal["off", "error", "warn", "warning", "info", "debug", "trace"]] = None) -> None:
+    r"""
+    Sets the level of the logs rnet sends to Python's `logging`, or turns them off with
+    `None` or `"off"`. Logs are off unless the `RNET_LOG` environment variable names a
+    level when rnet is imported.
+
+    Records go to loggers named after the module they come from, under `rnet`: requests
+    are logged by `rnet.async_impl.request`, and DNS lookups, connects and TLS handshakes
+    by `rnet.newwreq.*`. Those loggers still filter what they are sent, so both levels
+    must allow a record. Logging is free while it's off.
+
+    The URLs rnet logs leave out their username, password, query and fragment, which may
+    hold secrets. Records from wreq's own modules are passed on as they are.
+
+    # Examples
+
+    ```python
+    import logging
+    import rnet
+
+    logging.basicConfig(level=logging.DEBUG)
+    rnet.set_log_level("debug")
+    ```
+    """
+
+def strict_resources(enabled: bool = True) -> None:
+    r"""
+    Makes responses dropped with their body neither read nor closed raise
+    `ResourceWarning` instead of warning, or warn again with `False`.
+
+    Responses are dropped during garbage collection, where exceptions can't
+    propagate, so the error is reported through `sys.unraisablehook`. pytest
+    reports it as a warning, which `-W error` turns into a failure.
+
+    # Examples
+
+    ```python
+    import rnet
+
+    rnet.strict_resources(True)
+    ```
+    """
+
+def set_trust_env_default(enabled: bool = True) -> None:
+    r"""
+    Sets whether clients built without `trust_env` read proxies from the
+    `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` environment
+    variables. It's on by default; clients already built keep their proxies.
+
+    # Examples
+
+    ```python
+    import rnet
+
+    rnet.set_trust_env_default(False)
+    ```
+    """
+
+def configure_runtime(
+    worker_threads: Optional[int] = None,
+    max_blocking_threads: Optional[int] = None,
+    thread_name_prefix: Optional[str] = None,
+) -> None:
+    r"""
+    Configures the global runtime used by both the async and blocking APIs.
+
+    Must be called before the first request is made; once the runtime has been
+    initialized it cannot be reconfigured and a `RuntimeError` is raised.
+
+    Args:
+        worker_threads: Number of worker threads. Defaults to the number of CPU cores.
+        max_blocking_threads: Maximum number of threads used for blocking operations.
+        thread_name_prefix: Prefix for the names of the runtime threads.
+
+    # Examples
+
+    ```python
+    import rnet
+
+    rnet.configure_runtime(worker_threads=2, thread_name_prefix="rnet")
+    ```
+    """
+
+async def delete(
+    url: str,
+    **kwargs: Unpack[RequestParams],
+) -> Response:
+    r"""
+    Shortcut method to quickly make a request.
+
+    # Examples
+
+    ```python
+    import rnet
+    import asyncio
+
+    async def run():
         response = await rnet.delete("https://httpbin.org/anything")
         body = await response.text()
         print(body)
@@ -1930,261 +4398,748 @@ async def delete(
     asyncio.run(run())
     ```
     """
-
-async def get(
-    url: str,
-    **kwargs: Unpack[RequestParams],
-) -> Response:
+
+async def get(
+    url: str,
+    **kwargs: Unpack[RequestParams],
+) -> Response:
+    r"""
+    Shortcut method to quickly make a request.
+
+    # Examples
+
+    ```python
+    import rnet
+    import asyncio
+
+    async def run():
+        response = await rnet.get("https://httpbin.org/anything")
+        body = await response.text()
+        print(body)
+
+    asyncio.run(run())
+    ```
+    """
+
+async def head(
+    url: str,
+    **kwargs: Unpack[RequestParams],
+) -> Response:
+    r"""
+    Shortcut method to quickly make a request.
+
+    # Examples
+
+    ```python
+    import rnet
+    import asyncio
+
+    async def run():
+        response = await rnet.head("https://httpbin.org/anything")
+        print(response.status)
+
+    asyncio.run(run())
+    ```
+    """
+
+async def options(
+    url: str,
+    **kwargs: Unpack[RequestParams],
+) -> Response:
+    r"""
+    Shortcut method to quickly make a request.
+
+    # Examples
+
+    ```python
+    import rnet
+    import asyncio
+
+    async def run():
+        response = await rnet.options("https://httpbin.org/anything")
+        print(response.status)
+
+    asyncio.run(run())
+    ```
+    """
+
+async def patch(
+    url: str,
+    **kwargs: Unpack[RequestParams],
+) -> Response:
+    r"""
+    Shortcut method to quickly make a request.
+
+    # Examples
+
+    ```python
+    import rnet
+    import asyncio
+
+    async def run():
+        response = await rnet.patch("https://httpbin.org/anything")
+        body = await response.text()
+        print(body)
+
+    asyncio.run(run())
+    ```
+    """
+
+async def post(
+    url: str,
+    **kwargs: Unpack[RequestParams],
+) -> Response:
+    r"""
+    Shortcut method to quickly make a request.
+
+    # Examples
+
+    ```python
+    import rnet
+    import asyncio
+
+    async def run():
+        response = await rnet.post("https://httpbin.org/anything")
+        body = await response.text()
+        print(body)
+
+    asyncio.run(run())
+    ```
+    """
+
+async def put(
+    url: str,
+    **kwargs: Unpack[RequestParams],
+) -> Response:
+    r"""
+    Shortcut method to quickly make a request.
+
+    # Examples
+
+    ```python
+    import rnet
+    import asyncio
+
+    async def run():
+        response = await rnet.put("https://httpbin.org/anything")
+        body = await response.text()
+        print(body)
+
+    asyncio.run(run())
+    ```
+    """
+
+async def request(
+    method: Union[Method, str],
+    url: str,
+    **kwargs: Unpack[RequestParams],
+) -> Response:
+    r"""
+    Make a request with the given parameters.
+
+    # Arguments
+
+    * `method` - The method to use for the request.
+    * `url` - The URL to send the request to.
+    * `**kwargs` - Additional request parameters.
+
+    # Examples
+
+    ```python
+    import rnet
+    import asyncio
+    from rnet import Method
+
+    async def run():
+        response = await rnet.request(Method.GET, "https://www.rust-lang.org")
+        body = await response.text()
+        print(body)
+
+    asyncio.run(run())
+    ```
+    """
+
+async def trace(
+    url: str,
+    **kwargs: Unpack[RequestParams],
+) -> Response:
+    r"""
+    Shortcut method to quickly make a request.
+
+    # Examples
+
+    ```python
+    import rnet
+    import asyncio
+
+    async def run():
+        response = await rnet.trace("https://httpbin.org/anything")
+        print(response.status)
+
+    asyncio.run(run())
+    ```
+    """
+
+async def websocket(
+    url: str,
+    **kwargs: Unpack[WebSocketParams],
+) -> WebSocket:
+    r"""
+    Make a WebSocket connection with the given parameters.
+
+    # Examples
+
+    ```python
+    import rnet
+    import asyncio
+    from rnet import Message
+
+    async def run():
+        ws = await rnet.websocket("wss://echo.websocket.org")
+        await ws.send(Message.from_text("Hello, World!"))
+        message = await ws.recv()
+        print("Received:", message.data)
+        await ws.close()
+
+    asyncio.run(run())
+    ```
+    """
+
+class DNSResolverError(RuntimeError):
+    r"""
+    An error occurred while resolving a DNS name.
+    """
+
+class BodyError(Exception):
+    r"""
+    An error occurred while processing the body of a request or response.
+    """
+
+    url: Optional[str]
+    r"""
+    The URL of the request, if known.
+    """
+    method: Optional[str]
+    r"""
+    The HTTP method of the request, if known.
+    """
+    is_timeout: Optional[bool]
+    r"""
+    Whether the error was caused by a timeout. Like the other fields, it's `None`
+    unless the error was raised for a failed request.
+    """
+    is_connect: Optional[bool]
+    r"""
+    Whether the error occurred while connecting.
+    """
+    is_body: Optional[bool]
+    r"""
+    Whether the error is related to the request or response body.
+    """
+    phase: Optional[str]
+    r"""
+    The phase that timed out: `"connect"`, `"read"` or `"total"`, or `None` if the
+    error isn't a timeout.
+    """
+    host: Optional[str]
+    r"""
+    The target host of the connection, if the error occurred while connecting.
+    """
+    port: Optional[int]
+    r"""
+    The target port of the connection, if the error occurred while connecting.
+    """
+    os_error: Optional[int]
+    r"""
+    The OS error code (`errno`) that caused the failure, if any.
+    """
+    status: Optional[int]
+    r"""
+    The HTTP status code of the response, if the error is about one.
+    """
+
+class ResponseTooLargeError(BodyError):
+    r"""
+    A response body exceeded `max_response_size` once decompressed.
+    """
+
+class BuilderError(Exception):
+    r"""
+    An error occurred while building a request or response.
+    """
+
+    url: Optional[str]
+    r"""
+    The URL of the request, if known.
+    """
+    method: Optional[str]
+    r"""
+    The HTTP method of the request, if known.
+    """
+    is_timeout: Optional[bool]
+    r"""
+    Whether the error was caused by a timeout. Like the other fields, it's `None`
+    unless the error was raised for a failed request.
+    """
+    is_connect: Optional[bool]
+    r"""
+    Whether the error occurred while connecting.
+    """
+    is_body: Optional[bool]
+    r"""
+    Whether the error is related to the request or response body.
+    """
+    phase: Optional[str]
+    r"""
+    The phase that timed out: `"connect"`, `"read"` or `"total"`, or `None` if the
+    error isn't a timeout.
+    """
+    host: Optional[str]
+    r"""
+    The target host of the connection, if the error occurred while connecting.
+    """
+    port: Optional[int]
+    r"""
+    The target port of the connection, if the error occurred while connecting.
+    """
+    os_error: Optional[int]
+    r"""
+    The OS error code (`errno`) that caused the failure, if any.
+    """
+    status: Optional[int]
+    r"""
+    The HTTP status code of the response, if the error is about one.
+    """
+
+class ConnectionError(Exception):
+    r"""
+    An error occurred while establishing a connection.
+    """
+
+    url: Optional[str]
+    r"""
+    The URL of the request, if known.
+    """
+    method: Optional[str]
+    r"""
+    The HTTP method of the request, if known.
+    """
+    is_timeout: Optional[bool]
+    r"""
+    Whether the error was caused by a timeout. Like the other fields, it's `None`
+    unless the error was raised for a failed request.
+    """
+    is_connect: Optional[bool]
+    r"""
+    Whether the error occurred while connecting.
+    """
+    is_body: Optional[bool]
+    r"""
+    Whether the error is related to the request or response body.
+    """
+    phase: Optional[str]
+    r"""
+    The phase that timed out: `"connect"`, `"read"` or `"total"`, or `None` if the
+    error isn't a timeout.
+    """
+    host: Optional[str]
+    r"""
+    The target host of the connection, if the error occurred while connecting.
+    """
+    port: Optional[int]
+    r"""
+    The target port of the connection, if the error occurred while connecting.
+    """
+    os_error: Optional[int]
+    r"""
+    The OS error code (`errno`) that caused the failure, if any.
+    """
+    status: Optional[int]
+    r"""
+    The HTTP status code of the response, if the error is about one.
+    """
+
+class ProxyError(ConnectionError):
+    r"""
+    The PAC script of a proxy failed to pick the proxy for a request.
+    """
+
+class ConnectionResetError(Exception):
+    r"""
+    The connection was reset.
+    """
+
+    url: Optional[str]
+    r"""
+    The URL of the request, if known.
+    """
+    method: Optional[str]
+    r"""
+    The HTTP method of the request, if known.
+    """
+    is_timeout: Optional[bool]
+    r"""
+    Whether the error was caused by a timeout. Like the other fields, it's `None`
+    unless the error was raised for a failed request.
+    """
+    is_connect: Optional[bool]
+    r"""
+    Whether the error occurred while connecting.
+    """
+    is_body: Optional[bool]
+    r"""
+    Whether the error is related to the request or response body.
+    """
+    phase: Optional[str]
+    r"""
+    The phase that timed out: `"connect"`, `"read"` or `"total"`, or `None` if the
+    error isn't a timeout.
+    """
+    host: Optional[str]
+    r"""
+    The target host of the connection, if the error occurred while connecting.
+    """
+    port: Optional[int]
+    r"""
+    The target port of the connection, if the error occurred while connecting.
+    """
+    os_error: Optional[int]
+    r"""
+    The OS error code (`errno`) that caused the failure, if any.
+    """
+    status: Optional[int]
+    r"""
+    The HTTP status code of the response, if the error is about one.
+    """
+
+class DecodingError(Exception):
+    r"""
+    An error occurred while decoding a response.
+    """
+
+    url: Optional[str]
+    r"""
+    The URL of the request, if known.
+    """
+    method: Optional[str]
+    r"""
+    The HTTP method of the request, if known.
+    """
+    is_timeout: Optional[bool]
+    r"""
+    Whether the error was caused by a timeout. Like the other fields, it's `None`
+    unless the error was raised for a failed request.
+    """
+    is_connect: Optional[bool]
+    r"""
+    Whether the error occurred while connecting.
+    """
+    is_body: Optional[bool]
+    r"""
+    Whether the error is related to the request or response body.
+    """
+    phase: Optional[str]
+    r"""
+    The phase that timed out: `"connect"`, `"read"` or `"total"`, or `None` if the
+    error isn't a timeout.
+    """
+    host: Optional[str]
+    r"""
+    The target host of the connection, if the error occurred while connecting.
+    """
+    port: Optional[int]
+    r"""
+    The target port of the connection, if the error occurred while connecting.
+    """
+    os_error: Optional[int]
+    r"""
+    The OS error code (`errno`) that caused the failure, if any.
+    """
+    status: Optional[int]
+    r"""
+    The HTTP status code of the response, if the error is about one.
+    """
+
+class RedirectError(Exception):
+    r"""
+    An error occurred while following a redirect.
+    """
+
+    url: Optional[str]
+    r"""
+    The URL of the request, if known.
+    """
+    method: Optional[str]
+    r"""
+    The HTTP method of the request, if known.
+    """
+    is_timeout: Optional[bool]
+    r"""
+    Whether the error was caused by a timeout. Like the other fields, it's `None`
+    unless the error was raised for a failed request.
+    """
+    is_connect: Optional[bool]
+    r"""
+    Whether the error occurred while connecting.
+    """
+    is_body: Optional[bool]
+    r"""
+    Whether the error is related to the request or response body.
+    """
+    phase: Optional[str]
+    r"""
+    The phase that timed out: `"connect"`, `"read"` or `"total"`, or `None` if the
+    error isn't a timeout.
+    """
+    host: Optional[str]
+    r"""
+    The target host of the connection, if the error occurred while connecting.
+    """
+    port: Optional[int]
+    r"""
+    The target port of the connection, if the error occurred while connecting.
+    """
+    os_error: Optional[int]
+    r"""
+    The OS error code (`errno`) that caused the failure, if any.
+    """
+    status: Optional[int]
+    r"""
+    The HTTP status code of the response, if the error is about one.
+    """
+
+class TimeoutError(Exception):
+    r"""
+    A timeout occurred while waiting for a response.
+    """
+
+    url: Optional[str]
+    r"""
+    The URL of the request, if known.
+    """
+    method: Optional[str]
+    r"""
+    The HTTP method of the request, if known.
+    """
+    is_timeout: Optional[bool]
+    r"""
+    Whether the error was caused by a timeout. Like the other fields, it's `None`
+    unless the error was raised for a failed request.
+    """
+    is_connect: Optional[bool]
+    r"""
+    Whether the error occurred while connecting.
+    """
+    is_body: Optional[bool]
+    r"""
+    Whether the error is related to the request or response body.
+    """
+    phase: Optional[str]
+    r"""
+    The phase that timed out: `"connect"`, `"read"` or `"total"`, or `None` if the
+    error isn't a timeout.
+    """
+    host: Optional[str]
+    r"""
+    The target host of the connection, if the error occurred while connecting.
+    """
+    port: Optional[int]
+    r"""
+    The target port of the connection, if the error occurred while connecting.
+    """
+    os_error: Optional[int]
+    r"""
+    The OS error code (`errno`) that caused the failure, if any.
+    """
+    status: Optional[int]
+    r"""
+    The HTTP status code of the response, if the error is about one.
+    """
+
+class StatusError(Exception):
+    r"""
+    An error occurred while processing the status code of a response.
+    """
+
+    url: Optional[str]
+    r"""
+    The URL of the request, if known.
+    """
+    method: Optional[str]
+    r"""
+    The HTTP method of the request, if known.
+    """
+    is_timeout: Optional[bool]
+    r"""
+    Whether the error was caused by a timeout. Like the other fields, it's `None`
+    unless the error was raised for a failed request.
+    """
+    is_connect: Optional[bool]
+    r"""
+    Whether the error occurred while connecting.
+    """
+    is_body: Optional[bool]
+    r"""
+    Whether the error is related to the request or response body.
+    """
+    phase: Optional[str]
+    r"""
+    The phase that timed out: `"connect"`, `"read"` or `"total"`, or `None` if the
+    error isn't a timeout.
+    """
+    host: Optional[str]
+    r"""
+    The target host of the connection, if the error occurred while connecting.
+    """
+    port: Optional[int]
+    r"""
+    The target port of the connection, if the error occurred while connecting.
+    """
+    os_error: Optional[int]
+    r"""
+    The OS error code (`errno`) that caused the failure, if any.
+    """
+    status: Optional[int]
     r"""
-    Shortcut method to quickly make a request.
-
-    # Examples
-
-    ```python
-    import rnet
-    import asyncio
-
-    async def run():
-        response = await rnet.get("https://httpbin.org/anything")
-        body = await response.text()
-        print(body)
-
-    asyncio.run(run())
-    ```
+    The HTTP status code of the response, if the error is about one.
     """
 
-async def head(
-    url: str,
-    **kwargs: Unpack[RequestParams],
-) -> Response:
+class RequestError(Exception):
     r"""
-    Shortcut method to quickly make a request.
-
-    # Examples
-
-    ```python
-    import rnet
-    import asyncio
-
-    async def run():
-        response = await rnet.head("https://httpbin.org/anything")
-        print(response.status)
-
-    asyncio.run(run())
-    ```
+    An error occurred while making a request.
     """
 
-async def options(
-    url: str,
-    **kwargs: Unpack[RequestParams],
-) -> Response:
+    url: Optional[str]
     r"""
-    Shortcut method to quickly make a request.
-
-    # Examples
-
-    ```python
-    import rnet
-    import asyncio
-
-    async def run():
-        response = await rnet.options("https://httpbin.org/anything")
-        print(response.status)
-
-    asyncio.run(run())
-    ```
+    The URL of the request, if known.
     """
-
-async def patch(
-    url: str,
-    **kwargs: Unpack[RequestParams],
-) -> Response:
+    method: Optional[str]
     r"""
-    Shortcut method to quickly make a request.
-
-    # Examples
-
-    ```python
-    import rnet
-    import asyncio
-
-    async def run():
-        response = await rnet.patch("https://httpbin.org/anything")
-        body = await response.text()
-        print(body)
-
-    asyncio.run(run())
-    ```
+    The HTTP method of the request, if known.
     """
-
-async def post(
-    url: str,
-    **kwargs: Unpack[RequestParams],
-) -> Response:
+    is_timeout: Optional[bool]
     r"""
-    Shortcut method to quickly make a request.
-
-    # Examples
-
-    ```python
-    import rnet
-    import asyncio
-
-    async def run():
-        response = await rnet.post("https://httpbin.org/anything")
-        body = await response.text()
-        print(body)
-
-    asyncio.run(run())
-    ```
+    Whether the error was caused by a timeout. Like the other fields, it's `None`
+    unless the error was raised for a failed request.
     """
-
-async def put(
-    url: str,
-    **kwargs: Unpack[RequestParams],
-) -> Response:
+    is_connect: Optional[bool]
     r"""
-    Shortcut method to quickly make a request.
-
-    # Examples
-
-    ```python
-    import rnet
-    import asyncio
-
-    async def run():
-        response = await rnet.put("https://httpbin.org/anything")
-        body = await response.text()
-        print(body)
-
-    asyncio.run(run())
-    ```
+    Whether the error occurred while connecting.
     """
-
-async def request(
-    method: Method,
-    url: str,
-    **kwargs: Unpack[RequestParams],
-) -> Response:
+    is_body: Optional[bool]
     r"""
-    Make a request with the given parameters.
-
-    # Arguments
-
-    * `method` - The method to use for the request.
-    * `url` - The URL to send the request to.
-    * `**kwargs` - Additional request parameters.
-
-    # Examples
-
-    ```python
-    import rnet
-    import asyncio
-    from rnet import Method
-
-    async def run():
-        response = await rnet.request(Method.GET, "https://www.rust-lang.org")
-        body = await response.text()
-        print(body)
-
-    asyncio.run(run())
-    ```
+    Whether the error is related to the request or response body.
     """
-
-async def trace(
-    url: str,
-    **kwargs: Unpack[RequestParams],
-) -> Response:
+    phase: Optional[str]
     r"""
-    Shortcut method to quickly make a request.
-
-    # Examples
-
-    ```python
-    import rnet
-    import asyncio
-
-    async def run():
-        response = await rnet.trace("https://httpbin.org/anything")
-        print(response.status)
-
-    asyncio.run(run())
-    ```
+    The phase that timed out: `"connect"`, `"read"` or `"total"`, or `None` if the
+    error isn't a timeout.
     """
-
-async def websocket(
-    url: str,
-    **kwargs: Unpack[WebSocketParams],
-) -> WebSocket:
+    host: Optional[str]
     r"""
-    Make a WebSocket connection with the given parameters.
-
-    # Examples
-
-    ```python
-    import rnet
-    import asyncio
-    from rnet import Message
+    The target host of the connection, if the error occurred while connecting.
+    """
+    port: Optional[int]
+    r"""
+    The target port of the connection, if the error occurred while connecting.
+    """
+    os_error: Optional[int]
+    r"""
+    The OS error code (`errno`) that caused the failure, if any.
+    """
+    status: Optional[int]
+    r"""
+    The HTTP status code of the response, if the error is about one.
+    """
 
-    async def run():
-        ws = await rnet.websocket("wss://echo.websocket.org")
-        await ws.send(Message.from_text("Hello, World!"))
-        message = await ws.recv()
-        print("Received:", message.data)
-        await ws.close()
+class Http2Error(RequestError):
+    r"""
+    The server or the HTTP/2 layer reset the stream (`RST_STREAM`) or the connection
+    (`GOAWAY`).
 
-    asyncio.run(run())
-    ```
+    Requests the server refused (`REFUSED_STREAM`) or dropped with a graceful `GOAWAY`
+    are already retried on a new connection, up to the client's `http2_max_retry_count`
+    times; this error is raised once those retries are exhausted or for other failures.
     """
 
-class DNSResolverError(RuntimeError):
+    error_code: Optional[int]
     r"""
-    An error occurred while resolving a DNS name.
+    The HTTP/2 error code, e.g. `7` for `REFUSED_STREAM`.
     """
-
-class BodyError(Exception):
+    reason: Optional[str]
     r"""
-    An error occurred while processing the body of a request or response.
+    The name of the error code, e.g. `"REFUSED_STREAM"`.
     """
-
-class BuilderError(Exception):
+    is_goaway: Optional[bool]
     r"""
-    An error occurred while building a request or response.
+    Whether the connection was shut down with a `GOAWAY` frame.
     """
-
-class ConnectionError(Exception):
+    is_reset: Optional[bool]
     r"""
-    An error occurred while establishing a connection.
+    Whether the stream was reset with a `RST_STREAM` frame.
     """
-
-class ConnectionResetError(Exception):
+    is_remote: Optional[bool]
     r"""
-    The connection was reset.
+    Whether the server, rather than the client, sent the frame.
     """
-
-class DecodingError(Exception):
+    never_processed: Optional[bool]
     r"""
-    An error occurred while decoding a response.
+    Whether the server provably did not process the request, so it is safe to retry
+    even if it is not idempotent. Only a `REFUSED_STREAM` reset gives that guarantee;
+    the last stream id of a `GOAWAY` isn't reported by the HTTP/2 layer.
     """
 
-class RedirectError(Exception):
+class TlsError(Exception):
     r"""
-    An error occurred while following a redirect.
+    An error occurred during the TLS handshake.
     """
 
-class TimeoutError(Exception):
+    url: Optional[str]
     r"""
-    A timeout occurred while waiting for a response.
+    The URL of the request, if known.
     """
-
-class StatusError(Exception):
+    method: Optional[str]
     r"""
-    An error occurred while processing the status code of a response.
+    The HTTP method of the request, if known.
+    """
+    is_timeout: Optional[bool]
+    r"""
+    Whether the error was caused by a timeout. Like the other fields, it's `None`
+    unless the error was raised for a failed request.
+    """
+    is_connect: Optional[bool]
+    r"""
+    Whether the error occurred while connecting.
+    """
+    is_body: Optional[bool]
+    r"""
+    Whether the error is related to the request or response body.
+    """
+    phase: Optional[str]
+    r"""
+    The phase that timed out: `"connect"`, `"read"` or `"total"`, or `None` if the
+    error isn't a timeout.
+    """
+    host: Optional[str]
+    r"""
+    The target host of the connection, if the error occurred while connecting.
+    """
+    port: Optional[int]
+    r"""
+    The target port of the connection, if the error occurred while connecting.
+    """
+    os_error: Optional[int]
+    r"""
+    The OS error code (`errno`) that caused the failure, if any.
+    """
+    status: Optional[int]
+    r"""
+    The HTTP status code of the response, if the error is about one.
     """
 
-class RequestError(Exception):
+class CertificateError(TlsError):
     r"""
-    An error occurred while making a request.
+    The server certificate could not be verified.
     """
 
 class UpgradeError(Exception):
@@ -2192,6 +5147,49 @@ class UpgradeError(Exception):
     An error occurred while upgrading a connection.
     """
 
+    url: Optional[str]
+    r"""
+    The URL of the request, if known.
+    """
+    method: Optional[str]
+    r"""
+    The HTTP method of the request, if known.
+    """
+    is_timeout: Optional[bool]
+    r"""
+    Whether the error was caused by a timeout. Like the other fields, it's `None`
+    unless the error was raised for a failed request.
+    """
+    is_connect: Optional[bool]
+    r"""
+    Whether the error occurred while connecting.
+    """
+    is_body: Optional[bool]
+    r"""
+    Whether the error is related to the request or response body.
+    """
+    phase: Optional[str]
+    r"""
+    The phase that timed out: `"connect"`, `"read"` or `"total"`, or `None` if the
+    error isn't a timeout.
+    """
+    host: Optional[str]
+    r"""
+    The target host of the connection, if the error occurred while connecting.
+    """
+    port: Optional[int]
+    r"""
+    The target port of the connection, if the error occurred while connecting.
+    """
+    os_error: Optional[int]
+    r"""
+    The OS error code (`errno`) that caused the failure, if any.
+    """
+    status: Optional[int]
+    r"""
+    The HTTP status code of the response, if the error is about one.
+    """
+
 class URLParseError(Exception):
     r"""
     An error occurred while parsing a URL.
@@ -0,0 +1,809 @@
+use super::Response;
+use crate::{error::Error, typing::Version};
+use bytes::Bytes;
+use lru::LruCache;
+use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use uuid::Uuid;
+use wreq::{
+    Method, StatusCode,
+    header::{self, HeaderMap, HeaderName, HeaderValue},
+};
+
+const MAGIC: &[u8] = b"rnet-cache 1\n";
+
+/// An HTTP cache (RFC 9111) for the requests of a client.
+///
+/// Entries are kept in memory, or in `directory` when one is given so they survive restarts.
+/// Either way, the least recently stored entries are evicted once `max_size_mb` is exceeded.
+///
+/// Entries are keyed on the method and URL, and on the values of the request headers the
+/// response names in `Vary`, so each variant of a resource is stored on its own.
+#[pyclass]
+#[derive(Clone)]
+pub struct Cache(Arc<CacheInner>);
+
+struct CacheInner {
+    max_size: u64,
+    storage: Storage,
+}
+
+enum Storage {
+    Memory(Mutex<MemoryStore>),
+    Directory(PathBuf),
+}
+
+struct MemoryStore {
+    entries: LruCache<String, Arc<CacheEntry>>,
+    /// The header names the responses for a method and URL vary on.
+    vary: HashMap<String, Vec<HeaderName>>,
+    size: u64,
+}
+
+/// A stored response.
+pub struct CacheEntry {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    /// The request headers named by the response's `Vary` header, as they were sent.
+    vary: HeaderMap,
+    body: Bytes,
+    request_time: SystemTime,
+    response_time: SystemTime,
+}
+
+/// What the cache can do for a request.
+pub enum CacheLookup {
+    /// The request is neither served from nor stored in the cache.
+    Bypass,
+    /// A stored response is fresh and can be used without contacting the server.
+    Fresh(Arc<CacheEntry>),
+    /// Nothing usable is stored; a cacheable response will be stored.
+    Miss {
+        /// The method and URL, which the variant stored is keyed under.
+        key: String,
+        request_headers: HeaderMap,
+        request_time: SystemTime,
+    },
+    /// A stored response must be revalidated; the request carries its validators.
+    Stale {
+        key: String,
+        request_headers: HeaderMap,
+        request_time: SystemTime,
+        entry: Arc<CacheEntry>,
+    },
+    /// The request may change the resource, so a successful response drops every variant
+    /// stored for the URL.
+    Invalidate { key: String },
+}
+
+/// The `cache` client option: a flag or a configured [`Cache`].
+pub enum CacheOption {
+    Enabled(bool),
+    Cache(Cache),
+}
+
+impl FromPyObject<'_> for CacheOption {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(enabled) = ob.extract::<bool>() {
+            return Ok(Self::Enabled(enabled));
+        }
+        if let Ok(cache) = ob.extract::<Cache>() {
+            return Ok(Self::Cache(cache));
+        }
+
+        Err(PyTypeError::new_err(format!(
+            "cache: expected bool or Cache, got {}",
+            ob.get_type().name()?
+        )))
+    }
+}
+
+impl Cache {
+    /// Resolves the `cache` and `cache_dir` client options.
+    ///
+    /// `cache=True` creates a default cache, stored in `cache_dir` if it's given, and
+    /// `cache_dir` alone enables the cache as well.
+    pub fn from_options(
+        option: Option<CacheOption>,
+        directory: Option<PathBuf>,
+    ) -> PyResult<Option<Cache>> {
+        match (option, directory) {
+            (Some(CacheOption::Cache(_)), Some(_)) => Err(PyValueError::new_err(
+                "cache_dir can't be combined with a Cache instance, use Cache(directory=...)",
+            )),
+            (Some(CacheOption::Cache(cache)), None) => Ok(Some(cache)),
+            (Some(CacheOption::Enabled(false)), _) | (None, None) => Ok(None),
+            (Some(CacheOption::Enabled(true)), directory) | (None, directory) => {
+                Cache::new(64, directory).map(Some)
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl Cache {
+    /// Creates a new cache.
+    #[new]
+    #[pyo3(signature = (max_size_mb = 64, directory = None))]
+    fn new(max_size_mb: u64, directory: Option<PathBuf>) -> PyResult<Cache> {
+        let storage = match directory {
+            Some(directory) => {
+                std::fs::create_dir_all(&directory).map_err(Error::from)?;
+                Storage::Directory(directory)
+            }
+            None => Storage::Memory(Mutex::new(MemoryStore {
+                entries: LruCache::unbounded(),
+                vary: HashMap::new(),
+                size: 0,
+            })),
+        };
+
+        Ok(Cache(Arc::new(CacheInner {
+            max_size: max_size_mb.saturating_mul(1024 * 1024),
+            storage,
+        })))
+    }
+
+    /// Removes every entry from the cache.
+    fn clear(&self, py: Python) -> PyResult<()> {
+        py.allow_threads(|| match &self.0.storage {
+            Storage::Memory(store) => {
+                let mut store = store.lock().unwrap_or_else(|e| e.into_inner());
+                store.entries.clear();
+                store.vary.clear();
+                store.size = 0;
+                Ok(())
+            }
+            Storage::Directory(directory) => {
+                for file in std::fs::read_dir(directory).map_err(Error::from)? {
+                    let path = file.map_err(Error::from)?.path();
+                    // Temporary files may be left over from writes that were interrupted.
+                    if path
+                        .extension()
+                        .is_some_and(|ext| ext == "entry" || ext == "vary" || ext == "tmp")
+                    {
+                        std::fs::remove_file(path).map_err(Error::from)?;
+                    }
+                }
+                Ok(())
+            }
+        })
+    }
+}
+
+impl Cache {
+    /// Looks up `request`, adding validators to it when a stored response must be revalidated.
+    pub async fn lookup(
+        &self,
+        request: &mut wreq::Request,
+        default_headers: &HeaderMap,
+    ) -> CacheLookup {
+        if !request.method().is_safe() {
+            // Only `GET` responses are stored, so those are the ones to drop.
+            let key = base_key(&Method::GET, request.url().as_str());
+            return CacheLookup::Invalidate { key };
+        }
+        let request_directives = directives(request.headers(), header::CACHE_CONTROL);
+        if *request.method() != Method::GET
+            || request.headers().contains_key(header::RANGE)
+            || has(&request_directives, "no-store")
+        {
+            return CacheLookup::Bypass;
+        }
+
+        // The headers the request goes out with, to match against `Vary`.
+        let mut request_headers = default_headers.clone();
+        for name in request.headers().keys() {
+            request_headers.remove(name);
+        }
+        for (name, value) in request.headers() {
+            request_headers.append(name.clone(), value.clone());
+        }
+
+        let key = base_key(request.method(), request.url().as_str());
+        let names = self.load_vary(&key).await;
+        let variant = variant_key(&key, &names, &request_headers);
+        let request_time = SystemTime::now();
+        let entry = self.load(&key, &variant).await.filter(|entry| {
+            vary_names(&entry.headers)
+                .unwrap_or_default()
+                .iter()
+                .all(|name| request_headers.get(name) == entry.vary.get(name))
+        });
+
+        let Some(entry) = entry else {
+            return CacheLookup::Miss {
+                key,
+                request_headers,
+                request_time,
+            };
+        };
+
+        let age = entry.current_age(request_time);
+        let max_age = directive(&request_directives, "max-age")
+            .and_then(|value| value.parse().ok())
+            .map(Duration::from_secs);
+        let no_cache = has(&request_directives, "no-cache")
+            || request
+                .headers()
+                .get(header::PRAGMA)
+                .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"no-cache"))
+            || has(
+                &directives(&entry.headers, header::CACHE_CONTROL),
+                "no-cache",
+            );
+
+        if !no_cache
+            && entry
+                .freshness_lifetime()
+                .is_some_and(|lifetime| age < lifetime)
+            && max_age.is_none_or(|max_age| age <= max_age)
+        {
+            return CacheLookup::Fresh(entry);
+        }
+
+        // Conditional request: the server answers `304 Not Modified` if the entry still holds.
+        if let Some(etag) = entry.headers.get(header::ETAG) {
+            request
+                .headers_mut()
+                .insert(header::IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(last_modified) = entry.headers.get(header::LAST_MODIFIED) {
+            request
+                .headers_mut()
+                .insert(header::IF_MODIFIED_SINCE, last_modified.clone());
+        }
+
+        CacheLookup::Stale {
+            key,
+            request_headers,
+            request_time,
+            entry,
+        }
+    }
+
+    /// Updates the cache with the response to a looked up request and returns the response
+    /// to hand out, which is the stored one if the server confirmed it is still valid.
+    ///
+    /// Storing a new response reads its body in full, so it is skipped unless `store` is set.
+    pub async fn update(
+        &self,
+        lookup: CacheLookup,
+        response: Response,
+        store: bool,
+    ) -> PyResult<Response> {
+        let status = response.status_code().into_ffi();
+        let (key, request_headers, request_time) = match lookup {
+            CacheLookup::Bypass | CacheLookup::Fresh(_) => return Ok(response),
+            CacheLookup::Invalidate { key } => {
+                if status.is_success() || status.is_redirection() {
+                    self.remove_all(&key).await;
+                }
+                return Ok(response);
+            }
+            CacheLookup::Stale {
+                key,
+                request_headers,
+                request_time,
+                entry,
+            } if status == StatusCode::NOT_MODIFIED => {
+                let entry = Arc::new(entry.revalidated(
+                    response.header_map(),
+                    request_time,
+                    SystemTime::now(),
+                ));
+                self.store(&key, &request_headers, entry.clone()).await;
+                return Ok(entry.respond(response));
+            }
+            CacheLookup::Miss {
+                key,
+                request_headers,
+                request_time,
+            }
+            | CacheLookup::Stale {
+                key,
+                request_headers,
+                request_time,
+                ..
+            } => (key, request_headers, request_time),
+        };
+
+        if !store
+            || !is_storable(status, response.header_map())
+            || response.content_length() > self.0.max_size
+        {
+            return Ok(response);
+        }
+
+        let vary = vary_names(response.header_map())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|name| {
+                let value = request_headers.get(&name)?.clone();
+                Some((name, value))
+            })
+            .collect();
+
+        // The length may be unknown, so stop reading and leave the body to the caller as soon
+        // as it outgrows the cache.
+        let (response, body) = response.into_buffered_within(self.0.max_size).await?;
+        let Some(body) = body else {
+            return Ok(response);
+        };
+        let entry = Arc::new(CacheEntry {
+            status,
+            version: response.version(),
+            headers: response.header_map().clone(),
+            vary,
+            body,
+            request_time,
+            response_time: SystemTime::now(),
+        });
+        self.store(&key, &request_headers, entry).await;
+        Ok(response)
+    }
+
+    /// Returns the header names the responses stored under `key` vary on.
+    async fn load_vary(&self, key: &str) -> Vec<HeaderName> {
+        match &self.0.storage {
+            Storage::Memory(store) => {
+                let store = store.lock().unwrap_or_else(|e| e.into_inner());
+                store.vary.get(key).cloned().unwrap_or_default()
+            }
+            Storage::Directory(directory) => {
+                let path = directory.join(format!("{}.vary", key_hash(key)));
+                let Ok(data) = tokio::fs::read(path).await else {
+                    return Vec::new();
+                };
+                data.split(|&b| b == b'\n')
+                    .filter_map(|name| HeaderName::from_bytes(name).ok())
+                    .collect()
+            }
+        }
+    }
+
+    async fn load(&self, key: &str, variant: &str) -> Option<Arc<CacheEntry>> {
+        match &self.0.storage {
+            Storage::Memory(store) => {
+                let mut store = store.lock().unwrap_or_else(|e| e.into_inner());
+                store.entries.get(variant).cloned()
+            }
+            Storage::Directory(directory) => {
+                let path = directory.join(file_name(key, variant));
+                let data = tokio::fs::read(path).await.ok()?;
+                CacheEntry::decode(variant, &data).map(Arc::new)
+            }
+        }
+    }
+
+    /// Stores `entry` under `key` as the variant for the request sent with `request_headers`.
+    async fn store(&self, key: &str, request_headers: &HeaderMap, entry: Arc<CacheEntry>) {
+        let names = vary_names(&entry.headers).unwrap_or_default();
+        let variant = variant_key(key, &names, request_headers);
+        let size = entry.size();
+        if size > self.0.max_size {
+            self.remove(key, &variant).await;
+            return;
+        }
+
+        match &self.0.storage {
+            Storage::Memory(store) => {
+                let mut store = store.lock().unwrap_or_else(|e| e.into_inner());
+                store.vary.insert(key.to_owned(), names);
+                if let Some(old) = store.entries.put(variant, entry) {
+                    store.size -= old.size();
+                }
+                store.size += size;
+                while store.size > self.0.max_size {
+                    match store.entries.pop_lru() {
+                        Some((_, old)) => store.size -= old.size(),
+                        None => break,
+                    }
+                }
+            }
+            Storage::Directory(directory) => {
+                let vary = names
+                    .iter()
+                    .map(HeaderName::as_str)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let vary_path = directory.join(format!("{}.vary", key_hash(key)));
+                let path = directory.join(file_name(key, &variant));
+                if write_file(&vary_path, vary.as_bytes()).await
+                    && write_file(&path, &entry.encode(&variant)).await
+                {
+                    evict_files(directory, self.0.max_size).await;
+                }
+            }
+        }
+    }
+
+    async fn remove(&self, key: &str, variant: &str) {
+        match &self.0.storage {
+            Storage::Memory(store) => {
+                let mut store = store.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(old) = store.entries.pop(variant) {
+                    store.size -= old.size();
+                }
+            }
+            Storage::Directory(directory) => {
+                let _ = tokio::fs::remove_file(directory.join(file_name(key, variant))).await;
+            }
+        }
+    }
+
+    /// Removes every variant stored under `key`.
+    async fn remove_all(&self, key: &str) {
+        match &self.0.storage {
+            Storage::Memory(store) => {
+                let mut store = store.lock().unwrap_or_else(|e| e.into_inner());
+                let store = &mut *store;
+                store.vary.remove(key);
+                let variants = store
+                    .entries
+                    .iter()
+                    .map(|(variant, _)| variant)
+                    .filter(|variant| base_of(variant) == key)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for variant in variants {
+                    if let Some(old) = store.entries.pop(&variant) {
+                        store.size -= old.size();
+                    }
+                }
+            }
+            Storage::Directory(directory) => {
+                let Ok(mut dir) = tokio::fs::read_dir(directory).await else {
+                    return;
+                };
+                let prefix = key_hash(key);
+                while let Ok(Some(file)) = dir.next_entry().await {
+                    if file.file_name().to_string_lossy().starts_with(&prefix) {
+                        let _ = tokio::fs::remove_file(file.path()).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl CacheEntry {
+    /// Hands out the stored response in place of `response`.
+    pub fn respond(&self, response: Response) -> Response {
+        response.with_cached(
+            self.status,
+            self.version,
+            self.headers.clone(),
+            self.body.clone(),
+        )
+    }
+
+    /// Returns the size the entry is accounted with.
+    fn size(&self) -> u64 {
+        let headers = self
+            .headers
+            .iter()
+            .chain(self.vary.iter())
+            .map(|(name, value)| name.as_str().len() + value.len() + 4)
+            .sum::<usize>();
+        (headers + self.body.len()) as u64
+    }
+
+    /// The freshness lifetime of the response (RFC 9111, section 4.2.1).
+    fn freshness_lifetime(&self) -> Option<Duration> {
+        let directives = directives(&self.headers, header::CACHE_CONTROL);
+        if let Some(max_age) = directive(&directives, "max-age") {
+            return max_age.parse().ok().map(Duration::from_secs);
+        }
+
+        let date = http_date(&self.headers, header::DATE).unwrap_or(self.response_time);
+        if let Some(expires) = self.headers.get(header::EXPIRES) {
+            // An invalid `Expires` means the response is already expired.
+            return Some(
+                parse_http_date(expires)
+                    .and_then(|expires| expires.duration_since(date).ok())
+                    .unwrap_or_default(),
+            );
+        }
+
+        // Heuristic freshness: a tenth of the time since the resource was last modified.
+        let last_modified = http_date(&self.headers, header::LAST_MODIFIED)?;
+        date.duration_since(last_modified)
+            .ok()
+            .map(|elapsed| elapsed / 10)
+    }
+
+    /// The current age of the response (RFC 9111, section 4.2.3).
+    fn current_age(&self, now: SystemTime) -> Duration {
+        let date = http_date(&self.headers, header::DATE).unwrap_or(self.response_time);
+        let apparent_age = self.response_time.duration_since(date).unwrap_or_default();
+        let age_value = self
+            .headers
+            .get(header::AGE)
+            .and_then(|value| value.to_str().ok()?.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_default();
+        let response_delay = self
+            .response_time
+            .duration_since(self.request_time)
+            .unwrap_or_default();
+        let resident_time = now.duration_since(self.response_time).unwrap_or_default();
+
+        apparent_age.max(age_value + response_delay) + resident_time
+    }
+
+    /// Returns the entry with its headers updated from a `304 Not Modified` response.
+    fn revalidated(
+        &self,
+        headers: &HeaderMap,
+        request_time: SystemTime,
+        response_time: SystemTime,
+    ) -> CacheEntry {
+        let mut merged = self.headers.clone();
+        for name in headers.keys() {
+            if *name == header::CONTENT_LENGTH {
+                continue;
+            }
+            merged.remove(name);
+            for value in headers.get_all(name) {
+                merged.append(name.clone(), value.clone());
+            }
+        }
+
+        CacheEntry {
+            status: self.status,
+            version: self.version,
+            headers: merged,
+            vary: self.vary.clone(),
+            body: self.body.clone(),
+            request_time,
+            response_time,
+        }
+    }
+
+    /// Serializes the entry as a status line, the response and vary header blocks, and the body.
+    fn encode(&self, key: &str) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.size() as usize + key.len() + 64);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(key.as_bytes());
+        out.push(b'\n');
+        out.extend_from_slice(
+            format!(
+                "{} {} {} {}\n",
+                self.status.as_u16(),
+                self.version.as_str(),
+                unix_time(self.request_time),
+                unix_time(self.response_time),
+            )
+            .as_bytes(),
+        );
+        for headers in [&self.headers, &self.vary] {
+            for (name, value) in headers {
+                out.extend_from_slice(name.as_str().as_bytes());
+                out.extend_from_slice(b": ");
+                out.extend_from_slice(value.as_bytes());
+                out.push(b'\n');
+            }
+            out.push(b'\n');
+        }
+        out.extend_from_slice(&self.body);
+        out
+    }
+
+    /// Parses an entry written by [`CacheEntry::encode`], checking that it belongs to `key`.
+    fn decode(key: &str, data: &[u8]) -> Option<CacheEntry> {
+        let mut data = data.strip_prefix(MAGIC)?;
+        if next_line(&mut data)? != key.as_bytes() {
+            return None;
+        }
+
+        let status_line = std::str::from_utf8(next_line(&mut data)?).ok()?;
+        let mut fields = status_line.split(' ');
+        let status = StatusCode::from_u16(fields.next()?.parse().ok()?).ok()?;
+        let version = fields.next()?;
+        let version = [
+            Version::HTTP_09,
+            Version::HTTP_10,
+            Version::HTTP_11,
+            Version::HTTP_2,
+            Version::HTTP_3,
+        ]
+        .into_iter()
+        .find(|v| v.as_str() == version)?;
+        let request_time = UNIX_EPOCH + Duration::from_secs(fields.next()?.parse().ok()?);
+        let response_time = UNIX_EPOCH + Duration::from_secs(fields.next()?.parse().ok()?);
+
+        let headers = decode_headers(&mut data)?;
+        let vary = decode_headers(&mut data)?;
+
+        Some(CacheEntry {
+            status,
+            version,
+            headers,
+            vary,
+            body: Bytes::copy_from_slice(data),
+            request_time,
+            response_time,
+        })
+    }
+}
+
+fn decode_headers(data: &mut &[u8]) -> Option<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    loop {
+        let line = next_line(data)?;
+        if line.is_empty() {
+            return Some(headers);
+        }
+        let split = line.windows(2).position(|pair| pair == b": ")?;
+        headers.append(
+            HeaderName::from_bytes(&line[..split]).ok()?,
+            HeaderValue::from_bytes(&line[split + 2..]).ok()?,
+        );
+    }
+}
+
+fn next_line<'a>(data: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let end = data.iter().position(|&b| b == b'\n')?;
+    let line = &data[..end];
+    *data = &data[end + 1..];
+    Some(line)
+}
+
+/// Whether a response may be stored (RFC 9111, section 3).
+fn is_storable(status: StatusCode, headers: &HeaderMap) -> bool {
+    let directives = directives(headers, header::CACHE_CONTROL);
+    let understood = matches!(
+        status.as_u16(),
+        200 | 203 | 204 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501
+    );
+    let cacheable = has(&directives, "max-age")
+        || headers.contains_key(header::EXPIRES)
+        || headers.contains_key(header::ETAG)
+        || headers.contains_key(header::LAST_MODIFIED);
+
+    understood && cacheable && !has(&directives, "no-store") && vary_names(headers).is_some()
+}
+
+/// The header names listed in `Vary`, or `None` for `Vary: *`.
+fn vary_names(headers: &HeaderMap) -> Option<Vec<HeaderName>> {
+    let mut names = Vec::new();
+    for value in headers.get_all(header::VARY) {
+        for name in value.to_str().unwrap_or("*").split(',') {
+            let name = name.trim();
+            if name == "*" {
+                return None;
+            }
+            if let Ok(name) = HeaderName::from_bytes(name.as_bytes()) {
+                names.push(name);
+            }
+        }
+    }
+    Some(names)
+}
+
+/// Parses the comma-separated directives of a `Cache-Control` style header.
+fn directives(headers: &HeaderMap, name: HeaderName) -> Vec<(String, Option<String>)> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|directive| match directive.split_once('=') {
+            Some((name, value)) => (
+                name.trim().to_ascii_lowercase(),
+                Some(value.trim().trim_matches('"').to_owned()),
+            ),
+            None => (directive.trim().to_ascii_lowercase(), None),
+        })
+        .collect()
+}
+
+fn has(directives: &[(String, Option<String>)], name: &str) -> bool {
+    directives.iter().any(|(directive, _)| directive == name)
+}
+
+fn directive<'a>(directives: &'a [(String, Option<String>)], name: &str) -> Option<&'a str> {
+    directives
+        .iter()
+        .find(|(directive, _)| directive == name)
+        .and_then(|(_, value)| value.as_deref())
+}
+
+fn http_date(headers: &HeaderMap, name: HeaderName) -> Option<SystemTime> {
+    headers.get(name).and_then(parse_http_date)
+}
+
+fn parse_http_date(value: &HeaderValue) -> Option<SystemTime> {
+    httpdate::parse_http_date(value.to_str().ok()?).ok()
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// The key of the responses to `method` requests for `url`.
+fn base_key(method: &Method, url: &str) -> String {
+    format!("{method} {url}")
+}
+
+/// The key of the variant stored under `key` for a request with `request_headers`, given the
+/// header names the responses vary on. Header values can't contain NUL, so it separates them.
+fn variant_key(key: &str, names: &[HeaderName], request_headers: &HeaderMap) -> String {
+    let mut variant = key.to_owned();
+    for name in names {
+        variant.push('\0');
+        variant.push_str(name.as_str());
+        for value in request_headers.get_all(name) {
+            variant.push('\0');
+            variant.push_str(&String::from_utf8_lossy(value.as_bytes()));
+        }
+    }
+    variant
+}
+
+/// The key a variant key was built from.
+fn base_of(variant: &str) -> &str {
+    variant.split('\0').next().unwrap_or(variant)
+}
+
+fn key_hash(key: &str) -> String {
+    format!("{:x}", md5::compute(key))
+}
+
+/// The files of the variants of `key` share its hash as a prefix, so all of them can be found.
+fn file_name(key: &str, variant: &str) -> String {
+    format!("{}-{}.entry", key_hash(key), key_hash(variant))
+}
+
+/// Writes `data` to `path` through a temporary file, so readers never see a partial file.
+/// Each write has a temporary file of its own, so concurrent writes of an entry don't mix.
+async fn write_file(path: &Path, data: &[u8]) -> bool {
+    let tmp = path.with_extension(format!("{}.tmp", Uuid::new_v4().simple()));
+    if tokio::fs::write(&tmp, data).await.is_ok() && tokio::fs::rename(&tmp, path).await.is_ok() {
+        return true;
+    }
+    let _ = tokio::fs::remove_file(&tmp).await;
+    false
+}
+
+/// Deletes the oldest entries in `directory` until it fits in `max_size`.
+async fn evict_files(directory: &Path, max_size: u64) {
+    let Ok(mut dir) = tokio::fs::read_dir(directory).await else {
+        return;
+    };
+
+    let mut files = Vec::new();
+    while let Ok(Some(file)) = dir.next_entry().await {
+        let path = file.path();
+        if path.extension().is_none_or(|ext| ext != "entry") {
+            continue;
+        }
+        if let Ok(metadata) = file.metadata().await {
+            let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+            files.push((modified, metadata.len(), path));
+        }
+    }
+
+    let mut total = files.iter().map(|(_, len, _)| len).sum::<u64>();
+    files.sort_unstable_by_key(|(modified, ..)| *modified);
+    for (_, len, path) in files {
+        if total <= max_size {
+            break;
+        }
+        if tokio::fs::remove_file(&path).await.is_ok() {
+            total -= len;
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use super::{Response, transport::collect_body};
+use crate::error::{Error, RequestError};
+use bytes::Bytes;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+use tokio::sync::Mutex;
+use wreq::{
+    Url,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+
+/// A file of recorded requests and their responses, for tests that shouldn't depend on the
+/// network.
+///
+/// A recording cassette keeps the response to every request, rewriting the file after each
+/// one. A replaying cassette answers requests with the recorded responses instead of
+/// sending them, matching them by method, URL and body. Identical requests are answered in
+/// the order they were recorded, and the last answer is repeated once they run out.
+#[derive(Clone)]
+pub struct Cassette(Arc<Tape>);
+
+struct Tape {
+    path: PathBuf,
+    replay: bool,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    file: File,
+    /// Which interactions have been replayed.
+    replayed: Vec<bool>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct File {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+/// What a request is matched by.
+#[derive(PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    method: String,
+    url: String,
+    body: Body,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedResponse {
+    /// The URL the response came from, after redirects.
+    url: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Body,
+}
+
+/// A body, kept as text if it's UTF-8 so cassettes can be read and edited.
+#[derive(PartialEq, Serialize, Deserialize)]
+#[serde(tag = "encoding", content = "data", rename_all = "lowercase")]
+enum Body {
+    Text(String),
+    Hex(String),
+}
+
+impl Body {
+    fn new(bytes: &[u8]) -> Body {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Body::Text(text.to_owned()),
+            Err(_) => Body::Hex(bytes.iter().map(|b| format!("{b:02x}")).collect()),
+        }
+    }
+
+    fn bytes(&self) -> Option<Bytes> {
+        match self {
+            Body::Text(text) => Some(Bytes::copy_from_slice(text.as_bytes())),
+            Body::Hex(hex) => (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+                .collect::<Option<Vec<_>>>()
+                .map(Bytes::from),
+        }
+    }
+}
+
+impl RecordedRequest {
+    /// Reads what `request` is matched by, putting its body back once it's read.
+    pub async fn read(request: &mut wreq::Request) -> PyResult<RecordedRequest> {
+        let body = collect_body(request.body_mut().take()).await?;
+        let recorded = RecordedRequest {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            body: Body::new(&body),
+        };
+        if !body.is_empty() {
+            *request.body_mut() = Some(body.into());
+        }
+        Ok(recorded)
+    }
+}
+
+impl Cassette {
+    /// Creates a cassette that records into the file at `path`, replacing it.
+    pub fn record(path: PathBuf) -> Cassette {
+        Cassette::new(path, false, State::default())
+    }
+
+    /// Loads the cassette at `path` to replay it.
+    pub fn replay(path: PathBuf) -> PyResult<Cassette> {
+        let data = std::fs::read(&path).map_err(Error::IO)?;
+        let file = serde_json::from_slice::<File>(&data).map_err(|err| {
+            PyValueError::new_err(format!("invalid cassette {}: {err}", path.display()))
+        })?;
+        let state = State {
+            replayed: vec![false; file.interactions.len()],
+            file,
+        };
+        Ok(Cassette::new(path, true, state))
+    }
+
+    fn new(path: PathBuf, replay: bool, state: State) -> Cassette {
+        Cassette(Arc::new(Tape {
+            path,
+            replay,
+            state: Mutex::new(state),
+        }))
+    }
+
+    /// Whether the cassette answers requests instead of recording them.
+    pub fn replays(&self) -> bool {
+        self.0.replay
+    }
+
+    /// Returns the recorded response to `request`.
+    pub async fn replay_response(&self, request: &RecordedRequest) -> PyResult<Response> {
+        let mut state = self.0.state.lock().await;
+        let State { file, replayed } = &mut *state;
+        let interactions = &file.interactions;
+
+        let matching = (0..interactions.len())
+            .filter(|&i| interactions[i].request == *request)
+            .collect::<Vec<_>>();
+        let Some(&index) = matching.iter().find(|&&i| !replayed[i]).or(matching.last()) else {
+            return Err(RequestError::new_err(format!(
+                "no response recorded in {} for {} {}",
+                self.0.path.display(),
+                request.method,
+                request.url
+            )));
+        };
+        replayed[index] = true;
+
+        let response = &interactions[index].response;
+        let invalid = || {
+            PyValueError::new_err(format!(
+                "invalid response recorded in {} for {} {}",
+                self.0.path.display(),
+                request.method,
+                request.url
+            ))
+        };
+        let url = Url::parse(&response.url).map_err(|_| invalid())?;
+        let status = wreq::StatusCode::from_u16(response.status).map_err(|_| invalid())?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &response.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?;
+            let value = HeaderValue::from_str(value).map_err(|_| invalid())?;
+            headers.append(name, value);
+        }
+        let body = response.body.bytes().ok_or_else(invalid)?;
+        Ok(Response::mocked(url, status, headers, body))
+    }
+
+    /// Records `response` as the answer to `request`, reading its body in full, and saves
+    /// the cassette.
+    pub async fn record_response(
+        &self,
+        request: RecordedRequest,
+        response: Response,
+    ) -> PyResult<Response> {
+        let (response, body) = response.into_buffered().await?;
+        // wreq drops `Content-Encoding` and `Content-Length` from a response it decodes, so
+        // any left describe the body as it was read, encoded, and are kept with it.
+        let headers = response
+            .header_map()
+            .iter()
+            .map(|(name, value)| {
+                let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                (name.as_str().to_owned(), value)
+            })
+            .collect();
+        let interaction = Interaction {
+            request,
+            response: RecordedResponse {
+                url: response.url().0.to_string(),
+                status: response.status(),
+                headers,
+                body: Body::new(&body),
+            },
+        };
+
+        // The lock is held while writing, so the file always ends up with every response.
+        let mut state = self.0.state.lock().await;
+        state.file.interactions.push(interaction);
+        let data = serde_json::to_vec_pretty(&state.file)
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+
+        let tmp = self.0.path.with_extension("tmp");
+        tokio::fs::write(&tmp, data).await.map_err(Error::IO)?;
+        tokio::fs::rename(&tmp, &self.0.path)
+            .await
+            .map_err(Error::IO)?;
+        Ok(response)
+    }
+}
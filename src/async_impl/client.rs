@@ -1,33 +1,417 @@
-use super::request::{execute_request, execute_websocket_request};
+use super::{
+    Cache, Cassette, MockTransport, OAuth2ClientCredentials, PoolTracker, Response, TlsFingerprint,
+    download::execute_download,
+    gather::{BatchRequest, ProgressCallback, execute_gather},
+    redirect::DEFAULT_MAX_REDIRECTS,
+    request::{execute_connect_tunnel, execute_request, execute_websocket_request},
+    timing::{TimeConnectsLayer, TimedResolver},
+    transport::Mount,
+};
 use crate::{
     buffer::{HeaderValueBuffer, PyBufferProtocol},
     dns,
     error::Error,
     typing::{
-        Cookie, HeaderMap, Method, SslVerify, TlsVersion,
-        param::{ClientParams, RequestParams, UpdateClientParams, WebSocketParams},
+        Cookie, CookieJar, HeaderMap, ImpersonateExtractor, ImpersonateKey, LookupIpStrategy,
+        Method, MethodExtractor, ProxyExtractor, SslVerify, TlsVersion, UrlExtractor,
+        param::{ClientParams, RequestParams, Timeout, UpdateClientParams, WebSocketParams},
     },
 };
-use pyo3::{prelude::*, pybacked::PyBackedStr};
+use arc_swap::{ArcSwap, ArcSwapOption};
+use pyo3::{
+    IntoPyObjectExt,
+    exceptions::PyValueError,
+    prelude::*,
+    pybacked::PyBackedStr,
+    types::{PyDict, PyList, PyString},
+};
 use pyo3_async_runtimes::tokio::future_into_py;
-use std::ops::Deref;
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex as StdMutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use tokio::sync::Mutex;
 use wreq::{
-    CertStore, Url,
-    header::{Entry, OccupiedEntry},
+    CertStore, EmulationProvider, NoProxy, Url,
+    cookie::Jar,
+    header::{Entry, HeaderName, OccupiedEntry},
     redirect::Policy,
 };
 
+/// The smallest `max_header_size`: HTTP/1 response heads are limited by the size of the
+/// connection's read buffer, which can't be smaller.
+const MIN_MAX_HEADER_SIZE: usize = 8192;
+
 /// A client for making HTTP requests.
 #[pyclass(subclass)]
-pub struct Client(wreq::Client);
+pub struct Client {
+    client: ArcSwapOption<wreq::Client>,
+    /// A sibling of `client` that doesn't decode bodies, built for the first request with
+    /// `decompress=False`, with the tracker of its pool.
+    raw_client: ArcSwapOption<(wreq::Client, PoolTracker)>,
+    /// Siblings of `client` for the profiles requests override `impersonate` with, and
+    /// whether they decode bodies, with the trackers of their pools.
+    impersonated: StdMutex<HashMap<(ImpersonateKey, bool), (wreq::Client, PoolTracker)>>,
+    cache: Option<Cache>,
+    transport: ArcSwapOption<MockTransport>,
+    cassette: Option<Cassette>,
+    pool: PoolTracker,
+    max_redirects: ArcSwapOption<usize>,
+    request_id_header: Option<HeaderName>,
+    max_response_size: Option<u64>,
+    auth: Option<OAuth2ClientCredentials>,
+    lookup_ip_strategy: Option<LookupIpStrategy>,
+    websocket_defaults: Option<WebSocketParams>,
+    config: StdMutex<Py<PyDict>>,
+    cookie_jar: ArcSwap<Jar>,
+    stores_cookies: AtomicBool,
+}
+
+impl Client {
+    /// Returns a handle to the inner `wreq::Client`, or an error if the client has been closed.
+    pub fn inner(&self) -> PyResult<wreq::Client> {
+        self.client
+            .load()
+            .as_deref()
+            .cloned()
+            .ok_or_else(|| Error::ClientClosed)
+            .map_err(Into::into)
+    }
+
+    /// Returns the `wreq::Client` to send a request with `params` with, and the tracker of its
+    /// pool: the inner client, one that leaves bodies encoded if the request turns
+    /// `decompress` off, or one that impersonates the profile the request overrides
+    /// `impersonate` with, which is taken out of `params`.
+    pub fn client_for(
+        &self,
+        py: Python,
+        params: Option<&mut RequestParams>,
+    ) -> PyResult<(wreq::Client, PoolTracker)> {
+        let Some(params) = params else {
+            return Ok((self.inner()?, self.pool()));
+        };
+        let decompress = params.decompress != Some(false);
+        match params.impersonate.take() {
+            Some(impersonate) => self.impersonated_client(py, impersonate, decompress),
+            None if decompress => Ok((self.inner()?, self.pool())),
+            None => self.raw_client(py),
+        }
+    }
+
+    /// Returns a client with the options and headers of the inner one that impersonates
+    /// `impersonate` instead, building it the first time for a given profile.
+    ///
+    /// Every profile gets its own connection pool, so a request never reuses a connection
+    /// whose TLS handshake was made with the fingerprint of another. Random profiles aren't
+    /// known up front, so their clients aren't kept.
+    fn impersonated_client(
+        &self,
+        py: Python,
+        impersonate: ImpersonateExtractor,
+        decompress: bool,
+    ) -> PyResult<(wreq::Client, PoolTracker)> {
+        let client = self.inner()?;
+        let key = impersonate.1.map(|key| (key, decompress));
+        if let Some(key) = &key {
+            let impersonated = self.impersonated.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(sibling) = impersonated.get(key) {
+                return Ok(sibling.clone());
+            }
+        }
+
+        py.allow_threads(|| {
+            let config = self.config.lock().unwrap_or_else(|e| e.into_inner());
+            let (sibling, pool) = self.build_sibling(&config, client, |config| {
+                if has_tls_config(config)? {
+                    return Err(tls_config_conflict());
+                }
+                if !decompress {
+                    for name in ["gzip", "brotli", "deflate", "zstd"] {
+                        config.set_item(name, false)?;
+                    }
+                }
+                Ok(())
+            })?;
+            sibling
+                .update()
+                .emulation(impersonate.0)
+                .apply()
+                .map_err(Error::Request)?;
+
+            // Built under the config lock, so an update can't slip in before it's stored.
+            if let Some(key) = key {
+                self.impersonated
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(key, (sibling.clone(), pool.clone()));
+            }
+            Ok((sibling, pool))
+        })
+    }
+
+    /// Returns a client with the options and headers of the inner one, except that it doesn't
+    /// decode bodies, building it the first time.
+    ///
+    /// Decoding can't be turned off per request in wreq, so the sibling has its own
+    /// connection pool, sharing only the cookie jar.
+    pub fn raw_client(&self, py: Python) -> PyResult<(wreq::Client, PoolTracker)> {
+        let client = self.inner()?;
+        if let Some(raw) = self.raw_client.load().as_deref() {
+            return Ok(raw.clone());
+        }
+        py.allow_threads(|| self.build_raw_client(client))
+    }
+
+    fn build_raw_client(&self, client: wreq::Client) -> PyResult<(wreq::Client, PoolTracker)> {
+        let config = self.config.lock().unwrap_or_else(|e| e.into_inner());
+        let raw = self.build_sibling(&config, client, |config| {
+            for name in ["gzip", "brotli", "deflate", "zstd"] {
+                config.set_item(name, false)?;
+            }
+            Ok(())
+        })?;
+
+        // Built under the config lock, so an update can't slip in before it's stored.
+        self.raw_client.store(Some(Arc::new(raw.clone())));
+        Ok(raw)
+    }
+
+    /// Builds a client with its own connection pool from `config` as changed by `edit`, with
+    /// the headers of the inner one, sharing only the cookie jar. Returns it with the tracker
+    /// of its pool.
+    fn build_sibling(
+        &self,
+        config: &Py<PyDict>,
+        client: wreq::Client,
+        edit: impl FnOnce(&Bound<'_, PyDict>) -> PyResult<()>,
+    ) -> PyResult<(wreq::Client, PoolTracker)> {
+        // An update may have replaced the client while this waited for the lock.
+        let client = self.inner().unwrap_or(client);
+        let mut params = Python::with_gil(|py| {
+            let config = config.bind(py).copy()?;
+            if config.contains("headers")? {
+                config.del_item("headers")?;
+            }
+            edit(&config)?;
+            config.extract::<ClientParams>()
+        })?;
+        let jar = self.cookie_jar.load_full();
+        let pool = PoolTracker::default();
+        let sibling = build_client(&mut params, &pool, &jar)?;
+        sibling
+            .update()
+            .headers(|dst| *dst = client.headers())
+            .apply()
+            .map_err(Error::Request)?;
+        Ok((sibling, pool))
+    }
+
+    /// Picks the client each of `requests` is sent with, see [`Client::client_for`].
+    pub fn assign_clients(&self, py: Python, requests: &mut [BatchRequest]) -> PyResult<()> {
+        for request in requests {
+            let (client, pool) = self.client_for(py, request.params_mut())?;
+            request.set_client(client, pool);
+        }
+        Ok(())
+    }
 
-impl Deref for Client {
-    type Target = wreq::Client;
+    /// Returns the HTTP cache of the client, if it has one.
+    pub fn cache(&self) -> Option<Cache> {
+        self.cache.clone()
+    }
+
+    /// Returns the mock transport of the client with its mounts, if it has either.
+    pub fn transport(&self) -> Option<MockTransport> {
+        self.transport.load().as_deref().cloned()
+    }
+
+    /// Returns the cassette the client records its responses in or replays them from, if
+    /// it has one.
+    pub fn cassette(&self) -> Option<Cassette> {
+        self.cassette.clone()
+    }
 
-    #[inline(always)]
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    /// Returns the tracker of the client's connection pool.
+    pub fn pool(&self) -> PoolTracker {
+        self.pool.clone()
+    }
+
+    /// Returns how many redirects the client follows, or `None` if it doesn't follow any.
+    pub fn max_redirects(&self) -> Option<usize> {
+        self.max_redirects.load().as_deref().copied()
+    }
+
+    /// Returns the header the client sends request ids in, if it sends any.
+    pub fn request_id_header(&self) -> Option<HeaderName> {
+        self.request_id_header.clone()
+    }
+
+    /// Returns the size limit of the client's response bodies, if it has one.
+    pub fn max_response_size(&self) -> Option<u64> {
+        self.max_response_size
+    }
+
+    /// Summarizes the client's settings for its `repr`, under the class name `name`.
+    ///
+    /// Proxies are only counted, so their credentials never show up in logs.
+    pub fn describe(&self, py: Python, name: &str) -> PyResult<String> {
+        let Ok(client) = self.inner() else {
+            return Ok(format!("{name}(closed)"));
+        };
+        // Values are shown as Python would show them, so the repr reads as a call.
+        let user_agent = client
+            .user_agent()
+            .and_then(|hv| hv.to_str().ok())
+            .map(|ua| PyString::new(py, ua).repr().map(|repr| repr.to_string()))
+            .transpose()?
+            .unwrap_or_else(|| "None".to_owned());
+
+        // Read under the lock as updates are, without holding the GIL while waiting for it.
+        let config = py.allow_threads(|| {
+            let config = self.config.lock().unwrap_or_else(|e| e.into_inner());
+            Python::with_gil(|py| config.clone_ref(py))
+        });
+        let config = config.bind(py);
+        let impersonate = match config.get_item("impersonate")? {
+            Some(impersonate) if !impersonate.is_none() => impersonate.repr()?.to_string(),
+            _ => "None".to_owned(),
+        };
+        let mut compression = Vec::new();
+        for name in ["gzip", "brotli", "deflate", "zstd"] {
+            let enabled = config
+                .get_item(name)?
+                .map(|enabled| enabled.extract::<Option<bool>>())
+                .transpose()?
+                .flatten();
+            if enabled.unwrap_or(true) {
+                compression.push(name);
+            }
+        }
+        let compression = PyList::new(py, compression)?.repr()?;
+        let proxies = match config.get_item("proxies")? {
+            Some(proxies) if !proxies.is_none() => proxies.len()?,
+            _ => 0,
+        };
+
+        let cookie_store = if self.stores_cookies.load(Ordering::Relaxed) {
+            "True"
+        } else {
+            "False"
+        };
+        let redirects = match self.max_redirects() {
+            Some(max) => format!("allow_redirects=True, max_redirects={max}"),
+            None => "allow_redirects=False".to_owned(),
+        };
+        Ok(format!(
+            "{name}(user_agent={user_agent}, impersonate={impersonate}, cookie_store={cookie_store}, \
+             {redirects}, compression={compression}, proxies={proxies})"
+        ))
+    }
+
+    /// Returns the OAuth2 credentials the client authenticates its requests with, if any.
+    pub fn auth(&self) -> Option<OAuth2ClientCredentials> {
+        self.auth.clone()
+    }
+
+    /// Returns the strategy the client resolves host names with, if it was given one.
+    pub fn lookup_ip_strategy(&self) -> Option<LookupIpStrategy> {
+        self.lookup_ip_strategy
+    }
+
+    /// Returns the defaults for the client's WebSocket requests, if it has any.
+    pub fn websocket_defaults(&self) -> Option<WebSocketParams> {
+        self.websocket_defaults.clone()
+    }
+
+    /// Sends a `HEAD` request to `url`, following redirects unless `kwds` says otherwise,
+    /// and resolves to whether it ended with a `2xx` status.
+    pub fn head_status_ok(
+        &self,
+        url: UrlExtractor,
+        kwds: Option<RequestParams>,
+    ) -> PyResult<impl Future<Output = PyResult<bool>> + Send + 'static> {
+        let mut params = kwds.unwrap_or_default();
+        params.allow_redirects.get_or_insert(true);
+        let request = self.execute(wreq::Method::HEAD, url, params)?;
+        Ok(async move {
+            request.await.map(|response| {
+                let ok = response.ok();
+                response.discard();
+                ok
+            })
+        })
+    }
+
+    /// Resolves to the size of the body at `url` without reading it: the `Content-Length`
+    /// of a successful `HEAD` request, or failing that, the complete length reported for a
+    /// `GET` of its first byte. Both follow redirects unless `kwds` says otherwise.
+    pub fn content_length_of(
+        &self,
+        url: UrlExtractor,
+        kwds: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<impl Future<Output = PyResult<Option<u64>>> + Send + 'static> {
+        let params = || -> PyResult<RequestParams> {
+            let mut params = kwds
+                .map(|kwds| kwds.extract::<RequestParams>())
+                .transpose()?
+                .unwrap_or_default();
+            params.allow_redirects.get_or_insert(true);
+            params.prefetch_body = Some(false);
+            Ok(params)
+        };
+        let head = self.execute(wreq::Method::HEAD, url.clone(), params()?)?;
+        let mut params = params()?;
+        params.range = Some((0, Some(0)));
+        let get = self.execute(wreq::Method::GET, url, params)?;
+
+        Ok(async move {
+            // Some servers answer `HEAD` with an error or without a length, or drop it.
+            let length = head.await.ok().and_then(|response| {
+                let length = response.declared_content_length().filter(|_| response.ok());
+                response.discard();
+                length
+            });
+            if length.is_some() {
+                return Ok(length);
+            }
+
+            let response = get.await?;
+            let length = match response.status_code().as_int() {
+                206 => response.content_range_total(),
+                200 => response.declared_content_length(),
+                _ => None,
+            };
+            response.discard();
+            Ok(length)
+        })
+    }
+
+    /// Sends a request with the client's settings, as the request methods do.
+    fn execute(
+        &self,
+        method: wreq::Method,
+        url: UrlExtractor,
+        params: RequestParams,
+    ) -> PyResult<impl Future<Output = PyResult<Response>> + Send + 'static> {
+        Ok(execute_request(
+            self.inner()?,
+            self.cache(),
+            self.transport(),
+            self.cassette(),
+            Some(self.pool()),
+            self.max_redirects(),
+            self.request_id_header(),
+            self.max_response_size(),
+            self.auth(),
+            method,
+            url,
+            Some(params),
+        ))
     }
 }
 
@@ -38,10 +422,10 @@ impl Client {
     pub fn get<'py>(
         &self,
         py: Python<'py>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        self.request(py, Method::GET, url, kwds)
+        self.request(py, Method::GET.into(), url, kwds)
     }
 
     /// Make a HEAD request to the given URL.
@@ -49,10 +433,10 @@ impl Client {
     pub fn head<'py>(
         &self,
         py: Python<'py>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        self.request(py, Method::HEAD, url, kwds)
+        self.request(py, Method::HEAD.into(), url, kwds)
     }
 
     /// Make a POST request to the given URL.
@@ -60,10 +444,10 @@ impl Client {
     pub fn post<'py>(
         &self,
         py: Python<'py>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        self.request(py, Method::POST, url, kwds)
+        self.request(py, Method::POST.into(), url, kwds)
     }
 
     /// Make a PUT request to the given URL.
@@ -71,10 +455,10 @@ impl Client {
     pub fn put<'py>(
         &self,
         py: Python<'py>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        self.request(py, Method::PUT, url, kwds)
+        self.request(py, Method::PUT.into(), url, kwds)
     }
 
     /// Make a DELETE request to the given URL.
@@ -82,10 +466,10 @@ impl Client {
     pub fn delete<'py>(
         &self,
         py: Python<'py>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        self.request(py, Method::DELETE, url, kwds)
+        self.request(py, Method::DELETE.into(), url, kwds)
     }
 
     /// Make a PATCH request to the given URL.
@@ -93,10 +477,10 @@ impl Client {
     pub fn patch<'py>(
         &self,
         py: Python<'py>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        self.request(py, Method::PATCH, url, kwds)
+        self.request(py, Method::PATCH.into(), url, kwds)
     }
 
     /// Make a OPTIONS request to the given URL.
@@ -104,10 +488,10 @@ impl Client {
     pub fn options<'py>(
         &self,
         py: Python<'py>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        self.request(py, Method::OPTIONS, url, kwds)
+        self.request(py, Method::OPTIONS.into(), url, kwds)
     }
 
     /// Make a TRACE request to the given URL.
@@ -115,10 +499,10 @@ impl Client {
     pub fn trace<'py>(
         &self,
         py: Python<'py>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        self.request(py, Method::TRACE, url, kwds)
+        self.request(py, Method::TRACE.into(), url, kwds)
     }
 
     /// Make a request with the given method and URL.
@@ -126,12 +510,164 @@ impl Client {
     pub fn request<'py>(
         &self,
         py: Python<'py>,
-        method: Method,
-        url: PyBackedStr,
+        method: MethodExtractor,
+        url: UrlExtractor,
+        mut kwds: Option<RequestParams>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (client, pool) = self.client_for(py, kwds.as_mut())?;
+        future_into_py(
+            py,
+            execute_request(
+                client,
+                self.cache(),
+                self.transport(),
+                self.cassette(),
+                Some(pool),
+                self.max_redirects(),
+                self.request_id_header(),
+                self.max_response_size(),
+                self.auth(),
+                method.0,
+                url,
+                kwds,
+            ),
+        )
+    }
+
+    /// Check whether the given URL exists with a `HEAD` request, returning whether it ends
+    /// with a `2xx` status after following redirects.
+    #[pyo3(signature = (url, **kwds))]
+    pub fn head_ok<'py>(
+        &self,
+        py: Python<'py>,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        let client = self.0.clone();
-        future_into_py(py, execute_request(client, method, url, kwds))
+        future_into_py(py, self.head_status_ok(url, kwds)?)
+    }
+
+    /// Returns the size of the body at the given URL without downloading it, or `None` if
+    /// the server doesn't tell.
+    #[pyo3(signature = (url, **kwds))]
+    pub fn content_length<'py>(
+        &self,
+        py: Python<'py>,
+        url: UrlExtractor,
+        kwds: Option<Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, self.content_length_of(url, kwds.as_ref())?)
+    }
+
+    /// Download the given URL into `path`, resuming from the end of an existing file.
+    #[pyo3(signature = (url, path, resume = true, **kwds))]
+    pub fn download<'py>(
+        &self,
+        py: Python<'py>,
+        url: UrlExtractor,
+        path: PathBuf,
+        resume: bool,
+        mut kwds: Option<RequestParams>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (client, pool) = self.client_for(py, kwds.as_mut())?;
+        future_into_py(
+            py,
+            execute_download(
+                client,
+                self.cache(),
+                self.transport(),
+                self.cassette(),
+                Some(pool),
+                self.max_redirects(),
+                self.request_id_header(),
+                self.max_response_size(),
+                self.auth(),
+                url,
+                path,
+                resume,
+                kwds,
+            ),
+        )
+    }
+
+    /// Send a batch of `(method, url, kwargs)` requests with bounded concurrency, returning
+    /// the responses in the order of `requests`.
+    #[pyo3(signature = (requests, concurrency = 50, return_exceptions = false, on_progress = None))]
+    pub fn gather<'py>(
+        &self,
+        py: Python<'py>,
+        mut requests: Vec<BatchRequest>,
+        concurrency: usize,
+        return_exceptions: bool,
+        on_progress: Option<ProgressCallback>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner()?;
+        self.assign_clients(py, &mut requests)?;
+        let (cache, transport, pool) = (self.cache(), self.transport(), Some(self.pool()));
+        let cassette = self.cassette();
+        let (max_redirects, request_id_header) = (self.max_redirects(), self.request_id_header());
+        let (max_response_size, auth) = (self.max_response_size(), self.auth());
+        future_into_py(py, async move {
+            let results = execute_gather(
+                client,
+                cache,
+                transport,
+                cassette,
+                pool,
+                max_redirects,
+                request_id_header,
+                max_response_size,
+                auth,
+                requests,
+                concurrency,
+                return_exceptions,
+                on_progress,
+            )
+            .await?;
+            Python::with_gil(|py| {
+                results
+                    .into_iter()
+                    .map(|result| match result {
+                        Ok(response) => response.into_py_any(py),
+                        Err(err) => Ok(err.into_value(py).into_any()),
+                    })
+                    .collect::<PyResult<Vec<PyObject>>>()
+            })
+        })
+    }
+
+    /// Send `GET` requests to `urls` with bounded concurrency, returning the responses in the
+    /// order of `urls`, with the exceptions of failed requests in place of their responses.
+    #[pyo3(signature = (urls, concurrency = 50, **kwds))]
+    pub fn get_many<'py>(
+        &self,
+        py: Python<'py>,
+        urls: Vec<String>,
+        concurrency: usize,
+        kwds: Option<Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let requests = urls
+            .into_iter()
+            .map(|url| BatchRequest::new(wreq::Method::GET, url, kwds.as_ref()))
+            .collect::<PyResult<_>>()?;
+        self.gather(py, requests, concurrency, true, None)
+    }
+
+    /// Send a batch of `(method, url, kwargs)` requests with bounded concurrency, returning
+    /// the responses in order, with the exceptions of failed requests in their place.
+    #[pyo3(signature = (requests, concurrency = 50))]
+    pub fn request_many<'py>(
+        &self,
+        py: Python<'py>,
+        requests: Vec<BatchRequest>,
+        concurrency: usize,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.gather(py, requests, concurrency, true, None)
+    }
+
+    /// Resolves `host` with the client's DNS resolver and lookup strategy, returning the IP
+    /// addresses the client would connect to, in order.
+    pub fn resolve<'py>(&self, py: Python<'py>, host: String) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, dns::resolve(self.lookup_ip_strategy, host))
     }
 
     /// Make a WebSocket request to the given URL.
@@ -139,11 +675,30 @@ impl Client {
     pub fn websocket<'py>(
         &self,
         py: Python<'py>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<WebSocketParams>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        let client = self.0.clone();
-        future_into_py(py, execute_websocket_request(client, url, kwds))
+        let client = self.inner()?;
+        future_into_py(
+            py,
+            execute_websocket_request(client, self.websocket_defaults(), url, kwds),
+        )
+    }
+
+    /// Open a raw tunnel to the given host and port through an HTTP proxy.
+    #[pyo3(signature = (proxy, target_host, target_port))]
+    pub fn connect_tunnel<'py>(
+        &self,
+        py: Python<'py>,
+        proxy: ProxyExtractor,
+        target_host: PyBackedStr,
+        target_port: u16,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner()?;
+        future_into_py(
+            py,
+            execute_connect_tunnel(client, proxy.0, target_host, target_port),
+        )
     }
 }
 
@@ -152,234 +707,97 @@ impl Client {
     /// Creates a new Client instance.
     #[new]
     #[pyo3(signature = (**kwds))]
-    pub fn new(py: Python, mut kwds: Option<ClientParams>) -> PyResult<Client> {
-        py.allow_threads(|| {
-            let params = kwds.get_or_insert_default();
-            let mut builder = wreq::Client::builder().no_hickory_dns();
-
-            // Impersonation options.
-            if let Some(impersonate) = params.impersonate.take() {
-                builder = builder.emulation(impersonate.0);
-            }
+    pub fn new(py: Python, kwds: Option<Bound<'_, PyDict>>) -> PyResult<Client> {
+        let mut params = kwds
+            .as_ref()
+            .map(|kwds| kwds.extract::<ClientParams>())
+            .transpose()?
+            .unwrap_or_default();
+        // The options are kept, to rebuild the client when it's updated.
+        let config = match kwds {
+            Some(kwds) => kwds.copy()?,
+            None => PyDict::new(py),
+        }
+        .unbind();
 
-            // User agent options.
-            apply_option!(
-                apply_transformed_option_ref,
-                builder,
-                params.user_agent,
-                user_agent,
-                AsRef::<str>::as_ref
-            );
-
-            // Default headers options.
-            apply_option!(
-                apply_if_some_inner,
-                builder,
-                params.default_headers,
-                default_headers
-            );
-
-            // Headers order options.
-            apply_option!(
-                apply_if_some_inner,
-                builder,
-                params.headers_order,
-                headers_order
-            );
+        py.allow_threads(|| {
+            let params = &mut params;
 
-            // Referer options.
-            apply_option!(apply_if_some, builder, params.referer, referer);
+            // Cache options.
+            let cache = Cache::from_options(params.cache.take(), params.cache_dir.take())?;
 
-            // Allow redirects options.
-            apply_option!(
-                apply_option_or_default_with_value,
-                builder,
-                params.allow_redirects,
-                redirect,
-                false,
-                params
-                    .max_redirects
-                    .take()
-                    .map(Policy::limited)
-                    .unwrap_or_default()
-            );
+            // Cassette options.
+            let cassette = match (params.record.take(), params.replay.take()) {
+                (Some(_), Some(_)) => {
+                    return Err(PyValueError::new_err("record and replay can't be combined"));
+                }
+                (Some(path), None) => Some(Cassette::record(path)),
+                (None, Some(path)) => Some(Cassette::replay(path)?),
+                (None, None) => None,
+            };
 
-            // Cookie store options.
-            apply_option!(apply_if_some, builder, params.cookie_store, cookie_store);
+            // Request id options.
+            let request_id_header = params
+                .request_id_header
+                .take()
+                .map(|name| HeaderName::from_bytes(name.as_bytes()))
+                .transpose()
+                .map_err(Error::InvalidHeaderName)?;
 
-            // Async resolver options.
-            apply_option!(
-                apply_if_ok,
-                builder,
-                || dns::get_or_try_init(params.lookup_ip_strategy),
-                dns_resolver
-            );
+            // Count the connections the client opens, to tell pooled ones apart.
+            let pool = PoolTracker::default();
 
-            // Timeout options.
-            apply_option!(
-                apply_transformed_option,
-                builder,
-                params.timeout,
-                timeout,
-                Duration::from_secs
-            );
-            apply_option!(
-                apply_transformed_option,
-                builder,
-                params.connect_timeout,
-                connect_timeout,
-                Duration::from_secs
-            );
-            apply_option!(
-                apply_transformed_option,
-                builder,
-                params.read_timeout,
-                read_timeout,
-                Duration::from_secs
-            );
-            apply_option!(
-                apply_option_or_default,
-                builder,
-                params.no_keepalive,
-                no_keepalive,
-                false
-            );
-            apply_option!(
-                apply_transformed_option,
-                builder,
-                params.tcp_keepalive,
-                tcp_keepalive,
-                Duration::from_secs
-            );
-            apply_option!(
-                apply_transformed_option,
-                builder,
-                params.pool_idle_timeout,
-                pool_idle_timeout,
-                Duration::from_secs
-            );
-            apply_option!(
-                apply_if_some,
-                builder,
-                params.pool_max_idle_per_host,
-                pool_max_idle_per_host
-            );
-            apply_option!(apply_if_some, builder, params.pool_max_size, pool_max_size);
+            // Allow redirects options. Requests are sent with a policy of their own, which
+            // needs to know the client's.
+            let max_redirects = params
+                .allow_redirects
+                .unwrap_or(false)
+                .then(|| params.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS));
 
-            // Protocol options.
-            apply_option!(
-                apply_option_or_default,
-                builder,
-                params.http1_only,
-                http1_only,
-                false
-            );
-            apply_option!(
-                apply_option_or_default,
-                builder,
-                params.http2_only,
-                http2_only,
-                false
-            );
-            apply_option!(apply_if_some, builder, params.https_only, https_only);
-            apply_option!(apply_if_some, builder, params.tcp_nodelay, tcp_nodelay);
-            apply_option!(
-                apply_if_some,
-                builder,
-                params.http2_max_retry_count,
-                http2_max_retry_count
-            );
+            // Cookie options. The jar is kept even while the client doesn't store cookies.
+            let cookie_jar = params
+                .cookie_provider
+                .as_ref()
+                .map_or_else(Arc::default, |jar| jar.0.clone());
+            let stores_cookies = params.stores_cookies();
 
-            // TLS options.
-            apply_option!(
-                apply_transformed_option,
-                builder,
-                params.min_tls_version,
-                min_tls_version,
-                TlsVersion::into_ffi
-            );
-            apply_option!(
-                apply_transformed_option,
-                builder,
-                params.max_tls_version,
-                max_tls_version,
-                TlsVersion::into_ffi
-            );
-            apply_option!(apply_if_some, builder, params.tls_info, tls_info);
-
-            // SSL Verification options.
-            if let Some(verify) = params.verify.take() {
-                builder = match verify {
-                    SslVerify::DisableSslVerification(verify) => builder.cert_verification(verify),
-                    SslVerify::RootCertificateFilepath(path_buf) => {
-                        let store = CertStore::from_pem_file(path_buf).map_err(Error::Request)?;
-                        builder.cert_store(store)
-                    }
-                }
-            }
-
-            // Network options.
-            if let Some(proxies) = params.proxies.take() {
-                for proxy in proxies.0 {
-                    builder = builder.proxy(proxy);
-                }
-            }
-            apply_option!(
-                apply_option_or_default,
-                builder,
-                params.no_proxy,
-                no_proxy,
-                false
-            );
-            apply_option!(
-                apply_if_some_inner,
-                builder,
-                params.local_address,
-                local_address
-            );
-            #[cfg(any(
-                target_os = "android",
-                target_os = "fuchsia",
-                target_os = "linux",
-                target_os = "ios",
-                target_os = "visionos",
-                target_os = "macos",
-                target_os = "tvos",
-                target_os = "watchos"
-            ))]
-            apply_option!(apply_if_some, builder, params.interface, interface);
-
-            // Compression options.
-            apply_option!(apply_if_some, builder, params.gzip, gzip);
-            apply_option!(apply_if_some, builder, params.brotli, brotli);
-            apply_option!(apply_if_some, builder, params.deflate, deflate);
-            apply_option!(apply_if_some, builder, params.zstd, zstd);
-
-            builder
-                .http1(|mut http1| {
-                    http1.title_case_headers(true);
-                })
-                .build()
-                .map(Client)
-                .map_err(Error::Request)
-                .map_err(Into::into)
+            let client = build_client(params, &pool, &cookie_jar)?;
+            Ok(Client {
+                client: ArcSwapOption::from_pointee(client),
+                raw_client: ArcSwapOption::empty(),
+                impersonated: StdMutex::default(),
+                cache,
+                transport: ArcSwapOption::new(params.transport.take().map(Arc::new)),
+                cassette,
+                pool,
+                max_redirects: ArcSwapOption::new(max_redirects.map(Arc::new)),
+                request_id_header,
+                max_response_size: params.max_response_size,
+                auth: params.auth.take(),
+                lookup_ip_strategy: params.lookup_ip_strategy,
+                websocket_defaults: params.websocket_defaults.take(),
+                config: StdMutex::new(config),
+                cookie_jar: ArcSwap::new(cookie_jar),
+                stores_cookies: AtomicBool::new(stores_cookies),
+            })
         })
     }
 
     /// Returns the user agent of the client.
     #[getter]
-    pub fn user_agent(&self, py: Python) -> Option<String> {
-        py.allow_threads(|| {
-            self.0
+    pub fn user_agent(&self, py: Python) -> PyResult<Option<String>> {
+        let client = self.inner()?;
+        Ok(py.allow_threads(|| {
+            client
                 .user_agent()
                 .and_then(|hv| hv.to_str().map(ToString::to_string).ok())
-        })
+        }))
     }
 
     /// Returns the headers of the client.
     #[getter]
-    pub fn headers(&self) -> HeaderMap {
-        HeaderMap(self.0.headers())
+    pub fn headers(&self) -> PyResult<HeaderMap> {
+        self.inner().map(|client| HeaderMap(client.headers()))
     }
 
     /// Returns the cookies for the given URL.
@@ -387,11 +805,12 @@ impl Client {
     pub fn get_cookies<'py>(
         &self,
         py: Python<'py>,
-        url: PyBackedStr,
+        url: UrlExtractor,
     ) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let client = self.inner()?;
         let cookies = py.allow_threads(|| {
             let url = Url::parse(url.as_ref()).map_err(Error::from)?;
-            let cookies = self.0.get_cookies(&url);
+            let cookies = client.get_cookies(&url);
             Ok::<_, PyErr>(cookies.map(HeaderValueBuffer::new))
         })?;
 
@@ -400,39 +819,125 @@ impl Client {
 
     /// Sets the cookies for the given URL.
     #[pyo3(signature = (url, cookie))]
-    pub fn set_cookie(&self, py: Python, url: PyBackedStr, cookie: Cookie) -> PyResult<()> {
+    pub fn set_cookie(&self, py: Python, url: UrlExtractor, cookie: Cookie) -> PyResult<()> {
+        let client = self.inner()?;
         py.allow_threads(|| {
             let url = Url::parse(url.as_ref()).map_err(Error::from)?;
-            self.0.set_cookie(&url, cookie.0);
+            client.set_cookie(&url, cookie.0);
             Ok(())
         })
     }
 
     /// Removes the cookie with the given name for the given URL.
     #[pyo3(signature = (url, name))]
-    pub fn remove_cookie(&self, py: Python, url: PyBackedStr, name: PyBackedStr) -> PyResult<()> {
+    pub fn remove_cookie(&self, py: Python, url: UrlExtractor, name: PyBackedStr) -> PyResult<()> {
+        let client = self.inner()?;
         py.allow_threads(|| {
             let url = Url::parse(url.as_ref()).map_err(Error::from)?;
-            self.0.remove_cookie(&url, &name);
+            client.remove_cookie(&url, &name);
             Ok(())
         })
     }
 
     /// Clears the cookies for the given URL.
-    pub fn clear_cookies(&self, py: Python) {
-        py.allow_threads(|| {
-            self.0.clear_cookies();
-        })
+    pub fn clear_cookies(&self, py: Python) -> PyResult<()> {
+        let client = self.inner()?;
+        py.allow_threads(|| client.clear_cookies());
+        Ok(())
+    }
+
+    /// Returns the jar the client stores cookies in, or `None` if it doesn't store any.
+    #[getter]
+    pub fn cookie_jar(&self) -> Option<CookieJar> {
+        self.stores_cookies
+            .load(Ordering::Relaxed)
+            .then(|| CookieJar(self.cookie_jar.load_full()))
+    }
+
+    /// Makes the client store cookies in `jar`, or stop storing them if it's `None`.
+    #[setter]
+    pub fn set_cookie_jar(&self, py: Python, jar: Bound<'_, PyAny>) -> PyResult<()> {
+        let kwds = PyDict::new(py);
+        if !jar.is_none() {
+            kwds.set_item("cookie_provider", jar.downcast::<CookieJar>()?)?;
+        }
+        kwds.set_item("cookie_store", !jar.is_none())?;
+        self.update(py, Some(kwds))
     }
 
     /// Updates the client with the given parameters.
+    ///
+    /// Options wreq can't change on a live client (timeouts, pool settings, compression and the
+    /// cookie store) rebuild it from the options it was created and updated with, keeping its
+    /// cookies and headers. Requests in flight finish on the client they were sent with.
     #[pyo3(signature = (**kwds))]
-    pub fn update(&self, py: Python, mut kwds: Option<UpdateClientParams>) -> PyResult<()> {
+    pub fn update(&self, py: Python, kwds: Option<Bound<'_, PyDict>>) -> PyResult<()> {
+        self.inner()?;
+        let Some(kwds) = kwds else {
+            return Ok(());
+        };
+        let mut params = kwds.extract::<UpdateClientParams>()?;
+        let kwds = kwds.unbind();
+
         py.allow_threads(|| {
-            let params = kwds.get_or_insert_default();
+            let params = &mut params;
 
-            // Create a new client with the current configuration.
-            let mut update = self.0.update();
+            // Updates are applied one at a time, so each builds on the options of the last.
+            let mut config = self.config.lock().unwrap_or_else(|e| e.into_inner());
+            // Read the client under the lock, so a rebuild by the last update isn't missed.
+            let mut client = self.inner()?;
+
+            // Record the options for later rebuilds, except for the headers, which are kept
+            // from the live client.
+            let (merged, rebuilt, max_redirects) = Python::with_gil(|py| {
+                let merged = config.bind(py).copy()?;
+                merged.update(kwds.bind(py).as_mapping())?;
+                if params.impersonate.is_some() && has_tls_config(&merged)? {
+                    return Err(tls_config_conflict());
+                }
+                if merged.contains("headers")? {
+                    merged.del_item("headers")?;
+                }
+                let rebuilt = params
+                    .needs_rebuild()
+                    .then(|| merged.extract::<ClientParams>())
+                    .transpose()?;
+                let max_redirects =
+                    if params.allow_redirects.is_some() || params.max_redirects.is_some() {
+                        let allow = merged.get_item("allow_redirects")?;
+                        let max = merged.get_item("max_redirects")?;
+                        let allow = allow.map(|v| v.extract::<Option<bool>>()).transpose()?;
+                        let max = max.map(|v| v.extract::<Option<usize>>()).transpose()?;
+                        let (allow, max) = (allow.flatten(), max.flatten());
+                        Some(
+                            allow
+                                .unwrap_or(false)
+                                .then(|| max.unwrap_or(DEFAULT_MAX_REDIRECTS)),
+                        )
+                    } else {
+                        None
+                    };
+                Ok::<_, PyErr>((merged.unbind(), rebuilt, max_redirects))
+            })?;
+
+            // Rebuild the client, carrying over the headers it has now.
+            let (headers, cookies) = match rebuilt {
+                Some(mut rebuilt) => {
+                    let headers = client.headers();
+                    let jar = rebuilt
+                        .cookie_provider
+                        .as_ref()
+                        .map_or_else(|| self.cookie_jar.load_full(), |jar| jar.0.clone());
+                    let stores_cookies = rebuilt.stores_cookies();
+                    client = build_client(&mut rebuilt, &self.pool, &jar)?;
+                    (Some(headers), Some((jar, stores_cookies)))
+                }
+                None => (None, None),
+            };
+            let mut update = client.update();
+            if let Some(headers) = headers {
+                update = update.headers(|dst| *dst = headers);
+            }
 
             // Impersonation options.
             apply_option!(apply_if_some_inner, update, params.impersonate, emulation);
@@ -479,26 +984,630 @@ impl Client {
 
             // Network options.
             apply_option!(apply_if_some_inner, update, params.proxies, proxies);
-            apply_option!(
-                apply_if_some_inner,
-                update,
-                params.local_address,
-                local_address
-            );
-            #[cfg(any(
-                target_os = "android",
-                target_os = "fuchsia",
-                target_os = "linux",
-                target_os = "ios",
-                target_os = "visionos",
-                target_os = "macos",
-                target_os = "tvos",
-                target_os = "watchos"
-            ))]
-            apply_option!(apply_if_some, update, params.interface, interface);
+            apply_local_address!(update, params.local_address, params.interface);
+            apply_interface!(update, params.interface);
 
             // Apply the changes.
-            update.apply().map_err(Error::Request).map_err(Into::into)
+            update.apply().map_err(Error::Request)?;
+            if let Some(max_redirects) = max_redirects {
+                self.max_redirects.store(max_redirects.map(Arc::new));
+            }
+            if let Some((jar, stores_cookies)) = cookies {
+                self.cookie_jar.store(jar);
+                self.stores_cookies.store(stores_cookies, Ordering::Relaxed);
+
+                // A client closed in the meantime stays closed.
+                let client = Arc::new(client);
+                self.client
+                    .rcu(|current| current.as_ref().map(|_| client.clone()));
+            }
+            *config = merged;
+            self.raw_client.store(None);
+            self.impersonated
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clear();
+            Ok(())
+        })
+    }
+
+    fn __repr__(slf: &Bound<'_, Self>) -> PyResult<String> {
+        let name = slf.get_type().name()?;
+        slf.borrow().describe(slf.py(), &name.to_cow()?)
+    }
+
+    /// Closes the client, dropping its connection pool.
+    ///
+    /// Requests already in flight complete normally; any further use of the client raises an error.
+    pub fn close(&self, py: Python) {
+        py.allow_threads(|| {
+            self.client.store(None);
+            self.raw_client.store(None);
+            self.impersonated
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clear();
+        })
+    }
+
+    /// Returns the TLS fingerprint the client presents with its current settings.
+    ///
+    /// The capture goes over a sibling without proxies, so the `ClientHello` reaches the
+    /// local listener rather than a proxy configured explicitly or through `trust_env`.
+    pub fn tls_fingerprint<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner()?;
+        let (client, _) = py.allow_threads(|| {
+            let config = self.config.lock().unwrap_or_else(|e| e.into_inner());
+            self.build_sibling(&config, client, |config| {
+                if config.contains("proxies")? {
+                    config.del_item("proxies")?;
+                }
+                config.set_item("trust_env", false)
+            })
+        })?;
+        future_into_py(py, TlsFingerprint::capture(client))
+    }
+
+    /// Returns the OAuth2 token the client has cached, or `None` if it has no `auth` or
+    /// hasn't fetched a token yet.
+    pub fn token_info<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        match &self.auth {
+            Some(auth) => auth.token_info(py),
+            None => Ok(None),
+        }
+    }
+
+    /// Answers the requests `matcher` matches with `handler` instead of sending them, like a
+    /// [`MockTransport`] handler. The matcher is either a URL prefix or a callable taking
+    /// the method and URL. Mounts are tried in the order they were added.
+    pub fn mount(&self, matcher: Bound<'_, PyAny>, handler: Bound<'_, PyAny>) -> PyResult<()> {
+        let mount = Arc::new(Mount::new(matcher, handler)?);
+        self.transport.rcu(|transport| {
+            Some(Arc::new(MockTransport::mounted(
+                transport.as_deref(),
+                mount.clone(),
+            )))
+        });
+        Ok(())
+    }
+
+    /// Returns a [`Connection`] that pins subsequent requests to a single connection.
+    pub fn connection(&self, py: Python) -> PyResult<Connection> {
+        let client = self.inner()?;
+        let (client, pool) = py.allow_threads(|| {
+            let config = self.config.lock().unwrap_or_else(|e| e.into_inner());
+            self.build_sibling(&config, client, |config| {
+                config.set_item("pool_max_idle_per_host", 1)
+            })
+        })?;
+        Ok(Connection {
+            client: ArcSwapOption::from_pointee(client),
+            cache: self.cache(),
+            transport: self.transport(),
+            cassette: self.cassette(),
+            pool,
+            max_redirects: self.max_redirects(),
+            request_id_header: self.request_id_header(),
+            max_response_size: self.max_response_size(),
+            auth: self.auth(),
+            lock: Arc::new(Mutex::new(())),
+        })
+    }
+}
+
+/// A scoped view of a [`Client`] whose requests reuse a single connection.
+///
+/// It sends over a sibling of the client with a pool of its own, so no other request can take
+/// its connection. Requests are sent strictly one at a time, in the order they were issued:
+/// each waits until the body of the previous response is read or dropped, which hands the
+/// connection back to the pool. A body dropped unread closes the connection, as does the
+/// server, so the next request opens a new one.
+///
+/// Leaving its `async with` block drops the sibling and its connection, and any further use
+/// raises an error.
+#[pyclass]
+pub struct Connection {
+    client: ArcSwapOption<wreq::Client>,
+    cache: Option<Cache>,
+    transport: Option<MockTransport>,
+    cassette: Option<Cassette>,
+    pool: PoolTracker,
+    max_redirects: Option<usize>,
+    request_id_header: Option<HeaderName>,
+    max_response_size: Option<u64>,
+    auth: Option<OAuth2ClientCredentials>,
+    lock: Arc<Mutex<()>>,
+}
+
+#[pymethods]
+impl Connection {
+    /// Make a GET request on the pinned connection.
+    #[pyo3(signature = (url, **kwds))]
+    pub fn get<'py>(
+        &self,
+        py: Python<'py>,
+        url: UrlExtractor,
+        kwds: Option<RequestParams>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.request(py, Method::GET.into(), url, kwds)
+    }
+
+    /// Make a HEAD request on the pinned connection.
+    #[pyo3(signature = (url, **kwds))]
+    pub fn head<'py>(
+        &self,
+        py: Python<'py>,
+        url: UrlExtractor,
+        kwds: Option<RequestParams>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.request(py, Method::HEAD.into(), url, kwds)
+    }
+
+    /// Make a POST request on the pinned connection.
+    #[pyo3(signature = (url, **kwds))]
+    pub fn post<'py>(
+        &self,
+        py: Python<'py>,
+        url: UrlExtractor,
+        kwds: Option<RequestParams>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.request(py, Method::POST.into(), url, kwds)
+    }
+
+    /// Make a PUT request on the pinned connection.
+    #[pyo3(signature = (url, **kwds))]
+    pub fn put<'py>(
+        &self,
+        py: Python<'py>,
+        url: UrlExtractor,
+        kwds: Option<RequestParams>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.request(py, Method::PUT.into(), url, kwds)
+    }
+
+    /// Make a DELETE request on the pinned connection.
+    #[pyo3(signature = (url, **kwds))]
+    pub fn delete<'py>(
+        &self,
+        py: Python<'py>,
+        url: UrlExtractor,
+        kwds: Option<RequestParams>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.request(py, Method::DELETE.into(), url, kwds)
+    }
+
+    /// Make a PATCH request on the pinned connection.
+    #[pyo3(signature = (url, **kwds))]
+    pub fn patch<'py>(
+        &self,
+        py: Python<'py>,
+        url: UrlExtractor,
+        kwds: Option<RequestParams>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.request(py, Method::PATCH.into(), url, kwds)
+    }
+
+    /// Make an OPTIONS request on the pinned connection.
+    #[pyo3(signature = (url, **kwds))]
+    pub fn options<'py>(
+        &self,
+        py: Python<'py>,
+        url: UrlExtractor,
+        kwds: Option<RequestParams>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.request(py, Method::OPTIONS.into(), url, kwds)
+    }
+
+    /// Make a TRACE request on the pinned connection.
+    #[pyo3(signature = (url, **kwds))]
+    pub fn trace<'py>(
+        &self,
+        py: Python<'py>,
+        url: UrlExtractor,
+        kwds: Option<RequestParams>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.request(py, Method::TRACE.into(), url, kwds)
+    }
+
+    /// Make a request with the given method and URL on the pinned connection.
+    #[pyo3(signature = (method, url, **kwds))]
+    pub fn request<'py>(
+        &self,
+        py: Python<'py>,
+        method: MethodExtractor,
+        url: UrlExtractor,
+        kwds: Option<RequestParams>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if kwds.as_ref().is_some_and(|kwds| kwds.impersonate.is_some()) {
+            return Err(PyValueError::new_err(
+                "impersonate can't be overridden per request on a pinned connection",
+            ));
+        }
+        if kwds
+            .as_ref()
+            .is_some_and(|kwds| kwds.decompress == Some(false))
+        {
+            return Err(PyValueError::new_err(
+                "decompress can't be turned off per request on a pinned connection",
+            ));
+        }
+        let client = self
+            .client
+            .load()
+            .as_deref()
+            .cloned()
+            .ok_or(Error::ClientClosed)?;
+        let cache = self.cache.clone();
+        let transport = self.transport.clone();
+        let cassette = self.cassette.clone();
+        let pool = self.pool.clone();
+        let max_redirects = self.max_redirects;
+        let request_id_header = self.request_id_header.clone();
+        let max_response_size = self.max_response_size;
+        let auth = self.auth.clone();
+        let lock = self.lock.clone();
+        future_into_py(py, async move {
+            let guard = lock.lock_owned().await;
+            execute_request(
+                client,
+                cache,
+                transport,
+                cassette,
+                Some(pool),
+                max_redirects,
+                request_id_header,
+                max_response_size,
+                auth,
+                method.0,
+                url,
+                kwds,
+            )
+            .await
+            .map(|response| response.holding(guard))
         })
     }
+
+    fn __aenter__<'py>(slf: PyRef<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let slf = slf.into_py_any(py)?;
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: &Bound<'py, PyAny>,
+        _exc_value: &Bound<'py, PyAny>,
+        _traceback: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.client.store(None);
+        future_into_py(py, async move { Ok(()) })
+    }
+}
+
+/// Builds the `wreq::Client` for `params`, counting its connections in `pool`, timing its DNS
+/// lookups and connects, and keeping its cookies, if it stores any, in `cookie_jar`.
+///
+/// The options that only concern rnet, such as the cache or the transport, are left in `params`.
+fn build_client(
+    params: &mut ClientParams,
+    pool: &PoolTracker,
+    cookie_jar: &Arc<Jar>,
+) -> PyResult<wreq::Client> {
+    let mut builder = wreq::Client::builder()
+        .no_hickory_dns()
+        .connector_layer(pool.layer())
+        .connector_layer(TimeConnectsLayer);
+
+    // Impersonation options.
+    if params.impersonate.is_some() && params.tls_config.is_some() {
+        return Err(tls_config_conflict());
+    }
+    if let Some(impersonate) = params.impersonate.take() {
+        builder = builder.emulation(impersonate.0);
+    }
+
+    // An emulation without headers or HTTP settings only sets the TLS config.
+    if let Some(tls_config) = params.tls_config.take() {
+        builder = builder.emulation(
+            EmulationProvider::builder()
+                .tls_config(tls_config.0)
+                .build(),
+        );
+    }
+
+    // User agent options.
+    apply_option!(
+        apply_transformed_option_ref,
+        builder,
+        params.user_agent,
+        user_agent,
+        AsRef::<str>::as_ref
+    );
+
+    // Default headers options.
+    apply_option!(
+        apply_if_some_inner,
+        builder,
+        params.default_headers,
+        default_headers
+    );
+
+    // Headers order options.
+    apply_option!(
+        apply_if_some_inner,
+        builder,
+        params.headers_order,
+        headers_order
+    );
+
+    // Referer options.
+    apply_option!(apply_if_some, builder, params.referer, referer);
+
+    // Allow redirects options.
+    apply_option!(
+        apply_option_or_default_with_value,
+        builder,
+        params.allow_redirects,
+        redirect,
+        false,
+        params
+            .max_redirects
+            .take()
+            .map(Policy::limited)
+            .unwrap_or_default()
+    );
+
+    // Cookie store options. The jar outlives the client, so rebuilding it keeps the cookies.
+    if params.stores_cookies() {
+        builder = builder.cookie_provider(cookie_jar.clone());
+    }
+
+    // Async resolver options.
+    apply_option!(
+        apply_if_ok,
+        builder,
+        || dns::get_or_try_init(params.lookup_ip_strategy).map(TimedResolver::new),
+        dns_resolver
+    );
+
+    // Timeout options. A `(connect, read)` timeout sets those two, unless they're also given
+    // on their own.
+    match params.timeout.take() {
+        Some(Timeout::Total(timeout)) => builder = builder.timeout(timeout),
+        Some(Timeout::Split(connect, read)) => {
+            params.connect_timeout = params.connect_timeout.or(connect);
+            params.read_timeout = params.read_timeout.or(read);
+        }
+        None => {}
+    }
+    apply_option!(
+        apply_if_some_inner,
+        builder,
+        params.connect_timeout,
+        connect_timeout
+    );
+    apply_option!(
+        apply_if_some_inner,
+        builder,
+        params.read_timeout,
+        read_timeout
+    );
+    apply_option!(
+        apply_option_or_default,
+        builder,
+        params.no_keepalive,
+        no_keepalive,
+        false
+    );
+    apply_option!(
+        apply_transformed_option,
+        builder,
+        params.tcp_keepalive,
+        tcp_keepalive,
+        Duration::from_secs
+    );
+    apply_option!(
+        apply_if_some_inner,
+        builder,
+        params.pool_idle_timeout,
+        pool_idle_timeout
+    );
+    apply_option!(
+        apply_if_some,
+        builder,
+        params.pool_max_idle_per_host,
+        pool_max_idle_per_host
+    );
+    apply_option!(apply_if_some, builder, params.pool_max_size, pool_max_size);
+
+    // Protocol options.
+    apply_option!(
+        apply_option_or_default,
+        builder,
+        params.http1_only,
+        http1_only,
+        false
+    );
+    apply_option!(
+        apply_option_or_default,
+        builder,
+        params.http2_only,
+        http2_only,
+        false
+    );
+    apply_option!(apply_if_some, builder, params.https_only, https_only);
+    apply_option!(apply_if_some, builder, params.tcp_nodelay, tcp_nodelay);
+    apply_option!(
+        apply_if_some,
+        builder,
+        params.http2_max_retry_count,
+        http2_max_retry_count
+    );
+
+    // TLS options.
+    apply_option!(
+        apply_transformed_option,
+        builder,
+        params.min_tls_version,
+        min_tls_version,
+        TlsVersion::into_ffi
+    );
+    apply_option!(
+        apply_transformed_option,
+        builder,
+        params.max_tls_version,
+        max_tls_version,
+        TlsVersion::into_ffi
+    );
+    apply_option!(apply_if_some, builder, params.tls_info, tls_info);
+
+    // SSL Verification options. A client certificate lives in the store of the roots, so
+    // with one the store gets the roots wreq would use, unless a file is given.
+    let roots = match params.verify.take() {
+        Some(SslVerify::DisableSslVerification(verify)) => {
+            builder = builder.cert_verification(verify);
+            None
+        }
+        Some(SslVerify::RootCertificateFilepath(path_buf)) => Some(path_buf),
+        None => None,
+    };
+    let store = match (roots, params.cert.take()) {
+        (Some(path_buf), None) => Some(CertStore::from_pem_file(path_buf)),
+        (Some(path_buf), Some(cert)) => Some(
+            CertStore::builder()
+                .identity(cert.0)
+                .add_file_pem_certs(path_buf)
+                .build(),
+        ),
+        (None, Some(cert)) => Some(
+            CertStore::builder()
+                .identity(cert.0)
+                .add_der_certs(webpki_root_certs::TLS_SERVER_ROOT_CERTS)
+                .build(),
+        ),
+        (None, None) => None,
+    };
+    if let Some(store) = store {
+        builder = builder.cert_store(store.map_err(Error::Request)?);
+    }
+
+    // Network options.
+    let trust_env = params
+        .trust_env
+        .unwrap_or_else(|| TRUST_ENV.load(Ordering::Relaxed));
+    if !trust_env {
+        builder = builder.no_proxy();
+    }
+    if let Some(proxies) = params.proxies.take() {
+        for proxy in proxies.0 {
+            builder = builder.proxy(proxy);
+        }
+    } else if trust_env {
+        for proxy in env_proxies() {
+            builder = builder.proxy(proxy);
+        }
+    }
+    apply_option!(
+        apply_option_or_default,
+        builder,
+        params.no_proxy,
+        no_proxy,
+        false
+    );
+    apply_local_address!(builder, params.local_address, params.interface);
+    apply_interface!(builder, params.interface);
+
+    // Compression options.
+    apply_option!(apply_if_some, builder, params.gzip, gzip);
+    apply_option!(apply_if_some, builder, params.brotli, brotli);
+    apply_option!(apply_if_some, builder, params.deflate, deflate);
+    apply_option!(apply_if_some, builder, params.zstd, zstd);
+
+    // Header case options.
+    let preserve_header_case = params.preserve_header_case.unwrap_or(false);
+
+    // Header size options. HTTP/2 keeps the emulated `SETTINGS_MAX_HEADER_LIST_SIZE`,
+    // which is part of the fingerprint.
+    let max_header_size = params.max_header_size.take();
+    if max_header_size.is_some_and(|size| size < MIN_MAX_HEADER_SIZE) {
+        return Err(PyValueError::new_err(format!(
+            "max_header_size must be at least {MIN_MAX_HEADER_SIZE} bytes"
+        )));
+    }
+
+    builder
+        .http1(|mut http1| {
+            if preserve_header_case {
+                http1.preserve_header_case(true);
+            } else {
+                http1.title_case_headers(true);
+            }
+            if let Some(size) = max_header_size {
+                http1.max_buf_size(size);
+            }
+        })
+        .build()
+        .map_err(Error::Request)
+        .map_err(Into::into)
+}
+
+/// Whether the options in `config` include a `tls_config`.
+fn has_tls_config(config: &Bound<'_, PyDict>) -> PyResult<bool> {
+    Ok(config
+        .get_item("tls_config")?
+        .is_some_and(|tls_config| !tls_config.is_none()))
+}
+
+/// wreq gives no access to the TLS config of an impersonation profile, so a `tls_config`
+/// can't be merged onto one; it would replace the profile's TLS config as a whole.
+fn tls_config_conflict() -> PyErr {
+    PyValueError::new_err(
+        "tls_config can't be combined with impersonate: the profile's TLS settings can't be \
+         overridden field by field, and tls_config would replace them all",
+    )
+}
+
+/// Whether clients built without `trust_env` read proxies from the environment.
+static TRUST_ENV: AtomicBool = AtomicBool::new(true);
+
+/// Sets whether clients built without `trust_env` read proxies from the environment.
+///
+/// Clients that are already built keep their proxies.
+#[pyfunction]
+#[pyo3(signature = (enabled = true))]
+pub fn set_trust_env_default(enabled: bool) {
+    TRUST_ENV.store(enabled, Ordering::Relaxed);
+}
+
+/// Reads proxies from the `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY` environment variables
+/// (or their lowercase forms), bypassing the hosts listed in `NO_PROXY`.
+///
+/// Unlike the system proxy built into `wreq`, the environment is read every time a client is built.
+fn env_proxies() -> Vec<wreq::Proxy> {
+    let var = |name: &str| {
+        std::env::var(name)
+            .or_else(|_| std::env::var(name.to_ascii_lowercase()))
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+    };
+
+    let mut proxies = Vec::new();
+
+    // In a CGI context `HTTP_PROXY` can be set by the client through the `Proxy` header.
+    if std::env::var_os("REQUEST_METHOD").is_none() {
+        if let Some(proxy) = var("HTTP_PROXY").and_then(|url| wreq::Proxy::http(url).ok()) {
+            proxies.push(proxy);
+        }
+    }
+    if let Some(proxy) = var("HTTPS_PROXY").and_then(|url| wreq::Proxy::https(url).ok()) {
+        proxies.push(proxy);
+    }
+    if let Some(proxy) = var("ALL_PROXY").and_then(|url| wreq::Proxy::all(url).ok()) {
+        proxies.push(proxy);
+    }
+
+    proxies
+        .into_iter()
+        .map(|proxy| proxy.no_proxy(NoProxy::from_env()))
+        .collect()
 }
@@ -0,0 +1,464 @@
+use crate::error::{ResponseTooLarge, RewrappedBody};
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use pyo3::{exceptions::PyTypeError, prelude::*};
+use std::{
+    error::Error as StdError,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll},
+};
+use wreq::{ResponseBuilderExt, StatusCode, Url, header::HeaderMap};
+
+/// A shared, lock-free byte counter.
+#[derive(Clone, Default)]
+pub struct ByteCounter(Arc<AtomicU64>);
+
+impl ByteCounter {
+    /// Adds `n` bytes to the counter.
+    #[inline(always)]
+    pub fn add(&self, n: usize) {
+        self.0.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    /// Returns the number of bytes counted so far.
+    #[inline(always)]
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A body wrapper that accumulates the length of every data frame into a [`ByteCounter`].
+pub struct CountingBody {
+    inner: wreq::Body,
+    counter: ByteCounter,
+}
+
+impl CountingBody {
+    /// Wraps `inner` so that every polled data frame is counted.
+    #[inline]
+    pub fn wrap(inner: wreq::Body, counter: ByteCounter) -> wreq::Body {
+        wreq::Body::wrap(CountingBody { inner, counter })
+    }
+}
+
+impl Body for CountingBody {
+    type Data = Bytes;
+    type Error = wreq::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll {
+            if let Some(data) = frame.data_ref() {
+                self.counter.add(data.len());
+            }
+        }
+        poll
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// A body wrapper that fails with [`ResponseTooLarge`] once more than `limit` bytes have been
+/// read from it.
+pub struct LimitedBody {
+    inner: wreq::Body,
+    limit: u64,
+    read: u64,
+}
+
+impl Body for LimitedBody {
+    type Data = Bytes;
+    type Error = Box<dyn StdError + Send + Sync>;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll {
+            if let Some(data) = frame.data_ref() {
+                self.read += data.len() as u64;
+                if self.read > self.limit {
+                    let limit = self.limit;
+                    return Poll::Ready(Some(Err(Box::new(ResponseTooLarge { limit }))));
+                }
+            }
+        }
+        poll.map_err(Into::into)
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// How many bytes are sent between two calls of an upload progress callback.
+const PROGRESS_INTERVAL: u64 = 64 * 1024;
+
+/// A Python callable notified as the body of a request is sent.
+#[derive(Clone)]
+pub struct UploadProgress(Arc<PyObject>);
+
+impl FromPyObject<'_> for UploadProgress {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if !ob.is_callable() {
+            return Err(PyTypeError::new_err(format!(
+                "on_upload_progress must be callable, got {}",
+                ob.get_type().name()?
+            )));
+        }
+        Ok(UploadProgress(Arc::new(ob.clone().unbind())))
+    }
+}
+
+/// A body wrapper that calls an [`UploadProgress`] with `(sent, total)` as it is sent.
+///
+/// The callback runs at most once every [`PROGRESS_INTERVAL`] bytes, plus once when the body
+/// ends, so a fast upload doesn't keep taking the GIL. Buffered bodies are sent in chunks of
+/// that size, so their progress is reported too.
+pub struct ProgressBody {
+    inner: ProgressSource,
+    callback: UploadProgress,
+    total: Option<u64>,
+    sent: u64,
+    reported: u64,
+}
+
+enum ProgressSource {
+    Buffered(Bytes),
+    Streaming(wreq::Body),
+}
+
+impl ProgressBody {
+    fn report(&mut self) -> Result<(), Box<dyn StdError + Send + Sync>> {
+        self.reported = self.sent;
+        Python::with_gil(|py| self.callback.0.call1(py, (self.sent, self.total)))?;
+        Ok(())
+    }
+}
+
+impl Body for ProgressBody {
+    type Data = Bytes;
+    type Error = Box<dyn StdError + Send + Sync>;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let frame = match &mut self.inner {
+            ProgressSource::Buffered(bytes) if bytes.is_empty() => None,
+            ProgressSource::Buffered(bytes) => {
+                let len = bytes.len().min(PROGRESS_INTERVAL as usize);
+                Some(Ok(Frame::data(bytes.split_to(len))))
+            }
+            ProgressSource::Streaming(body) => match Pin::new(body).poll_frame(cx) {
+                Poll::Ready(frame) => frame.map(|frame| frame.map_err(Into::into)),
+                Poll::Pending => return Poll::Pending,
+            },
+        };
+
+        match &frame {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    self.sent += data.len() as u64;
+                }
+                if self.sent - self.reported >= PROGRESS_INTERVAL
+                    || (self.sent > self.reported && self.is_end_stream())
+                {
+                    if let Err(err) = self.report() {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                }
+            }
+            None if self.sent > self.reported => {
+                if let Err(err) = self.report() {
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+            _ => {}
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.inner {
+            ProgressSource::Buffered(bytes) => bytes.is_empty(),
+            ProgressSource::Streaming(body) => body.is_end_stream(),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match &self.inner {
+            ProgressSource::Buffered(bytes) => SizeHint::with_exact(bytes.len() as u64),
+            ProgressSource::Streaming(body) => body.size_hint(),
+        }
+    }
+}
+
+/// Wraps the body of `request` so that `callback` is told how much of it has been sent.
+///
+/// The wrapped body is a stream, so it can't be replayed on a `307` or `308` redirect.
+pub fn track_upload(request: &mut wreq::Request, callback: UploadProgress) {
+    let Some(body) = request.body_mut().take() else {
+        return;
+    };
+    let total = body.size_hint().exact();
+    let inner = match body.as_bytes() {
+        Some(bytes) => ProgressSource::Buffered(Bytes::copy_from_slice(bytes)),
+        None => ProgressSource::Streaming(body),
+    };
+    *request.body_mut() = Some(wreq::Body::wrap(ProgressBody {
+        inner,
+        callback,
+        total,
+        sent: 0,
+        reported: 0,
+    }));
+}
+
+/// Wraps the body of `response` so that reading more than `limit` bytes of it fails.
+///
+/// The body is limited after decompression, which is what a compression bomb inflates.
+/// The error drops the body, so the connection is closed rather than reused.
+pub fn limit_response(response: wreq::Response, limit: u64) -> wreq::Response {
+    map_body(response, |inner| {
+        wreq::Body::wrap(LimitedBody {
+            inner,
+            limit,
+            read: 0,
+        })
+    })
+}
+
+/// Counts the request head and body of `request` into `counter`.
+///
+/// Reusable bodies are counted up front so they can still be replayed on redirects;
+/// streaming bodies are counted as they are sent.
+pub fn count_request(
+    request: &mut wreq::Request,
+    default_headers: &HeaderMap,
+    counter: &ByteCounter,
+) {
+    // Default headers are only sent when the request doesn't override them.
+    let default_headers_size = default_headers
+        .iter()
+        .filter(|(name, _)| !request.headers().contains_key(*name))
+        .map(|(name, value)| header_line_size(name.as_str(), value.as_bytes()))
+        .sum::<usize>();
+
+    counter.add(
+        request_line_size(request.method().as_str(), request.url())
+            + header_block_size(request.headers())
+            + default_headers_size,
+    );
+
+    if let Some(body) = request.body_mut().take() {
+        let body = match body.as_bytes() {
+            Some(bytes) => {
+                counter.add(bytes.len());
+                body
+            }
+            None => CountingBody::wrap(body, counter.clone()),
+        };
+        *request.body_mut() = Some(body);
+    }
+}
+
+/// Counts the response head into `counter` and wraps the body so that it is counted as it is read.
+pub fn count_response(
+    response: wreq::Response,
+    headers: &HeaderMap,
+    counter: &ByteCounter,
+) -> wreq::Response {
+    counter.add(status_line_size(response.status()) + header_block_size(headers));
+
+    map_body(response, |body| CountingBody::wrap(body, counter.clone()))
+}
+
+/// A body wrapper that drops `guard` once `inner` is read to its end.
+struct GuardedBody<T> {
+    inner: wreq::Body,
+    guard: Option<T>,
+}
+
+impl<T: Send + Sync + Unpin + 'static> Body for GuardedBody<T> {
+    type Data = Bytes;
+    type Error = wreq::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_frame(cx);
+        if matches!(poll, Poll::Ready(None)) || self.inner.is_end_stream() {
+            self.guard = None;
+        }
+        poll
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Wraps the body of `response` so that `guard` is dropped once it's read to its end, or with
+/// the body.
+pub fn hold_until_read<T: Send + Sync + 'static>(
+    response: wreq::Response,
+    guard: T,
+) -> wreq::Response {
+    map_body(response, |inner| {
+        if inner.is_end_stream() {
+            return inner;
+        }
+        wreq::Body::wrap(GuardedBody {
+            inner,
+            guard: Some(Box::new(guard)),
+        })
+    })
+}
+
+/// A body wrapper that yields `head` before the frames of `inner`.
+struct PrefixedBody {
+    head: Option<Bytes>,
+    inner: wreq::Body,
+}
+
+impl Body for PrefixedBody {
+    type Data = Bytes;
+    type Error = wreq::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match self.head.take() {
+            Some(head) => Poll::Ready(Some(Ok(Frame::data(head)))),
+            None => Pin::new(&mut self.inner).poll_frame(cx),
+        }
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.head.is_none() && self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let head = self.head.as_ref().map_or(0, |head| head.len() as u64);
+        let inner = self.inner.size_hint();
+        let mut hint = SizeHint::new();
+        hint.set_lower(inner.lower() + head);
+        if let Some(upper) = inner.upper() {
+            hint.set_upper(upper + head);
+        }
+        hint
+    }
+}
+
+/// Puts `head` back in front of the rest of the body of `response`.
+pub fn prepend_body(response: wreq::Response, head: Bytes) -> wreq::Response {
+    let head = (!head.is_empty()).then_some(head);
+    map_body(response, |inner| {
+        wreq::Body::wrap(PrefixedBody { head, inner })
+    })
+}
+
+/// A body wrapper that marks its errors as [`RewrappedBody`], so that they're classified by
+/// the error of the original body.
+struct MappedBody(wreq::Body);
+
+impl Body for MappedBody {
+    type Data = Bytes;
+    type Error = RewrappedBody;
+
+    #[inline]
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.0).poll_frame(cx).map_err(RewrappedBody)
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.0.is_end_stream()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint()
+    }
+}
+
+/// Replaces the body of `response` with `f(body)`, keeping its head and URL.
+pub fn map_body(
+    response: wreq::Response,
+    f: impl FnOnce(wreq::Body) -> wreq::Body,
+) -> wreq::Response {
+    let url = response.url().clone();
+    let (mut parts, body) = http::Response::<wreq::Body>::from(response).into_parts();
+    let (head, ()) = http::Response::builder()
+        .url(url)
+        .body(())
+        .expect("an empty response head is valid")
+        .into_parts();
+    parts.extensions.extend(head.extensions);
+    let body = wreq::Body::wrap(MappedBody(f(body)));
+    wreq::Response::from(http::Response::from_parts(parts, body))
+}
+
+/// `METHOD /path?query HTTP/1.1\r\n`
+#[inline]
+fn request_line_size(method: &str, url: &Url) -> usize {
+    let query = url.query().map_or(0, |query| query.len() + 1);
+    method.len() + 1 + url.path().len() + query + 11
+}
+
+/// `HTTP/1.1 200 OK\r\n`
+#[inline]
+fn status_line_size(status: StatusCode) -> usize {
+    let reason = status.canonical_reason().map_or(0, str::len);
+    13 + reason + 2
+}
+
+/// `name: value\r\n` for every header, followed by the terminating `\r\n`.
+#[inline]
+fn header_block_size(headers: &HeaderMap) -> usize {
+    headers
+        .iter()
+        .map(|(name, value)| header_line_size(name.as_str(), value.as_bytes()))
+        .sum::<usize>()
+        + 2
+}
+
+#[inline(always)]
+fn header_line_size(name: &str, value: &[u8]) -> usize {
+    name.len() + 2 + value.len() + 2
+}
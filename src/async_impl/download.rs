@@ -0,0 +1,100 @@
+use super::{
+    Cache, Cassette, MockTransport, OAuth2ClientCredentials, PoolTracker, request::execute_request,
+};
+use crate::{error::Error, typing::param::RequestParams};
+use futures_util::TryStreamExt;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use std::path::PathBuf;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+use wreq::StatusCode;
+
+/// Downloads `url` into `path`, returning the size of the file.
+///
+/// With `resume`, an existing file is treated as the start of the body and only the rest is
+/// requested with a `Range` header. Servers that ignore the range answer with the whole body,
+/// which replaces the file.
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_download<U>(
+    client: wreq::Client,
+    cache: Option<Cache>,
+    transport: Option<MockTransport>,
+    cassette: Option<Cassette>,
+    pool: Option<PoolTracker>,
+    max_redirects: Option<usize>,
+    request_id_header: Option<wreq::header::HeaderName>,
+    max_response_size: Option<u64>,
+    auth: Option<OAuth2ClientCredentials>,
+    url: U,
+    path: PathBuf,
+    resume: bool,
+    mut params: Option<RequestParams>,
+) -> PyResult<u64>
+where
+    U: AsRef<str>,
+{
+    let offset = match tokio::fs::metadata(&path).await {
+        Ok(metadata) if resume => metadata.len(),
+        _ => 0,
+    };
+    let params_ref = params.get_or_insert_default();
+    if offset > 0 && params_ref.range.is_none() {
+        params_ref.range = Some((offset, None));
+    }
+
+    let response = execute_request(
+        client,
+        cache,
+        transport,
+        cassette,
+        pool,
+        max_redirects,
+        request_id_header,
+        max_response_size,
+        auth,
+        wreq::Method::GET,
+        url,
+        params,
+    )
+    .await?;
+    let append = match response.status_code().into_ffi() {
+        StatusCode::PARTIAL_CONTENT => match response.content_range() {
+            Some((start, ..)) if start == offset => true,
+            _ => {
+                response.discard();
+                return Err(PyValueError::new_err(format!(
+                    "server returned a range that doesn't continue the file at byte {offset}"
+                )));
+            }
+        },
+        // The file already holds the whole body.
+        StatusCode::RANGE_NOT_SATISFIABLE
+            if offset > 0 && response.content_range_total() == Some(offset) =>
+        {
+            response.discard();
+            return Ok(offset);
+        }
+        _ => false,
+    };
+
+    let mut body = response
+        .inner()?
+        .error_for_status()
+        .map_err(Error::Request)?
+        .bytes_stream();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(&path)
+        .await
+        .map_err(Error::IO)?;
+
+    let mut size = if append { offset } else { 0 };
+    while let Some(chunk) = body.try_next().await.map_err(Error::Request)? {
+        file.write_all(&chunk).await.map_err(Error::IO)?;
+        size += chunk.len() as u64;
+    }
+    file.flush().await.map_err(Error::IO)?;
+    Ok(size)
+}
@@ -0,0 +1,193 @@
+use crate::error::Error;
+use pyo3::prelude::*;
+use std::{io, net::Ipv4Addr, time::Duration};
+use tokio::{
+    io::AsyncReadExt,
+    net::{TcpListener, TcpStream},
+};
+
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The TLS fingerprint presented by a client.
+#[pyclass]
+pub struct TlsFingerprint {
+    ja3: String,
+    ja3_hash: String,
+}
+
+#[pymethods]
+impl TlsFingerprint {
+    /// Returns the JA3 string, e.g. `771,4865-4866-...,0-23-...,29-23-24,0`.
+    #[getter]
+    pub fn ja3(&self) -> &str {
+        &self.ja3
+    }
+
+    /// Returns the MD5 hash of the JA3 string.
+    #[getter]
+    pub fn ja3_hash(&self) -> &str {
+        &self.ja3_hash
+    }
+
+    fn __str__(&self) -> &str {
+        &self.ja3
+    }
+}
+
+impl TlsFingerprint {
+    /// Captures the fingerprint `client` presents by sending its `ClientHello` to a local
+    /// listener and parsing it, so the result reflects the effective emulation settings.
+    pub async fn capture(client: wreq::Client) -> PyResult<TlsFingerprint> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .map_err(Error::from)?;
+        let port = listener.local_addr().map_err(Error::from)?.port();
+
+        // Use a hostname so the server_name extension is sent like it would be for a real site.
+        let request = client.get(format!("https://localhost:{}/", port)).send();
+        let accept = async {
+            let (mut stream, _) = listener.accept().await?;
+            read_client_hello(&mut stream).await
+        };
+
+        // The handshake is never answered; dropping the request aborts it.
+        let capture = async {
+            tokio::select! {
+                hello = accept => hello.map_err(Error::from),
+                result = request => Err(match result {
+                    Err(err) => Error::Request(err),
+                    Ok(_) => Error::IO(invalid_data("unexpected response from the local listener")),
+                }),
+            }
+        };
+
+        let hello = tokio::time::timeout(CAPTURE_TIMEOUT, capture)
+            .await
+            .map_err(|_| Error::IO(io::ErrorKind::TimedOut.into()))??;
+
+        let ja3 = ja3(&hello).ok_or_else(|| Error::IO(invalid_data("malformed ClientHello")))?;
+        let ja3_hash = format!("{:x}", md5::compute(&ja3));
+        Ok(TlsFingerprint { ja3, ja3_hash })
+    }
+}
+
+/// Reads the TLS records of a `ClientHello` and returns the handshake message body.
+async fn read_client_hello(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let mut handshake = Vec::new();
+    loop {
+        let mut header = [0u8; 5];
+        stream.read_exact(&mut header).await?;
+        if header[0] != 0x16 {
+            return Err(invalid_data("expected a TLS handshake record"));
+        }
+
+        let len = u16::from_be_bytes([header[3], header[4]]) as usize;
+        let start = handshake.len();
+        handshake.resize(start + len, 0);
+        stream.read_exact(&mut handshake[start..]).await?;
+
+        // A ClientHello may span several records; stop once the whole message is in.
+        if handshake.len() >= 4 {
+            if handshake[0] != 0x01 {
+                return Err(invalid_data("expected a ClientHello"));
+            }
+            let body_len =
+                u32::from_be_bytes([0, handshake[1], handshake[2], handshake[3]]) as usize;
+            if handshake.len() >= 4 + body_len {
+                handshake.truncate(4 + body_len);
+                return Ok(handshake.split_off(4));
+            }
+        }
+    }
+}
+
+/// Builds the JA3 string of a `ClientHello` body, ignoring GREASE values.
+fn ja3(mut hello: &[u8]) -> Option<String> {
+    let buf = &mut hello;
+    let version = take_u16(buf)?;
+    take(buf, 32)?;
+    take_u8_prefixed(buf)?;
+    let ciphers = u16_list(take_u16_prefixed(buf)?);
+    take_u8_prefixed(buf)?;
+
+    let mut extensions = Vec::new();
+    let mut curves = Vec::new();
+    let mut point_formats = Vec::new();
+
+    if !buf.is_empty() {
+        let exts = &mut take_u16_prefixed(buf)?;
+        while !exts.is_empty() {
+            let ty = take_u16(exts)?;
+            let data = &mut take_u16_prefixed(exts)?;
+            extensions.push(ty);
+            match ty {
+                // supported_groups
+                0x000a => curves = u16_list(take_u16_prefixed(data)?),
+                // ec_point_formats
+                0x000b => {
+                    point_formats = take_u8_prefixed(data)?
+                        .iter()
+                        .map(|&format| u16::from(format))
+                        .collect()
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Some(format!(
+        "{},{},{},{},{}",
+        version,
+        join(&ciphers),
+        join(&extensions),
+        join(&curves),
+        join(&point_formats)
+    ))
+}
+
+fn join(values: &[u16]) -> String {
+    values
+        .iter()
+        .filter(|value| !is_grease(**value))
+        .map(u16::to_string)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// GREASE values (RFC 8701) look like `0x?a?a` with both bytes equal.
+fn is_grease(value: u16) -> bool {
+    value & 0x0f0f == 0x0a0a && value >> 8 == value & 0xff
+}
+
+fn u16_list(buf: &[u8]) -> Vec<u16> {
+    buf.chunks_exact(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+        .collect()
+}
+
+fn take<'a>(buf: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+    if buf.len() < n {
+        return None;
+    }
+    let (head, tail) = buf.split_at(n);
+    *buf = tail;
+    Some(head)
+}
+
+fn take_u16(buf: &mut &[u8]) -> Option<u16> {
+    take(buf, 2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn take_u8_prefixed<'a>(buf: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let len = take(buf, 1)?[0] as usize;
+    take(buf, len)
+}
+
+fn take_u16_prefixed<'a>(buf: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let len = take_u16(buf)? as usize;
+    take(buf, len)
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
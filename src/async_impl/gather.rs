@@ -0,0 +1,167 @@
+use super::{
+    Cache, Cassette, MockTransport, OAuth2ClientCredentials, PoolTracker, Response,
+    request::execute_request,
+};
+use crate::typing::{MethodExtractor, param::RequestParams};
+use futures_util::{StreamExt, stream};
+use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    types::{PyDict, PyTuple},
+};
+
+/// One request of a batch, given as `(method, url)` or `(method, url, kwargs)`.
+pub struct BatchRequest {
+    method: wreq::Method,
+    url: String,
+    params: Option<RequestParams>,
+    /// The client to send the request with and the tracker of its pool, if not those of the
+    /// batch.
+    client: Option<(wreq::Client, PoolTracker)>,
+}
+
+impl BatchRequest {
+    /// Creates a request of a batch, with `params` given as keyword arguments.
+    pub fn new(
+        method: wreq::Method,
+        url: String,
+        params: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Self> {
+        Ok(BatchRequest {
+            method,
+            url,
+            params: params.map(|params| params.extract()).transpose()?,
+            client: None,
+        })
+    }
+
+    /// Returns the keyword arguments of the request.
+    pub fn params_mut(&mut self) -> Option<&mut RequestParams> {
+        self.params.as_mut()
+    }
+
+    /// Sends the request with `client`, whose pool `pool` tracks, rather than the client of
+    /// the batch.
+    pub fn set_client(&mut self, client: wreq::Client, pool: PoolTracker) {
+        self.client = Some((client, pool));
+    }
+}
+
+impl FromPyObject<'_> for BatchRequest {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let tuple = ob.downcast::<PyTuple>().map_err(|_| {
+            PyTypeError::new_err("requests: expected (method, url) or (method, url, kwargs) tuples")
+        })?;
+        match tuple.len() {
+            2 => Ok(BatchRequest {
+                method: tuple.get_item(0)?.extract::<MethodExtractor>()?.0,
+                url: tuple.get_item(1)?.extract()?,
+                params: None,
+                client: None,
+            }),
+            3 => Ok(BatchRequest {
+                method: tuple.get_item(0)?.extract::<MethodExtractor>()?.0,
+                url: tuple.get_item(1)?.extract()?,
+                params: tuple.get_item(2)?.extract()?,
+                client: None,
+            }),
+            len => Err(PyTypeError::new_err(format!(
+                "requests: expected a tuple of 2 or 3 items, got {len}"
+            ))),
+        }
+    }
+}
+
+/// A Python callable notified as the requests of a batch complete.
+pub struct ProgressCallback(PyObject);
+
+impl FromPyObject<'_> for ProgressCallback {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if !ob.is_callable() {
+            return Err(PyTypeError::new_err(format!(
+                "on_progress must be callable, got {}",
+                ob.get_type().name()?
+            )));
+        }
+        Ok(ProgressCallback(ob.clone().unbind()))
+    }
+}
+
+/// Sends `requests` with at most `concurrency` of them in flight, returning the results in
+/// the order of `requests`.
+///
+/// Requests are started as soon as a slot frees up, so a slow response doesn't hold back
+/// the ones behind it. Unless `return_exceptions` is set, the first error is raised and the
+/// requests still in flight are cancelled. `on_progress(completed, total)` is called after
+/// each request finishes. Requests are sent with `client` unless they were given one of
+/// their own.
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_gather(
+    client: wreq::Client,
+    cache: Option<Cache>,
+    transport: Option<MockTransport>,
+    cassette: Option<Cassette>,
+    pool: Option<PoolTracker>,
+    max_redirects: Option<usize>,
+    request_id_header: Option<wreq::header::HeaderName>,
+    max_response_size: Option<u64>,
+    auth: Option<OAuth2ClientCredentials>,
+    requests: Vec<BatchRequest>,
+    concurrency: usize,
+    return_exceptions: bool,
+    on_progress: Option<ProgressCallback>,
+) -> PyResult<Vec<PyResult<Response>>> {
+    if concurrency == 0 {
+        return Err(PyValueError::new_err("concurrency must be at least 1"));
+    }
+
+    let total = requests.len();
+    let mut results: Vec<Option<PyResult<Response>>> = (0..total).map(|_| None).collect();
+    let mut responses = stream::iter(requests.into_iter().enumerate())
+        .map(|(index, request)| {
+            let (client, pool) = match request.client {
+                Some((client, pool)) => (client, Some(pool)),
+                None => (client.clone(), pool.clone()),
+            };
+            let cache = cache.clone();
+            let transport = transport.clone();
+            let cassette = cassette.clone();
+            let request_id_header = request_id_header.clone();
+            let auth = auth.clone();
+            async move {
+                let result = execute_request(
+                    client,
+                    cache,
+                    transport,
+                    cassette,
+                    pool,
+                    max_redirects,
+                    request_id_header,
+                    max_response_size,
+                    auth,
+                    request.method,
+                    request.url,
+                    request.params,
+                )
+                .await;
+                (index, result)
+            }
+        })
+        .buffer_unordered(concurrency);
+
+    let mut completed = 0;
+    while let Some((index, result)) = responses.next().await {
+        let result = match result {
+            Err(err) if !return_exceptions => return Err(err),
+            result => result,
+        };
+        results[index] = Some(result);
+
+        completed += 1;
+        if let Some(callback) = &on_progress {
+            Python::with_gil(|py| callback.0.call1(py, (completed, total)))?;
+        }
+    }
+
+    Ok(results.into_iter().flatten().collect())
+}
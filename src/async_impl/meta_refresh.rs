@@ -0,0 +1,200 @@
+use super::Response;
+use pyo3::PyResult;
+use wreq::{
+    Method, Url,
+    header::{self, HeaderMap},
+};
+
+/// How much of an HTML body is scanned for a meta refresh.
+const SCAN_LIMIT: usize = 16 * 1024;
+
+/// Returns the URL a `<meta http-equiv="refresh">` in `response` points to.
+///
+/// Only successful HTML responses are scanned, and only the first [`SCAN_LIMIT`] bytes of
+/// their body, which is left in place. Refreshes to the same page or to anything but an
+/// `http` or `https` URL are ignored.
+pub async fn target(response: &Response) -> PyResult<Option<Url>> {
+    if !response.status_code().is_success() || !is_html(response.header_map()) {
+        return Ok(None);
+    }
+
+    let head = response.peek(SCAN_LIMIT).await?;
+    let Some(target) = find_refresh(&head) else {
+        return Ok(None);
+    };
+    let base = response.url().0;
+    Ok(base
+        .join(&target)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https") && url.as_str() != base.as_str()))
+}
+
+/// Builds the request following a meta refresh from `from` to `to`, out of a copy of the
+/// original request without its body.
+///
+/// Like an HTTP redirect, the refresh is followed with a `GET`, and credentials aren't
+/// sent to another host.
+pub fn next_request(template: &wreq::Request, from: &Url, to: Url) -> Option<wreq::Request> {
+    let mut request = template.try_clone()?;
+    let cross_host = to.host_str() != from.host_str()
+        || to.port_or_known_default() != from.port_or_known_default();
+
+    *request.method_mut() = Method::GET;
+    *request.url_mut() = to;
+    *request.body_mut() = None;
+    strip_headers(request.headers_mut(), cross_host);
+    Some(request)
+}
+
+fn strip_headers(headers: &mut HeaderMap, cross_host: bool) {
+    for name in [
+        header::CONTENT_TYPE,
+        header::CONTENT_LENGTH,
+        header::CONTENT_ENCODING,
+        header::TRANSFER_ENCODING,
+    ] {
+        headers.remove(name);
+    }
+    if cross_host {
+        for name in [
+            header::AUTHORIZATION,
+            header::COOKIE,
+            header::PROXY_AUTHORIZATION,
+            header::WWW_AUTHENTICATE,
+        ] {
+            headers.remove(name);
+        }
+        headers.remove("cookie2");
+    }
+}
+
+fn is_html(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<mime::Mime>().ok())
+        .is_some_and(|mime| matches!(mime.essence_str(), "text/html" | "application/xhtml+xml"))
+}
+
+/// Finds the URL of the first `<meta http-equiv="refresh">` tag in `html`.
+fn find_refresh(html: &[u8]) -> Option<String> {
+    let mut rest = html;
+    while let Some(start) = find_tag(rest, b"<meta") {
+        rest = &rest[start + b"<meta".len()..];
+        // `<metadata>` and the like aren't meta tags.
+        if !rest
+            .first()
+            .is_some_and(|&b| b.is_ascii_whitespace() || b == b'/')
+        {
+            continue;
+        }
+
+        let (attributes, end) = parse_attributes(rest);
+        rest = &rest[end..];
+        let is_refresh = attributes.iter().any(|(name, value)| {
+            name.eq_ignore_ascii_case("http-equiv") && value.eq_ignore_ascii_case("refresh")
+        });
+        if !is_refresh {
+            continue;
+        }
+        return attributes
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content"))
+            .and_then(|(_, content)| refresh_url(content));
+    }
+    None
+}
+
+/// Finds `tag` in `html`, ignoring ASCII case.
+fn find_tag(html: &[u8], tag: &[u8]) -> Option<usize> {
+    html.windows(tag.len())
+        .position(|window| window.eq_ignore_ascii_case(tag))
+}
+
+/// Parses the attributes of a tag up to its closing `>`, returning them with the number of
+/// bytes read.
+fn parse_attributes(tag: &[u8]) -> (Vec<(String, String)>, usize) {
+    let mut attributes = Vec::new();
+    let mut i = 0;
+    loop {
+        while i < tag.len() && (tag[i].is_ascii_whitespace() || tag[i] == b'/') {
+            i += 1;
+        }
+        if i >= tag.len() || tag[i] == b'>' {
+            return (attributes, i);
+        }
+
+        let name_start = i;
+        while i < tag.len()
+            && !matches!(tag[i], b'=' | b'>' | b'/')
+            && !tag[i].is_ascii_whitespace()
+        {
+            i += 1;
+        }
+        let name = String::from_utf8_lossy(&tag[name_start..i]).into_owned();
+        while i < tag.len() && tag[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i >= tag.len() || tag[i] != b'=' {
+            attributes.push((name, String::new()));
+            continue;
+        }
+
+        i += 1;
+        while i < tag.len() && tag[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let value = match tag.get(i) {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let start = i + 1;
+                let end = tag[start..]
+                    .iter()
+                    .position(|&b| b == quote)
+                    .map_or(tag.len(), |len| start + len);
+                i = (end + 1).min(tag.len());
+                &tag[start..end]
+            }
+            _ => {
+                let start = i;
+                while i < tag.len() && tag[i] != b'>' && !tag[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                &tag[start..i]
+            }
+        };
+        let value = String::from_utf8_lossy(value).replace("&amp;", "&");
+        attributes.push((name, value));
+    }
+}
+
+/// Extracts the URL from a refresh `content` such as `0; url=/next`, following the HTML
+/// parsing rules for it.
+fn refresh_url(content: &str) -> Option<String> {
+    let content = content
+        .trim_start()
+        .trim_start_matches(|c: char| c.is_ascii_digit() || c == '.')
+        .trim_start();
+    let content = content
+        .strip_prefix([';', ','])
+        .unwrap_or(content)
+        .trim_start();
+    let content = match content.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("url") => {
+            match content[3..].trim_start().strip_prefix('=') {
+                Some(rest) => rest.trim_start(),
+                None => content,
+            }
+        }
+        _ => content,
+    };
+
+    let url = match content.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let content = &content[1..];
+            &content[..content.find(quote).unwrap_or(content.len())]
+        }
+        _ => content,
+    };
+    let url = url.trim();
+    (!url.is_empty()).then(|| url.to_owned())
+}
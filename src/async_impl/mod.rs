@@ -1,16 +1,41 @@
+mod cache;
+mod cassette;
 mod client;
+mod counter;
+mod download;
+mod fingerprint;
+mod gather;
+mod meta_refresh;
+mod oauth;
+mod pool;
+mod redirect;
 mod request;
 mod response;
+mod timing;
+mod transport;
 
 pub use self::{
-    client::Client,
-    response::{Message, Response, Streamer, WebSocket},
+    cache::{Cache, CacheOption},
+    cassette::Cassette,
+    client::{Client, Connection, set_trust_env_default},
+    counter::UploadProgress,
+    fingerprint::TlsFingerprint,
+    oauth::OAuth2ClientCredentials,
+    response::{
+        EncodingDetection, Event, Events, Frames, JsonItems, Message, MessagesExtractor, Parts,
+        Response, Streamer, TextEncoding, TextErrors, Tunnel, WebSocket, read_text, save_body,
+        save_json, strict_resources,
+    },
+    transport::MockTransport,
 };
 use crate::dns;
+use crate::typing::LookupIpStrategy;
 use crate::typing::param::{RequestParams, WebSocketParams};
-use crate::typing::{LookupIpStrategy, Method};
+pub use download::execute_download;
+pub use gather::{BatchRequest, ProgressCallback, execute_gather};
+pub use pool::PoolTracker;
 use pyo3::PyResult;
-pub use request::{execute_request, execute_websocket_request};
+pub use request::{execute_connect_tunnel, execute_request, execute_websocket_request};
 use std::sync::LazyLock;
 
 static DEFAULT_CLIENT: LazyLock<wreq::Client> = LazyLock::new(|| {
@@ -34,13 +59,27 @@ static DEFAULT_CLIENT: LazyLock<wreq::Client> = LazyLock::new(|| {
 /// Send a shortcut HTTP request.
 pub async fn shortcut_request<U>(
     url: U,
-    method: Method,
+    method: wreq::Method,
     params: Option<RequestParams>,
 ) -> PyResult<Response>
 where
     U: AsRef<str>,
 {
-    execute_request(DEFAULT_CLIENT.clone(), method, url, params).await
+    execute_request(
+        DEFAULT_CLIENT.clone(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        method,
+        url,
+        params,
+    )
+    .await
 }
 
 /// Send a shortcut WebSocket request.
@@ -52,5 +91,5 @@ pub async fn shortcut_websocket_request<U>(
 where
     U: AsRef<str>,
 {
-    execute_websocket_request(DEFAULT_CLIENT.clone(), url, params).await
+    execute_websocket_request(DEFAULT_CLIENT.clone(), None, url, params).await
 }
@@ -0,0 +1,277 @@
+use super::MockTransport;
+use crate::{
+    error::{DecodingError, Error, StatusError},
+    typing::UrlExtractor,
+};
+use pyo3::{prelude::*, types::PyDict};
+use serde::Deserialize;
+use std::{
+    sync::{Arc, Mutex as StdMutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use wreq::{
+    Url,
+    header::{self, HeaderValue},
+};
+
+/// How long before it expires a token is refreshed, so it doesn't expire in flight.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// Authenticates a client's requests with bearer tokens from an OAuth2 token endpoint,
+/// using the client credentials grant.
+///
+/// The token is fetched with the first request, cached, and fetched again when it's about
+/// to expire or a request is refused with `401 Unauthorized`. Clones share the token.
+///
+/// The token is only sent to URLs under one of `base_urls`, by default the origin of the
+/// token endpoint, so that requests to other hosts don't leak it.
+#[pyclass(frozen)]
+#[derive(Clone)]
+pub struct OAuth2ClientCredentials(Arc<Credentials>);
+
+struct Credentials {
+    token_url: Url,
+    base_urls: Vec<Url>,
+    client_id: String,
+    client_secret: String,
+    scope: Option<String>,
+    token: StdMutex<Option<Token>>,
+    /// Held while a token is fetched, so concurrent requests wait for one fetch.
+    refresh: Mutex<()>,
+}
+
+#[derive(Clone)]
+struct Token {
+    access_token: String,
+    token_type: String,
+    expires_at: Option<Instant>,
+    scope: Option<String>,
+}
+
+impl Token {
+    fn is_fresh(&self) -> bool {
+        self.expires_at
+            .is_none_or(|expires_at| Instant::now() + EXPIRY_MARGIN < expires_at)
+    }
+}
+
+/// The successful response of a token endpoint.
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    token_type: Option<String>,
+    /// Seconds, which some servers send as a string.
+    #[serde(default)]
+    expires_in: Option<serde_json::Value>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+#[pymethods]
+impl OAuth2ClientCredentials {
+    /// Creates credentials for the token endpoint at `token_url`.
+    #[new]
+    #[pyo3(signature = (token_url, client_id, client_secret, scope = None, base_urls = None))]
+    fn new(
+        token_url: UrlExtractor,
+        client_id: String,
+        client_secret: String,
+        scope: Option<String>,
+        base_urls: Option<Vec<UrlExtractor>>,
+    ) -> PyResult<OAuth2ClientCredentials> {
+        let token_url = Url::parse(token_url.as_ref()).map_err(Error::UrlParse)?;
+        let base_urls = match base_urls {
+            Some(base_urls) => base_urls
+                .iter()
+                .map(|url| Url::parse(url.as_ref()).map_err(Error::UrlParse))
+                .collect::<Result<_, _>>()?,
+            None => {
+                let mut origin = token_url.clone();
+                origin.set_path("/");
+                origin.set_query(None);
+                origin.set_fragment(None);
+                vec![origin]
+            }
+        };
+        Ok(OAuth2ClientCredentials(Arc::new(Credentials {
+            token_url,
+            base_urls,
+            client_id,
+            client_secret,
+            scope,
+            token: StdMutex::new(None),
+            refresh: Mutex::new(()),
+        })))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "OAuth2ClientCredentials(token_url={:?}, client_id={:?}, scope={:?}, base_urls={:?})",
+            self.0.token_url.as_str(),
+            self.0.client_id,
+            self.0.scope,
+            self.0.base_urls.iter().map(Url::as_str).collect::<Vec<_>>()
+        )
+    }
+}
+
+impl OAuth2ClientCredentials {
+    /// Returns whether the token is sent with a request to `url`: whether it has the origin
+    /// of one of the base URLs, and a path under that URL's.
+    pub fn applies_to(&self, url: &Url) -> bool {
+        self.0.base_urls.iter().any(|base| {
+            base.origin() == url.origin()
+                && url.path().strip_prefix(base.path()).is_some_and(|rest| {
+                    base.path().ends_with('/') || rest.is_empty() || rest.starts_with('/')
+                })
+        })
+    }
+
+    /// Returns the cached token as a dict with its `access_token`, `token_type`, `scope`
+    /// and the seconds it `expires_in`, or `None` if no token has been fetched yet.
+    pub fn token_info<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(token) = self.cached() else {
+            return Ok(None);
+        };
+        let info = PyDict::new(py);
+        info.set_item("access_token", token.access_token)?;
+        info.set_item("token_type", token.token_type)?;
+        info.set_item(
+            "expires_in",
+            token.expires_at.map(|expires_at| {
+                expires_at
+                    .saturating_duration_since(Instant::now())
+                    .as_secs_f64()
+            }),
+        )?;
+        info.set_item("scope", token.scope)?;
+        Ok(Some(info))
+    }
+
+    /// Sets the `Authorization` header of `request` to a fresh token, fetching one with
+    /// `client` if needed, and returns the access token used.
+    ///
+    /// A `refused` token is fetched again even if it hasn't expired.
+    pub async fn authorize(
+        &self,
+        client: &wreq::Client,
+        transport: Option<&MockTransport>,
+        request: &mut wreq::Request,
+        refused: Option<&str>,
+    ) -> PyResult<String> {
+        let token = self.token(client, transport, refused).await?;
+        let scheme = if token.token_type.eq_ignore_ascii_case("bearer") {
+            "Bearer"
+        } else {
+            &token.token_type
+        };
+        let value = HeaderValue::from_str(&format!("{scheme} {}", token.access_token))
+            .map_err(Error::InvalidHeaderValue)?;
+        request.headers_mut().insert(header::AUTHORIZATION, value);
+        Ok(token.access_token)
+    }
+
+    fn cached(&self) -> Option<Token> {
+        self.0
+            .token
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Returns the cached token unless it's about to expire or was `refused`.
+    fn usable(&self, refused: Option<&str>) -> Option<Token> {
+        self.cached()
+            .filter(|token| token.is_fresh() && Some(token.access_token.as_str()) != refused)
+    }
+
+    async fn token(
+        &self,
+        client: &wreq::Client,
+        transport: Option<&MockTransport>,
+        refused: Option<&str>,
+    ) -> PyResult<Token> {
+        if let Some(token) = self.usable(refused) {
+            return Ok(token);
+        }
+        let _guard = self.0.refresh.lock().await;
+        // Another request may have fetched a token while this one waited.
+        if let Some(token) = self.usable(refused) {
+            return Ok(token);
+        }
+
+        let token = self.fetch(client, transport).await?;
+        *self.0.token.lock().unwrap_or_else(|e| e.into_inner()) = Some(token.clone());
+        Ok(token)
+    }
+
+    /// Requests a new token from the token endpoint, sending the client's id and secret
+    /// with basic authentication, form-urlencoded first as RFC 6749 section 2.3.1 asks.
+    async fn fetch(
+        &self,
+        client: &wreq::Client,
+        transport: Option<&MockTransport>,
+    ) -> PyResult<Token> {
+        let credentials = &self.0;
+        let mut form = vec![("grant_type", "client_credentials")];
+        if let Some(scope) = &credentials.scope {
+            form.push(("scope", scope.as_str()));
+        }
+        let request = client
+            .post(credentials.token_url.clone())
+            .basic_auth(
+                form_urlencode(&credentials.client_id),
+                Some(form_urlencode(&credentials.client_secret)),
+            )
+            .header(header::ACCEPT, "application/json")
+            .form(&form)
+            .build()
+            .map_err(Error::Request)?;
+
+        let route = match transport {
+            Some(transport) => transport.route(&request)?,
+            None => None,
+        };
+        let (status, body) = match route {
+            Some(route) => {
+                let response = route.send(client, request).await?;
+                let status = response.status();
+                (status, response.into_buffered().await?.1)
+            }
+            None => {
+                let response = client.execute(request).await.map_err(Error::Request)?;
+                let status = response.status().as_u16();
+                (status, response.bytes().await.map_err(Error::Request)?)
+            }
+        };
+        if !(200..300).contains(&status) {
+            return Err(StatusError::new_err(format!(
+                "token request to {} failed with status {status}: {}",
+                credentials.token_url,
+                String::from_utf8_lossy(&body)
+            )));
+        }
+
+        let response = serde_json::from_slice::<TokenResponse>(&body)
+            .map_err(|err| DecodingError::new_err(format!("invalid token response: {err}")))?;
+        let expires_in = response.expires_in.and_then(|value| match value {
+            serde_json::Value::Number(n) => n.as_f64(),
+            serde_json::Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        });
+        Ok(Token {
+            access_token: response.access_token,
+            token_type: response.token_type.unwrap_or_else(|| "Bearer".to_owned()),
+            expires_at: expires_in
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .and_then(|expires_in| Instant::now().checked_add(expires_in)),
+            scope: response.scope.or_else(|| credentials.scope.clone()),
+        })
+    }
+}
+
+fn form_urlencode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
@@ -0,0 +1,156 @@
+use super::Response;
+use futures_util::{FutureExt, future::BoxFuture};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// The id of the next connection a tracker tells apart, unique across trackers.
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Tracks the connections a connection pool opens, to tell whether a response came over a new
+/// connection or one taken from the pool, and which one.
+///
+/// wreq neither reports pool reuse nor tells connections apart, so this is inferred: a request
+/// during which the pool opened no connection was sent over a pooled one, found by its remote
+/// address. Where that's ambiguous, because a connection was opened while other requests were
+/// in flight or several connections were opened to one address, nothing is reported rather
+/// than a guess. Each pool has a tracker of its own, so pools don't mix up their connections.
+#[derive(Clone, Default)]
+pub struct PoolTracker(Arc<PoolState>);
+
+#[derive(Default)]
+struct PoolState {
+    opened: AtomicU64,
+    /// The requests sent and not identified yet.
+    in_flight: AtomicU64,
+    /// The requests sent so far, to tell whether others were sent while one was in flight.
+    sent: AtomicU64,
+    by_remote: Mutex<HashMap<SocketAddr, Remote>>,
+}
+
+/// The connections opened to a remote address.
+struct Remote {
+    /// The id of the last one, if it's known.
+    id: Option<u64>,
+    count: u64,
+}
+
+/// The state of a [`PoolTracker`] when a request was sent.
+pub struct PoolSnapshot {
+    tracker: PoolTracker,
+    opened: u64,
+    sent: u64,
+    /// Whether other requests were in flight when this one was sent.
+    overlapped: bool,
+}
+
+impl PoolTracker {
+    /// Returns a connector layer that counts the connections the client opens.
+    pub fn layer(&self) -> CountConnectionsLayer {
+        CountConnectionsLayer(self.clone())
+    }
+
+    /// Records the state before sending a request.
+    pub fn snapshot(&self) -> PoolSnapshot {
+        let state = &self.0;
+        let overlapped = state.in_flight.fetch_add(1, Ordering::AcqRel) > 0;
+        PoolSnapshot {
+            tracker: self.clone(),
+            opened: state.opened.load(Ordering::Acquire),
+            sent: state.sent.fetch_add(1, Ordering::AcqRel) + 1,
+            overlapped,
+        }
+    }
+}
+
+impl PoolSnapshot {
+    /// Marks `response` as sent over a new or a reused connection, when that can be told.
+    pub fn identify(self, response: Response) -> Response {
+        let state = &self.tracker.0;
+        let opened = state.opened.load(Ordering::Acquire) - self.opened;
+        let overlapped = self.overlapped || state.sent.load(Ordering::Acquire) != self.sent;
+        let remote_addr = response.remote_addr().map(|addr| addr.0);
+        let mut by_remote = state.by_remote.lock().unwrap_or_else(|e| e.into_inner());
+
+        if opened == 0 {
+            let id = remote_addr
+                .and_then(|addr| by_remote.get(&addr))
+                .filter(|remote| remote.count == 1)
+                .and_then(|remote| remote.id);
+            return response.with_connection(Some(true), id);
+        }
+
+        // Another request in flight may have opened the connection, so this one may have gone
+        // over a pooled one; either way, its address may have one connection more.
+        let id = (!overlapped).then(|| NEXT_ID.fetch_add(1, Ordering::Relaxed));
+        if let Some(addr) = remote_addr {
+            let remote = by_remote
+                .entry(addr)
+                .or_insert(Remote { id: None, count: 0 });
+            remote.count += 1;
+            remote.id = id;
+        }
+        response.with_connection((!overlapped).then_some(false), id)
+    }
+}
+
+impl Drop for PoolSnapshot {
+    fn drop(&mut self) {
+        self.tracker.0.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// A connector layer counting the connections that were established.
+#[derive(Clone)]
+pub struct CountConnectionsLayer(PoolTracker);
+
+impl<S> Layer<S> for CountConnectionsLayer {
+    type Service = CountConnections<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CountConnections {
+            inner,
+            tracker: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CountConnections<S> {
+    inner: S,
+    tracker: PoolTracker,
+}
+
+impl<S, R> Service<R> for CountConnections<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: R) -> Self::Future {
+        let tracker = self.tracker.clone();
+        self.inner
+            .call(req)
+            .inspect(move |result| {
+                if result.is_ok() {
+                    tracker.0.opened.fetch_add(1, Ordering::AcqRel);
+                }
+            })
+            .boxed()
+    }
+}
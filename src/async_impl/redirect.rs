@@ -0,0 +1,113 @@
+use crate::error::RedirectError;
+use pyo3::PyResult;
+use std::sync::{Arc, Mutex};
+use wreq::{Url, redirect::Policy};
+
+/// The number of redirects wreq follows by default.
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// The redirects a request may follow, recording the URLs it was redirected from.
+///
+/// HTTP redirects and meta refreshes both go through it, so they share one limit and one
+/// history. As with `Policy::limited`, a chain fails once `max` URLs have been visited
+/// before the next one. Redirects from `https` to `http` fail too, unless insecure
+/// redirects are allowed, and so does any redirect away from `https` if `https_only` is set.
+#[derive(Clone)]
+pub struct Redirects {
+    max: Option<usize>,
+    schemes: Schemes,
+    history: Arc<Mutex<Vec<Url>>>,
+}
+
+/// The schemes a redirect may go to.
+#[derive(Clone, Copy, Default)]
+struct Schemes {
+    https_only: bool,
+    allow_insecure: bool,
+}
+
+impl Schemes {
+    /// Returns why the redirect from `from` to `to` mustn't be followed, if it mustn't.
+    fn check(self, from: &Url, to: &Url) -> Option<&'static str> {
+        if to.scheme() == "https" {
+            None
+        } else if self.https_only {
+            Some("redirect away from https with https_only set")
+        } else if from.scheme() == "https" && !self.allow_insecure {
+            Some("redirect from https to http; set allow_insecure_redirects to follow it")
+        } else {
+            None
+        }
+    }
+}
+
+impl Redirects {
+    /// Creates the redirects of a request, with `max` set to `None` if it mustn't follow any.
+    pub fn new(max: Option<usize>) -> Redirects {
+        Redirects {
+            max,
+            schemes: Schemes::default(),
+            history: Arc::default(),
+        }
+    }
+
+    /// Sets whether redirects must stay on `https`, and whether they may go from `https`
+    /// to `http` otherwise.
+    pub fn schemes(mut self, https_only: bool, allow_insecure: bool) -> Redirects {
+        self.schemes = Schemes {
+            https_only,
+            allow_insecure,
+        };
+        self
+    }
+
+    /// Returns the policy the request is sent with.
+    pub fn policy(&self) -> Policy {
+        let Some(max) = self.max else {
+            return Policy::none();
+        };
+        let (schemes, history) = (self.schemes, self.history.clone());
+        Policy::custom(move |attempt| {
+            let Some(from) = attempt.previous().last().cloned() else {
+                return attempt.follow();
+            };
+            if let Some(reason) = schemes.check(&from, attempt.url()) {
+                return attempt.error(reason);
+            }
+            let mut history = history.lock().unwrap_or_else(|e| e.into_inner());
+            if history.len() + 1 >= max {
+                attempt.error("too many redirects")
+            } else {
+                history.push(from);
+                attempt.follow()
+            }
+        })
+    }
+
+    /// Records a redirect from `from` to `to` that isn't handled by wreq, returning whether
+    /// it may be followed.
+    pub fn follow(&self, from: &Url, to: &Url) -> PyResult<bool> {
+        let Some(max) = self.max else {
+            return Ok(false);
+        };
+        if let Some(reason) = self.schemes.check(from, to) {
+            return Err(RedirectError::new_err(format!("{reason}: {from} to {to}")));
+        }
+        let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        if history.len() + 1 >= max {
+            return Err(RedirectError::new_err(format!(
+                "too many redirects following {from}"
+            )));
+        }
+        history.push(from.clone());
+        Ok(true)
+    }
+
+    /// Returns the URLs the request was redirected from, in order.
+    pub fn history(&self) -> Vec<Url> {
+        self.history
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
@@ -1,17 +1,49 @@
-use crate::error::Error;
+use crate::error::{BuilderError, Error};
 use crate::{
-    async_impl::{Response, WebSocket},
-    typing::param::{RequestParams, WebSocketParams},
-    typing::{Method, Version},
+    async_impl::{
+        Response, Tunnel, WebSocket,
+        cache::{Cache, CacheLookup},
+        cassette::{Cassette, RecordedRequest},
+        counter::{self, ByteCounter},
+        meta_refresh,
+        oauth::OAuth2ClientCredentials,
+        pool::PoolTracker,
+        redirect::{DEFAULT_MAX_REDIRECTS, Redirects},
+        response::Reconnect,
+        timing::Timing,
+        transport::MockTransport,
+    },
+    logging,
+    typing::param::{RequestParams, Timeout, WebSocketParams},
+    typing::{Version, check_pac, check_pac_future},
 };
-use pyo3::PyResult;
-use std::time::Duration;
-use wreq::redirect::Policy;
-use wreq::{Client, header};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use uuid::Uuid;
+use wreq::{Client, Method, header};
 
-/// Executes an HTTP request.
+/// Executes an HTTP request, going through `cache` if the client has one and answering it
+/// with `transport` instead of the network if the client was given a mock transport. A
+/// `cassette` either replays a recorded response instead, or records the response.
+///
+/// Responses from the network are marked with the connection they came over if the client
+/// tracks its `pool`. Redirects are followed up to `max_redirects` unless the request says
+/// otherwise, or not at all if it's `None`. With a `request_id_header`, the request is sent
+/// with a generated id in it, which the response records. Response bodies are limited to
+/// `max_response_size` bytes unless the request sets its own limit. Unless the request
+/// turns `prefetch_body` off, the body may be read before returning to store it in `cache`.
+/// With `auth`, requests without an `Authorization` header of their own are sent with a
+/// token, and retried once with a new one if it's refused.
+#[allow(clippy::too_many_arguments)]
 pub async fn execute_request<U>(
     client: Client,
+    cache: Option<Cache>,
+    transport: Option<MockTransport>,
+    cassette: Option<Cassette>,
+    pool: Option<PoolTracker>,
+    max_redirects: Option<usize>,
+    request_id_header: Option<header::HeaderName>,
+    max_response_size: Option<u64>,
+    auth: Option<OAuth2ClientCredentials>,
     method: Method,
     url: U,
     mut params: Option<RequestParams>,
@@ -20,7 +52,73 @@ where
     U: AsRef<str>,
 {
     let params = params.get_or_insert_default();
-    let mut builder = client.request(method.into_ffi(), url.as_ref());
+    let max_response_size = params.max_response_size.take().or(max_response_size);
+    let prefetch_body = params.prefetch_body.take().unwrap_or(true);
+
+    // Each of these defines the whole body, so at most one may be given.
+    let bodies = [
+        ("json", params.json.is_some()),
+        ("form", params.form.is_some()),
+        ("body", params.body.is_some()),
+        ("multipart", params.multipart.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(name, set)| set.then_some(name))
+    .collect::<Vec<_>>();
+    if bodies.len() > 1 {
+        return Err(BuilderError::new_err(format!(
+            "conflicting body options: {}; pass only one of json, form, body and multipart",
+            bodies.join(", ")
+        )));
+    }
+    // A multipart body carries its boundary in its own `Content-Type`.
+    let user_content_type = params
+        .headers
+        .as_ref()
+        .is_some_and(|headers| headers.0.contains_key(header::CONTENT_TYPE));
+    if params.multipart.is_some() && user_content_type {
+        return Err(BuilderError::new_err(
+            "conflicting Content-Type: a multipart body sets its own Content-Type with its \
+             boundary; pass content_type and boundary to Multipart instead of the header",
+        ));
+    }
+    // Meta refreshes are found in the body, so following them means reading it.
+    if !prefetch_body && params.follow_meta_refresh == Some(true) {
+        return Err(BuilderError::new_err(
+            "follow_meta_refresh reads the body, so it can't be combined with prefetch_body=False",
+        ));
+    }
+
+    // Impersonation and headers order options. Both are client-wide in wreq, and the order
+    // is applied as the request is sent, so apply them to a detached copy that still shares
+    // the connection pool, cookie store and other settings. `Client` sends requests that
+    // override the profile with a client of their own instead, so only clients that don't
+    // keep connections alive get here with one.
+    let (impersonate, headers_order) = (params.impersonate.take(), params.headers_order.take());
+    let client = if impersonate.is_some() || headers_order.is_some() {
+        let client = client.cloned();
+        if let Some(impersonate) = impersonate {
+            client
+                .update()
+                .emulation(impersonate.0)
+                .apply()
+                .map_err(Error::Request)?;
+        }
+        // Emulation brings its own order, which wreq applies last within an update, so the
+        // request's order goes in a second one.
+        if let Some(headers_order) = headers_order {
+            client
+                .update()
+                .headers_order(headers_order.0)
+                .apply()
+                .map_err(Error::Request)?;
+        }
+        client
+    } else {
+        client
+    };
+
+    let mut builder = client.request(method.clone(), url.as_ref());
 
     // Version options.
     apply_option!(
@@ -31,55 +129,73 @@ where
         Version::into_ffi
     );
 
-    // Allow redirects options.
-    apply_option!(
-        apply_option_or_default_with_value,
-        builder,
-        params.allow_redirects,
-        redirect,
-        false,
-        params
-            .max_redirects
-            .take()
-            .map(Policy::limited)
-            .unwrap_or_default()
+    // Allow redirects options. The policy is set on every request, so the redirects it
+    // follows can be recorded.
+    let https_only = params.https_only.take().unwrap_or(false);
+    let redirects = Redirects::new(match params.allow_redirects.take() {
+        Some(true) => Some(params.max_redirects.take().unwrap_or(DEFAULT_MAX_REDIRECTS)),
+        Some(false) => None,
+        None => max_redirects,
+    })
+    .schemes(
+        https_only,
+        params.allow_insecure_redirects.take().unwrap_or(false),
     );
+    builder = builder.redirect(redirects.policy());
 
-    // Timeout options.
-    apply_option!(
-        apply_transformed_option,
-        builder,
-        params.timeout,
-        timeout,
-        Duration::from_secs
-    );
+    // Timeout options. wreq only sets connect timeouts per client, so a `(connect, read)`
+    // timeout may only set the read one here.
+    match params.timeout.take() {
+        Some(Timeout::Total(timeout)) => builder = builder.timeout(timeout),
+        Some(Timeout::Split(Some(_), _)) => {
+            return Err(PyValueError::new_err(
+                "timeout: a connect timeout can only be set on the client",
+            ));
+        }
+        Some(Timeout::Split(None, read)) => params.read_timeout = params.read_timeout.or(read),
+        None => {}
+    }
     apply_option!(
-        apply_transformed_option,
+        apply_if_some_inner,
         builder,
         params.read_timeout,
-        read_timeout,
-        Duration::from_secs
+        read_timeout
     );
 
-    // Network options.
-    apply_option!(apply_if_some_inner, builder, params.proxy, proxy);
-    apply_option!(
-        apply_if_some_inner,
-        builder,
-        params.local_address,
-        local_address
-    );
-    #[cfg(any(
-        target_os = "android",
-        target_os = "fuchsia",
-        target_os = "linux",
-        target_os = "ios",
-        target_os = "visionos",
-        target_os = "macos",
-        target_os = "tvos",
-        target_os = "watchos"
-    ))]
-    apply_option!(apply_if_some, builder, params.interface, interface);
+    // Network options. wreq picks the proxy of the request as it's set, which is when a PAC
+    // script runs.
+    if let Some(proxy) = params.proxy.take() {
+        builder = check_pac(|| builder.proxy(proxy.0))?;
+    }
+    apply_local_address!(builder, params.local_address, params.interface);
+    apply_interface!(builder, params.interface);
+
+    // Decompression options. The client was picked to leave the body encoded; impersonation
+    // headers advertise compression too, so ask for an unencoded body explicitly. An
+    // `Accept-Encoding` in `headers` still wins, to fetch the encoded body.
+    let decompress = params.decompress.take() != Some(false);
+    if !decompress {
+        builder = builder
+            .allow_compression(false)
+            .header(header::ACCEPT_ENCODING, "identity");
+    }
+    // The cache keeps decoded bodies, so `GET` and `HEAD` requests for an encoded one skip
+    // it. Other methods still go through it, to invalidate what it stored.
+    let cache = cache.filter(|_| decompress || !matches!(method, Method::GET | Method::HEAD));
+
+    // Range options.
+    if let Some((start, end)) = params.range.take() {
+        let range = match end {
+            Some(end) if end < start => {
+                return Err(PyValueError::new_err(format!(
+                    "range: end ({end}) must not be before start ({start})"
+                )));
+            }
+            Some(end) => format!("bytes={start}-{end}"),
+            None => format!("bytes={start}-"),
+        };
+        builder = builder.header(header::RANGE, range);
+    }
 
     // Headers options.
     apply_option!(apply_if_some_inner, builder, params.headers, headers);
@@ -119,31 +235,336 @@ where
     apply_option!(apply_if_some, builder, params.body, body);
 
     // Multipart options.
-    apply_option!(apply_if_some_inner, builder, params.multipart, multipart);
+    if let Some(multipart) = params.multipart.take() {
+        builder = multipart.apply(builder);
+    }
+
+    // Build the request.
+    let (client, request) = builder.build_split();
+    let mut request = request.map_err(Error::Request)?;
+
+    // HTTPS only options. The client's own `https_only` is enforced by wreq.
+    if https_only && request.url().scheme() != "https" {
+        return Err(BuilderError::new_err(format!(
+            "URL scheme is not allowed with https_only: {}",
+            request.url()
+        )));
+    }
+
+    // Request id options. An id set by the request itself is kept.
+    let request_id = request_id_header.map(|name| {
+        let id = match request.headers().get(&name).and_then(|v| v.to_str().ok()) {
+            Some(id) => id.to_owned(),
+            None => {
+                let id = Uuid::new_v4().to_string();
+                if let Ok(value) = header::HeaderValue::from_str(&id) {
+                    request.headers_mut().insert(&name, value);
+                }
+                id
+            }
+        };
+        (name, id)
+    });
+
+    // OAuth2 options. A copy is kept to retry with a new token, unless the body can't be
+    // sent again.
+    let oauth = match auth {
+        Some(auth)
+            if !request.headers().contains_key(header::AUTHORIZATION)
+                && auth.applies_to(request.url()) =>
+        {
+            let token = auth
+                .authorize(&client, transport.as_ref(), &mut request, None)
+                .await?;
+            let retry = request.try_clone();
+            Some((auth, token, retry))
+        }
+        _ => None,
+    };
+
+    // Upload progress options.
+    if let Some(callback) = params.on_upload_progress.take() {
+        counter::track_upload(&mut request, callback);
+    }
+
+    // Keep a copy without the body to follow meta refreshes with.
+    let template = if params.follow_meta_refresh.take() == Some(true) {
+        let body = request.body_mut().take();
+        let template = request.try_clone();
+        *request.body_mut() = body;
+        template
+    } else {
+        None
+    };
+
+    let mut response = send(
+        &client,
+        cache.clone(),
+        transport.clone(),
+        cassette.clone(),
+        pool.as_ref(),
+        request,
+        params.version_fallback,
+        max_response_size,
+        prefetch_body,
+        method.clone(),
+    )
+    .await?;
+
+    // A refused token may have been revoked before it expired, so fetch a new one.
+    if let Some((auth, token, Some(mut retry))) = oauth {
+        if response.status() == 401 {
+            auth.authorize(&client, transport.as_ref(), &mut retry, Some(&token))
+                .await?;
+            response = send(
+                &client,
+                cache.clone(),
+                transport.clone(),
+                cassette.clone(),
+                pool.as_ref(),
+                retry,
+                params.version_fallback,
+                max_response_size,
+                prefetch_body,
+                method,
+            )
+            .await?;
+        }
+    }
+
+    if let Some(template) = template {
+        while let Some(target) = meta_refresh::target(&response).await? {
+            let from = response.url().0;
+            let Some(request) = meta_refresh::next_request(&template, &from, target) else {
+                break;
+            };
+            if !redirects.follow(&from, request.url())? {
+                break;
+            }
+            response = send(
+                &client,
+                cache.clone(),
+                transport.clone(),
+                cassette.clone(),
+                pool.as_ref(),
+                request,
+                params.version_fallback,
+                max_response_size,
+                prefetch_body,
+                Method::GET,
+            )
+            .await?;
+        }
+    }
+
+    let response = response.with_history(redirects.history());
+    let response = response.warn_if_unread();
+    Ok(match request_id {
+        Some((name, id)) => response.with_request_id(&name, id),
+        None => response,
+    })
+}
+
+/// Sends `request` through the cache, the cassette, the mock transport or the network.
+#[allow(clippy::too_many_arguments)]
+async fn send(
+    client: &Client,
+    cache: Option<Cache>,
+    transport: Option<MockTransport>,
+    cassette: Option<Cassette>,
+    pool: Option<&PoolTracker>,
+    mut request: wreq::Request,
+    version_fallback: Option<bool>,
+    max_response_size: Option<u64>,
+    prefetch_body: bool,
+    method: Method,
+) -> PyResult<Response> {
+    let url = request.url().clone();
+    let logged = logging::redact(&url);
+    log::debug!("sending {method} {logged}");
+
+    // Serve fresh responses from the cache, and revalidate stale ones.
+    let lookup = match &cache {
+        Some(cache) => cache.lookup(&mut request, &client.headers()).await,
+        None => CacheLookup::Bypass,
+    };
+    if let CacheLookup::Fresh(entry) = lookup {
+        log::debug!("{method} {logged} answered from the cache");
+        return Ok(entry.respond(Response::cached(url)));
+    }
+
+    // A replaying cassette answers the request in place of the network, and a recording one
+    // keeps what the network answered.
+    let interaction = match cassette {
+        Some(cassette) => Some((cassette, RecordedRequest::read(&mut request).await?)),
+        None => None,
+    };
+    let replayed = match &interaction {
+        Some((cassette, recorded)) if cassette.replays() => {
+            Some(cassette.replay_response(recorded).await?)
+        }
+        _ => None,
+    };
+
+    let route = match (&replayed, transport) {
+        (None, Some(transport)) => transport.route(&request)?,
+        _ => None,
+    };
+    let response = match (replayed, route) {
+        (Some(response), _) => {
+            log::debug!("{method} {logged} replayed from the cassette");
+            response
+        }
+        (None, Some(route)) => {
+            log::debug!("{method} {logged} answered by the mock transport");
+            route.send(client, request).await?
+        }
+        (None, None) => {
+            let snapshot = pool.map(PoolTracker::snapshot);
+            // The client's proxies are picked as the request and its redirects are sent.
+            let response = check_pac_future(send_with_fallback(
+                client,
+                request,
+                version_fallback,
+                method.clone(),
+            ))
+            .await??;
+            match snapshot {
+                Some(snapshot) => snapshot.identify(response),
+                None => response,
+            }
+        }
+    };
+    log::debug!(
+        "{method} {logged} responded {} from {}",
+        response.status(),
+        logging::redact(&response.url().0)
+    );
+    // Limit the body before the cache or the cassette reads it in full.
+    let response = match max_response_size {
+        Some(limit) => response.with_size_limit(limit)?,
+        None => response,
+    };
+    let response = match interaction {
+        Some((cassette, recorded)) if !cassette.replays() => {
+            cassette.record_response(recorded, response).await?
+        }
+        _ => response,
+    };
+    match cache {
+        Some(cache) => cache.update(lookup, response, prefetch_body).await,
+        None => Ok(response),
+    }
+}
+
+/// Sends `request` over the network, retrying over HTTP/1.1 if `version_fallback` is set
+/// and a newer version can't be negotiated.
+async fn send_with_fallback(
+    client: &Client,
+    request: wreq::Request,
+    version_fallback: Option<bool>,
+    method: Method,
+) -> PyResult<Response> {
+    // Keep a copy to retry over HTTP/1.1 if a newer version can't be negotiated.
+    let fallback = if version_fallback.unwrap_or(false)
+        && request
+            .version()
+            .is_some_and(|version| version > wreq::Version::HTTP_11)
+    {
+        request.try_clone().map(|mut request| {
+            *request.version_mut() = Some(wreq::Version::HTTP_11);
+            request
+        })
+    } else {
+        None
+    };
 
     // Send the request.
-    builder
-        .send()
-        .await
-        .map(Response::new)
-        .map_err(Error::Request)
+    let response = match (send_request(client, request).await, fallback) {
+        (Err(err), Some(fallback)) if err.is_connect() || err.is_request() => {
+            log::debug!(
+                "{method} failed, retrying over HTTP/1.1: {}",
+                logging::redact_error(&err)
+            );
+            send_request(client, fallback).await
+        }
+        (result, _) => result,
+    };
+
+    response
+        .inspect_err(|err| log::debug!("{method} failed: {}", logging::redact_error(err)))
+        .map_err(|err| Error::RequestWithMethod(err, method))
         .map_err(Into::into)
 }
 
+/// Sends `request`, accounting for the bytes it sends and receives and timing it.
+async fn send_request(client: &Client, mut request: wreq::Request) -> wreq::Result<Response> {
+    let bytes_sent = ByteCounter::default();
+    counter::count_request(&mut request, &client.headers(), &bytes_sent);
+    let timing = Timing::start();
+    let response = timing.scope(client.execute(request)).await?;
+    timing.first_byte();
+    Ok(Response::new(response, bytes_sent).with_timing(timing))
+}
+
 /// Executes a WebSocket request.
 pub async fn execute_websocket_request<U>(
     client: Client,
+    defaults: Option<WebSocketParams>,
     url: U,
-    mut params: Option<WebSocketParams>,
+    params: Option<WebSocketParams>,
 ) -> PyResult<WebSocket>
 where
     U: AsRef<str>,
 {
-    let params = params.get_or_insert_default();
+    let mut params = params.unwrap_or_default();
+    if let Some(defaults) = defaults {
+        params = params.merge(&defaults);
+    }
+    let reconnect = Reconnect::from_params(&client, url.as_ref(), &mut params);
+
+    // Remember what the handshake sends, so it can be read back from the `WebSocket`.
+    let sent_header = |value: &Option<String>, name: header::HeaderName| {
+        value.as_deref().map(str::to_owned).or_else(|| {
+            params
+                .headers
+                .as_ref()
+                .and_then(|headers| headers.0.get(name))
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+        })
+    };
+    let origin = sent_header(&params.origin, header::ORIGIN);
+    let user_agent = sent_header(&params.user_agent, header::USER_AGENT);
+
+    let max_recv_queue = params.max_recv_queue;
+    let (max_message_size, max_frame_size) = (params.max_message_size, params.max_frame_size);
+    let builder = build_websocket_request(&client, url, params)?;
+    WebSocket::new(builder, reconnect, max_recv_queue)
+        .await
+        .map(|websocket| {
+            websocket
+                .with_handshake(origin, user_agent)
+                .with_limits(max_message_size, max_frame_size)
+        })
+        .map_err(Error::Request)
+        .map_err(Into::into)
+}
+
+/// Builds the handshake request of a WebSocket.
+pub fn build_websocket_request<U>(
+    client: &Client,
+    url: U,
+    mut params: WebSocketParams,
+) -> PyResult<wreq::WebSocketRequestBuilder>
+where
+    U: AsRef<str>,
+{
+    let params = &mut params;
     let mut builder = client.websocket(url.as_ref());
 
     // The protocols to use for the request.
-    apply_option!(apply_if_some, builder, params.protocols, protocols);
+    apply_option!(apply_if_some_inner, builder, params.protocols, protocols);
 
     // The WebSocket config
     apply_option!(
@@ -194,23 +615,8 @@ where
 
     // Network options.
     apply_option!(apply_if_some_inner, builder, params.proxy, proxy);
-    apply_option!(
-        apply_if_some_inner,
-        builder,
-        params.local_address,
-        local_address
-    );
-    #[cfg(any(
-        target_os = "android",
-        target_os = "fuchsia",
-        target_os = "linux",
-        target_os = "ios",
-        target_os = "visionos",
-        target_os = "macos",
-        target_os = "tvos",
-        target_os = "watchos"
-    ))]
-    apply_option!(apply_if_some, builder, params.interface, interface);
+    apply_local_address!(builder, params.local_address, params.interface);
+    apply_interface!(builder, params.interface);
 
     // Authentication options.
     apply_option!(
@@ -237,10 +643,44 @@ where
         builder = builder.header(header::COOKIE, cookies.0);
     }
 
+    // Origin and user agent options, taking precedence over `headers`.
+    if let Some(origin) = params.origin.take() {
+        builder = builder.header(header::ORIGIN, &*origin);
+    }
+    if let Some(user_agent) = params.user_agent.take() {
+        builder = builder.header(header::USER_AGENT, &*user_agent);
+    }
+
     // Query options.
     apply_option!(apply_if_some_ref, builder, params.query, query);
 
-    WebSocket::new(builder)
+    Ok(builder)
+}
+
+/// Opens a raw tunnel to `host:port` through `proxy` with an HTTP `CONNECT` request.
+pub async fn execute_connect_tunnel<H>(
+    client: Client,
+    proxy: wreq::Proxy,
+    host: H,
+    port: u16,
+) -> PyResult<Tunnel>
+where
+    H: AsRef<str>,
+{
+    let host = host.as_ref();
+    // IPv6 literals must be bracketed in the authority.
+    let url = if host.contains(':') && !host.starts_with('[') {
+        format!("http://[{}]:{}", host, port)
+    } else {
+        format!("http://{}:{}", host, port)
+    };
+
+    let builder = client
+        .request(wreq::Method::CONNECT, url)
+        .version(wreq::Version::HTTP_11)
+        .proxy(proxy);
+
+    Tunnel::new(builder)
         .await
         .map_err(Error::Request)
         .map_err(Into::into)
@@ -1,15 +1,47 @@
+use super::{
+    json_array::ArraySplitter,
+    multipart::PartSplitter,
+    sse::{Event, EventParser},
+};
 use crate::{
+    async_impl::{
+        counter::{self, ByteCounter},
+        timing::Timing,
+    },
     buffer::{Buffer, BytesBuffer, PyBufferProtocol},
-    error::Error,
-    typing::{Cookie, HeaderMap, Json, SocketAddr, StatusCode, Version},
+    error::{BodyError, DecodingError, Error},
+    logging, runtime,
+    typing::{
+        self, BodyExtractor, Cookie, HeaderMap, HeaderMapExtractor, Json, SocketAddr, StatusCode,
+        Version,
+    },
 };
 use arc_swap::ArcSwapOption;
-use futures_util::{Stream, TryStreamExt};
+use bytes::{Bytes, BytesMut};
+use chardetng::EncodingDetector;
+use encoding_rs::{DecoderResult, Encoding, UTF_8};
+use futures_util::{Stream, StreamExt, TryStreamExt};
 use mime::Mime;
-use pyo3::{IntoPyObjectExt, prelude::*};
+use pyo3::{
+    IntoPyObjectExt,
+    exceptions::{PyResourceWarning, PyTypeError, PyValueError},
+    prelude::*,
+    pybacked::PyBackedStr,
+    types::PyDict,
+};
 use pyo3_async_runtimes::tokio::future_into_py;
-use std::{ops::Deref, pin::Pin, sync::Arc};
-use tokio::sync::Mutex;
+use std::{
+    collections::VecDeque,
+    ffi::CString,
+    ops::Deref,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+use tokio::{fs::File, io::AsyncWriteExt, sync::Mutex};
 use wreq::{TlsInfo, Url, header};
 
 /// A response from a request.
@@ -21,39 +53,409 @@ pub struct Response {
     remote_addr: Option<SocketAddr>,
     content_length: Option<u64>,
     headers: wreq::header::HeaderMap,
-    response: ArcSwapOption<wreq::Response>,
+    bytes_sent: ByteCounter,
+    bytes_received: ByteCounter,
+    from_cache: bool,
+    connection_reused: Option<bool>,
+    connection_id: Option<u64>,
+    timing: Option<Timing>,
+    history: Vec<Url>,
+    request_id: Option<String>,
+    client_request_id: Option<String>,
+    detected_encoding: Arc<OnceLock<&'static Encoding>>,
+    response: Arc<ArcSwapOption<wreq::Response>>,
+    /// Whether the body is still read from the connection, rather than from memory.
+    streamed: bool,
+    /// Whether dropping the response with its body unread is reported.
+    warn_if_unread: bool,
+}
+
+/// Whether a response dropped with its body unread raises `ResourceWarning` instead of
+/// warning.
+static STRICT_RESOURCES: AtomicBool = AtomicBool::new(false);
+
+/// Makes responses dropped with their body unread raise `ResourceWarning` instead of
+/// warning, or warn again with `False`.
+///
+/// The exception can't propagate from garbage collection, so it's reported through
+/// `sys.unraisablehook`, which pytest turns into a failing warning.
+#[pyfunction]
+#[pyo3(signature = (enabled = true))]
+pub fn strict_resources(enabled: bool) {
+    STRICT_RESOURCES.store(enabled, Ordering::Relaxed);
+}
+
+impl Drop for Response {
+    fn drop(&mut self) {
+        // A body some other handle still reads from isn't abandoned.
+        if self.warn_if_unread
+            && Arc::strong_count(&self.response) == 1
+            && self.response.load().is_some()
+        {
+            report_unread(&self.url);
+        }
+        runtime::drop_in_runtime(std::mem::take(&mut self.response));
+    }
+}
+
+/// Reports that the response from `url` was dropped with its body unread, keeping its
+/// connection out of the pool until then.
+fn report_unread(url: &Url) {
+    // Responses may be dropped on runtime threads, which may outlive the interpreter.
+    runtime::with_live_gil(|py| {
+        let message = format!(
+            "response from {} was dropped with its body unread; read it, or close it \
+             with close() or a with block, to release its connection",
+            logging::redact(url)
+        );
+        let category = py.get_type::<PyResourceWarning>();
+        let result = if STRICT_RESOURCES.load(Ordering::Relaxed) {
+            Err(PyResourceWarning::new_err(message))
+        } else {
+            CString::new(message)
+                .map_err(|err| PyValueError::new_err(err.to_string()))
+                .and_then(|message| PyErr::warn(py, &category, &message, 1))
+        };
+        // A filter may turn the warning into an error too.
+        if let Err(err) = result {
+            err.write_unraisable(py, None);
+        }
+    });
 }
 
 impl Response {
     /// Create a new `Response` instance.
-    pub fn new(mut response: wreq::Response) -> Self {
+    pub fn new(mut response: wreq::Response, bytes_sent: ByteCounter) -> Self {
+        let url = response.url().clone();
+        let version = Version::from_ffi(response.version());
+        let status_code = StatusCode::from(response.status());
+        let remote_addr = response.remote_addr().map(SocketAddr);
+        let content_length = response.content_length();
+        let headers = std::mem::take(response.headers_mut());
+
+        let bytes_received = ByteCounter::default();
+        let response = counter::count_response(response, &headers, &bytes_received);
+        runtime::remember_current();
+
         Response {
-            url: response.url().clone(),
-            version: Version::from_ffi(response.version()),
-            status_code: StatusCode::from(response.status()),
-            remote_addr: response.remote_addr().map(SocketAddr),
-            content_length: response.content_length(),
-            headers: std::mem::take(response.headers_mut()),
-            response: ArcSwapOption::from_pointee(response),
+            url,
+            version,
+            status_code,
+            remote_addr,
+            content_length,
+            headers,
+            bytes_sent,
+            bytes_received,
+            from_cache: false,
+            connection_reused: None,
+            connection_id: None,
+            timing: None,
+            history: Vec::new(),
+            request_id: None,
+            client_request_id: None,
+            detected_encoding: Arc::default(),
+            response: Arc::new(ArcSwapOption::from_pointee(response)),
+            streamed: true,
+            warn_if_unread: false,
         }
     }
 
+    /// Create a `Response` for `url` that is served from the cache without a network request.
+    pub fn cached(url: Url) -> Self {
+        Self::detached(url, true)
+    }
+
+    /// Create a `Response` for `url` answered by a mock transport.
+    pub fn mocked(
+        url: Url,
+        status: wreq::StatusCode,
+        headers: header::HeaderMap,
+        body: Bytes,
+    ) -> Self {
+        Self::detached(url, false).with_parts(status, Version::HTTP_11, headers, body)
+    }
+
+    /// Create a `Response` for `url` that isn't backed by a connection.
+    fn detached(url: Url, from_cache: bool) -> Self {
+        Response {
+            url,
+            version: Version::HTTP_11,
+            status_code: StatusCode::from(wreq::StatusCode::OK),
+            remote_addr: None,
+            content_length: None,
+            headers: header::HeaderMap::new(),
+            bytes_sent: ByteCounter::default(),
+            bytes_received: ByteCounter::default(),
+            from_cache,
+            connection_reused: None,
+            connection_id: None,
+            timing: None,
+            history: Vec::new(),
+            request_id: None,
+            client_request_id: None,
+            detected_encoding: Arc::default(),
+            response: Arc::new(ArcSwapOption::empty()),
+            streamed: false,
+            warn_if_unread: false,
+        }
+    }
+
+    /// Replaces the status, headers and body with those of a cached response.
+    pub fn with_cached(
+        mut self,
+        status: wreq::StatusCode,
+        version: Version,
+        headers: header::HeaderMap,
+        body: Bytes,
+    ) -> Self {
+        self.from_cache = true;
+        self.with_parts(status, version, headers, body)
+    }
+
+    /// Records whether the response came over a reused connection, and which one, as far as
+    /// they're known.
+    pub fn with_connection(mut self, reused: Option<bool>, id: Option<u64>) -> Self {
+        self.connection_reused = reused;
+        self.connection_id = id;
+        self
+    }
+
+    /// Records the timing of the request, which notes when the body is read to its end.
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        if let Ok(response) = self.inner() {
+            self.response
+                .store(Some(Arc::new(timing.track_response(response))));
+        }
+        self.timing = Some(timing);
+        self
+    }
+
+    /// Keeps `guard` alive until the body streaming from the connection is read to its end or
+    /// dropped.
+    pub fn holding<T: Send + Sync + 'static>(self, guard: T) -> Self {
+        if !self.streamed {
+            return self;
+        }
+        if let Ok(response) = self.inner() {
+            self.response
+                .store(Some(Arc::new(counter::hold_until_read(response, guard))));
+        }
+        self
+    }
+
+    /// Makes dropping the response with its body still streaming from the connection
+    /// report a `ResourceWarning`, once it's handed to the caller. An empty body doesn't
+    /// hold the connection.
+    pub fn warn_if_unread(mut self) -> Self {
+        self.warn_if_unread = self.streamed && self.content_length != Some(0);
+        self
+    }
+
+    /// Records the URLs the request was redirected from.
+    pub fn with_history(mut self, history: Vec<Url>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Records the id sent in the `header` of the request, preferring the server's if it
+    /// echoed a different one back.
+    pub fn with_request_id(mut self, header: &header::HeaderName, client_id: String) -> Self {
+        let echoed = self
+            .headers
+            .get(header)
+            .filter(|_| !self.from_cache)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| !id.is_empty())
+            .map(str::to_owned);
+        self.request_id = Some(echoed.unwrap_or_else(|| client_id.clone()));
+        self.client_request_id = Some(client_id);
+        self
+    }
+
+    /// Replaces the status, headers and body with in-memory ones.
+    fn with_parts(
+        mut self,
+        status: wreq::StatusCode,
+        version: Version,
+        headers: header::HeaderMap,
+        body: Bytes,
+    ) -> Self {
+        self.status_code = StatusCode::from(status);
+        self.version = version;
+        self.content_length = Some(body.len() as u64);
+        self.headers = headers;
+        self.response = Arc::new(ArcSwapOption::from_pointee(body_response(body)));
+        self.streamed = false;
+        self.warn_if_unread = false;
+        self
+    }
+
+    /// Makes reading more than `limit` bytes of the body fail with `ResponseTooLargeError`.
+    pub fn with_size_limit(self, limit: u64) -> PyResult<Self> {
+        let response = self.inner()?;
+        self.response
+            .store(Some(Arc::new(counter::limit_response(response, limit))));
+        Ok(self)
+    }
+
+    /// Reads the whole body, keeping a copy in the response so it can still be consumed.
+    pub async fn into_buffered(mut self) -> PyResult<(Self, Bytes)> {
+        let body = self.inner()?.bytes().await.map_err(Error::Request)?;
+        self.response
+            .store(Some(Arc::new(body_response(body.clone()))));
+        self.streamed = false;
+        self.warn_if_unread = false;
+        Ok((self, body))
+    }
+
+    /// Reads the body in full if it's at most `limit` bytes, or returns `None` for it as soon
+    /// as it grows past that, with what was read put back in front of the rest.
+    pub async fn into_buffered_within(mut self, limit: u64) -> PyResult<(Self, Option<Bytes>)> {
+        let mut response = take_response(&self.response)?;
+        let mut body = BytesMut::new();
+        while let Some(chunk) = response.chunk().await.map_err(Error::Request)? {
+            body.extend_from_slice(&chunk);
+            if body.len() as u64 > limit {
+                let response = counter::prepend_body(response, body.freeze());
+                self.response.store(Some(Arc::new(response)));
+                return Ok((self, None));
+            }
+        }
+
+        let body = body.freeze();
+        self.response
+            .store(Some(Arc::new(body_response(body.clone()))));
+        self.streamed = false;
+        self.warn_if_unread = false;
+        Ok((self, Some(body)))
+    }
+
+    /// Reads up to `limit` bytes from the start of the body, leaving the body in place.
+    pub async fn peek(&self, limit: usize) -> PyResult<Bytes> {
+        peek_body(&self.response, limit).await
+    }
+
+    /// Guesses the charset of the body from a sample of it, left in place, returning its
+    /// name and whether the guess is confident.
+    pub fn sniff_encoding(
+        &self,
+    ) -> impl Future<Output = PyResult<(String, bool)>> + Send + 'static {
+        let slot = self.response.clone();
+        let tld = top_level_domain(&self.url);
+        async move {
+            let head = peek_body(&slot, DETECT_SAMPLE).await?;
+            let (encoding, confident) =
+                guess_encoding(&head, head.len() < DETECT_SAMPLE, tld.as_deref());
+            Ok((encoding.name().to_ascii_lowercase(), confident))
+        }
+    }
+
+    /// Returns when `text` detects the charset of the body, given its `detect_encoding` and
+    /// `autodetect` flags and its `encoding` argument, which spells them `"detect"` and
+    /// `"auto"`.
+    pub fn detection(
+        &self,
+        detect_encoding: bool,
+        autodetect: bool,
+        encoding: Option<TextEncoding>,
+    ) -> EncodingDetection {
+        let detected = self.detected_encoding.clone();
+        if detect_encoding || matches!(encoding, Some(TextEncoding::Detect)) {
+            EncodingDetection::Always(detected)
+        } else if autodetect || matches!(encoding, Some(TextEncoding::Auto)) {
+            EncodingDetection::Undeclared(detected)
+        } else {
+            EncodingDetection::Never
+        }
+    }
+
+    /// Returns the response headers.
+    pub fn header_map(&self) -> &header::HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the length declared by `Content-Length`, which unlike `content_length` is
+    /// also known for the response to a `HEAD` request.
+    pub fn declared_content_length(&self) -> Option<u64> {
+        let value = self.headers.get(header::CONTENT_LENGTH)?.to_str().ok()?;
+        value.trim().parse().ok()
+    }
+
+    /// Returns the complete length from `Content-Range`, which is also sent with
+    /// `416 Range Not Satisfiable` as `bytes */length`.
+    pub fn content_range_total(&self) -> Option<u64> {
+        let value = self.headers.get(header::CONTENT_RANGE)?.to_str().ok()?;
+        let (_, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+        total.trim().parse().ok()
+    }
+
+    /// Drops the body of a response rnet only looked at itself, releasing its connection
+    /// without reporting it unread.
+    pub fn discard(self) {
+        let _ = take_response(&self.response);
+    }
+
     /// Consumes the `Response` and returns the inner `wreq::Response`.
     pub fn inner(&self) -> PyResult<wreq::Response> {
-        self.response
-            .swap(None)
-            .and_then(Arc::into_inner)
-            .ok_or_else(|| Error::Memory)
-            .map_err(Into::into)
+        take_response(&self.response)
+    }
+}
+
+/// Takes the response out of `slot`, failing if its body has already been consumed.
+fn take_response(slot: &ArcSwapOption<wreq::Response>) -> PyResult<wreq::Response> {
+    slot.swap(None)
+        .and_then(Arc::into_inner)
+        .ok_or_else(|| Error::Memory)
+        .map_err(Into::into)
+}
+
+/// Reads up to `limit` bytes from the start of the body of the response in `slot`, putting
+/// the whole body back.
+async fn peek_body(slot: &ArcSwapOption<wreq::Response>, limit: usize) -> PyResult<Bytes> {
+    let mut response = take_response(slot)?;
+    let mut head = BytesMut::new();
+    while head.len() < limit {
+        match response.chunk().await.map_err(Error::Request)? {
+            Some(chunk) => head.extend_from_slice(&chunk),
+            None => break,
+        }
     }
+
+    let head = head.freeze();
+    let response = counter::prepend_body(response, head.clone());
+    slot.store(Some(Arc::new(response)));
+    Ok(head)
 }
 
 #[pymethods]
 impl Response {
+    /// Builds a response in memory, without a request, for testing code that handles
+    /// responses.
+    #[staticmethod]
+    #[pyo3(signature = (status = 200, headers = None, body = None, url = "http://test", version = Version::HTTP_11))]
+    pub fn build(
+        status: u16,
+        headers: Option<HeaderMapExtractor>,
+        body: Option<BodyExtractor>,
+        url: &str,
+        version: Version,
+    ) -> PyResult<Response> {
+        let status = wreq::StatusCode::from_u16(status)
+            .map_err(|_| PyValueError::new_err(format!("invalid status code: {status}")))?;
+        let body = match body {
+            Some(BodyExtractor::Text(bytes) | BodyExtractor::Bytes(bytes)) => bytes,
+            None => Bytes::new(),
+            Some(_) => return Err(PyTypeError::new_err("body must be str or bytes")),
+        };
+        let url = Url::parse(url).map_err(Error::UrlParse)?;
+        let headers = headers.map(|headers| headers.0).unwrap_or_default();
+        Ok(Self::detached(url, false).with_parts(status, version, headers, body))
+    }
+
     /// Returns the URL of the response.
     #[getter]
-    pub fn url(&self) -> &str {
-        self.url.as_str()
+    pub fn url(&self) -> typing::Url {
+        typing::Url(self.url.clone())
     }
 
     /// Returns whether the response is successful.
@@ -62,6 +464,36 @@ impl Response {
         self.status_code.is_success()
     }
 
+    /// Returns whether the response is informational (1xx).
+    #[getter]
+    pub fn is_informational(&self) -> bool {
+        self.status_code.is_informational()
+    }
+
+    /// Returns whether the response redirects: a redirect status with a `Location`.
+    #[getter]
+    pub fn is_redirect(&self) -> bool {
+        self.status_code.is_redirect() && self.headers.contains_key(header::LOCATION)
+    }
+
+    /// Returns whether the response is a permanent redirect (301 or 308) with a `Location`.
+    #[getter]
+    pub fn is_permanent_redirect(&self) -> bool {
+        self.status_code.is_permanent_redirect() && self.headers.contains_key(header::LOCATION)
+    }
+
+    /// Returns whether the response is a client error (4xx).
+    #[getter]
+    pub fn is_client_error(&self) -> bool {
+        self.status_code.is_client_error()
+    }
+
+    /// Returns whether the response is a server error (5xx).
+    #[getter]
+    pub fn is_server_error(&self) -> bool {
+        self.status_code.is_server_error()
+    }
+
     /// Returns the status code as integer of the response.
     #[getter]
     pub fn status(&self) -> u16 {
@@ -93,31 +525,112 @@ impl Response {
     }
 
     /// Returns the content length of the response.
+    ///
+    /// This is the length of the body as received, so it's `0` for a body the client
+    /// decoded, and the encoded length for one left encoded with `decompress=False`.
     #[getter]
     pub fn content_length(&self) -> u64 {
         self.content_length.unwrap_or_default()
     }
 
+    /// Returns the `Content-Encoding` the body is still encoded with, or `None` if it isn't
+    /// or the client decoded it.
+    #[getter]
+    pub fn content_encoding(&self) -> Option<&str> {
+        self.headers
+            .get(header::CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+    }
+
     /// Returns the remote address of the response.
     #[getter]
     pub fn remote_addr(&self) -> Option<SocketAddr> {
         self.remote_addr
     }
 
-    /// Encoding to decode with when accessing text.
+    /// Returns the `Content-Range` of a partial response as `(start, end, total)`, where
+    /// `total` is `None` if the server doesn't know it.
+    #[getter]
+    pub fn content_range(&self) -> Option<(u64, u64, Option<u64>)> {
+        let value = self.headers.get(header::CONTENT_RANGE)?.to_str().ok()?;
+        let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+        let (start, end) = range.split_once('-')?;
+        let total = match total.trim() {
+            "*" => None,
+            total => Some(total.parse().ok()?),
+        };
+        Some((start.trim().parse().ok()?, end.trim().parse().ok()?, total))
+    }
+
+    /// Returns whether the response was served from the client's cache.
+    #[getter]
+    pub fn from_cache(&self) -> bool {
+        self.from_cache
+    }
+
+    /// Returns whether the request was sent over a connection taken from the pool.
+    #[getter]
+    pub fn connection_reused(&self) -> Option<bool> {
+        self.connection_reused
+    }
+
+    /// Returns an opaque id of the pooled connection the response came over.
+    #[getter]
+    pub fn connection_id(&self) -> Option<u64> {
+        self.connection_id
+    }
+
+    /// Returns how long the phases of the request took, in seconds.
+    #[getter]
+    pub fn timing<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        self.timing
+            .as_ref()
+            .map(|timing| timing.to_dict(py))
+            .transpose()
+    }
+
+    /// Returns the URLs the request was redirected from, in order.
+    #[getter]
+    pub fn history(&self) -> Vec<&str> {
+        self.history.iter().map(Url::as_str).collect()
+    }
+
+    /// Returns the id of the request, as echoed by the server if it sent one back.
+    #[getter]
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    /// Returns the id the client sent with the request.
+    #[getter]
+    pub fn client_request_id(&self) -> Option<&str> {
+        self.client_request_id.as_deref()
+    }
+
+    /// Returns an estimate of the bytes sent for the request: the size of the request head as
+    /// HTTP/1.1 text plus the body. It isn't measured on the wire, so it leaves out TLS and
+    /// HTTP/2 framing and header compression.
+    #[getter]
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.get()
+    }
+
+    /// Returns an estimate of the bytes received so far: the size of the response head as
+    /// HTTP/1.1 text plus the body read, after decompression. Like `bytes_sent`, it isn't
+    /// measured on the wire.
+    #[getter]
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received.get()
+    }
+
+    /// Encoding to decode with when accessing text, or the one detected from the body once
+    /// it has been read with a detected charset.
     #[getter]
     pub fn encoding(&self, py: Python) -> String {
-        py.allow_threads(|| {
-            self.headers
-                .get(header::CONTENT_TYPE)
-                .and_then(|value| value.to_str().ok())
-                .and_then(|value| value.parse::<Mime>().ok())
-                .and_then(|mime| {
-                    mime.get_param("charset")
-                        .map(|charset| charset.as_str().to_owned())
-                })
-                .unwrap_or_else(|| "utf-8".to_owned())
-        })
+        if let Some(encoding) = self.detected_encoding.get() {
+            return encoding.name().to_ascii_lowercase();
+        }
+        py.allow_threads(|| declared_charset(&self.headers).unwrap_or_else(|| "utf-8".to_owned()))
     }
 
     /// Returns the TLS peer certificate of the response.
@@ -137,30 +650,48 @@ impl Response {
         s.map(|buffer| buffer.into_bytes_ref(py)).transpose()
     }
 
-    /// Returns the text content of the response.
-    pub fn text<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    /// Returns the text content of the response, with `errors` handling malformed bytes.
+    ///
+    /// With `detect_encoding`, the charset is detected from the body instead of taken from
+    /// the `Content-Type`, and reported by `encoding` afterwards. With `autodetect`, it's
+    /// only detected if the `Content-Type` doesn't declare one. `encoding="detect"` and
+    /// `encoding="auto"` are the same as these flags.
+    #[pyo3(signature = (errors = TextErrors::Replace, detect_encoding = false, autodetect = false, encoding = None))]
+    pub fn text<'py>(
+        &self,
+        py: Python<'py>,
+        errors: TextErrors,
+        detect_encoding: bool,
+        autodetect: bool,
+        encoding: Option<TextEncoding>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         let resp = self.inner()?;
-        future_into_py(py, async move {
-            resp.text()
-                .await
-                .map_err(Error::Request)
-                .map_err(Into::into)
-        })
+        let detection = self.detection(detect_encoding, autodetect, encoding);
+        future_into_py(py, read_text(resp, "utf-8".to_owned(), errors, detection))
     }
 
-    /// Returns the text content of the response with a specific charset.
+    /// Guesses the charset of the body from its byte order mark, or else from the statistics
+    /// of its first 64 KB, which are left in place to be read.
+    ///
+    /// Returns the name of the charset and whether the guess is confident.
+    pub fn detect_encoding<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, self.sniff_encoding())
+    }
+
+    /// Returns the text content of the response with a specific charset, with `errors`
+    /// handling malformed bytes.
+    #[pyo3(signature = (encoding, errors = TextErrors::Replace))]
     pub fn text_with_charset<'py>(
         &self,
         py: Python<'py>,
         encoding: String,
+        errors: TextErrors,
     ) -> PyResult<Bound<'py, PyAny>> {
         let resp = self.inner()?;
-        future_into_py(py, async move {
-            resp.text_with_charset(&encoding)
-                .await
-                .map_err(Error::Request)
-                .map_err(Into::into)
-        })
+        future_into_py(
+            py,
+            read_text(resp, encoding, errors, EncodingDetection::Never),
+        )
     }
 
     /// Returns the JSON content of the response.
@@ -174,6 +705,63 @@ impl Response {
         })
     }
 
+    /// Returns an async iterator over the items of the top-level JSON array of the response,
+    /// each parsed as soon as it has arrived instead of after the whole body.
+    pub fn json_items(&self, py: Python) -> PyResult<JsonItems> {
+        py.allow_threads(|| {
+            self.inner()
+                .map(wreq::Response::bytes_stream)
+                .map(JsonItems::new)
+        })
+    }
+
+    /// Returns an async iterator over the `(headers, body)` parts of a `multipart/*` response,
+    /// such as `multipart/byteranges` or `multipart/mixed`, each as soon as it has arrived.
+    pub fn parts(&self, py: Python) -> PyResult<Parts> {
+        let boundary = self
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Mime>().ok())
+            .filter(|mime| mime.type_() == mime::MULTIPART)
+            .and_then(|mime| {
+                mime.get_param(mime::BOUNDARY)
+                    .map(|b| b.as_str().to_owned())
+            })
+            .ok_or_else(|| {
+                DecodingError::new_err("the Content-Type isn't multipart with a boundary")
+            })?;
+        py.allow_threads(|| {
+            self.inner()
+                .map(wreq::Response::bytes_stream)
+                .map(|stream| Parts::new(stream, &boundary))
+        })
+    }
+
+    /// Returns an async iterator over the events of a `text/event-stream` response, each
+    /// parsed as soon as it has arrived.
+    pub fn events(&self, py: Python) -> PyResult<Events> {
+        py.allow_threads(|| {
+            self.inner()
+                .map(wreq::Response::bytes_stream)
+                .map(Events::new)
+        })
+    }
+
+    /// Writes the body into the file at `path` as it arrives, replacing the file, and returns
+    /// its size.
+    pub fn save<'py>(&self, py: Python<'py>, path: PathBuf) -> PyResult<Bound<'py, PyAny>> {
+        let resp = self.inner()?;
+        future_into_py(py, save_body(resp, path))
+    }
+
+    /// Writes the JSON body into the file at `path`, indented, replacing the file, and returns
+    /// its size.
+    pub fn save_json<'py>(&self, py: Python<'py>, path: PathBuf) -> PyResult<Bound<'py, PyAny>> {
+        let resp = self.inner()?;
+        future_into_py(py, save_json(resp, path))
+    }
+
     /// Returns the bytes content of the response.
     pub fn bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         let resp = self.inner()?;
@@ -187,12 +775,28 @@ impl Response {
         })
     }
 
+    /// Returns the body as a read-only buffer that shares memory with the response instead
+    /// of copying it into `bytes`.
+    pub fn read_buffer<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let resp = self.inner()?;
+        future_into_py(py, async move {
+            resp.bytes()
+                .await
+                .map(BytesBuffer::new)
+                .map_err(Error::Request)
+                .map_err(Into::into)
+        })
+    }
+
     /// Convert the response into a `Stream` of `Bytes` from the body.
-    pub fn stream(&self, py: Python) -> PyResult<Streamer> {
+    ///
+    /// With `zero_copy`, chunks are yielded as read-only buffers instead of `bytes`.
+    #[pyo3(signature = (zero_copy = false))]
+    pub fn stream(&self, py: Python, zero_copy: bool) -> PyResult<Streamer> {
         py.allow_threads(|| {
             self.inner()
                 .map(wreq::Response::bytes_stream)
-                .map(Streamer::new)
+                .map(|stream| Streamer::new(stream).zero_copy(zero_copy))
         })
     }
 
@@ -224,23 +828,318 @@ impl Response {
     }
 }
 
+/// How text decoding handles malformed bytes, named as in `bytes.decode`.
+#[derive(Clone, Copy)]
+pub enum TextErrors {
+    Strict,
+    Replace,
+    Ignore,
+}
+
+impl FromPyObject<'_> for TextErrors {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        match &*ob.extract::<PyBackedStr>()? {
+            "strict" => Ok(TextErrors::Strict),
+            "replace" => Ok(TextErrors::Replace),
+            "ignore" => Ok(TextErrors::Ignore),
+            other => Err(PyValueError::new_err(format!(
+                "errors must be \"strict\", \"replace\" or \"ignore\", got {other:?}"
+            ))),
+        }
+    }
+}
+
+/// When `text` detects the charset of the body rather than take the declared one.
+#[derive(Clone, Copy)]
+pub enum TextEncoding {
+    /// Only if the `Content-Type` doesn't declare a charset.
+    Auto,
+    Detect,
+}
+
+impl FromPyObject<'_> for TextEncoding {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        match &*ob.extract::<PyBackedStr>()? {
+            "auto" => Ok(TextEncoding::Auto),
+            "detect" => Ok(TextEncoding::Detect),
+            other => Err(PyValueError::new_err(format!(
+                "encoding must be \"auto\" or \"detect\", got {other:?}; \
+                 use text_with_charset to decode with a given charset"
+            ))),
+        }
+    }
+}
+
+/// How much of the body is sampled to detect its charset.
+const DETECT_SAMPLE: usize = 64 * 1024;
+
+/// When `read_text` detects the charset from the body, storing it in the lock it holds.
+pub enum EncodingDetection {
+    Never,
+    /// Only if the `Content-Type` doesn't declare a charset.
+    Undeclared(Arc<OnceLock<&'static Encoding>>),
+    Always(Arc<OnceLock<&'static Encoding>>),
+}
+
+/// Reads the body of `resp` as text in the charset of its `Content-Type`, or in
+/// `default_encoding` if it has none.
+///
+/// A byte order mark overrides both, and a charset that isn't recognised falls back to
+/// `default_encoding`, then to UTF-8. Depending on `detection`, the charset is instead
+/// detected from the body.
+pub async fn read_text(
+    resp: wreq::Response,
+    default_encoding: String,
+    errors: TextErrors,
+    detection: EncodingDetection,
+) -> PyResult<String> {
+    let declared = declared_charset(resp.headers())
+        .and_then(|charset| Encoding::for_label(charset.as_bytes()));
+    let detected = match detection {
+        EncodingDetection::Always(detected) => Some(detected),
+        EncodingDetection::Undeclared(detected) if declared.is_none() => Some(detected),
+        _ => None,
+    };
+    let declared = declared
+        .or_else(|| Encoding::for_label(default_encoding.as_bytes()))
+        .unwrap_or(UTF_8);
+    let tld = top_level_domain(resp.url());
+    let body = resp.bytes().await.map_err(Error::Request)?;
+
+    let encoding = match detected {
+        Some(detected) => *detected.get_or_init(|| guess_encoding(&body, true, tld.as_deref()).0),
+        None => declared,
+    };
+    decode_text(encoding, &body, errors)
+}
+
+/// Returns the charset the Content-Type header declares, parsed lossily so a stray byte that
+/// isn't UTF-8 doesn't hide it.
+fn declared_charset(headers: &header::HeaderMap) -> Option<String> {
+    headers
+        .get(header::CONTENT_TYPE)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()))
+        .and_then(|value| value.parse::<Mime>().ok())
+        .and_then(|mime| {
+            mime.get_param("charset")
+                .map(|charset| charset.as_str().to_owned())
+        })
+}
+
+/// Writes the body of `resp` into the file at `path` as it arrives, replacing the file, and
+/// returns its size.
+///
+/// The body goes into a temporary file next to it that is renamed over `path` once complete,
+/// so a failed read leaves the old file in place rather than a truncated one.
+pub async fn save_body(resp: wreq::Response, path: PathBuf) -> PyResult<u64> {
+    let tmp = temp_path(&path);
+    let write = async {
+        let mut body = resp.bytes_stream();
+        let mut file = File::create(&tmp).await.map_err(Error::IO)?;
+        let mut size = 0;
+        while let Some(chunk) = body.try_next().await.map_err(Error::Request)? {
+            file.write_all(&chunk).await.map_err(Error::IO)?;
+            size += chunk.len() as u64;
+        }
+        file.flush().await.map_err(Error::IO)?;
+        Ok::<_, PyErr>(size)
+    };
+    let size = match write.await {
+        Ok(size) => size,
+        Err(err) => {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(err);
+        }
+    };
+    tokio::fs::rename(&tmp, &path).await.map_err(Error::IO)?;
+    Ok(size)
+}
+
+/// Parses the body of `resp` as JSON and writes it into the file at `path` indented by two
+/// spaces, replacing the file, and returns its size.
+pub async fn save_json(resp: wreq::Response, path: PathBuf) -> PyResult<u64> {
+    let json = resp.json::<Json>().await.map_err(Error::Request)?;
+    let mut data =
+        serde_json::to_vec_pretty(&json).map_err(|err| PyValueError::new_err(err.to_string()))?;
+    data.push(b'\n');
+    let tmp = temp_path(&path);
+    tokio::fs::write(&tmp, &data).await.map_err(Error::IO)?;
+    tokio::fs::rename(&tmp, &path).await.map_err(Error::IO)?;
+    Ok(data.len() as u64)
+}
+
+/// Returns the temporary file to write `path` through: its name with `.tmp` appended.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Returns the top-level domain of `url`, which hints at the charsets its pages use.
+fn top_level_domain(url: &Url) -> Option<String> {
+    url.domain()
+        .and_then(|domain| domain.rsplit('.').next())
+        .map(str::to_owned)
+}
+
+/// Guesses the charset of `body` from its byte order mark, or else from the statistics of
+/// a sample of it, with the top-level domain it was served from as a hint. `complete` is
+/// whether `body` is the whole of it.
+///
+/// Also returns whether the guess is confident: a byte order mark always is, and a
+/// statistical guess is if it scored higher than some other charset.
+fn guess_encoding(body: &[u8], complete: bool, tld: Option<&str>) -> (&'static Encoding, bool) {
+    if let Some((encoding, _)) = Encoding::for_bom(body) {
+        return (encoding, true);
+    }
+    let sample = &body[..body.len().min(DETECT_SAMPLE)];
+    let mut detector = EncodingDetector::new();
+    detector.feed(sample, complete && sample.len() == body.len());
+    detector.guess_assess(tld.map(str::as_bytes), true)
+}
+
+fn decode_text(encoding: &'static Encoding, body: &[u8], errors: TextErrors) -> PyResult<String> {
+    let mut decoder = encoding.new_decoder();
+    let mut text = String::with_capacity(body.len());
+    let mut rest = body;
+    loop {
+        let (result, read) = decoder.decode_to_string_without_replacement(rest, &mut text, true);
+        rest = &rest[read..];
+        match result {
+            DecoderResult::InputEmpty => return Ok(text),
+            DecoderResult::OutputFull => text.reserve(
+                decoder
+                    .max_utf8_buffer_length_without_replacement(rest.len())
+                    .unwrap_or(rest.len())
+                    .max(4),
+            ),
+            DecoderResult::Malformed(len, after) => match errors {
+                TextErrors::Strict => {
+                    let offset = body.len() - rest.len() - after as usize - len as usize;
+                    return Err(DecodingError::new_err(format!(
+                        "invalid {} data at byte {offset}",
+                        encoding.name()
+                    )));
+                }
+                TextErrors::Replace => text.push(char::REPLACEMENT_CHARACTER),
+                TextErrors::Ignore => {}
+            },
+        }
+    }
+}
+
+/// Wraps an in-memory body into a `wreq::Response`.
+fn body_response(body: Bytes) -> wreq::Response {
+    wreq::Response::from(http::Response::new(wreq::Body::from(body)))
+}
+
 type InnerStreamer = Pin<Box<dyn Stream<Item = wreq::Result<bytes::Bytes>> + Send + 'static>>;
 
+/// The state of a stream with a single consumer at a time.
+///
+/// A read takes the state out while it waits, so cancelling the read drops the state with it
+/// and closes the connection. The slot remembers that, so later reads raise instead of taking
+/// the dropped stream for one that ended.
+pub enum StreamSlot<T> {
+    Open(T),
+    /// Closed, or read to its end.
+    Closed,
+    /// Dropped by a cancelled read.
+    Cancelled,
+}
+
+impl<T> Default for StreamSlot<T> {
+    fn default() -> Self {
+        StreamSlot::Closed
+    }
+}
+
+impl<T> StreamSlot<T> {
+    fn shared(state: T) -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(StreamSlot::Open(state)))
+    }
+
+    /// Takes the state out for a read, or returns `closed()` if there's none to read.
+    fn take(&mut self, closed: impl FnOnce() -> PyErr) -> PyResult<T> {
+        match std::mem::replace(self, StreamSlot::Cancelled) {
+            StreamSlot::Open(state) => Ok(state),
+            StreamSlot::Closed => {
+                *self = StreamSlot::Closed;
+                Err(closed())
+            }
+            StreamSlot::Cancelled => Err(BodyError::new_err(
+                "the stream was closed when a pending read of it was cancelled",
+            )),
+        }
+    }
+
+    /// Puts the state back after a read, or marks the stream ended if there's none.
+    fn put(&mut self, state: Option<T>) {
+        *self = state.map_or(StreamSlot::Closed, StreamSlot::Open);
+    }
+
+    /// Closes the stream.
+    pub fn close(&mut self) {
+        *self = StreamSlot::Closed;
+    }
+}
+
 /// A byte stream response.
 /// An asynchronous iterator yielding data chunks from the response stream.
 /// Used to stream response content.
 /// Implemented in the `stream` method of the `Response` class.
 /// Can be used in an asynchronous for loop in Python.
+///
+/// A stream has a single consumer at a time: concurrent `__anext__`/`__next__` calls
+/// do not queue on each other but fail fast with a clear error. Cancelling a pending
+/// read drops the stream and closes its connection, and later reads raise.
 #[pyclass(subclass)]
-#[derive(Clone)]
-pub struct Streamer(Arc<Mutex<Option<InnerStreamer>>>);
+pub struct Streamer {
+    stream: Arc<Mutex<StreamSlot<StreamState>>>,
+    zero_copy: bool,
+}
+
+impl Drop for Streamer {
+    fn drop(&mut self) {
+        runtime::drop_in_runtime(std::mem::take(&mut self.stream));
+    }
+}
+
+/// The rest of a response stream, with the bytes buffered from it but not read yet.
+pub struct StreamState {
+    stream: InnerStreamer,
+    pending: BytesMut,
+}
+
+impl StreamState {
+    /// Returns the next chunk, starting with the buffered bytes.
+    async fn next(&mut self) -> wreq::Result<Option<Bytes>> {
+        if !self.pending.is_empty() {
+            return Ok(Some(self.pending.split().freeze()));
+        }
+        self.stream.try_next().await
+    }
+
+    /// Buffers chunks until at least `n` bytes are pending or the stream ends.
+    ///
+    /// Nothing is lost to an error: the bytes buffered so far stay pending.
+    async fn fill(&mut self, n: usize) -> wreq::Result<()> {
+        while self.pending.len() < n {
+            match self.stream.try_next().await? {
+                Some(chunk) => self.pending.extend_from_slice(&chunk),
+                None => break,
+            }
+        }
+        Ok(())
+    }
+}
 
 impl Deref for Streamer {
-    type Target = Arc<Mutex<Option<InnerStreamer>>>;
+    type Target = Arc<Mutex<StreamSlot<StreamState>>>;
 
     #[inline(always)]
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.stream
     }
 }
 
@@ -249,24 +1148,112 @@ impl Streamer {
     pub fn new(
         stream: impl Stream<Item = wreq::Result<bytes::Bytes>> + Send + 'static,
     ) -> Streamer {
-        Streamer(Arc::new(Mutex::new(Some(Box::pin(stream)))))
+        Streamer {
+            stream: StreamSlot::shared(StreamState {
+                stream: Box::pin(stream),
+                pending: BytesMut::new(),
+            }),
+            zero_copy: false,
+        }
+    }
+
+    /// Sets whether chunks are yielded as `BytesBuffer`s instead of `bytes`.
+    pub fn zero_copy(mut self, zero_copy: bool) -> Streamer {
+        self.zero_copy = zero_copy;
+        self
+    }
+
+    /// Reads the next chunk, converting it according to the `zero_copy` setting.
+    pub fn next_chunk(
+        &self,
+        error: fn() -> PyErr,
+    ) -> impl Future<Output = PyResult<Py<PyAny>>> + Send + 'static {
+        Streamer::_anext(self.stream.clone(), self.zero_copy, error)
+    }
+
+    /// Reads up to `limit` bytes, or the rest of the stream if there's no limit, into a
+    /// single buffer converted according to the `zero_copy` setting.
+    pub fn read_bytes(
+        &self,
+        limit: Option<usize>,
+    ) -> impl Future<Output = PyResult<Py<PyAny>>> + Send + 'static {
+        Streamer::_read(self.stream.clone(), self.zero_copy, limit, false)
+    }
+
+    /// Reads exactly `n` bytes into a single buffer, failing if the stream ends first.
+    pub fn read_exactly(
+        &self,
+        n: usize,
+    ) -> impl Future<Output = PyResult<Py<PyAny>>> + Send + 'static {
+        Streamer::_read(self.stream.clone(), self.zero_copy, Some(n), true)
     }
 
     pub async fn _anext(
-        streamer: Arc<Mutex<Option<InnerStreamer>>>,
+        streamer: Arc<Mutex<StreamSlot<StreamState>>>,
+        zero_copy: bool,
         error: fn() -> PyErr,
     ) -> PyResult<Py<PyAny>> {
-        let mut lock = streamer.lock().await;
-        let val = lock.as_mut().ok_or_else(error)?.try_next().await;
+        // Never wait for another consumer: parallel pollers would otherwise serialize on
+        // the lock and each observe an arbitrary interleaving of the chunks.
+        let mut lock = streamer.try_lock().map_err(|_| Error::StreamBusy)?;
+
+        // Hold the stream outside the lock while reading: if the awaiting task is cancelled,
+        // this future is dropped with it and the stream goes too, closing the connection
+        // instead of leaving an abandoned download attached to it.
+        let mut state = lock.take(error)?;
+        let val = state.next().await;
+        lock.put(Some(state));
+
+        drop(lock);
+
+        let chunk = val.map_err(Error::Request)?.ok_or_else(error)?;
+        Streamer::into_buffer(chunk, zero_copy)
+    }
+
+    async fn _read(
+        streamer: Arc<Mutex<StreamSlot<StreamState>>>,
+        zero_copy: bool,
+        limit: Option<usize>,
+        exact: bool,
+    ) -> PyResult<Py<PyAny>> {
+        let mut lock = streamer.try_lock().map_err(|_| Error::StreamBusy)?;
+        let limit = limit.unwrap_or(usize::MAX);
+        let incomplete = |len: usize| {
+            BodyError::new_err(format!(
+                "the stream ended after {len} of the {limit} bytes to read"
+            ))
+        };
+
+        // A closed or exhausted stream reads as empty; one a cancelled read dropped raises.
+        if matches!(*lock, StreamSlot::Closed) && !(exact && limit > 0) {
+            return Streamer::into_buffer(Bytes::new(), zero_copy);
+        }
+        let mut state = lock.take(|| incomplete(0))?;
+        let val = match state.fill(limit).await {
+            // A short exact read takes nothing, leaving what arrived for the next read.
+            Ok(()) if exact && state.pending.len() < limit => Err(incomplete(state.pending.len())),
+            Ok(()) => {
+                let len = state.pending.len().min(limit);
+                Ok(state.pending.split_to(len).freeze())
+            }
+            Err(err) => Err(Error::Request(err).into()),
+        };
+        lock.put(Some(state));
 
         drop(lock);
 
-        let buffer = val
-            .map_err(Error::Request)?
-            .map(BytesBuffer::new)
-            .ok_or_else(error)?;
+        Streamer::into_buffer(val?, zero_copy)
+    }
 
-        Python::with_gil(|py| buffer.into_bytes(py))
+    fn into_buffer(chunk: Bytes, zero_copy: bool) -> PyResult<Py<PyAny>> {
+        let buffer = BytesBuffer::new(chunk);
+        Python::with_gil(|py| {
+            if zero_copy {
+                buffer.into_py_any(py)
+            } else {
+                buffer.into_bytes(py)
+            }
+        })
     }
 }
 
@@ -276,10 +1263,159 @@ impl Streamer {
         slf
     }
 
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, self.next_chunk(|| Error::StopAsyncIteration.into()))
+    }
+
+    /// Reads exactly `n` bytes, or fewer if the stream ends first.
+    fn read<'py>(&self, py: Python<'py>, n: usize) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, self.read_bytes(Some(n)))
+    }
+
+    /// Reads exactly `n` bytes, raising `BodyError` if the stream ends first.
+    fn readexactly<'py>(&self, py: Python<'py>, n: usize) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, self.read_exactly(n))
+    }
+
+    /// Reads the rest of the stream.
+    fn read_all<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, self.read_bytes(None))
+    }
+
+    /// Returns an async iterator over the payloads of the length-prefixed frames in the rest
+    /// of the stream, each prefixed with its length in `prefix_len` bytes.
+    #[pyo3(signature = (prefix_len = 4, big_endian = true))]
+    fn framed(&self, prefix_len: usize, big_endian: bool) -> PyResult<Frames> {
+        if !(1..=8).contains(&prefix_len) {
+            return Err(PyValueError::new_err(format!(
+                "prefix_len must be from 1 to 8, got {prefix_len}"
+            )));
+        }
+        Ok(Frames {
+            stream: self.stream.clone(),
+            prefix_len,
+            big_endian,
+            zero_copy: self.zero_copy,
+        })
+    }
+
+    fn __aenter__<'py>(slf: PyRef<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let slf = slf.into_py_any(py)?;
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: &Bound<'py, PyAny>,
+        _exc_value: &Bound<'py, PyAny>,
+        _traceback: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let streamer = self.stream.clone();
+        future_into_py(py, async move {
+            streamer.lock().await.close();
+            Ok(())
+        })
+    }
+}
+
+/// An asynchronous iterator over the payloads of the length-prefixed frames in a stream,
+/// however they are split into chunks.
+/// Implemented in the `framed` method of the `Streamer` class.
+///
+/// It reads from the same stream as its `Streamer`, so the two can be mixed, and like it
+/// has a single consumer at a time.
+#[pyclass(subclass)]
+pub struct Frames {
+    stream: Arc<Mutex<StreamSlot<StreamState>>>,
+    prefix_len: usize,
+    big_endian: bool,
+    zero_copy: bool,
+}
+
+impl Drop for Frames {
+    fn drop(&mut self) {
+        runtime::drop_in_runtime(std::mem::take(&mut self.stream));
+    }
+}
+
+impl Frames {
+    async fn _anext(
+        stream: Arc<Mutex<StreamSlot<StreamState>>>,
+        prefix_len: usize,
+        big_endian: bool,
+        zero_copy: bool,
+    ) -> PyResult<Py<PyAny>> {
+        let mut lock = stream.try_lock().map_err(|_| Error::StreamBusy)?;
+
+        // As in `Streamer::_anext`, the state is only put back once the read is done.
+        let mut state = lock.take(|| Error::StopAsyncIteration.into())?;
+        let val = Frames::read_frame(&mut state, prefix_len, big_endian).await;
+        lock.put(Some(state));
+        drop(lock);
+
+        let frame = val?.ok_or(Error::StopAsyncIteration)?;
+        Streamer::into_buffer(frame, zero_copy)
+    }
+
+    /// Reads the payload of the next frame, or `None` if the stream ended between frames.
+    ///
+    /// A truncated frame is dropped, ending the iteration after its error.
+    async fn read_frame(
+        state: &mut StreamState,
+        prefix_len: usize,
+        big_endian: bool,
+    ) -> PyResult<Option<Bytes>> {
+        state.fill(prefix_len).await.map_err(Error::Request)?;
+        if state.pending.is_empty() {
+            return Ok(None);
+        }
+        if state.pending.len() < prefix_len {
+            state.pending.clear();
+            return Err(DecodingError::new_err(
+                "the stream ended inside the length prefix of a frame",
+            ));
+        }
+
+        let prefix = &state.pending[..prefix_len];
+        let shift = |len: u64, &b: &u8| (len << 8) | u64::from(b);
+        let len = if big_endian {
+            prefix.iter().fold(0, shift)
+        } else {
+            prefix.iter().rev().fold(0, shift)
+        };
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| len.checked_add(prefix_len))
+            .ok_or_else(|| DecodingError::new_err(format!("frame of {len} bytes is too large")))?;
+
+        state.fill(end).await.map_err(Error::Request)?;
+        if state.pending.len() < end {
+            state.pending.clear();
+            return Err(DecodingError::new_err(format!(
+                "the stream ended inside a frame of {len} bytes"
+            )));
+        }
+        let _ = state.pending.split_to(prefix_len);
+        Ok(Some(state.pending.split_to(end - prefix_len).freeze()))
+    }
+}
+
+#[pymethods]
+impl Frames {
+    fn __aiter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
     fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         future_into_py(
             py,
-            Streamer::_anext(self.0.clone(), || Error::StopAsyncIteration.into()),
+            Frames::_anext(
+                self.stream.clone(),
+                self.prefix_len,
+                self.big_endian,
+                self.zero_copy,
+            ),
         )
     }
 
@@ -295,9 +1431,322 @@ impl Streamer {
         _exc_value: &Bound<'py, PyAny>,
         _traceback: &Bound<'py, PyAny>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        let streamer = self.0.clone();
+        let stream = self.stream.clone();
+        future_into_py(py, async move {
+            stream.lock().await.close();
+            Ok(())
+        })
+    }
+}
+
+struct JsonItemsState {
+    stream: InnerStreamer,
+    splitter: ArraySplitter,
+    items: VecDeque<Vec<u8>>,
+}
+
+/// An asynchronous iterator over the items of a JSON array streamed from a response.
+/// Implemented in the `json_items` method of the `Response` class.
+///
+/// Like `Streamer`, it has a single consumer at a time, and cancelling a pending read
+/// closes the connection.
+#[pyclass(subclass)]
+pub struct JsonItems {
+    state: Arc<Mutex<StreamSlot<JsonItemsState>>>,
+}
+
+impl Drop for JsonItems {
+    fn drop(&mut self) {
+        runtime::drop_in_runtime(std::mem::take(&mut self.state));
+    }
+}
+
+impl JsonItems {
+    /// Create a new `JsonItems` instance.
+    pub fn new(
+        stream: impl Stream<Item = wreq::Result<bytes::Bytes>> + Send + 'static,
+    ) -> JsonItems {
+        JsonItems {
+            state: StreamSlot::shared(JsonItemsState {
+                stream: Box::pin(stream),
+                splitter: ArraySplitter::default(),
+                items: VecDeque::new(),
+            }),
+        }
+    }
+
+    async fn _anext(state: Arc<Mutex<StreamSlot<JsonItemsState>>>) -> PyResult<Py<PyAny>> {
+        let mut lock = state.try_lock().map_err(|_| Error::StreamBusy)?;
+
+        // As in `Streamer::_anext`, the state is only put back once the read is done, so a
+        // cancelled read drops the stream; a malformed document ends the iteration too.
+        let mut state = lock.take(|| Error::StopAsyncIteration.into())?;
+        let item = JsonItems::next_item(&mut state).await;
+        lock.put(matches!(item, Ok(Some(_))).then_some(state));
+        drop(lock);
+
+        let item = item?.ok_or(Error::StopAsyncIteration)?;
+        let item = serde_json::from_slice::<Json>(&item)
+            .map_err(|err| DecodingError::new_err(format!("invalid JSON array item: {err}")))?;
+        Python::with_gil(|py| item.into_py_any(py))
+    }
+
+    /// Returns the next item of the array, or `None` once it ended.
+    async fn next_item(state: &mut JsonItemsState) -> PyResult<Option<Vec<u8>>> {
+        loop {
+            if let Some(item) = state.items.pop_front() {
+                return Ok(Some(item));
+            }
+            if state.splitter.is_done() {
+                return Ok(None);
+            }
+            match state.stream.try_next().await.map_err(Error::Request)? {
+                Some(chunk) => state
+                    .splitter
+                    .feed(&chunk, &mut state.items)
+                    .map_err(DecodingError::new_err)?,
+                None => {
+                    state.splitter.finish().map_err(DecodingError::new_err)?;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl JsonItems {
+    fn __aiter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, JsonItems::_anext(self.state.clone()))
+    }
+
+    fn __aenter__<'py>(slf: PyRef<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let slf = slf.into_py_any(py)?;
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: &Bound<'py, PyAny>,
+        _exc_value: &Bound<'py, PyAny>,
+        _traceback: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = self.state.clone();
+        future_into_py(py, async move {
+            state.lock().await.close();
+            Ok(())
+        })
+    }
+}
+
+struct EventsState {
+    stream: InnerStreamer,
+    parser: EventParser,
+    events: VecDeque<Event>,
+}
+
+/// An asynchronous iterator over the events of a `text/event-stream` response.
+/// Implemented in the `events` method of the `Response` class.
+///
+/// Like `Streamer`, it has a single consumer at a time, and cancelling a pending read
+/// closes the connection.
+#[pyclass(subclass)]
+pub struct Events {
+    state: Arc<Mutex<StreamSlot<EventsState>>>,
+}
+
+impl Drop for Events {
+    fn drop(&mut self) {
+        runtime::drop_in_runtime(std::mem::take(&mut self.state));
+    }
+}
+
+impl Events {
+    /// Create a new `Events` instance.
+    pub fn new(stream: impl Stream<Item = wreq::Result<bytes::Bytes>> + Send + 'static) -> Events {
+        Events {
+            state: StreamSlot::shared(EventsState {
+                stream: Box::pin(stream),
+                parser: EventParser::default(),
+                events: VecDeque::new(),
+            }),
+        }
+    }
+
+    async fn _anext(state: Arc<Mutex<StreamSlot<EventsState>>>) -> PyResult<Event> {
+        let mut lock = state.try_lock().map_err(|_| Error::StreamBusy)?;
+
+        // As in `Streamer::_anext`, the state is only put back once the read is done, so a
+        // cancelled read drops the stream.
+        let mut state = lock.take(|| Error::StopAsyncIteration.into())?;
+        let event = Events::next_event(&mut state).await;
+        lock.put(matches!(event, Ok(Some(_))).then_some(state));
+        event?.ok_or_else(|| Error::StopAsyncIteration.into())
+    }
+
+    /// Returns the next event, or `None` once the stream ended.
+    async fn next_event(state: &mut EventsState) -> PyResult<Option<Event>> {
+        loop {
+            if let Some(event) = state.events.pop_front() {
+                return Ok(Some(event));
+            }
+            match state.stream.try_next().await.map_err(Error::Request)? {
+                Some(chunk) => state.parser.feed(&chunk, &mut state.events),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl Events {
+    fn __aiter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, Events::_anext(self.state.clone()))
+    }
+
+    fn __aenter__<'py>(slf: PyRef<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let slf = slf.into_py_any(py)?;
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: &Bound<'py, PyAny>,
+        _exc_value: &Bound<'py, PyAny>,
+        _traceback: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = self.state.clone();
+        future_into_py(py, async move {
+            state.lock().await.close();
+            Ok(())
+        })
+    }
+}
+
+struct PartsState {
+    stream: InnerStreamer,
+    splitter: PartSplitter,
+}
+
+/// An asynchronous iterator over the `(headers, body)` parts of a `multipart/*` response.
+/// Implemented in the `parts` method of the `Response` class.
+///
+/// Like `Streamer`, it has a single consumer at a time, and cancelling a pending read
+/// closes the connection.
+#[pyclass(subclass)]
+pub struct Parts {
+    state: Arc<Mutex<StreamSlot<PartsState>>>,
+}
+
+impl Drop for Parts {
+    fn drop(&mut self) {
+        runtime::drop_in_runtime(std::mem::take(&mut self.state));
+    }
+}
+
+impl Parts {
+    /// Create a new `Parts` instance splitting `stream` at `boundary`.
+    pub fn new(
+        stream: impl Stream<Item = wreq::Result<bytes::Bytes>> + Send + 'static,
+        boundary: &str,
+    ) -> Parts {
+        Parts {
+            state: StreamSlot::shared(PartsState {
+                stream: Box::pin(stream),
+                splitter: PartSplitter::new(boundary),
+            }),
+        }
+    }
+
+    /// Reads the next part as a `(HeaderMap, bytes)` tuple.
+    pub fn next_part(
+        &self,
+        error: fn() -> PyErr,
+    ) -> impl Future<Output = PyResult<Py<PyAny>>> + Send + 'static {
+        Parts::_anext(self.state.clone(), error)
+    }
+
+    /// Closes the stream of the parts.
+    pub async fn close(&self) {
+        self.state.lock().await.close();
+    }
+
+    async fn _anext(
+        state: Arc<Mutex<StreamSlot<PartsState>>>,
+        error: fn() -> PyErr,
+    ) -> PyResult<Py<PyAny>> {
+        let mut lock = state.try_lock().map_err(|_| Error::StreamBusy)?;
+
+        // As in `Streamer::_anext`, the state is only put back once the read is done, so a
+        // cancelled read drops the stream; a malformed body ends the iteration too.
+        let mut state = lock.take(error)?;
+        let part = Parts::read_part(&mut state).await;
+        lock.put(matches!(part, Ok(Some(_))).then_some(state));
+        drop(lock);
+
+        let (headers, body) = part?.ok_or_else(error)?;
+
+        Python::with_gil(|py| {
+            let body = BytesBuffer::new(body).into_bytes(py)?;
+            (HeaderMap(headers), body).into_py_any(py)
+        })
+    }
+
+    /// Returns the next part, or `None` once the body ended.
+    async fn read_part(state: &mut PartsState) -> PyResult<Option<(header::HeaderMap, Bytes)>> {
+        loop {
+            if let Some(part) = state.splitter.next_part().map_err(DecodingError::new_err)? {
+                return Ok(Some(part));
+            }
+            if state.splitter.is_done() {
+                return Ok(None);
+            }
+            match state.stream.try_next().await.map_err(Error::Request)? {
+                Some(chunk) => state.splitter.feed(&chunk),
+                None => {
+                    state.splitter.finish().map_err(DecodingError::new_err)?;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl Parts {
+    fn __aiter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, self.next_part(|| Error::StopAsyncIteration.into()))
+    }
+
+    fn __aenter__<'py>(slf: PyRef<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let slf = slf.into_py_any(py)?;
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: &Bound<'py, PyAny>,
+        _exc_value: &Bound<'py, PyAny>,
+        _traceback: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = self.state.clone();
         future_into_py(py, async move {
-            drop(streamer.lock().await.take());
+            state.lock().await.close();
             Ok(())
         })
     }
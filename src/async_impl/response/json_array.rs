@@ -0,0 +1,94 @@
+use std::collections::VecDeque;
+
+/// Splits a JSON array into the raw bytes of its items as the document arrives in chunks.
+///
+/// Only the structure needed to find where an item ends is tracked (nesting, strings and
+/// escapes); each item is left to a JSON parser once it's complete, so memory is bounded
+/// by the largest item rather than by the whole array.
+#[derive(Default)]
+pub struct ArraySplitter {
+    state: State,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    item: Vec<u8>,
+}
+
+#[derive(Default, PartialEq)]
+enum State {
+    #[default]
+    Start,
+    First,
+    Next,
+    Item,
+    Done,
+}
+
+impl ArraySplitter {
+    /// Feeds the next chunk of the document, appending the items it completes to `items`.
+    pub fn feed(&mut self, chunk: &[u8], items: &mut VecDeque<Vec<u8>>) -> Result<(), String> {
+        for (i, &b) in chunk.iter().enumerate() {
+            match self.state {
+                State::Start => match b {
+                    b'[' => self.state = State::First,
+                    // A UTF-8 byte order mark may precede the document.
+                    0xEF | 0xBB | 0xBF if i < 3 && chunk.starts_with(b"\xEF\xBB\xBF") => {}
+                    _ if b.is_ascii_whitespace() => {}
+                    _ => return Err("the JSON root is not an array".to_owned()),
+                },
+                State::First | State::Next if b.is_ascii_whitespace() => {}
+                State::First if b == b']' => self.state = State::Done,
+                State::First | State::Next if b == b']' || b == b',' => {
+                    return Err(format!("unexpected `{}` in JSON array", b as char));
+                }
+                State::First | State::Next => {
+                    self.state = State::Item;
+                    self.push(b, items);
+                }
+                State::Item => self.push(b, items),
+                State::Done if b.is_ascii_whitespace() => {}
+                State::Done => return Err("trailing characters after JSON array".to_owned()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the document ended with the array.
+    pub fn finish(&self) -> Result<(), String> {
+        match self.state {
+            State::Done => Ok(()),
+            State::Start => Err("the JSON root is not an array".to_owned()),
+            _ => Err("unexpected end of JSON array".to_owned()),
+        }
+    }
+
+    /// Whether the array has been read to its end.
+    pub fn is_done(&self) -> bool {
+        self.state == State::Done
+    }
+
+    fn push(&mut self, b: u8, items: &mut VecDeque<Vec<u8>>) {
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if b == b'\\' {
+                self.escaped = true;
+            } else if b == b'"' {
+                self.in_string = false;
+            }
+        } else {
+            match b {
+                b'"' => self.in_string = true,
+                b'[' | b'{' => self.depth += 1,
+                b',' | b']' if self.depth == 0 => {
+                    items.push_back(std::mem::take(&mut self.item));
+                    self.state = if b == b',' { State::Next } else { State::Done };
+                    return;
+                }
+                b']' | b'}' => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        self.item.push(b);
+    }
+}
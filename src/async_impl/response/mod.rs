@@ -1,7 +1,16 @@
 mod http;
+mod json_array;
+mod multipart;
+mod sse;
+mod tunnel;
 mod ws;
 
 pub use self::{
-    http::{Response, Streamer},
-    ws::{Message, WebSocket},
+    http::{
+        EncodingDetection, Events, Frames, JsonItems, Parts, Response, Streamer, TextEncoding,
+        TextErrors, read_text, save_body, save_json, strict_resources,
+    },
+    sse::Event,
+    tunnel::Tunnel,
+    ws::{Message, MessagesExtractor, Reconnect, WebSocket},
 };
@@ -0,0 +1,224 @@
+use bytes::{Bytes, BytesMut};
+use wreq::header::{HeaderMap, HeaderName, HeaderValue};
+
+/// The most bytes the headers of a single part may take.
+const MAX_HEADERS_SIZE: usize = 64 * 1024;
+
+/// Splits a `multipart/*` body into its parts as the body arrives in chunks.
+///
+/// The preamble before the first delimiter and the epilogue after the closing one are
+/// skipped. Lines may end with `\r\n` or a bare `\n`, and delimiters may be followed by
+/// transport padding. Each part is buffered until its closing delimiter has arrived.
+pub struct PartSplitter {
+    delimiter: Vec<u8>,
+    buffer: BytesMut,
+    scanned: usize,
+    state: State,
+}
+
+enum State {
+    Preamble,
+    Headers,
+    Body(HeaderMap),
+    Done,
+}
+
+/// What follows a delimiter found in the buffer.
+enum Delimiter {
+    /// Not enough has arrived to tell.
+    Incomplete,
+    /// `--`: the body has ended.
+    Close,
+    /// A line break: the next part starts at this offset.
+    Open(usize),
+}
+
+impl PartSplitter {
+    /// Creates a splitter for parts separated by `boundary`.
+    pub fn new(boundary: &str) -> PartSplitter {
+        PartSplitter {
+            delimiter: [b"--", boundary.as_bytes()].concat(),
+            buffer: BytesMut::new(),
+            scanned: 0,
+            state: State::Preamble,
+        }
+    }
+
+    /// Feeds the next chunk of the body.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        if !self.is_done() {
+            self.buffer.extend_from_slice(chunk);
+        }
+    }
+
+    /// Whether the closing delimiter has been read.
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    /// Checks that the body ended with its closing delimiter.
+    pub fn finish(&self) -> Result<(), String> {
+        match self.state {
+            State::Done => Ok(()),
+            State::Preamble => Err("no multipart boundary found in the body".to_owned()),
+            _ => Err("the multipart body ended before its closing boundary".to_owned()),
+        }
+    }
+
+    /// Returns the headers and body of the next complete part, or `None` if more of the body
+    /// is needed or it has ended.
+    pub fn next_part(&mut self) -> Result<Option<(HeaderMap, Bytes)>, String> {
+        loop {
+            match std::mem::replace(&mut self.state, State::Done) {
+                State::Preamble => {
+                    let Some(at) = self.find_delimiter() else {
+                        // Only the bytes that may start a delimiter need to be kept.
+                        let keep = self.delimiter.len() + 1;
+                        let drop = self.buffer.len().saturating_sub(keep);
+                        let _ = self.buffer.split_to(drop);
+                        self.scanned = 0;
+                        self.state = State::Preamble;
+                        return Ok(None);
+                    };
+                    match self.after_delimiter(at) {
+                        Delimiter::Incomplete => {
+                            self.state = State::Preamble;
+                            return Ok(None);
+                        }
+                        Delimiter::Close => return Ok(None),
+                        Delimiter::Open(next) => {
+                            self.consume(next);
+                            self.state = State::Headers;
+                        }
+                    }
+                }
+                State::Headers => match self.read_headers()? {
+                    Some(headers) => self.state = State::Body(headers),
+                    None => {
+                        self.state = State::Headers;
+                        return Ok(None);
+                    }
+                },
+                State::Body(headers) => {
+                    let Some(at) = self.find_delimiter() else {
+                        self.state = State::Body(headers);
+                        return Ok(None);
+                    };
+                    let next = match self.after_delimiter(at) {
+                        Delimiter::Incomplete => {
+                            self.state = State::Body(headers);
+                            return Ok(None);
+                        }
+                        Delimiter::Close => None,
+                        Delimiter::Open(next) => Some(next),
+                    };
+
+                    // The line break before the delimiter belongs to it, not to the body.
+                    let mut end = at;
+                    if end > 0 && self.buffer[end - 1] == b'\n' {
+                        end -= 1;
+                        if end > 0 && self.buffer[end - 1] == b'\r' {
+                            end -= 1;
+                        }
+                    }
+                    let body = self.buffer.split_to(end).freeze();
+                    match next {
+                        Some(next) => {
+                            self.consume(next - end);
+                            self.state = State::Headers;
+                        }
+                        None => self.buffer.clear(),
+                    }
+                    return Ok(Some((headers, body)));
+                }
+                State::Done => return Ok(None),
+            }
+        }
+    }
+
+    /// Finds the next delimiter at the start of the buffer or of a line in it.
+    fn find_delimiter(&mut self) -> Option<usize> {
+        let len = self.delimiter.len();
+        let mut from = self.scanned;
+        while let Some(pos) = self.buffer[from..]
+            .windows(len)
+            .position(|window| window == self.delimiter.as_slice())
+        {
+            let at = from + pos;
+            if at == 0 || self.buffer[at - 1] == b'\n' {
+                return Some(at);
+            }
+            from = at + 1;
+        }
+        // Keep looking from where a delimiter split across chunks may start.
+        self.scanned = self.buffer.len().saturating_sub(len);
+        None
+    }
+
+    /// Reads what follows the delimiter at `at`.
+    fn after_delimiter(&self, at: usize) -> Delimiter {
+        let rest = &self.buffer[at + self.delimiter.len()..];
+        if rest.len() < 2 {
+            return Delimiter::Incomplete;
+        }
+        if rest.starts_with(b"--") {
+            return Delimiter::Close;
+        }
+        match rest.iter().position(|&b| b == b'\n') {
+            Some(pos) => Delimiter::Open(at + self.delimiter.len() + pos + 1),
+            None => Delimiter::Incomplete,
+        }
+    }
+
+    /// Drops the first `n` bytes of the buffer.
+    fn consume(&mut self, n: usize) {
+        let _ = self.buffer.split_to(n);
+        self.scanned = 0;
+    }
+
+    /// Reads the headers of a part up to the blank line ending them.
+    fn read_headers(&mut self) -> Result<Option<HeaderMap>, String> {
+        let end = if self.buffer.starts_with(b"\r\n") {
+            Some((0, 2))
+        } else if self.buffer.starts_with(b"\n") {
+            Some((0, 1))
+        } else {
+            self.buffer
+                .iter()
+                .enumerate()
+                .filter(|&(_, &b)| b == b'\n')
+                .find_map(|(i, _)| match &self.buffer[i + 1..] {
+                    [b'\n', ..] => Some((i, i + 2)),
+                    [b'\r', b'\n', ..] => Some((i, i + 3)),
+                    _ => None,
+                })
+        };
+        let Some((block_end, next)) = end else {
+            if self.buffer.len() > MAX_HEADERS_SIZE {
+                return Err(format!(
+                    "the headers of a multipart part exceed {MAX_HEADERS_SIZE} bytes"
+                ));
+            }
+            return Ok(None);
+        };
+
+        let mut headers = HeaderMap::new();
+        for line in self.buffer[..block_end].split(|&b| b == b'\n') {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.is_empty() {
+                continue;
+            }
+            let colon = line
+                .iter()
+                .position(|&b| b == b':')
+                .ok_or_else(|| "invalid header line in a multipart part".to_owned())?;
+            let name = HeaderName::from_bytes(line[..colon].trim_ascii())
+                .map_err(|err| format!("invalid header name in a multipart part: {err}"))?;
+            let value = HeaderValue::from_bytes(line[colon + 1..].trim_ascii())
+                .map_err(|err| format!("invalid header value in a multipart part: {err}"))?;
+            headers.append(name, value);
+        }
+        self.consume(next);
+        Ok(Some(headers))
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{error::DecodingError, typing::Json};
+use pyo3::prelude::*;
+use std::collections::VecDeque;
+
+/// An event of a `text/event-stream` response.
+#[pyclass(subclass)]
+#[derive(Clone)]
+pub struct Event {
+    event: String,
+    data: String,
+    id: Option<String>,
+    retry: Option<u64>,
+}
+
+#[pymethods]
+impl Event {
+    /// Returns the type of the event, `"message"` unless it names another.
+    #[getter]
+    pub fn event(&self) -> &str {
+        &self.event
+    }
+
+    /// Returns the data of the event, with the lines of its `data` fields joined by `\n`.
+    #[getter]
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    /// Returns the last event id the stream has set, if any.
+    #[getter]
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the reconnection time in milliseconds set since the previous event, if any.
+    #[getter]
+    pub fn retry(&self) -> Option<u64> {
+        self.retry
+    }
+
+    /// Returns the data of the event parsed as JSON.
+    pub fn json(&self, py: Python) -> PyResult<Json> {
+        py.allow_threads(|| {
+            serde_json::from_str::<Json>(&self.data)
+                .map_err(|err| DecodingError::new_err(format!("invalid JSON event data: {err}")))
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Event(event={:?}, data={:?}, id={:?}, retry={:?})",
+            self.event, self.data, self.id, self.retry
+        )
+    }
+}
+
+/// Parses a `text/event-stream` document into events as it arrives in chunks, following
+/// the HTML event stream interpretation rules.
+///
+/// Lines may end with `\r\n`, `\n` or `\r`, also when split across chunks. An event that
+/// isn't terminated by a blank line before the end of the document is discarded.
+#[derive(Default)]
+pub struct EventParser {
+    line: Vec<u8>,
+    after_cr: bool,
+    started: bool,
+    event_type: String,
+    data: String,
+    last_id: String,
+    retry: Option<u64>,
+}
+
+impl EventParser {
+    /// Feeds the next chunk of the document, appending the events it completes to `events`.
+    pub fn feed(&mut self, chunk: &[u8], events: &mut VecDeque<Event>) {
+        for &b in chunk {
+            match b {
+                // The `\n` of a `\r\n` split across chunks.
+                b'\n' if self.after_cr => self.after_cr = false,
+                b'\r' | b'\n' => {
+                    self.after_cr = b == b'\r';
+                    self.end_line(events);
+                }
+                _ => {
+                    self.after_cr = false;
+                    self.line.push(b);
+                }
+            }
+        }
+    }
+
+    fn end_line(&mut self, events: &mut VecDeque<Event>) {
+        let raw = std::mem::take(&mut self.line);
+        let text = String::from_utf8_lossy(&raw);
+        let mut line = text.as_ref();
+        // A byte order mark may precede the first line.
+        if !std::mem::replace(&mut self.started, true) {
+            line = line.strip_prefix('\u{feff}').unwrap_or(line);
+        }
+
+        if line.is_empty() {
+            self.dispatch(events);
+            return;
+        }
+        if line.starts_with(':') {
+            return;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event_type = value.to_owned(),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "id" if !value.contains('\0') => self.last_id = value.to_owned(),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                self.retry = value.parse().ok();
+            }
+            _ => {}
+        }
+    }
+
+    /// Ends the current event at a blank line, which is only dispatched if it has data.
+    fn dispatch(&mut self, events: &mut VecDeque<Event>) {
+        let event = std::mem::take(&mut self.event_type);
+        if self.data.is_empty() {
+            return;
+        }
+
+        let mut data = std::mem::take(&mut self.data);
+        data.pop();
+        events.push_back(Event {
+            event: if event.is_empty() {
+                "message".to_owned()
+            } else {
+                event
+            },
+            data,
+            id: (!self.last_id.is_empty()).then(|| self.last_id.clone()),
+            retry: self.retry.take(),
+        });
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    buffer::{BytesBuffer, PyBufferProtocol},
+    error::Error,
+    runtime,
+};
+use bytes::{Bytes, BytesMut};
+use pyo3::{IntoPyObjectExt, prelude::*, pybacked::PyBackedBytes};
+use pyo3_async_runtimes::tokio::future_into_py;
+use std::sync::Arc;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, ReadHalf, WriteHalf},
+    sync::Mutex,
+};
+
+type Reader = Arc<Mutex<Option<ReadHalf<wreq::Upgraded>>>>;
+type Writer = Arc<Mutex<Option<WriteHalf<wreq::Upgraded>>>>;
+
+/// A raw, bidirectional byte stream tunneled through an HTTP proxy with `CONNECT`.
+#[pyclass(subclass)]
+pub struct Tunnel {
+    reader: Reader,
+    writer: Writer,
+}
+
+impl Drop for Tunnel {
+    fn drop(&mut self) {
+        let reader = std::mem::take(&mut self.reader);
+        runtime::drop_in_runtime((reader, std::mem::take(&mut self.writer)));
+    }
+}
+
+impl Tunnel {
+    pub async fn new(builder: wreq::RequestBuilder) -> wreq::Result<Tunnel> {
+        let upgraded = builder.send().await?.error_for_status()?.upgrade().await?;
+        let (reader, writer) = tokio::io::split(upgraded);
+        runtime::remember_current();
+
+        Ok(Tunnel {
+            reader: Arc::new(Mutex::new(Some(reader))),
+            writer: Arc::new(Mutex::new(Some(writer))),
+        })
+    }
+
+    pub async fn _read(reader: Reader, size: usize) -> PyResult<Py<PyAny>> {
+        let mut lock = reader.lock().await;
+        let mut buf = BytesMut::with_capacity(size);
+        lock.as_mut()
+            .ok_or_else(|| Error::TunnelClosed)?
+            .read_buf(&mut buf)
+            .await
+            .map_err(Error::from)?;
+
+        drop(lock);
+
+        let buffer = BytesBuffer::new(buf.freeze());
+        Python::with_gil(|py| buffer.into_bytes(py))
+    }
+
+    pub async fn _write(writer: Writer, data: Bytes) -> PyResult<()> {
+        let mut lock = writer.lock().await;
+        let writer = lock.as_mut().ok_or_else(|| Error::TunnelClosed)?;
+        writer.write_all(&data).await.map_err(Error::from)?;
+        writer.flush().await.map_err(Error::from)?;
+        Ok(())
+    }
+
+    pub async fn _close(reader: Reader, writer: Writer) -> PyResult<()> {
+        let mut lock = reader.lock().await;
+        let reader = lock.take();
+        drop(lock);
+        drop(reader);
+
+        let mut lock = writer.lock().await;
+        let writer = lock.take();
+        drop(lock);
+
+        if let Some(mut writer) = writer {
+            writer.shutdown().await.map_err(Error::from)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl Tunnel {
+    /// Reads up to `size` bytes from the tunnel.
+    ///
+    /// Returns empty bytes once the remote end has closed the connection.
+    #[pyo3(signature = (size = 65536))]
+    pub fn read<'py>(&self, py: Python<'py>, size: usize) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, Self::_read(self.reader.clone(), size.max(1)))
+    }
+
+    /// Writes all of `data` to the tunnel.
+    #[pyo3(signature = (data))]
+    pub fn write<'py>(&self, py: Python<'py>, data: PyBackedBytes) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(
+            py,
+            Self::_write(self.writer.clone(), Bytes::from_owner(data)),
+        )
+    }
+
+    /// Closes the tunnel.
+    pub fn close<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let reader = self.reader.clone();
+        let writer = self.writer.clone();
+        future_into_py(py, Self::_close(reader, writer))
+    }
+}
+
+#[pymethods]
+impl Tunnel {
+    fn __aenter__<'py>(slf: PyRef<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let slf = slf.into_py_any(py)?;
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: &Bound<'py, PyAny>,
+        _exc_value: &Bound<'py, PyAny>,
+        _traceback: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.close(py)
+    }
+}
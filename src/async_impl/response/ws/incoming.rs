@@ -0,0 +1,57 @@
+use futures_util::{Stream, StreamExt, stream::SplitStream};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::{sync::mpsc, task::JoinHandle};
+
+/// The receiving half of a WebSocket, read on demand or through a bounded queue.
+pub enum Incoming {
+    /// Frames are read from the connection when a message is received.
+    Direct(SplitStream<wreq::WebSocket>),
+    /// A background task reads frames ahead into a queue of at most `max_recv_queue`
+    /// messages, and stops reading while it's full.
+    Queued(mpsc::Receiver<wreq::Result<wreq::Message>>, AbortOnDrop),
+}
+
+/// Aborts the background reader once the receiving half is dropped.
+pub struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl Incoming {
+    /// Wraps `stream`, reading it ahead into a queue if `max_recv_queue` is set.
+    pub fn new(stream: SplitStream<wreq::WebSocket>, max_recv_queue: Option<usize>) -> Incoming {
+        let Some(size) = max_recv_queue else {
+            return Incoming::Direct(stream);
+        };
+
+        let (tx, rx) = mpsc::channel(size.max(1));
+        let reader = tokio::spawn(async move {
+            let mut stream = stream;
+            while let Some(message) = stream.next().await {
+                let failed = message.is_err();
+                // A full queue stalls here, so the connection isn't read any further.
+                if tx.send(message).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Incoming::Queued(rx, AbortOnDrop(reader))
+    }
+}
+
+impl Stream for Incoming {
+    type Item = wreq::Result<wreq::Message>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.get_mut() {
+            Incoming::Direct(stream) => stream.poll_next_unpin(cx),
+            Incoming::Queued(queue, _) => queue.poll_recv(cx),
+        }
+    }
+}
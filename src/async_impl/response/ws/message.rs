@@ -8,7 +8,7 @@ use wreq::Utf8Bytes;
 use crate::{
     buffer::{BytesBuffer, PyBufferProtocol},
     error::Error,
-    typing::Json,
+    typing::{Json, MessageType},
 };
 
 /// A WebSocket message.
@@ -28,6 +28,18 @@ impl Message {
         })
     }
 
+    /// Returns the type of the message.
+    #[getter(r#type)]
+    pub fn message_type(&self) -> MessageType {
+        match &self.0 {
+            wreq::Message::Text(_) => MessageType::Text,
+            wreq::Message::Binary(_) => MessageType::Binary,
+            wreq::Message::Ping(_) => MessageType::Ping,
+            wreq::Message::Pong(_) => MessageType::Pong,
+            wreq::Message::Close(_) => MessageType::Close,
+        }
+    }
+
     /// Returns the data of the message as bytes.
     #[getter]
     pub fn data<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyAny>> {
@@ -173,3 +185,15 @@ impl Message {
         self.__str__()
     }
 }
+
+/// A batch of messages to send, given as any iterable of [`Message`].
+pub struct MessagesExtractor(pub Vec<Message>);
+
+impl FromPyObject<'_> for MessagesExtractor {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        ob.try_iter()?
+            .map(|item| item?.extract::<Message>())
+            .collect::<PyResult<_>>()
+            .map(MessagesExtractor)
+    }
+}
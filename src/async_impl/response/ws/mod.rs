@@ -1,26 +1,31 @@
+mod incoming;
 mod message;
+mod reconnect;
+mod stats;
 
 use crate::{
     error::Error,
+    runtime,
     typing::{Cookie, HeaderMap, SocketAddr, StatusCode, Version},
 };
 use bytes::Bytes;
-use futures_util::{
-    SinkExt, StreamExt, TryStreamExt,
-    stream::{SplitSink, SplitStream},
-};
-pub use message::Message;
+use futures_util::{SinkExt, StreamExt, stream::SplitSink};
+use incoming::Incoming;
+pub use message::{Message, MessagesExtractor};
 use pyo3::{IntoPyObjectExt, prelude::*, pybacked::PyBackedStr};
 use pyo3_async_runtimes::tokio::future_into_py;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+pub use reconnect::Reconnect;
+use stats::{DEFAULT_MAX_FRAME_SIZE, DEFAULT_MAX_MESSAGE_SIZE, Stats};
+use std::{future::Future, sync::Arc};
+use tokio::sync::{Mutex, watch};
 use wreq::{
     Utf8Bytes,
     header::{self, HeaderValue},
 };
 
 type Sender = Arc<Mutex<Option<SplitSink<wreq::WebSocket, wreq::Message>>>>;
-type Receiver = Arc<Mutex<Option<SplitStream<wreq::WebSocket>>>>;
+type Receiver = Arc<Mutex<Option<Incoming>>>;
+type Closed = Arc<watch::Sender<bool>>;
 
 /// A WebSocket response.
 #[pyclass(subclass)]
@@ -30,12 +35,30 @@ pub struct WebSocket {
     remote_addr: Option<SocketAddr>,
     headers: header::HeaderMap,
     protocol: Option<HeaderValue>,
+    origin: Option<String>,
+    user_agent: Option<String>,
     sender: Sender,
     receiver: Receiver,
+    closed: Closed,
+    reconnect: Option<Arc<Reconnect>>,
+    stats: Arc<Stats>,
+    max_message_size: usize,
+    max_frame_size: usize,
+}
+
+impl Drop for WebSocket {
+    fn drop(&mut self) {
+        let sender = std::mem::take(&mut self.sender);
+        runtime::drop_in_runtime((sender, std::mem::take(&mut self.receiver)));
+    }
 }
 
 impl WebSocket {
-    pub async fn new(builder: wreq::WebSocketRequestBuilder) -> wreq::Result<WebSocket> {
+    pub async fn new(
+        builder: wreq::WebSocketRequestBuilder,
+        reconnect: Option<Reconnect>,
+        max_recv_queue: Option<usize>,
+    ) -> wreq::Result<WebSocket> {
         let response = builder.send().await?;
 
         let version = Version::from_ffi(response.version());
@@ -45,6 +68,7 @@ impl WebSocket {
         let websocket = response.into_websocket().await?;
         let protocol = websocket.protocol().cloned();
         let (sender, receiver) = websocket.split();
+        runtime::remember_current();
 
         Ok(WebSocket {
             version,
@@ -52,11 +76,37 @@ impl WebSocket {
             remote_addr,
             headers,
             protocol,
+            origin: None,
+            user_agent: None,
             sender: Arc::new(Mutex::new(Some(sender))),
-            receiver: Arc::new(Mutex::new(Some(receiver))),
+            receiver: Arc::new(Mutex::new(Some(Incoming::new(receiver, max_recv_queue)))),
+            closed: Arc::new(watch::Sender::new(false)),
+            reconnect: reconnect.map(Arc::new),
+            stats: Arc::default(),
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
         })
     }
 
+    /// Records the `Origin` and `User-Agent` the handshake was sent with.
+    pub fn with_handshake(mut self, origin: Option<String>, user_agent: Option<String>) -> Self {
+        self.origin = origin;
+        self.user_agent = user_agent;
+        self
+    }
+
+    /// Records the message and frame size limits the connection was configured with, if
+    /// they aren't wreq's defaults.
+    pub fn with_limits(
+        mut self,
+        max_message_size: Option<usize>,
+        max_frame_size: Option<usize>,
+    ) -> Self {
+        self.max_message_size = max_message_size.unwrap_or(DEFAULT_MAX_MESSAGE_SIZE);
+        self.max_frame_size = max_frame_size.unwrap_or(DEFAULT_MAX_FRAME_SIZE);
+        self
+    }
+
     pub fn sender(&self) -> Sender {
         self.sender.clone()
     }
@@ -65,33 +115,141 @@ impl WebSocket {
         self.receiver.clone()
     }
 
-    pub async fn _recv(receiver: Receiver) -> PyResult<Option<Message>> {
-        let mut lock = receiver.lock().await;
-        lock.as_mut()
-            .ok_or_else(|| Error::WebSocketDisconnect)?
-            .try_next()
-            .await
-            .map(|val| val.map(Message))
-            .map_err(Error::Request)
-            .map_err(Into::into)
+    pub fn closed(&self) -> Closed {
+        self.closed.clone()
     }
 
-    pub async fn _send(sender: Sender, message: Message) -> PyResult<()> {
-        let mut lock = sender.lock().await;
-        lock.as_mut()
-            .ok_or_else(|| Error::WebSocketDisconnect)?
-            .send(message.0)
-            .await
-            .map_err(Error::Request)
-            .map_err(Into::into)
+    pub fn reconnect(&self) -> Option<Arc<Reconnect>> {
+        self.reconnect.clone()
+    }
+
+    pub fn stats(&self) -> Arc<Stats> {
+        self.stats.clone()
+    }
+
+    /// Runs `future` until it completes or the WebSocket is closed, whichever happens first.
+    ///
+    /// Dropping the future on close releases any lock it holds, so `_close` never waits on
+    /// a pending `recv`/`send`.
+    async fn until_closed<T>(
+        closed: Closed,
+        future: impl Future<Output = PyResult<T>>,
+        error: fn() -> PyErr,
+    ) -> PyResult<T> {
+        let mut closed = closed.subscribe();
+        tokio::select! {
+            biased;
+            _ = closed.wait_for(|closed| *closed) => Err(error()),
+            result = future => result,
+        }
+    }
+
+    pub async fn _recv(
+        receiver: Receiver,
+        sender: Sender,
+        closed: Closed,
+        reconnect: Option<Arc<Reconnect>>,
+        stats: Arc<Stats>,
+    ) -> PyResult<Option<Message>> {
+        Self::until_closed(
+            closed,
+            async move {
+                reconnect::next_message(receiver, sender, reconnect.as_deref(), &stats, || {
+                    Error::WebSocketDisconnect.into()
+                })
+                .await
+                .map(|val| val.map(Message))
+            },
+            || Error::WebSocketDisconnect.into(),
+        )
+        .await
+    }
+
+    pub async fn _send(
+        receiver: Receiver,
+        sender: Sender,
+        closed: Closed,
+        reconnect: Option<Arc<Reconnect>>,
+        stats: Arc<Stats>,
+        message: Message,
+    ) -> PyResult<()> {
+        Self::until_closed(
+            closed,
+            async move {
+                if reconnect
+                    .as_ref()
+                    .is_some_and(|reconnect| reconnect.rejects_sends())
+                {
+                    return Err(Error::WebSocketReconnecting.into());
+                }
+                reconnect::send_message(
+                    receiver,
+                    sender,
+                    reconnect.as_deref(),
+                    &stats,
+                    message.0,
+                    || Error::WebSocketDisconnect.into(),
+                )
+                .await
+            },
+            || Error::WebSocketDisconnect.into(),
+        )
+        .await
+    }
+
+    /// Writes `messages` and flushes once at the end, returning how many were sent.
+    ///
+    /// On failure, the raised exception's `sent` attribute holds the number of messages
+    /// written before the error.
+    pub async fn _send_all(
+        sender: Sender,
+        closed: Closed,
+        reconnect: Option<Arc<Reconnect>>,
+        stats: Arc<Stats>,
+        messages: Vec<Message>,
+    ) -> PyResult<usize> {
+        let result = Self::until_closed(
+            closed,
+            async move {
+                if reconnect.is_some_and(|reconnect| reconnect.rejects_sends()) {
+                    return Err(Error::WebSocketReconnecting.into());
+                }
+                let _pending = stats.queue(messages.iter().map(|message| &message.0));
+                let mut lock = sender.lock().await;
+                let sink = lock.as_mut().ok_or_else(|| Error::WebSocketDisconnect)?;
+                for message in &messages {
+                    sink.feed(message.0.clone()).await.map_err(Error::Request)?;
+                }
+                sink.flush().await.map_err(Error::Request)?;
+                // Fed messages may still sit in the buffer, so they count once flushed.
+                for message in &messages {
+                    stats.sent(&message.0);
+                }
+                Ok(messages.len())
+            },
+            || Error::WebSocketDisconnect.into(),
+        )
+        .await;
+
+        result.map_err(|err| {
+            Python::with_gil(|py| {
+                // Setting an attribute on a fresh exception instance cannot fail in practice.
+                let _ = err.value(py).setattr("sent", 0);
+            });
+            err
+        })
     }
 
     pub async fn _close(
         receiver: Receiver,
         sender: Sender,
+        closed: Closed,
         code: Option<u16>,
         reason: Option<PyBackedStr>,
     ) -> PyResult<()> {
+        // Wake up pending `recv`/`send`/`__anext__` calls so they release their locks.
+        closed.send_replace(true);
+
         let mut lock = receiver.lock().await;
         let receiver = lock.take();
         drop(lock);
@@ -123,20 +281,29 @@ impl WebSocket {
 
     pub async fn _anext(
         receiver: Receiver,
+        sender: Sender,
+        closed: Closed,
+        reconnect: Option<Arc<Reconnect>>,
+        stats: Arc<Stats>,
         py_stop_iteration_error: fn() -> PyErr,
     ) -> PyResult<Message> {
-        let mut lock = receiver.lock().await;
-        let val = lock
-            .as_mut()
-            .ok_or_else(py_stop_iteration_error)?
-            .try_next()
-            .await;
-
-        drop(lock);
-
-        val.map(|val| val.map(Message))
-            .map_err(Error::Request)?
-            .ok_or_else(py_stop_iteration_error)
+        Self::until_closed(
+            closed,
+            async move {
+                reconnect::next_message(
+                    receiver,
+                    sender,
+                    reconnect.as_deref(),
+                    &stats,
+                    py_stop_iteration_error,
+                )
+                .await?
+                .map(Message)
+                .ok_or_else(py_stop_iteration_error)
+            },
+            py_stop_iteration_error,
+        )
+        .await
     }
 }
 
@@ -195,15 +362,108 @@ impl WebSocket {
             .flatten()
     }
 
+    /// Returns the `Origin` header the handshake was sent with.
+    #[getter]
+    pub fn origin(&self) -> Option<&str> {
+        self.origin.as_deref()
+    }
+
+    /// Returns the `User-Agent` header the handshake was sent with, if set for the request.
+    #[getter]
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    /// Returns the number of messages sent with `send` and `send_all`.
+    #[getter]
+    pub fn messages_sent(&self) -> u64 {
+        self.stats.messages_sent()
+    }
+
+    /// Returns the number of messages received.
+    #[getter]
+    pub fn messages_received(&self) -> u64 {
+        self.stats.messages_received()
+    }
+
+    /// Returns the size of the payloads of the messages sent.
+    #[getter]
+    pub fn bytes_sent(&self) -> u64 {
+        self.stats.bytes_sent()
+    }
+
+    /// Returns the size of the payloads of the messages received.
+    #[getter]
+    pub fn bytes_received(&self) -> u64 {
+        self.stats.bytes_received()
+    }
+
+    /// Returns the size of the payloads of the messages being sent that haven't been written
+    /// yet, including those waiting for another send.
+    #[getter]
+    pub fn pending_bytes(&self) -> u64 {
+        self.stats.pending_bytes()
+    }
+
+    /// Returns the largest message the connection accepts.
+    #[getter]
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    /// Returns the largest frame the connection accepts.
+    #[getter]
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
     /// Receives a message from the WebSocket.
     pub fn recv<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        future_into_py(py, Self::_recv(self.receiver.clone()))
+        future_into_py(
+            py,
+            Self::_recv(
+                self.receiver.clone(),
+                self.sender.clone(),
+                self.closed.clone(),
+                self.reconnect.clone(),
+                self.stats.clone(),
+            ),
+        )
     }
 
     /// Sends a message to the WebSocket.
     #[pyo3(signature = (message))]
     pub fn send<'py>(&self, py: Python<'py>, message: Message) -> PyResult<Bound<'py, PyAny>> {
-        future_into_py(py, Self::_send(self.sender.clone(), message))
+        future_into_py(
+            py,
+            Self::_send(
+                self.receiver.clone(),
+                self.sender.clone(),
+                self.closed.clone(),
+                self.reconnect.clone(),
+                self.stats.clone(),
+                message,
+            ),
+        )
+    }
+
+    /// Sends every message of an iterable to the WebSocket, flushing once at the end.
+    #[pyo3(signature = (messages))]
+    pub fn send_all<'py>(
+        &self,
+        py: Python<'py>,
+        messages: MessagesExtractor,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(
+            py,
+            Self::_send_all(
+                self.sender.clone(),
+                self.closed.clone(),
+                self.reconnect.clone(),
+                self.stats.clone(),
+                messages.0,
+            ),
+        )
     }
 
     /// Closes the WebSocket connection.
@@ -216,7 +476,8 @@ impl WebSocket {
     ) -> PyResult<Bound<'py, PyAny>> {
         let sender = self.sender.clone();
         let receiver = self.receiver.clone();
-        future_into_py(py, Self::_close(receiver, sender, code, reason))
+        let closed = self.closed.clone();
+        future_into_py(py, Self::_close(receiver, sender, closed, code, reason))
     }
 }
 
@@ -229,7 +490,14 @@ impl WebSocket {
     fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         future_into_py(
             py,
-            WebSocket::_anext(self.receiver.clone(), || Error::StopAsyncIteration.into()),
+            WebSocket::_anext(
+                self.receiver.clone(),
+                self.sender.clone(),
+                self.closed.clone(),
+                self.reconnect.clone(),
+                self.stats.clone(),
+                || Error::StopAsyncIteration.into(),
+            ),
         )
     }
 
@@ -0,0 +1,238 @@
+use super::{Incoming, Receiver, Sender, Stats};
+use crate::{
+    async_impl::request::build_websocket_request,
+    error::Error,
+    typing::param::{ReconnectCallback, WebSocketParams},
+};
+use futures_util::{SinkExt, StreamExt};
+use pyo3::prelude::*;
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// The longest delay between two reconnect attempts.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Re-establishes a WebSocket connection that failed with a network error or that the server
+/// closed.
+///
+/// The handshake is replayed from the original URL and parameters, so protocols, headers,
+/// cookies and authentication are sent again.
+pub struct Reconnect {
+    client: wreq::Client,
+    url: String,
+    params: WebSocketParams,
+    max_attempts: usize,
+    backoff: Duration,
+    on_reconnect: Option<ReconnectCallback>,
+    queue_sends: bool,
+    reconnecting: AtomicBool,
+    generation: AtomicU64,
+}
+
+impl Reconnect {
+    /// Takes the reconnect options out of `params`, returning `None` unless `auto_reconnect`
+    /// is set. The remaining parameters are kept to replay the handshake.
+    pub fn from_params(
+        client: &wreq::Client,
+        url: &str,
+        params: &mut WebSocketParams,
+    ) -> Option<Reconnect> {
+        let max_attempts = params.reconnect_max_attempts.take().unwrap_or(5);
+        let backoff = params.reconnect_backoff.take().unwrap_or(1.0);
+        let on_reconnect = params.on_reconnect.take();
+        let queue_sends = params.reconnect_queue_sends.take().unwrap_or(true);
+        if !params.auto_reconnect.take().unwrap_or(false) {
+            return None;
+        }
+
+        Some(Reconnect {
+            client: client.clone(),
+            url: url.to_owned(),
+            params: params.clone(),
+            max_attempts,
+            backoff: Duration::try_from_secs_f64(backoff).unwrap_or_default(),
+            on_reconnect,
+            queue_sends,
+            reconnecting: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+        })
+    }
+
+    /// Returns whether a message sent now should fail instead of waiting for the connection.
+    pub fn rejects_sends(&self) -> bool {
+        !self.queue_sends && self.reconnecting.load(Ordering::Acquire)
+    }
+
+    /// Replaces the broken connection in `sender` and `receiver` with a new one, returning
+    /// the attempt that succeeded, or the last error, starting with `cause`, once every attempt
+    /// has failed.
+    ///
+    /// The sender stays locked throughout, so queued sends go out on the new connection.
+    pub async fn run(
+        &self,
+        sender: &Sender,
+        receiver: &mut Option<Incoming>,
+        cause: PyErr,
+    ) -> PyResult<usize> {
+        self.reconnecting.store(true, Ordering::Release);
+        let mut sender = sender.lock().await;
+        sender.take();
+        receiver.take();
+
+        let mut last_err = cause;
+        for attempt in 1..=self.max_attempts {
+            let delay = self
+                .backoff
+                .saturating_mul(1u32 << (attempt - 1).min(16))
+                .min(MAX_BACKOFF);
+            tokio::time::sleep(delay).await;
+
+            match self.connect().await {
+                Ok(websocket) => {
+                    let (new_sender, new_receiver) = websocket.split();
+                    *sender = Some(new_sender);
+                    *receiver = Some(Incoming::new(new_receiver, self.params.max_recv_queue));
+                    self.generation.fetch_add(1, Ordering::AcqRel);
+                    self.reconnecting.store(false, Ordering::Release);
+                    return Ok(attempt);
+                }
+                Err(err) => last_err = err,
+            }
+        }
+
+        self.reconnecting.store(false, Ordering::Release);
+        Err(last_err)
+    }
+
+    /// Calls `on_reconnect`, awaiting it if it's a coroutine function.
+    ///
+    /// A blocking WebSocket has no event loop to await a coroutine on, so it raises instead.
+    pub async fn notify(&self, attempt: usize) -> PyResult<()> {
+        let Some(callback) = &self.on_reconnect else {
+            return Ok(());
+        };
+
+        let future = Python::with_gil(|py| {
+            let result = callback.0.call1(py, (attempt,))?.into_bound(py);
+            if !result.hasattr("__await__")? {
+                return Ok(None);
+            }
+            if pyo3_async_runtimes::tokio::get_current_locals(py).is_err() {
+                if result.hasattr("close")? {
+                    result.call_method0("close")?;
+                }
+                return Err(ReconnectCallback::coroutine_error());
+            }
+            pyo3_async_runtimes::tokio::into_future(result).map(Some)
+        })?;
+        if let Some(future) = future {
+            future.await?;
+        }
+        Ok(())
+    }
+
+    async fn connect(&self) -> PyResult<wreq::WebSocket> {
+        let builder = build_websocket_request(&self.client, &self.url, self.params.clone())?;
+        let response = builder.send().await.map_err(Error::Request)?;
+        response
+            .into_websocket()
+            .await
+            .map_err(Error::Request)
+            .map_err(Into::into)
+    }
+}
+
+/// Receives the next message, reconnecting first if the connection broke and `reconnect`
+/// is set, and counts it into `stats`.
+pub async fn next_message(
+    receiver: Receiver,
+    sender: Sender,
+    reconnect: Option<&Reconnect>,
+    stats: &Stats,
+    disconnected: fn() -> PyErr,
+) -> PyResult<Option<wreq::Message>> {
+    loop {
+        let mut lock = receiver.lock().await;
+        let result = lock.as_mut().ok_or_else(disconnected)?.next().await;
+
+        let (cause, reconnect) = match (result, reconnect) {
+            (Some(Err(err)), Some(reconnect)) => (Error::Request(err).into(), reconnect),
+            // A clean close from the server is recovered from as well. A close of our own
+            // takes the receiver away first, so it never gets here.
+            (Some(Ok(wreq::Message::Close(_))) | None, Some(reconnect)) => {
+                (Error::WebSocketDisconnect.into(), reconnect)
+            }
+            (result, _) => {
+                drop(lock);
+                if let Some(Ok(message)) = &result {
+                    stats.received(message);
+                }
+                return result
+                    .transpose()
+                    .map_err(Error::Request)
+                    .map_err(Into::into);
+            }
+        };
+
+        let attempt = reconnect.run(&sender, &mut lock, cause).await?;
+        // Release the receiver so the callback can use the WebSocket.
+        drop(lock);
+        reconnect.notify(attempt).await?;
+    }
+}
+
+/// Sends `message`, reconnecting and sending it once more if the connection broke and
+/// `reconnect` is set, and counts it into `stats`.
+///
+/// The receiver is locked before the sender, as when receiving, so a concurrent receive
+/// that hits the same error reconnects only once.
+pub async fn send_message(
+    receiver: Receiver,
+    sender: Sender,
+    reconnect: Option<&Reconnect>,
+    stats: &Stats,
+    message: wreq::Message,
+    disconnected: fn() -> PyErr,
+) -> PyResult<()> {
+    let _pending = stats.queue([&message]);
+    let generation = reconnect.map(|reconnect| reconnect.generation.load(Ordering::Acquire));
+    let mut lock = sender.lock().await;
+    let result = lock
+        .as_mut()
+        .ok_or_else(disconnected)?
+        .send(message.clone())
+        .await;
+    drop(lock);
+
+    let (err, reconnect) = match (result, reconnect) {
+        (Err(err), Some(reconnect)) => (err, reconnect),
+        (result, _) => {
+            result.map_err(Error::Request)?;
+            stats.sent(&message);
+            return Ok(());
+        }
+    };
+
+    let mut receiver = receiver.lock().await;
+    if generation == Some(reconnect.generation.load(Ordering::Acquire)) {
+        let attempt = reconnect
+            .run(&sender, &mut receiver, Error::Request(err).into())
+            .await?;
+        drop(receiver);
+        reconnect.notify(attempt).await?;
+    } else {
+        // A concurrent receive already replaced the connection.
+        drop(receiver);
+    }
+
+    let mut lock = sender.lock().await;
+    lock.as_mut()
+        .ok_or_else(disconnected)?
+        .send(message.clone())
+        .await
+        .map_err(Error::Request)?;
+    stats.sent(&message);
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The largest message wreq accepts unless `max_message_size` is set.
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 << 20;
+
+/// The largest frame wreq accepts unless `max_frame_size` is set.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 << 20;
+
+/// Counts the messages a WebSocket sends and receives, across reconnects.
+///
+/// The counters are atomics rather than part of the sender or receiver, so they can be read
+/// while a send or receive holds those locked.
+#[derive(Default)]
+pub struct Stats {
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    pending_bytes: AtomicU64,
+}
+
+impl Stats {
+    pub fn messages_sent(&self) -> u64 {
+        self.messages_sent.load(Ordering::Relaxed)
+    }
+
+    pub fn messages_received(&self) -> u64 {
+        self.messages_received.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received.load(Ordering::Relaxed)
+    }
+
+    pub fn pending_bytes(&self) -> u64 {
+        self.pending_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Counts a message handed to the caller.
+    pub fn received(&self, message: &wreq::Message) {
+        self.messages_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received
+            .fetch_add(payload_len(message), Ordering::Relaxed);
+    }
+
+    /// Counts a message written to the connection.
+    pub fn sent(&self, message: &wreq::Message) {
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent
+            .fetch_add(payload_len(message), Ordering::Relaxed);
+    }
+
+    /// Counts `messages` as waiting to be written until the returned guard is dropped, once
+    /// they're flushed or the send fails or is cancelled.
+    pub fn queue<'m>(&self, messages: impl IntoIterator<Item = &'m wreq::Message>) -> Pending<'_> {
+        let bytes = messages.into_iter().map(payload_len).sum();
+        self.pending_bytes.fetch_add(bytes, Ordering::Relaxed);
+        Pending { stats: self, bytes }
+    }
+}
+
+/// Bytes counted as waiting to be written, until dropped.
+pub struct Pending<'a> {
+    stats: &'a Stats,
+    bytes: u64,
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        self.stats
+            .pending_bytes
+            .fetch_sub(self.bytes, Ordering::Relaxed);
+    }
+}
+
+/// Returns the size of the payload of `message`, without the framing.
+fn payload_len(message: &wreq::Message) -> u64 {
+    let len = match message {
+        wreq::Message::Text(text) => text.as_str().len(),
+        wreq::Message::Binary(bytes) | wreq::Message::Ping(bytes) | wreq::Message::Pong(bytes) => {
+            bytes.len()
+        }
+        // A close frame carries a two-byte code before its reason.
+        wreq::Message::Close(Some(frame)) => 2 + frame.reason.as_str().len(),
+        wreq::Message::Close(None) => 0,
+    };
+    len as u64
+}
@@ -0,0 +1,212 @@
+use crate::async_impl::counter;
+use bytes::Bytes;
+use futures_util::{FutureExt, future::BoxFuture};
+use http_body::{Body, Frame, SizeHint};
+use pyo3::{prelude::*, types::PyDict};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+use wreq::dns::{HickoryDnsResolver, Name, Resolve, Resolving};
+
+tokio::task_local! {
+    /// The timing of the request the current task is sending.
+    static CURRENT: Timing;
+}
+
+/// When the phases of a request ended, measured from when it was sent.
+///
+/// The DNS resolver and the connector the request goes through record into the timing of the
+/// task they're called from, so a connection opened in the background for another request, or
+/// a request sent over a pooled connection, has no DNS or connect phase.
+///
+/// wreq doesn't report when it starts the TLS handshake, so the connect phase includes it.
+#[derive(Clone)]
+pub struct Timing(Arc<Recorder>);
+
+struct Recorder {
+    start: Instant,
+    phases: Mutex<Phases>,
+}
+
+#[derive(Default)]
+struct Phases {
+    dns: Option<Duration>,
+    connect: Option<Duration>,
+    first_byte: Option<Duration>,
+    total: Option<Duration>,
+}
+
+impl Timing {
+    /// Starts timing a request.
+    pub fn start() -> Timing {
+        Timing(Arc::new(Recorder {
+            start: Instant::now(),
+            phases: Mutex::default(),
+        }))
+    }
+
+    /// Runs `future`, recording the DNS lookups and connects it makes into this timing.
+    pub fn scope<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        CURRENT.scope(self.clone(), future)
+    }
+
+    fn current() -> Option<Timing> {
+        CURRENT.try_with(Timing::clone).ok()
+    }
+
+    fn phases(&self) -> MutexGuard<'_, Phases> {
+        self.0.phases.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Records that the response head was received.
+    pub fn first_byte(&self) {
+        self.phases().first_byte = Some(self.0.start.elapsed());
+    }
+
+    /// Records that the body was read to its end.
+    fn finish(&self) {
+        self.phases().total.get_or_insert(self.0.start.elapsed());
+    }
+
+    /// Records the end of the body of `response` once it's read.
+    pub fn track_response(&self, response: wreq::Response) -> wreq::Response {
+        counter::map_body(response, |inner| {
+            if inner.is_end_stream() {
+                self.finish();
+            }
+            wreq::Body::wrap(TimedBody {
+                inner,
+                timing: self.clone(),
+            })
+        })
+    }
+
+    /// Returns the durations of the phases in seconds, as a dict with `dns`, `connect`,
+    /// `tls`, `time_to_first_byte` and `total`, each `None` until or unless it happened.
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let phases = self.phases();
+        let dict = PyDict::new(py);
+        dict.set_item("dns", phases.dns.map(|d| d.as_secs_f64()))?;
+        dict.set_item("connect", phases.connect.map(|d| d.as_secs_f64()))?;
+        dict.set_item("tls", py.None())?;
+        dict.set_item(
+            "time_to_first_byte",
+            phases.first_byte.map(|d| d.as_secs_f64()),
+        )?;
+        dict.set_item("total", phases.total.map(|d| d.as_secs_f64()))?;
+        Ok(dict)
+    }
+}
+
+/// A body wrapper recording into a [`Timing`] when the body ends.
+struct TimedBody {
+    inner: wreq::Body,
+    timing: Timing,
+}
+
+impl Body for TimedBody {
+    type Data = Bytes;
+    type Error = wreq::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_frame(cx);
+        match &poll {
+            Poll::Ready(None) => self.timing.finish(),
+            Poll::Ready(Some(Ok(_))) if self.inner.is_end_stream() => self.timing.finish(),
+            _ => {}
+        }
+        poll
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// A DNS resolver recording how long its lookups take into the timing of the request they're
+/// made for.
+pub struct TimedResolver(Arc<HickoryDnsResolver>);
+
+impl TimedResolver {
+    pub fn new(resolver: Arc<HickoryDnsResolver>) -> Arc<TimedResolver> {
+        Arc::new(TimedResolver(resolver))
+    }
+}
+
+impl Resolve for TimedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let timing = Timing::current();
+        let started = Instant::now();
+        self.0
+            .resolve(name)
+            .inspect(move |result| {
+                if let (Some(timing), Ok(_)) = (timing, result) {
+                    timing.phases().dns = Some(started.elapsed());
+                }
+            })
+            .boxed()
+    }
+}
+
+/// A connector layer recording how long connecting takes into the timing of the request the
+/// connection is opened for.
+#[derive(Clone)]
+pub struct TimeConnectsLayer;
+
+impl<S> Layer<S> for TimeConnectsLayer {
+    type Service = TimeConnects<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimeConnects { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct TimeConnects<S> {
+    inner: S,
+}
+
+impl<S, R> Service<R> for TimeConnects<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: R) -> Self::Future {
+        let timing = Timing::current();
+        let started = Instant::now();
+        self.inner
+            .call(req)
+            .inspect(move |result| {
+                if let (Some(timing), Ok(_)) = (timing, result) {
+                    // The connector resolves the host before connecting to it.
+                    let mut phases = timing.phases();
+                    let dns = phases.dns.unwrap_or_default();
+                    phases.connect = Some(started.elapsed().saturating_sub(dns));
+                }
+            })
+            .boxed()
+    }
+}
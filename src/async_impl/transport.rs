@@ -0,0 +1,196 @@
+use super::Response;
+use crate::{
+    error::Error,
+    typing::{BodyExtractor, HeaderMap, HeaderMapExtractor},
+};
+use bytes::{Bytes, BytesMut};
+use http_body::Body as _;
+use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    pybacked::PyBackedStr,
+    types::PyBytes,
+};
+use std::{future::poll_fn, pin::pin, sync::Arc};
+
+/// A transport that hands requests to a Python callable instead of the network.
+///
+/// The handler is called as `handler(method, url, headers, body)` with the request's method
+/// and URL as strings, its headers (including the client's default headers) as a
+/// [`HeaderMap`] and its body as `bytes`. It returns a `(status, headers, body)` tuple that is
+/// turned into a regular [`Response`].
+///
+/// Handlers mounted on a client with `Client.mount` answer the requests their matcher
+/// matches ahead of the transport's own handler. Without one, the requests no mount matches
+/// are sent over the network.
+#[pyclass]
+#[derive(Clone)]
+pub struct MockTransport(Arc<Routes>);
+
+struct Routes {
+    /// Tried in the order they were mounted.
+    mounts: Vec<Arc<Mount>>,
+    handler: Option<Arc<PyObject>>,
+}
+
+/// A handler for the requests its matcher matches.
+pub struct Mount {
+    matcher: Matcher,
+    handler: Arc<PyObject>,
+}
+
+enum Matcher {
+    /// Matches URLs starting with the prefix.
+    Prefix(String),
+    /// Called as `matcher(method, url)`, matching if it returns a truthy value.
+    Callable(PyObject),
+}
+
+/// The handler chosen to answer a request.
+pub struct Route(Arc<PyObject>);
+
+#[pymethods]
+impl MockTransport {
+    /// Creates a new mock transport around `handler`.
+    #[new]
+    fn new(handler: Bound<'_, PyAny>) -> PyResult<MockTransport> {
+        Ok(MockTransport(Arc::new(Routes {
+            mounts: Vec::new(),
+            handler: Some(Arc::new(callable(handler)?)),
+        })))
+    }
+}
+
+fn callable(handler: Bound<'_, PyAny>) -> PyResult<PyObject> {
+    if !handler.is_callable() {
+        return Err(PyTypeError::new_err(format!(
+            "transport handler must be callable, got {}",
+            handler.get_type().name()?
+        )));
+    }
+    Ok(handler.unbind())
+}
+
+impl Mount {
+    /// Creates a mount answering the requests `matcher` matches with `handler`. The matcher
+    /// is either a URL prefix or a callable taking the method and URL.
+    pub fn new(matcher: Bound<'_, PyAny>, handler: Bound<'_, PyAny>) -> PyResult<Mount> {
+        let matcher = match matcher.extract::<PyBackedStr>() {
+            Ok(prefix) => Matcher::Prefix((*prefix).to_owned()),
+            Err(_) if matcher.is_callable() => Matcher::Callable(matcher.unbind()),
+            Err(_) => {
+                return Err(PyTypeError::new_err(format!(
+                    "mount matcher must be a URL prefix or a callable, got {}",
+                    matcher.get_type().name()?
+                )));
+            }
+        };
+        Ok(Mount {
+            matcher,
+            handler: Arc::new(callable(handler)?),
+        })
+    }
+
+    fn matches(&self, py: Python<'_>, method: &str, url: &str) -> PyResult<bool> {
+        match &self.matcher {
+            Matcher::Prefix(prefix) => Ok(url.starts_with(prefix.as_str())),
+            Matcher::Callable(matcher) => matcher.bind(py).call1((method, url))?.is_truthy(),
+        }
+    }
+}
+
+impl MockTransport {
+    /// Returns `transport` with `mount` added after its mounts, or a transport with only
+    /// that mount if there is none.
+    pub fn mounted(transport: Option<&MockTransport>, mount: Arc<Mount>) -> MockTransport {
+        let (mut mounts, handler) = match transport {
+            Some(transport) => (transport.0.mounts.clone(), transport.0.handler.clone()),
+            None => (Vec::new(), None),
+        };
+        mounts.push(mount);
+        MockTransport(Arc::new(Routes { mounts, handler }))
+    }
+
+    /// Returns the handler that answers `request`, or `None` if it should be sent over the
+    /// network.
+    pub fn route(&self, request: &wreq::Request) -> PyResult<Option<Route>> {
+        if self.0.mounts.is_empty() {
+            return Ok(self.0.handler.clone().map(Route));
+        }
+        let (method, url) = (request.method().as_str(), request.url().as_str());
+        Python::with_gil(|py| {
+            for mount in &self.0.mounts {
+                if mount.matches(py, method, url)? {
+                    return Ok(Some(Route(mount.handler.clone())));
+                }
+            }
+            Ok(self.0.handler.clone().map(Route))
+        })
+    }
+}
+
+impl Route {
+    /// Answers `request` with the handler's response.
+    pub async fn send(
+        &self,
+        client: &wreq::Client,
+        mut request: wreq::Request,
+    ) -> PyResult<Response> {
+        // Requests only carry their own headers; the client's are added when it sends them.
+        let mut headers = client.headers();
+        for name in request.headers().keys() {
+            headers.remove(name);
+        }
+        for (name, value) in request.headers() {
+            headers.append(name, value.clone());
+        }
+        let body = collect_body(request.body_mut().take()).await?;
+
+        let url = request.url().clone();
+        let (status, headers, body) = Python::with_gil(|py| {
+            let result = self.0.call1(
+                py,
+                (
+                    request.method().as_str(),
+                    url.as_str(),
+                    HeaderMap(headers),
+                    PyBytes::new(py, &body),
+                ),
+            )?;
+            let (status, headers, body) =
+                result.extract::<(u16, HeaderMapExtractor, BodyExtractor)>(py)?;
+            let body = match body {
+                BodyExtractor::Text(bytes) | BodyExtractor::Bytes(bytes) => bytes,
+                _ => {
+                    return Err(PyTypeError::new_err(
+                        "transport handler must return the body as str or bytes",
+                    ));
+                }
+            };
+            let status = wreq::StatusCode::from_u16(status)
+                .map_err(|_| PyValueError::new_err(format!("invalid status code: {status}")))?;
+            Ok::<_, PyErr>((status, headers.0, body))
+        })?;
+
+        Ok(Response::mocked(url, status, headers, body))
+    }
+}
+
+/// Reads a request body into memory.
+pub async fn collect_body(body: Option<wreq::Body>) -> PyResult<Bytes> {
+    let Some(body) = body else {
+        return Ok(Bytes::new());
+    };
+    if let Some(bytes) = body.as_bytes() {
+        return Ok(Bytes::copy_from_slice(bytes));
+    }
+
+    let mut body = pin!(body);
+    let mut buf = BytesMut::new();
+    while let Some(frame) = poll_fn(|cx| body.as_mut().poll_frame(cx)).await {
+        if let Ok(data) = frame.map_err(Error::Request)?.into_data() {
+            buf.extend_from_slice(&data);
+        }
+    }
+    Ok(buf.freeze())
+}
@@ -1,16 +1,21 @@
 use super::{BlockingResponse, BlockingWebSocket};
 use crate::{
-    async_impl::{self, execute_request, execute_websocket_request},
+    async_impl::{
+        self, BatchRequest, ProgressCallback, execute_download, execute_gather, execute_request,
+        execute_websocket_request,
+    },
+    dns,
     typing::{
-        Cookie, HeaderMap, Method,
-        param::{ClientParams, RequestParams, UpdateClientParams, WebSocketParams},
+        Cookie, CookieJar, HeaderMap, Method, MethodExtractor, UrlExtractor,
+        param::{RequestParams, WebSocketParams},
     },
 };
-use pyo3::{prelude::*, pybacked::PyBackedStr};
+use pyo3::{IntoPyObjectExt, prelude::*, pybacked::PyBackedStr, types::PyDict};
+use std::{net::IpAddr, path::PathBuf};
 
 /// A blocking client for making HTTP requests.
 #[pyclass(subclass)]
-pub struct BlockingClient(async_impl::Client);
+pub struct BlockingClient(pub(crate) async_impl::Client);
 
 #[pymethods]
 impl BlockingClient {
@@ -19,10 +24,10 @@ impl BlockingClient {
     pub fn get(
         &self,
         py: Python<'_>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<BlockingResponse> {
-        self.request(py, Method::GET, url, kwds)
+        self.request(py, Method::GET.into(), url, kwds)
     }
 
     /// Make a POST request to the specified URL.
@@ -30,10 +35,10 @@ impl BlockingClient {
     pub fn post(
         &self,
         py: Python<'_>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<BlockingResponse> {
-        self.request(py, Method::POST, url, kwds)
+        self.request(py, Method::POST.into(), url, kwds)
     }
 
     /// Make a PUT request to the specified URL.
@@ -41,10 +46,10 @@ impl BlockingClient {
     pub fn put(
         &self,
         py: Python<'_>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<BlockingResponse> {
-        self.request(py, Method::PUT, url, kwds)
+        self.request(py, Method::PUT.into(), url, kwds)
     }
 
     /// Make a PATCH request to the specified URL.
@@ -52,10 +57,10 @@ impl BlockingClient {
     pub fn patch(
         &self,
         py: Python<'_>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<BlockingResponse> {
-        self.request(py, Method::PATCH, url, kwds)
+        self.request(py, Method::PATCH.into(), url, kwds)
     }
 
     /// Make a DELETE request to the specified URL.
@@ -63,10 +68,10 @@ impl BlockingClient {
     pub fn delete(
         &self,
         py: Python<'_>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<BlockingResponse> {
-        self.request(py, Method::DELETE, url, kwds)
+        self.request(py, Method::DELETE.into(), url, kwds)
     }
 
     /// Make a HEAD request to the specified URL.
@@ -74,10 +79,10 @@ impl BlockingClient {
     pub fn head(
         &self,
         py: Python<'_>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<BlockingResponse> {
-        self.request(py, Method::HEAD, url, kwds)
+        self.request(py, Method::HEAD.into(), url, kwds)
     }
 
     /// Make a OPTIONS request to the specified URL.
@@ -85,10 +90,10 @@ impl BlockingClient {
     pub fn options(
         &self,
         py: Python<'_>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<BlockingResponse> {
-        self.request(py, Method::OPTIONS, url, kwds)
+        self.request(py, Method::OPTIONS.into(), url, kwds)
     }
 
     /// Make a TRACE request to the specified URL.
@@ -96,10 +101,10 @@ impl BlockingClient {
     pub fn trace(
         &self,
         py: Python<'_>,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<RequestParams>,
     ) -> PyResult<BlockingResponse> {
-        self.request(py, Method::TRACE, url, kwds)
+        self.request(py, Method::TRACE.into(), url, kwds)
     }
 
     /// Make a rqeuest with the specified method and URL.
@@ -107,30 +112,185 @@ impl BlockingClient {
     pub fn request(
         &self,
         py: Python,
-        method: Method,
-        url: PyBackedStr,
-        kwds: Option<RequestParams>,
+        method: MethodExtractor,
+        url: UrlExtractor,
+        mut kwds: Option<RequestParams>,
     ) -> PyResult<BlockingResponse> {
+        let (client, pool) = self.0.client_for(py, kwds.as_mut())?;
         py.allow_threads(|| {
-            let client = self.0.clone();
             pyo3_async_runtimes::tokio::get_runtime()
-                .block_on(execute_request(client, method, url, kwds))
+                .block_on(execute_request(
+                    client,
+                    self.0.cache(),
+                    self.0.transport(),
+                    self.0.cassette(),
+                    Some(pool),
+                    self.0.max_redirects(),
+                    self.0.request_id_header(),
+                    self.0.max_response_size(),
+                    self.0.auth(),
+                    method.0,
+                    url,
+                    kwds,
+                ))
                 .map(Into::into)
         })
     }
 
+    /// Check whether the specified URL exists with a `HEAD` request, returning whether it
+    /// ends with a `2xx` status after following redirects.
+    #[pyo3(signature = (url, **kwds))]
+    pub fn head_ok(
+        &self,
+        py: Python,
+        url: UrlExtractor,
+        kwds: Option<RequestParams>,
+    ) -> PyResult<bool> {
+        let request = self.0.head_status_ok(url, kwds)?;
+        py.allow_threads(|| pyo3_async_runtimes::tokio::get_runtime().block_on(request))
+    }
+
+    /// Returns the size of the body at the specified URL without downloading it, or `None`
+    /// if the server doesn't tell.
+    #[pyo3(signature = (url, **kwds))]
+    pub fn content_length(
+        &self,
+        py: Python,
+        url: UrlExtractor,
+        kwds: Option<Bound<'_, PyDict>>,
+    ) -> PyResult<Option<u64>> {
+        let request = self.0.content_length_of(url, kwds.as_ref())?;
+        py.allow_threads(|| pyo3_async_runtimes::tokio::get_runtime().block_on(request))
+    }
+
+    /// Download the specified URL into `path`, resuming from the end of an existing file.
+    #[pyo3(signature = (url, path, resume = true, **kwds))]
+    pub fn download(
+        &self,
+        py: Python,
+        url: UrlExtractor,
+        path: PathBuf,
+        resume: bool,
+        mut kwds: Option<RequestParams>,
+    ) -> PyResult<u64> {
+        let (client, pool) = self.0.client_for(py, kwds.as_mut())?;
+        py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime().block_on(execute_download(
+                client,
+                self.0.cache(),
+                self.0.transport(),
+                self.0.cassette(),
+                Some(pool),
+                self.0.max_redirects(),
+                self.0.request_id_header(),
+                self.0.max_response_size(),
+                self.0.auth(),
+                url,
+                path,
+                resume,
+                kwds,
+            ))
+        })
+    }
+
+    /// Send a batch of `(method, url, kwargs)` requests with bounded concurrency, returning
+    /// the responses in the order of `requests`.
+    #[pyo3(signature = (requests, concurrency = 50, return_exceptions = false, on_progress = None))]
+    pub fn map(
+        &self,
+        py: Python,
+        mut requests: Vec<BatchRequest>,
+        concurrency: usize,
+        return_exceptions: bool,
+        on_progress: Option<ProgressCallback>,
+    ) -> PyResult<Vec<PyObject>> {
+        let client = self.0.inner()?;
+        self.0.assign_clients(py, &mut requests)?;
+        let results = py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime().block_on(execute_gather(
+                client,
+                self.0.cache(),
+                self.0.transport(),
+                self.0.cassette(),
+                Some(self.0.pool()),
+                self.0.max_redirects(),
+                self.0.request_id_header(),
+                self.0.max_response_size(),
+                self.0.auth(),
+                requests,
+                concurrency,
+                return_exceptions,
+                on_progress,
+            ))
+        })?;
+        results
+            .into_iter()
+            .map(|result| match result {
+                Ok(response) => BlockingResponse::from(response).into_py_any(py),
+                Err(err) => Ok(err.into_value(py).into_any()),
+            })
+            .collect()
+    }
+
+    /// Send `GET` requests to `urls` with bounded concurrency, returning the responses in the
+    /// order of `urls`, with the exceptions of failed requests in place of their responses.
+    #[pyo3(signature = (urls, concurrency = 50, **kwds))]
+    pub fn get_many(
+        &self,
+        py: Python,
+        urls: Vec<String>,
+        concurrency: usize,
+        kwds: Option<Bound<'_, PyDict>>,
+    ) -> PyResult<Vec<PyObject>> {
+        let requests = urls
+            .into_iter()
+            .map(|url| BatchRequest::new(wreq::Method::GET, url, kwds.as_ref()))
+            .collect::<PyResult<_>>()?;
+        self.map(py, requests, concurrency, true, None)
+    }
+
+    /// Send a batch of `(method, url, kwargs)` requests with bounded concurrency, returning
+    /// the responses in order, with the exceptions of failed requests in their place.
+    #[pyo3(signature = (requests, concurrency = 50))]
+    pub fn request_many(
+        &self,
+        py: Python,
+        requests: Vec<BatchRequest>,
+        concurrency: usize,
+    ) -> PyResult<Vec<PyObject>> {
+        self.map(py, requests, concurrency, true, None)
+    }
+
+    /// Resolves `host` with the client's DNS resolver and lookup strategy, returning the IP
+    /// addresses the client would connect to, in order.
+    pub fn resolve(&self, py: Python, host: String) -> PyResult<Vec<IpAddr>> {
+        py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime()
+                .block_on(dns::resolve(self.0.lookup_ip_strategy(), host))
+        })
+    }
+
     /// Make a WebSocket request to the specified URL.
     #[pyo3(signature = (url, **kwds))]
     pub fn websocket(
         &self,
         py: Python,
-        url: PyBackedStr,
+        url: UrlExtractor,
         kwds: Option<WebSocketParams>,
     ) -> PyResult<BlockingWebSocket> {
+        let client = self.0.inner()?;
+        let defaults = self.0.websocket_defaults();
+        for params in [&kwds, &defaults] {
+            let callback = params
+                .as_ref()
+                .and_then(|params| params.on_reconnect.as_ref());
+            if let Some(callback) = callback {
+                callback.check_blocking(py)?;
+            }
+        }
         py.allow_threads(|| {
-            let client = self.0.clone();
             pyo3_async_runtimes::tokio::get_runtime()
-                .block_on(execute_websocket_request(client, url, kwds))
+                .block_on(execute_websocket_request(client, defaults, url, kwds))
                 .map(Into::into)
         })
     }
@@ -141,19 +301,19 @@ impl BlockingClient {
     /// Creates a new BlockingClient instance.
     #[new]
     #[pyo3(signature = (**kwds))]
-    fn new(py: Python, kwds: Option<ClientParams>) -> PyResult<BlockingClient> {
+    fn new(py: Python, kwds: Option<Bound<'_, PyDict>>) -> PyResult<BlockingClient> {
         async_impl::Client::new(py, kwds).map(BlockingClient)
     }
 
     /// Returns the user agent of the client.
     #[getter]
-    fn user_agent(&self, py: Python) -> Option<String> {
+    fn user_agent(&self, py: Python) -> PyResult<Option<String>> {
         self.0.user_agent(py)
     }
 
     /// Returns the headers of the client.
     #[getter]
-    fn headers(&self) -> HeaderMap {
+    fn headers(&self) -> PyResult<HeaderMap> {
         self.0.headers()
     }
 
@@ -162,31 +322,65 @@ impl BlockingClient {
     pub fn get_cookies<'py>(
         &self,
         py: Python<'py>,
-        url: PyBackedStr,
+        url: UrlExtractor,
     ) -> PyResult<Option<Bound<'py, PyAny>>> {
         self.0.get_cookies(py, url)
     }
 
     /// Sets the cookies for the given URL.
     #[pyo3(signature = (url, cookie))]
-    pub fn set_cookie(&self, py: Python, url: PyBackedStr, cookie: Cookie) -> PyResult<()> {
+    pub fn set_cookie(&self, py: Python, url: UrlExtractor, cookie: Cookie) -> PyResult<()> {
         self.0.set_cookie(py, url, cookie)
     }
 
     /// Removes the cookie with the given name for the given URL.
     #[pyo3(signature = (url, name))]
-    pub fn remove_cookie(&self, py: Python, url: PyBackedStr, name: PyBackedStr) -> PyResult<()> {
+    pub fn remove_cookie(&self, py: Python, url: UrlExtractor, name: PyBackedStr) -> PyResult<()> {
         self.0.remove_cookie(py, url, name)
     }
 
     /// Clears the cookies for the given URL.
-    pub fn clear_cookies(&self, py: Python) {
-        self.0.clear_cookies(py);
+    pub fn clear_cookies(&self, py: Python) -> PyResult<()> {
+        self.0.clear_cookies(py)
+    }
+
+    /// Returns the jar the client stores cookies in, or `None` if it doesn't store any.
+    #[getter]
+    pub fn cookie_jar(&self) -> Option<CookieJar> {
+        self.0.cookie_jar()
+    }
+
+    /// Makes the client store cookies in `jar`, or stop storing them if it's `None`.
+    #[setter]
+    pub fn set_cookie_jar(&self, py: Python, jar: Bound<'_, PyAny>) -> PyResult<()> {
+        self.0.set_cookie_jar(py, jar)
+    }
+
+    /// Returns the OAuth2 token the client has cached, or `None` if it has no `auth` or
+    /// hasn't fetched a token yet.
+    pub fn token_info<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        self.0.token_info(py)
+    }
+
+    /// Answers the requests `matcher` matches with `handler` instead of sending them. The
+    /// matcher is either a URL prefix or a callable taking the method and URL.
+    pub fn mount(&self, matcher: Bound<'_, PyAny>, handler: Bound<'_, PyAny>) -> PyResult<()> {
+        self.0.mount(matcher, handler)
     }
 
     /// Updates the client with the given parameters.
     #[pyo3(signature = (**kwds))]
-    pub fn update(&self, py: Python, kwds: Option<UpdateClientParams>) -> PyResult<()> {
+    pub fn update(&self, py: Python, kwds: Option<Bound<'_, PyDict>>) -> PyResult<()> {
         self.0.update(py, kwds)
     }
+
+    fn __repr__(slf: &Bound<'_, Self>) -> PyResult<String> {
+        let name = slf.get_type().name()?;
+        slf.borrow().0.describe(slf.py(), &name.to_cow()?)
+    }
+
+    /// Closes the client, dropping its connection pool.
+    pub fn close(&self, py: Python) {
+        self.0.close(py)
+    }
 }
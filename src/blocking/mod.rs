@@ -3,5 +3,5 @@ mod response;
 
 pub use self::{
     client::BlockingClient,
-    response::{BlockingResponse, BlockingStreamer, BlockingWebSocket},
+    response::{BlockingParts, BlockingResponse, BlockingStreamer, BlockingWebSocket},
 };
@@ -1,12 +1,17 @@
-use std::ops::Deref;
+use std::{ops::Deref, path::PathBuf};
 
 use crate::{
-    async_impl::{self},
+    async_impl::{
+        self, EncodingDetection, TextEncoding, TextErrors, read_text, save_body, save_json,
+    },
     buffer::{BytesBuffer, PyBufferProtocol},
     error::Error,
-    typing::{Cookie, HeaderMap, Json, SocketAddr, StatusCode, Version},
+    typing::{
+        BodyExtractor, Cookie, HeaderMap, HeaderMapExtractor, Json, SocketAddr, StatusCode, Url,
+        Version,
+    },
 };
-use pyo3::prelude::*;
+use pyo3::{prelude::*, types::PyDict};
 
 /// A blocking response from a request.
 #[pyclass(subclass)]
@@ -20,9 +25,23 @@ impl From<async_impl::Response> for BlockingResponse {
 
 #[pymethods]
 impl BlockingResponse {
+    /// Builds a response in memory, without a request, for testing code that handles
+    /// responses.
+    #[staticmethod]
+    #[pyo3(signature = (status = 200, headers = None, body = None, url = "http://test", version = Version::HTTP_11))]
+    pub fn build(
+        status: u16,
+        headers: Option<HeaderMapExtractor>,
+        body: Option<BodyExtractor>,
+        url: &str,
+        version: Version,
+    ) -> PyResult<BlockingResponse> {
+        async_impl::Response::build(status, headers, body, url, version).map(Into::into)
+    }
+
     /// Returns the URL of the response.
     #[getter]
-    pub fn url(&self) -> &str {
+    pub fn url(&self) -> Url {
         self.0.url()
     }
 
@@ -32,6 +51,36 @@ impl BlockingResponse {
         self.0.ok()
     }
 
+    /// Returns whether the response is informational (1xx).
+    #[getter]
+    pub fn is_informational(&self) -> bool {
+        self.0.is_informational()
+    }
+
+    /// Returns whether the response redirects: a redirect status with a `Location`.
+    #[getter]
+    pub fn is_redirect(&self) -> bool {
+        self.0.is_redirect()
+    }
+
+    /// Returns whether the response is a permanent redirect (301 or 308) with a `Location`.
+    #[getter]
+    pub fn is_permanent_redirect(&self) -> bool {
+        self.0.is_permanent_redirect()
+    }
+
+    /// Returns whether the response is a client error (4xx).
+    #[getter]
+    pub fn is_client_error(&self) -> bool {
+        self.0.is_client_error()
+    }
+
+    /// Returns whether the response is a server error (5xx).
+    #[getter]
+    pub fn is_server_error(&self) -> bool {
+        self.0.is_server_error()
+    }
+
     /// Returns the status code as integer of the response.
     #[getter]
     pub fn status(&self) -> u16 {
@@ -68,12 +117,83 @@ impl BlockingResponse {
         self.0.content_length()
     }
 
+    /// Returns the `Content-Encoding` the body is still encoded with, or `None` if it isn't
+    /// or the client decoded it.
+    #[getter]
+    pub fn content_encoding(&self) -> Option<&str> {
+        self.0.content_encoding()
+    }
+
     /// Returns the remote address of the response.
     #[getter]
     pub fn remote_addr(&self) -> Option<SocketAddr> {
         self.0.remote_addr()
     }
 
+    /// Returns the `Content-Range` of a partial response as `(start, end, total)`.
+    #[getter]
+    pub fn content_range(&self) -> Option<(u64, u64, Option<u64>)> {
+        self.0.content_range()
+    }
+
+    /// Returns an estimate of the bytes sent for the request: the size of the request head as
+    /// HTTP/1.1 text plus the body. It isn't measured on the wire, so it leaves out TLS and
+    /// HTTP/2 framing and header compression.
+    #[getter]
+    pub fn bytes_sent(&self) -> u64 {
+        self.0.bytes_sent()
+    }
+
+    /// Returns an estimate of the bytes received so far: the size of the response head as
+    /// HTTP/1.1 text plus the body read, after decompression. Like `bytes_sent`, it isn't
+    /// measured on the wire.
+    #[getter]
+    pub fn bytes_received(&self) -> u64 {
+        self.0.bytes_received()
+    }
+
+    /// Returns whether the response was served from the client's cache.
+    #[getter]
+    pub fn from_cache(&self) -> bool {
+        self.0.from_cache()
+    }
+
+    /// Returns whether the request was sent over a connection taken from the pool.
+    #[getter]
+    pub fn connection_reused(&self) -> Option<bool> {
+        self.0.connection_reused()
+    }
+
+    /// Returns an opaque id of the pooled connection the response came over.
+    #[getter]
+    pub fn connection_id(&self) -> Option<u64> {
+        self.0.connection_id()
+    }
+
+    /// Returns how long the phases of the request took, in seconds.
+    #[getter]
+    pub fn timing<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        self.0.timing(py)
+    }
+
+    /// Returns the URLs the request was redirected from, in order.
+    #[getter]
+    pub fn history(&self) -> Vec<&str> {
+        self.0.history()
+    }
+
+    /// Returns the id of the request, as echoed by the server if it sent one back.
+    #[getter]
+    pub fn request_id(&self) -> Option<&str> {
+        self.0.request_id()
+    }
+
+    /// Returns the id the client sent with the request.
+    #[getter]
+    pub fn client_request_id(&self) -> Option<&str> {
+        self.0.client_request_id()
+    }
+
     /// Encoding to decode with when accessing text.
     #[getter]
     pub fn encoding(&self, py: Python) -> String {
@@ -88,25 +208,60 @@ impl BlockingResponse {
         self.0.peer_certificate(py)
     }
 
-    /// Returns the text content of the response.
-    pub fn text(&self, py: Python) -> PyResult<String> {
+    /// Returns the text content of the response, with `errors` handling malformed bytes.
+    ///
+    /// With `detect_encoding`, the charset is detected from the body instead of taken from
+    /// the `Content-Type`, and reported by `encoding` afterwards. With `autodetect`, it's
+    /// only detected if the `Content-Type` doesn't declare one. `encoding="detect"` and
+    /// `encoding="auto"` are the same as these flags.
+    #[pyo3(signature = (errors = TextErrors::Replace, detect_encoding = false, autodetect = false, encoding = None))]
+    pub fn text(
+        &self,
+        py: Python,
+        errors: TextErrors,
+        detect_encoding: bool,
+        autodetect: bool,
+        encoding: Option<TextEncoding>,
+    ) -> PyResult<String> {
         py.allow_threads(|| {
             let resp = self.0.inner()?;
-            pyo3_async_runtimes::tokio::get_runtime()
-                .block_on(resp.text())
-                .map_err(Error::Request)
-                .map_err(Into::into)
+            let detection = self.0.detection(detect_encoding, autodetect, encoding);
+            pyo3_async_runtimes::tokio::get_runtime().block_on(read_text(
+                resp,
+                "utf-8".to_owned(),
+                errors,
+                detection,
+            ))
         })
     }
 
-    /// Returns the text content of the response with a specific charset.
-    pub fn text_with_charset(&self, py: Python, encoding: String) -> PyResult<String> {
+    /// Guesses the charset of the body from its byte order mark, or else from the statistics
+    /// of its first 64 KB, which are left in place to be read.
+    ///
+    /// Returns the name of the charset and whether the guess is confident.
+    pub fn detect_encoding(&self, py: Python) -> PyResult<(String, bool)> {
+        py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime().block_on(self.0.sniff_encoding())
+        })
+    }
+
+    /// Returns the text content of the response with a specific charset, with `errors`
+    /// handling malformed bytes.
+    #[pyo3(signature = (encoding, errors = TextErrors::Replace))]
+    pub fn text_with_charset(
+        &self,
+        py: Python,
+        encoding: String,
+        errors: TextErrors,
+    ) -> PyResult<String> {
         py.allow_threads(|| {
             let resp = self.0.inner()?;
-            pyo3_async_runtimes::tokio::get_runtime()
-                .block_on(resp.text_with_charset(&encoding))
-                .map_err(Error::Request)
-                .map_err(Into::into)
+            pyo3_async_runtimes::tokio::get_runtime().block_on(read_text(
+                resp,
+                encoding,
+                errors,
+                EncodingDetection::Never,
+            ))
         })
     }
 
@@ -121,6 +276,24 @@ impl BlockingResponse {
         })
     }
 
+    /// Writes the body into the file at `path` as it arrives, replacing the file, and returns
+    /// its size.
+    pub fn save(&self, py: Python, path: PathBuf) -> PyResult<u64> {
+        py.allow_threads(|| {
+            let resp = self.0.inner()?;
+            pyo3_async_runtimes::tokio::get_runtime().block_on(save_body(resp, path))
+        })
+    }
+
+    /// Writes the JSON body into the file at `path`, indented, replacing the file, and returns
+    /// its size.
+    pub fn save_json(&self, py: Python, path: PathBuf) -> PyResult<u64> {
+        py.allow_threads(|| {
+            let resp = self.0.inner()?;
+            pyo3_async_runtimes::tokio::get_runtime().block_on(save_json(resp, path))
+        })
+    }
+
     /// Returns the bytes content of the response.
     pub fn bytes(&self, py: Python) -> PyResult<Py<PyAny>> {
         py.allow_threads(|| {
@@ -134,9 +307,31 @@ impl BlockingResponse {
         })
     }
 
+    /// Returns the body as a read-only buffer that shares memory with the response instead
+    /// of copying it into `bytes`.
+    pub fn read_buffer(&self, py: Python) -> PyResult<BytesBuffer> {
+        py.allow_threads(|| {
+            let resp = self.0.inner()?;
+            pyo3_async_runtimes::tokio::get_runtime()
+                .block_on(resp.bytes())
+                .map(BytesBuffer::new)
+                .map_err(Error::Request)
+                .map_err(Into::into)
+        })
+    }
+
     /// Convert the response into a `Stream` of `Bytes` from the body.
-    pub fn stream(&self, py: Python) -> PyResult<BlockingStreamer> {
-        self.0.stream(py).map(BlockingStreamer)
+    ///
+    /// With `zero_copy`, chunks are yielded as read-only buffers instead of `bytes`.
+    #[pyo3(signature = (zero_copy = false))]
+    pub fn stream(&self, py: Python, zero_copy: bool) -> PyResult<BlockingStreamer> {
+        self.0.stream(py, zero_copy).map(BlockingStreamer)
+    }
+
+    /// Returns an iterator over the `(headers, body)` parts of a `multipart/*` response,
+    /// such as `multipart/byteranges` or `multipart/mixed`.
+    pub fn parts(&self, py: Python) -> PyResult<BlockingParts> {
+        self.0.parts(py).map(BlockingParts)
     }
 
     /// Closes the response connection.
@@ -182,9 +377,28 @@ impl BlockingStreamer {
     fn __next__(&self, py: Python) -> PyResult<Py<PyAny>> {
         py.allow_threads(|| {
             pyo3_async_runtimes::tokio::get_runtime()
-                .block_on(async_impl::Streamer::_anext(self.0.deref().clone(), || {
-                    Error::StopIteration.into()
-                }))
+                .block_on(self.0.next_chunk(|| Error::StopIteration.into()))
+        })
+    }
+
+    /// Reads exactly `n` bytes, or fewer if the stream ends first.
+    fn read(&self, py: Python, n: usize) -> PyResult<Py<PyAny>> {
+        py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime().block_on(self.0.read_bytes(Some(n)))
+        })
+    }
+
+    /// Reads exactly `n` bytes, raising `BodyError` if the stream ends first.
+    fn readexactly(&self, py: Python, n: usize) -> PyResult<Py<PyAny>> {
+        py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime().block_on(self.0.read_exactly(n))
+        })
+    }
+
+    /// Reads the rest of the stream.
+    fn read_all(&self, py: Python) -> PyResult<Py<PyAny>> {
+        py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime().block_on(self.0.read_bytes(None))
         })
     }
 
@@ -200,12 +414,44 @@ impl BlockingStreamer {
         _traceback: &Bound<'py, PyAny>,
     ) -> PyResult<()> {
         py.allow_threads(|| {
-            let streamer = self.0.clone();
+            let streamer = self.0.deref().clone();
             pyo3_async_runtimes::tokio::get_runtime().block_on(async move {
-                let mut lock = streamer.lock().await;
-                drop(lock.take());
+                streamer.lock().await.close();
                 Ok(())
             })
         })
     }
 }
+
+/// A blocking iterator over the `(headers, body)` parts of a `multipart/*` response.
+/// Implemented in the `parts` method of the `BlockingResponse` class.
+#[pyclass(subclass)]
+pub struct BlockingParts(async_impl::Parts);
+
+#[pymethods]
+impl BlockingParts {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python) -> PyResult<Py<PyAny>> {
+        py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime()
+                .block_on(self.0.next_part(|| Error::StopIteration.into()))
+        })
+    }
+
+    fn __enter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __exit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: &Bound<'py, PyAny>,
+        _exc_value: &Bound<'py, PyAny>,
+        _traceback: &Bound<'py, PyAny>,
+    ) {
+        py.allow_threads(|| pyo3_async_runtimes::tokio::get_runtime().block_on(self.0.close()))
+    }
+}
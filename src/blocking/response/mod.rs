@@ -2,6 +2,6 @@ mod http;
 mod ws;
 
 pub use self::{
-    http::{BlockingResponse, BlockingStreamer},
+    http::{BlockingParts, BlockingResponse, BlockingStreamer},
     ws::BlockingWebSocket,
 };
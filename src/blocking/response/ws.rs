@@ -1,9 +1,10 @@
 use crate::{
-    async_impl::{self, Message},
-    error::Error,
+    async_impl::{self, Message, MessagesExtractor},
+    error::{Error, TimeoutError},
     typing::{Cookie, HeaderMap, SocketAddr, StatusCode, Version},
 };
-use pyo3::{prelude::*, pybacked::PyBackedStr};
+use pyo3::{exceptions::PyValueError, prelude::*, pybacked::PyBackedStr};
+use std::time::Duration;
 
 /// A blocking WebSocket response.
 #[pyclass(subclass)]
@@ -65,11 +66,88 @@ impl BlockingWebSocket {
         self.0.protocol()
     }
 
-    /// Receives a message from the WebSocket.
-    pub fn recv(&self, py: Python) -> PyResult<Option<Message>> {
+    /// Returns the `Origin` header the handshake was sent with.
+    #[getter]
+    pub fn origin(&self) -> Option<&str> {
+        self.0.origin()
+    }
+
+    /// Returns the `User-Agent` header the handshake was sent with, if set for the request.
+    #[getter]
+    pub fn user_agent(&self) -> Option<&str> {
+        self.0.user_agent()
+    }
+
+    /// Returns the number of messages sent with `send` and `send_all`.
+    #[getter]
+    pub fn messages_sent(&self) -> u64 {
+        self.0.messages_sent()
+    }
+
+    /// Returns the number of messages received.
+    #[getter]
+    pub fn messages_received(&self) -> u64 {
+        self.0.messages_received()
+    }
+
+    /// Returns the size of the payloads of the messages sent.
+    #[getter]
+    pub fn bytes_sent(&self) -> u64 {
+        self.0.bytes_sent()
+    }
+
+    /// Returns the size of the payloads of the messages received.
+    #[getter]
+    pub fn bytes_received(&self) -> u64 {
+        self.0.bytes_received()
+    }
+
+    /// Returns the size of the payloads of the messages being sent that haven't been written
+    /// yet, including those waiting for another send.
+    #[getter]
+    pub fn pending_bytes(&self) -> u64 {
+        self.0.pending_bytes()
+    }
+
+    /// Returns the largest message the connection accepts.
+    #[getter]
+    pub fn max_message_size(&self) -> usize {
+        self.0.max_message_size()
+    }
+
+    /// Returns the largest frame the connection accepts.
+    #[getter]
+    pub fn max_frame_size(&self) -> usize {
+        self.0.max_frame_size()
+    }
+
+    /// Receives a message from the WebSocket, waiting at most `timeout` seconds if given.
+    #[pyo3(signature = (timeout=None))]
+    pub fn recv(&self, py: Python, timeout: Option<f64>) -> PyResult<Option<Message>> {
+        let timeout = timeout
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|err| PyValueError::new_err(format!("invalid timeout: {err}")))?;
+        let recv = async_impl::WebSocket::_recv(
+            self.0.receiver(),
+            self.0.sender(),
+            self.0.closed(),
+            self.0.reconnect(),
+            self.0.stats(),
+        );
         py.allow_threads(|| {
-            pyo3_async_runtimes::tokio::get_runtime()
-                .block_on(async_impl::WebSocket::_recv(self.0.receiver()))
+            pyo3_async_runtimes::tokio::get_runtime().block_on(async {
+                match timeout {
+                    // Dropping a pending receive leaves the connection intact.
+                    Some(timeout) => tokio::time::timeout(timeout, recv).await.map_err(|_| {
+                        TimeoutError::new_err(format!(
+                            "no message received within {}s",
+                            timeout.as_secs_f64()
+                        ))
+                    })?,
+                    None => recv.await,
+                }
+            })
         })
     }
 
@@ -77,8 +155,28 @@ impl BlockingWebSocket {
     #[pyo3(signature = (message))]
     pub fn send(&self, py: Python, message: Message) -> PyResult<()> {
         py.allow_threads(|| {
-            pyo3_async_runtimes::tokio::get_runtime()
-                .block_on(async_impl::WebSocket::_send(self.0.sender(), message))
+            pyo3_async_runtimes::tokio::get_runtime().block_on(async_impl::WebSocket::_send(
+                self.0.receiver(),
+                self.0.sender(),
+                self.0.closed(),
+                self.0.reconnect(),
+                self.0.stats(),
+                message,
+            ))
+        })
+    }
+
+    /// Sends every message of an iterable to the WebSocket, flushing once at the end.
+    #[pyo3(signature = (messages))]
+    pub fn send_all(&self, py: Python, messages: MessagesExtractor) -> PyResult<usize> {
+        py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime().block_on(async_impl::WebSocket::_send_all(
+                self.0.sender(),
+                self.0.closed(),
+                self.0.reconnect(),
+                self.0.stats(),
+                messages.0,
+            ))
         })
     }
 
@@ -94,6 +192,7 @@ impl BlockingWebSocket {
             pyo3_async_runtimes::tokio::get_runtime().block_on(async_impl::WebSocket::_close(
                 self.0.receiver(),
                 self.0.sender(),
+                self.0.closed(),
                 code,
                 reason,
             ))
@@ -109,10 +208,14 @@ impl BlockingWebSocket {
 
     fn __next__(&self, py: Python) -> PyResult<Message> {
         py.allow_threads(|| {
-            pyo3_async_runtimes::tokio::get_runtime()
-                .block_on(async_impl::WebSocket::_anext(self.0.receiver(), || {
-                    Error::StopIteration.into()
-                }))
+            pyo3_async_runtimes::tokio::get_runtime().block_on(async_impl::WebSocket::_anext(
+                self.0.receiver(),
+                self.0.sender(),
+                self.0.closed(),
+                self.0.reconnect(),
+                self.0.stats(),
+                || Error::StopIteration.into(),
+            ))
         })
     }
 
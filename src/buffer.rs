@@ -22,6 +22,7 @@ use bytes::Bytes;
 use pyo3::IntoPyObjectExt;
 use pyo3::ffi;
 use pyo3::prelude::*;
+use pyo3::types::PyBytes;
 use std::os::raw::c_int;
 use wreq::header::{HeaderName, HeaderValue};
 
@@ -71,6 +72,15 @@ macro_rules! impl_buffer {
             ) -> PyResult<()> {
                 unsafe { fill_buffer_info(slf.as_slice(), slf.as_ptr(), view, flags, slf.py()) }
             }
+
+            fn __len__(&self) -> usize {
+                self.as_slice().len()
+            }
+
+            /// Copies the contents into a new `bytes` object.
+            fn __bytes__<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+                PyBytes::new(py, self.as_slice())
+            }
         }
     };
 }
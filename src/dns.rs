@@ -1,7 +1,10 @@
 use crate::{error::DNSResolverError, typing::LookupIpStrategy};
-use pyo3::PyResult;
-use std::sync::{Arc, OnceLock};
-use wreq::dns::HickoryDnsResolver;
+use pyo3::{PyResult, exceptions::PyValueError};
+use std::{
+    net::IpAddr,
+    sync::{Arc, OnceLock},
+};
+use wreq::dns::{HickoryDnsResolver, Name, Resolve};
 
 macro_rules! dns_resolver {
     ($strategy:expr) => {{
@@ -54,3 +57,22 @@ fn init(
         .map(Arc::clone)
         .map_err(DNSResolverError::new_err)
 }
+
+/// Resolves `host` the way a client with the given strategy does before connecting,
+/// returning the addresses in the order they are tried.
+///
+/// The resolver, and so its cache, is the one shared by the clients with that strategy.
+pub async fn resolve(strategy: Option<LookupIpStrategy>, host: String) -> PyResult<Vec<IpAddr>> {
+    let resolver = get_or_try_init(strategy)?;
+    // IPv6 literals are written in brackets in URLs, but resolved without them.
+    let name = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<Name>()
+        .map_err(|_| PyValueError::new_err(format!("invalid host name: {host:?}")))?;
+    resolver
+        .resolve(name)
+        .await
+        .map(|addrs| addrs.map(|addr| addr.ip()).collect())
+        .map_err(|err| DNSResolverError::new_err(format!("failed to resolve {host}: {err}")))
+}
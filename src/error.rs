@@ -1,8 +1,13 @@
+use boring2::error::ErrorStack;
 use pyo3::{
     PyErr, create_exception,
-    exceptions::{PyException, PyRuntimeError, PyStopAsyncIteration, PyStopIteration},
+    exceptions::{
+        PyBaseException, PyException, PyRuntimeError, PyStopAsyncIteration, PyStopIteration,
+    },
+    prelude::*,
 };
-use wreq::header;
+use std::error::Error as StdError;
+use wreq::{Method, header};
 
 const RACE_CONDITION_ERROR_MSG: &str = r#"Due to Rust's memory management with borrowing,
 you cannot use certain instances multiple times as they may be consumed.
@@ -21,14 +26,19 @@ Potential solutions:
 create_exception!(exceptions, DNSResolverError, PyRuntimeError);
 
 create_exception!(exceptions, BodyError, PyException);
+create_exception!(exceptions, ResponseTooLargeError, BodyError);
 create_exception!(exceptions, BuilderError, PyException);
 create_exception!(exceptions, ConnectionError, PyException);
+create_exception!(exceptions, ProxyError, ConnectionError);
 create_exception!(exceptions, ConnectionResetError, PyException);
 create_exception!(exceptions, DecodingError, PyException);
 create_exception!(exceptions, RedirectError, PyException);
 create_exception!(exceptions, TimeoutError, PyException);
 create_exception!(exceptions, StatusError, PyException);
 create_exception!(exceptions, RequestError, PyException);
+create_exception!(exceptions, Http2Error, RequestError);
+create_exception!(exceptions, TlsError, PyException);
+create_exception!(exceptions, CertificateError, TlsError);
 create_exception!(exceptions, UpgradeError, PyException);
 
 create_exception!(exceptions, URLParseError, PyException);
@@ -47,6 +57,37 @@ macro_rules! wrap_error {
     };
 }
 
+/// The error a response body fails with once it's larger than `max_response_size`.
+#[derive(Debug)]
+pub struct ResponseTooLarge {
+    pub limit: u64,
+}
+
+impl std::fmt::Display for ResponseTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "response body exceeds the limit of {} bytes", self.limit)
+    }
+}
+
+impl StdError for ResponseTooLarge {}
+
+/// The error of a response body that was replaced with a wrapper, which the rebuilt response
+/// reports nested inside a decode error.
+#[derive(Debug)]
+pub struct RewrappedBody(pub wreq::Error);
+
+impl std::fmt::Display for RewrappedBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl StdError for RewrappedBody {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.0)
+    }
+}
+
 /// Unified error enum
 #[derive(Debug)]
 pub enum Error {
@@ -54,11 +95,17 @@ pub enum Error {
     StopIteration,
     StopAsyncIteration,
     WebSocketDisconnect,
+    WebSocketReconnecting,
+    RuntimeInitialized,
+    ClientClosed,
+    StreamBusy,
+    TunnelClosed,
     InvalidHeaderName(header::InvalidHeaderName),
     InvalidHeaderValue(header::InvalidHeaderValue),
     UrlParse(url::ParseError),
     IO(std::io::Error),
     Request(wreq::Error),
+    RequestWithMethod(wreq::Error, Method),
 }
 
 impl From<Error> for PyErr {
@@ -70,6 +117,17 @@ impl From<Error> for PyErr {
             Error::WebSocketDisconnect => {
                 PyRuntimeError::new_err("The WebSocket has been disconnected")
             }
+            Error::WebSocketReconnecting => {
+                PyRuntimeError::new_err("The WebSocket is reconnecting")
+            }
+            Error::RuntimeInitialized => PyRuntimeError::new_err(
+                "The runtime has already been initialized; call configure_runtime before making any requests",
+            ),
+            Error::ClientClosed => PyRuntimeError::new_err("The client has been closed"),
+            Error::StreamBusy => PyRuntimeError::new_err(
+                "The stream is already being consumed; a stream cannot be iterated concurrently",
+            ),
+            Error::TunnelClosed => PyRuntimeError::new_err("The tunnel has been closed"),
             Error::InvalidHeaderName(err) => {
                 PyRuntimeError::new_err(format!("Invalid header name: {:?}", err))
             }
@@ -78,21 +136,269 @@ impl From<Error> for PyErr {
             }
             Error::UrlParse(err) => URLParseError::new_err(format!("URL parse error: {:?}", err)),
             Error::IO(err) => PyRuntimeError::new_err(format!("IO error: {:?}", err)),
-            Error::Request(err) => wrap_error!(err,
-                is_body => BodyError,
-                is_connect => ConnectionError,
-                is_connection_reset => ConnectionResetError,
-                is_decode => DecodingError,
-                is_redirect => RedirectError,
-                is_timeout => TimeoutError,
-                is_status => StatusError,
-                is_request => RequestError,
-                is_builder => BuilderError
-            ),
+            Error::Request(err) => request_error(&err, None),
+            Error::RequestWithMethod(err, method) => request_error(&err, Some(&method)),
         }
     }
 }
 
+/// The structured fields of request exceptions, which are `None` unless a failed request set them.
+const REQUEST_ERROR_FIELDS: &[&str] = &[
+    "url",
+    "method",
+    "is_timeout",
+    "is_connect",
+    "is_body",
+    "phase",
+    "host",
+    "port",
+    "os_error",
+    "status",
+];
+
+/// The fields an [`Http2Error`] has on top of [`REQUEST_ERROR_FIELDS`].
+const HTTP2_ERROR_FIELDS: &[&str] = &[
+    "error_code",
+    "reason",
+    "is_goaway",
+    "is_reset",
+    "is_remote",
+    "never_processed",
+];
+
+/// Defaults the structured fields of the request exceptions to `None` on their classes, so that
+/// every instance has them, however it was raised.
+pub fn init_request_error_fields(py: Python) -> PyResult<()> {
+    let types = [
+        py.get_type::<BodyError>(),
+        py.get_type::<BuilderError>(),
+        py.get_type::<ConnectionError>(),
+        py.get_type::<ConnectionResetError>(),
+        py.get_type::<DecodingError>(),
+        py.get_type::<RedirectError>(),
+        py.get_type::<TimeoutError>(),
+        py.get_type::<StatusError>(),
+        py.get_type::<RequestError>(),
+        py.get_type::<TlsError>(),
+        py.get_type::<UpgradeError>(),
+    ];
+    for ty in types {
+        for field in REQUEST_ERROR_FIELDS {
+            ty.setattr(*field, py.None())?;
+        }
+    }
+    let http2 = py.get_type::<Http2Error>();
+    for field in HTTP2_ERROR_FIELDS {
+        http2.setattr(*field, py.None())?;
+    }
+    Ok(())
+}
+
+/// Maps a `wreq::Error` to the matching Python exception, with structured context attached.
+fn request_error(err: &wreq::Error, method: Option<&Method>) -> PyErr {
+    // A Python callback that raised while the request was sent, like an upload progress
+    // callback, fails it with its own exception.
+    if let Some(callback_err) = callback_error(err) {
+        return Python::with_gil(|py| callback_err.clone_ref(py));
+    }
+    let inner = unwrap_body_error(err);
+    let py_err = classify_request_error(inner);
+    Python::with_gil(|py| {
+        // Setting attributes on a fresh exception instance cannot fail in practice.
+        let _ = annotate_request_error(py_err.value(py), err, inner, method);
+    });
+    py_err
+}
+
+fn classify_request_error(err: &wreq::Error) -> PyErr {
+    if let Some(too_large) = response_too_large(err) {
+        return ResponseTooLargeError::new_err(too_large.to_string());
+    }
+
+    // TLS failures also report as connect errors, so route them first.
+    if !err.is_timeout() {
+        if let Some(stack) = tls_error(err) {
+            return if is_certificate_error(stack) {
+                CertificateError::new_err(format!("certificate error: {:?}", err))
+            } else {
+                TlsError::new_err(format!("tls error: {:?}", err))
+            };
+        }
+    }
+
+    if let Some(h2) = http2_error(err) {
+        return Http2Error::new_err(format!("http2 error: {:?}", h2));
+    }
+
+    wrap_error!(err,
+        is_timeout => TimeoutError,
+        is_body => BodyError,
+        is_connect => ConnectionError,
+        is_connection_reset => ConnectionResetError,
+        is_decode => DecodingError,
+        is_redirect => RedirectError,
+        is_status => StatusError,
+        is_request => RequestError,
+        is_builder => BuilderError
+    )
+}
+
+fn annotate_request_error(
+    value: &Bound<'_, PyBaseException>,
+    err: &wreq::Error,
+    inner: &wreq::Error,
+    method: Option<&Method>,
+) -> PyResult<()> {
+    let url = inner.url().or_else(|| err.url());
+    value.setattr("url", url.map(wreq::Url::as_str))?;
+    value.setattr("method", method.map(Method::as_str))?;
+    value.setattr("is_timeout", inner.is_timeout())?;
+    value.setattr("is_connect", inner.is_connect())?;
+    value.setattr("is_body", inner.is_body())?;
+
+    if inner.is_timeout() {
+        let phase = if inner.is_connect() {
+            "connect"
+        } else if inner.is_body() || inner.is_decode() {
+            "read"
+        } else {
+            "total"
+        };
+        value.setattr("phase", phase)?;
+    }
+
+    if inner.is_connect() {
+        value.setattr("host", url.and_then(wreq::Url::host_str))?;
+        value.setattr("port", url.and_then(wreq::Url::port_or_known_default))?;
+        value.setattr("os_error", os_error(inner))?;
+    }
+
+    if let Some(status) = inner.status() {
+        value.setattr("status", status.as_u16())?;
+    }
+
+    if let Some(h2) = http2_error(inner) {
+        let reason = h2.reason().unwrap_or(http2::Reason::NO_ERROR);
+        value.setattr("error_code", u32::from(reason))?;
+        value.setattr("reason", format!("{:?}", reason))?;
+        value.setattr("is_goaway", h2.is_go_away())?;
+        value.setattr("is_reset", h2.is_reset())?;
+        value.setattr("is_remote", h2.is_remote())?;
+        // Only a refused stream proves the server never processed the request (RFC 9113,
+        // section 8.7); a GOAWAY doesn't say which side of its last stream id we were on.
+        value.setattr(
+            "never_processed",
+            h2.is_reset() && h2.is_remote() && reason == http2::Reason::REFUSED_STREAM,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Returns the OS error code of the first `io::Error` in the source chain.
+fn os_error(err: &wreq::Error) -> Option<i32> {
+    let mut source = err.source();
+    while let Some(err) = source {
+        if let Some(code) = err
+            .downcast_ref::<std::io::Error>()
+            .and_then(std::io::Error::raw_os_error)
+        {
+            return Some(code);
+        }
+        source = err.source();
+    }
+    None
+}
+
+/// Returns the size limit error in the source chain, if a response body was cut off by it.
+fn response_too_large(err: &wreq::Error) -> Option<&ResponseTooLarge> {
+    let mut source = err.source();
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<ResponseTooLarge>() {
+            return Some(err);
+        }
+        source = err.source();
+    }
+    None
+}
+
+/// Returns the exception a Python callback raised, if it's in the source chain.
+fn callback_error(err: &wreq::Error) -> Option<&PyErr> {
+    let mut source = err.source();
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<PyErr>() {
+            return Some(err);
+        }
+        source = err.source();
+    }
+    None
+}
+
+/// Returns the TLS error stack in the source chain, if the failure came from the TLS layer.
+fn tls_error(err: &wreq::Error) -> Option<&ErrorStack> {
+    let mut source = err.source();
+    while let Some(err) = source {
+        if let Some(stack) = err.downcast_ref::<ErrorStack>() {
+            return Some(stack);
+        }
+        source = err.source();
+    }
+    None
+}
+
+/// Returns the HTTP/2 protocol error in the source chain, if the server or the HTTP/2 layer
+/// reset the stream or the connection.
+fn http2_error(err: &wreq::Error) -> Option<&http2::Error> {
+    let mut source = err.source();
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<http2::Error>() {
+            return err.reason().is_some().then_some(err);
+        }
+        source = err.source();
+    }
+    None
+}
+
+/// Returns whether the TLS error stack reports a failed certificate verification.
+fn is_certificate_error(stack: &ErrorStack) -> bool {
+    stack
+        .errors()
+        .iter()
+        .any(|err| err.reason() == Some("CERTIFICATE_VERIFY_FAILED"))
+}
+
+/// Returns the error a response body failed with before it was rewrapped.
+///
+/// A body replaced by `counter::map_body` fails with a decode error over a body error over
+/// [`RewrappedBody`], and wreq wraps the original error in a body error for every body
+/// wrapper in between. Only those layers are peeled off, so every error is classified as it
+/// would be without the wrappers.
+fn unwrap_body_error(err: &wreq::Error) -> &wreq::Error {
+    let mut err = err;
+    while let Some(rewrapped) = rewrapped_body(err) {
+        err = &rewrapped.0;
+        while err.is_body() {
+            match err.source().and_then(|e| e.downcast_ref::<wreq::Error>()) {
+                Some(inner) => err = inner,
+                None => break,
+            }
+        }
+    }
+    err
+}
+
+/// Returns the [`RewrappedBody`] a decode error was raised for, if any.
+fn rewrapped_body(err: &wreq::Error) -> Option<&RewrappedBody> {
+    if !err.is_decode() {
+        return None;
+    }
+    let body = err.source()?.downcast_ref::<wreq::Error>()?;
+    if !body.is_body() {
+        return None;
+    }
+    body.source()?.downcast_ref::<RewrappedBody>()
+}
+
 impl From<header::InvalidHeaderName> for Error {
     fn from(err: header::InvalidHeaderName) -> Self {
         Error::InvalidHeaderName(err)
@@ -0,0 +1,271 @@
+use pyo3::{PyResult, exceptions::PyValueError};
+// Interfaces are only resolved to addresses where sockets can't be bound to a device.
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "fuchsia",
+    target_os = "linux",
+    target_os = "ios",
+    target_os = "visionos",
+    target_os = "macos",
+    target_os = "tvos",
+    target_os = "watchos"
+)))]
+use {
+    crate::error::Error,
+    std::net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+/// Resolves the network interface `name` to the addresses used to bind to it on
+/// platforms without device binding (`SO_BINDTODEVICE` / `IP_BOUND_IF`).
+///
+/// The primary address of each family is the first one assigned to the interface,
+/// skipping IPv6 link-local addresses since those can't be bound without a scope id.
+///
+/// # Errors
+///
+/// Raises `ValueError` if the interface doesn't exist or has no usable address.
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "fuchsia",
+    target_os = "linux",
+    target_os = "ios",
+    target_os = "visionos",
+    target_os = "macos",
+    target_os = "tvos",
+    target_os = "watchos"
+)))]
+pub fn resolve(name: &str) -> PyResult<(Option<Ipv4Addr>, Option<Ipv6Addr>)> {
+    let addrs = interface_addrs(name).map_err(Error::IO)?;
+    if addrs.is_empty() {
+        return Err(PyValueError::new_err(format!(
+            "network interface {name:?} not found or has no IP address"
+        )));
+    }
+
+    let ipv4 = addrs.iter().find_map(|addr| match addr {
+        IpAddr::V4(ip) => Some(*ip),
+        IpAddr::V6(_) => None,
+    });
+    let ipv6 = addrs.iter().find_map(|addr| match addr {
+        IpAddr::V6(ip) if !ip.is_unicast_link_local() => Some(*ip),
+        _ => None,
+    });
+    if ipv4.is_none() && ipv6.is_none() {
+        return Err(PyValueError::new_err(format!(
+            "network interface {name:?} has only link-local addresses"
+        )));
+    }
+    Ok((ipv4, ipv6))
+}
+
+/// Resolves the zone of a scoped IPv6 address, an interface name or index, to the name of
+/// the interface sockets are bound to so that the address can be bound.
+///
+/// # Errors
+///
+/// Raises `ValueError` if no such interface exists, or if the platform can't bind sockets
+/// to an interface.
+#[cfg(any(
+    target_os = "android",
+    target_os = "fuchsia",
+    target_os = "linux",
+    target_os = "ios",
+    target_os = "visionos",
+    target_os = "macos",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn zone_interface(zone: &str) -> PyResult<String> {
+    use std::ffi::{CStr, CString};
+
+    if let Ok(index) = zone.parse::<u32>() {
+        let mut name = [0 as libc::c_char; libc::IF_NAMESIZE];
+        if unsafe { libc::if_indextoname(index, name.as_mut_ptr()) }.is_null() {
+            return Err(PyValueError::new_err(format!(
+                "no network interface with index {index}"
+            )));
+        }
+        // SAFETY: `if_indextoname` wrote a NUL-terminated name into `name`.
+        let name = unsafe { CStr::from_ptr(name.as_ptr()) };
+        return Ok(name.to_string_lossy().into_owned());
+    }
+
+    let exists =
+        CString::new(zone).is_ok_and(|name| unsafe { libc::if_nametoindex(name.as_ptr()) } != 0);
+    if !exists {
+        return Err(PyValueError::new_err(format!(
+            "network interface {zone:?} not found"
+        )));
+    }
+    Ok(zone.to_owned())
+}
+
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "fuchsia",
+    target_os = "linux",
+    target_os = "ios",
+    target_os = "visionos",
+    target_os = "macos",
+    target_os = "tvos",
+    target_os = "watchos"
+)))]
+pub fn zone_interface(_zone: &str) -> PyResult<String> {
+    Err(PyValueError::new_err(
+        "local_address: IPv6 zones are not supported on this platform",
+    ))
+}
+
+/// Lists the addresses assigned to the interface `name` with `getifaddrs`.
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "linux",
+        target_os = "ios",
+        target_os = "visionos",
+        target_os = "macos",
+        target_os = "tvos",
+        target_os = "watchos"
+    ))
+))]
+fn interface_addrs(name: &str) -> std::io::Result<Vec<IpAddr>> {
+    use std::ffi::CStr;
+
+    let mut ifap: *mut libc::ifaddrs = std::ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut ifap) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    let mut addrs = Vec::new();
+    let mut cursor = ifap;
+    while !cursor.is_null() {
+        // SAFETY: `cursor` points into the list returned by `getifaddrs`, which stays
+        // valid until `freeifaddrs` below.
+        let ifa = unsafe { &*cursor };
+        cursor = ifa.ifa_next;
+
+        if ifa.ifa_addr.is_null() || ifa.ifa_name.is_null() {
+            continue;
+        }
+        if unsafe { CStr::from_ptr(ifa.ifa_name) }.to_bytes() != name.as_bytes() {
+            continue;
+        }
+
+        match i32::from(unsafe { (*ifa.ifa_addr).sa_family }) {
+            libc::AF_INET => {
+                let sin = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in) };
+                addrs.push(IpAddr::V4(Ipv4Addr::from(u32::from_be(
+                    sin.sin_addr.s_addr,
+                ))));
+            }
+            libc::AF_INET6 => {
+                let sin6 = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in6) };
+                addrs.push(IpAddr::V6(Ipv6Addr::from(sin6.sin6_addr.s6_addr)));
+            }
+            _ => {}
+        }
+    }
+
+    unsafe { libc::freeifaddrs(ifap) };
+    Ok(addrs)
+}
+
+/// Lists the addresses assigned to the adapter `name` with `GetAdaptersAddresses`.
+///
+/// Adapters are matched by friendly name (`"Ethernet"`, `"Wi-Fi"`) or by their GUID name.
+#[cfg(windows)]
+fn interface_addrs(name: &str) -> std::io::Result<Vec<IpAddr>> {
+    use std::ffi::CStr;
+    use windows_sys::Win32::{
+        Foundation::{ERROR_BUFFER_OVERFLOW, NO_ERROR},
+        NetworkManagement::IpHelper::{
+            GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST,
+            GetAdaptersAddresses, IP_ADAPTER_ADDRESSES_LH,
+        },
+        Networking::WinSock::{AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR_IN, SOCKADDR_IN6},
+    };
+
+    // Use a `u64` buffer so the adapter structs are suitably aligned.
+    let mut size: u32 = 16 * 1024;
+    let mut buffer: Vec<u64>;
+    loop {
+        buffer = vec![0; (size as usize).div_ceil(8)];
+        let ret = unsafe {
+            GetAdaptersAddresses(
+                u32::from(AF_UNSPEC),
+                GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER,
+                std::ptr::null(),
+                buffer.as_mut_ptr().cast(),
+                &mut size,
+            )
+        };
+        match ret {
+            NO_ERROR => break,
+            ERROR_BUFFER_OVERFLOW => continue,
+            err => return Err(std::io::Error::from_raw_os_error(err as i32)),
+        }
+    }
+
+    let mut addrs = Vec::new();
+    let mut adapter = buffer.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH;
+    while !adapter.is_null() {
+        // SAFETY: `adapter` points into the linked list written into `buffer`.
+        let current = unsafe { &*adapter };
+        adapter = current.Next;
+
+        let friendly_name = unsafe { wide_to_string(current.FriendlyName) };
+        let adapter_name = unsafe { CStr::from_ptr(current.AdapterName.cast()) };
+        if friendly_name != name && adapter_name.to_bytes() != name.as_bytes() {
+            continue;
+        }
+
+        let mut unicast = current.FirstUnicastAddress;
+        while !unicast.is_null() {
+            let address = unsafe { &*unicast };
+            unicast = address.Next;
+
+            let sockaddr = address.Address.lpSockaddr;
+            if sockaddr.is_null() {
+                continue;
+            }
+            match unsafe { (*sockaddr).sa_family } {
+                AF_INET => {
+                    let sin = unsafe { &*(sockaddr as *const SOCKADDR_IN) };
+                    addrs.push(IpAddr::V4(Ipv4Addr::from(u32::from_be(unsafe {
+                        sin.sin_addr.S_un.S_addr
+                    }))));
+                }
+                AF_INET6 => {
+                    let sin6 = unsafe { &*(sockaddr as *const SOCKADDR_IN6) };
+                    addrs.push(IpAddr::V6(Ipv6Addr::from(unsafe { sin6.sin6_addr.u.Byte })));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(addrs)
+}
+
+/// Reads a NUL-terminated UTF-16 string.
+#[cfg(windows)]
+unsafe fn wide_to_string(ptr: *const u16) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    let mut len = 0;
+    while unsafe { *ptr.add(len) } != 0 {
+        len += 1;
+    }
+    String::from_utf16_lossy(unsafe { std::slice::from_raw_parts(ptr, len) })
+}
+
+#[cfg(not(any(unix, windows, target_os = "fuchsia")))]
+fn interface_addrs(_name: &str) -> std::io::Result<Vec<IpAddr>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "binding to a network interface is not supported on this platform",
+    ))
+}
@@ -6,19 +6,30 @@ mod blocking;
 mod buffer;
 mod dns;
 mod error;
+mod interface;
+mod logging;
+mod pac;
+mod runtime;
 mod stream;
 mod typing;
 
-use async_impl::{Client, Message, Response, Streamer, WebSocket};
-use blocking::{BlockingClient, BlockingResponse, BlockingStreamer, BlockingWebSocket};
+use async_impl::{
+    Cache, Client, Connection, Event, Events, Frames, JsonItems, Message, MockTransport,
+    OAuth2ClientCredentials, Parts, Response, Streamer, TlsFingerprint, Tunnel, WebSocket,
+};
+use blocking::{
+    BlockingClient, BlockingParts, BlockingResponse, BlockingStreamer, BlockingWebSocket,
+};
+use buffer::BytesBuffer;
 use error::*;
 use pyo3::{prelude::*, pybacked::PyBackedStr};
 use pyo3_async_runtimes::tokio::future_into_py;
 use typing::param::{RequestParams, WebSocketParams};
 use typing::{
-    Cookie, HeaderMap, HeaderMapItemsIter, HeaderMapKeysIter, HeaderMapValuesIter, Impersonate,
-    ImpersonateOS, ImpersonateOption, LookupIpStrategy, Method, Multipart, Part, Proxy, SameSite,
-    SocketAddr, StatusCode, TlsVersion, Version,
+    Cookie, CookieJar, HeaderMap, HeaderMapItemsIter, HeaderMapKeysIter, HeaderMapValuesIter,
+    Impersonate, ImpersonateOS, ImpersonateOption, LookupIpStrategy, MessageType, Method,
+    MethodExtractor, Multipart, Part, Proxy, SameSite, SocketAddr, StatusCode, TlsVersion, Url,
+    Version,
 };
 
 #[cfg(all(
@@ -36,7 +47,10 @@ fn get(
     url: PyBackedStr,
     kwds: Option<RequestParams>,
 ) -> PyResult<Bound<'_, PyAny>> {
-    future_into_py(py, async_impl::shortcut_request(url, Method::GET, kwds))
+    future_into_py(
+        py,
+        async_impl::shortcut_request(url, wreq::Method::GET, kwds),
+    )
 }
 
 /// Make a POST request with the given parameters.
@@ -47,7 +61,10 @@ fn post(
     url: PyBackedStr,
     kwds: Option<RequestParams>,
 ) -> PyResult<Bound<'_, PyAny>> {
-    future_into_py(py, async_impl::shortcut_request(url, Method::POST, kwds))
+    future_into_py(
+        py,
+        async_impl::shortcut_request(url, wreq::Method::POST, kwds),
+    )
 }
 
 /// Make a PUT request with the given parameters.
@@ -58,7 +75,10 @@ fn put(
     url: PyBackedStr,
     kwds: Option<RequestParams>,
 ) -> PyResult<Bound<'_, PyAny>> {
-    future_into_py(py, async_impl::shortcut_request(url, Method::PUT, kwds))
+    future_into_py(
+        py,
+        async_impl::shortcut_request(url, wreq::Method::PUT, kwds),
+    )
 }
 
 /// Make a PATCH request with the given parameters.
@@ -69,7 +89,10 @@ fn patch(
     url: PyBackedStr,
     kwds: Option<RequestParams>,
 ) -> PyResult<Bound<'_, PyAny>> {
-    future_into_py(py, async_impl::shortcut_request(url, Method::PATCH, kwds))
+    future_into_py(
+        py,
+        async_impl::shortcut_request(url, wreq::Method::PATCH, kwds),
+    )
 }
 
 /// Make a DELETE request with the given parameters.
@@ -80,7 +103,10 @@ fn delete(
     url: PyBackedStr,
     kwds: Option<RequestParams>,
 ) -> PyResult<Bound<'_, PyAny>> {
-    future_into_py(py, async_impl::shortcut_request(url, Method::DELETE, kwds))
+    future_into_py(
+        py,
+        async_impl::shortcut_request(url, wreq::Method::DELETE, kwds),
+    )
 }
 
 /// Make a HEAD request with the given parameters.
@@ -91,7 +117,10 @@ fn head(
     url: PyBackedStr,
     kwds: Option<RequestParams>,
 ) -> PyResult<Bound<'_, PyAny>> {
-    future_into_py(py, async_impl::shortcut_request(url, Method::HEAD, kwds))
+    future_into_py(
+        py,
+        async_impl::shortcut_request(url, wreq::Method::HEAD, kwds),
+    )
 }
 
 /// Make a OPTIONS request with the given parameters.
@@ -102,7 +131,10 @@ fn options(
     url: PyBackedStr,
     kwds: Option<RequestParams>,
 ) -> PyResult<Bound<'_, PyAny>> {
-    future_into_py(py, async_impl::shortcut_request(url, Method::OPTIONS, kwds))
+    future_into_py(
+        py,
+        async_impl::shortcut_request(url, wreq::Method::OPTIONS, kwds),
+    )
 }
 
 /// Make a TRACE request with the given parameters.
@@ -113,7 +145,10 @@ fn trace(
     url: PyBackedStr,
     kwds: Option<RequestParams>,
 ) -> PyResult<Bound<'_, PyAny>> {
-    future_into_py(py, async_impl::shortcut_request(url, Method::TRACE, kwds))
+    future_into_py(
+        py,
+        async_impl::shortcut_request(url, wreq::Method::TRACE, kwds),
+    )
 }
 
 /// Make a request with the given parameters.
@@ -121,11 +156,11 @@ fn trace(
 #[pyo3(signature = (method, url, **kwds))]
 fn request(
     py: Python<'_>,
-    method: Method,
+    method: MethodExtractor,
     url: PyBackedStr,
     kwds: Option<RequestParams>,
 ) -> PyResult<Bound<'_, PyAny>> {
-    future_into_py(py, async_impl::shortcut_request(url, method, kwds))
+    future_into_py(py, async_impl::shortcut_request(url, method.0, kwds))
 }
 
 /// Make a WebSocket connection with the given parameters.
@@ -142,6 +177,7 @@ fn websocket(
 #[pymodule(gil_used = false)]
 fn rnet(m: &Bound<'_, PyModule>) -> PyResult<()> {
     pyo3::prepare_freethreaded_python();
+    logging::init();
 
     m.add_class::<Method>()?;
     m.add_class::<Version>()?;
@@ -157,27 +193,48 @@ fn rnet(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Proxy>()?;
     m.add_class::<LookupIpStrategy>()?;
     m.add_class::<Message>()?;
+    m.add_class::<MessageType>()?;
     m.add_class::<StatusCode>()?;
+    m.add_class::<Url>()?;
     m.add_class::<Part>()?;
     m.add_class::<Multipart>()?;
 
     m.add_class::<SameSite>()?;
     m.add_class::<Cookie>()?;
+    m.add_class::<CookieJar>()?;
 
+    m.add_class::<Cache>()?;
+    m.add_class::<MockTransport>()?;
+    m.add_class::<OAuth2ClientCredentials>()?;
     m.add_class::<Client>()?;
+    m.add_class::<Connection>()?;
+    m.add_class::<TlsFingerprint>()?;
     m.add_class::<Response>()?;
     m.add_class::<WebSocket>()?;
     m.add_class::<Streamer>()?;
+    m.add_class::<JsonItems>()?;
+    m.add_class::<Frames>()?;
+    m.add_class::<Events>()?;
+    m.add_class::<Event>()?;
+    m.add_class::<Parts>()?;
+    m.add_class::<BytesBuffer>()?;
+    m.add_class::<Tunnel>()?;
     m.add_class::<BlockingClient>()?;
     m.add_class::<BlockingResponse>()?;
     m.add_class::<BlockingWebSocket>()?;
     m.add_class::<BlockingStreamer>()?;
+    m.add_class::<BlockingParts>()?;
 
     let py = m.py();
     m.add("DNSResolverError", py.get_type::<DNSResolverError>())?;
     m.add("BodyError", py.get_type::<BodyError>())?;
+    m.add(
+        "ResponseTooLargeError",
+        py.get_type::<ResponseTooLargeError>(),
+    )?;
     m.add("BuilderError", py.get_type::<BuilderError>())?;
     m.add("ConnectionError", py.get_type::<ConnectionError>())?;
+    m.add("ProxyError", py.get_type::<ProxyError>())?;
     m.add(
         "ConnectionResetError",
         py.get_type::<ConnectionResetError>(),
@@ -187,9 +244,13 @@ fn rnet(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add("TimeoutError", py.get_type::<TimeoutError>())?;
     m.add("StatusError", py.get_type::<StatusError>())?;
     m.add("RequestError", py.get_type::<RequestError>())?;
+    m.add("Http2Error", py.get_type::<Http2Error>())?;
+    m.add("TlsError", py.get_type::<TlsError>())?;
+    m.add("CertificateError", py.get_type::<CertificateError>())?;
     m.add("UpgradeError", py.get_type::<UpgradeError>())?;
     m.add("URLParseError", py.get_type::<URLParseError>())?;
     m.add("MIMEParseError", py.get_type::<MIMEParseError>())?;
+    error::init_request_error_fields(py)?;
 
     m.add_function(wrap_pyfunction!(get, m)?)?;
     m.add_function(wrap_pyfunction!(post, m)?)?;
@@ -201,6 +262,10 @@ fn rnet(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(trace, m)?)?;
     m.add_function(wrap_pyfunction!(request, m)?)?;
     m.add_function(wrap_pyfunction!(websocket, m)?)?;
+    m.add_function(wrap_pyfunction!(runtime::configure_runtime, m)?)?;
+    m.add_function(wrap_pyfunction!(logging::set_log_level, m)?)?;
+    m.add_function(wrap_pyfunction!(async_impl::strict_resources, m)?)?;
+    m.add_function(wrap_pyfunction!(async_impl::set_trust_env_default, m)?)?;
 
     Ok(())
 }
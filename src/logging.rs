@@ -0,0 +1,118 @@
+use crate::runtime;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use wreq::Url;
+
+/// The environment variable that sets the log level when rnet is imported.
+const LOG_ENV: &str = "RNET_LOG";
+
+static LOGGER: PythonLogger = PythonLogger;
+
+/// Forwards the `log` records of rnet and the crates it uses, such as DNS lookups,
+/// connects and TLS handshakes in wreq, to Python's `logging`.
+struct PythonLogger;
+
+impl Log for PythonLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // Records come from runtime threads, which may outlive the interpreter.
+        runtime::with_live_gil(|py| {
+            // A failing handler must not fail the request that logged.
+            let _ = forward(py, record);
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+fn forward(py: Python<'_>, record: &Record) -> PyResult<()> {
+    let level = match record.level() {
+        Level::Error => 40,
+        Level::Warn => 30,
+        Level::Info => 20,
+        Level::Debug => 10,
+        Level::Trace => 5,
+    };
+    let logger = py
+        .import("logging")?
+        .call_method1("getLogger", (logger_name(record.target()),))?;
+    if logger.call_method1("isEnabledFor", (level,))?.is_truthy()? {
+        logger.call_method1("log", (level, record.args().to_string()))?;
+    }
+    Ok(())
+}
+
+/// Names the Python logger after the module a record comes from, with the crates rnet
+/// uses under `rnet`, as in `rnet.async_impl.request` or `rnet.newwreq.connect`.
+fn logger_name(target: &str) -> String {
+    let name = target.replace("::", ".");
+    if name == "rnet" || name.starts_with("rnet.") {
+        name
+    } else {
+        format!("rnet.{name}")
+    }
+}
+
+/// Returns `url` without its credentials, query and fragment, which may hold secrets, for
+/// logging.
+pub fn redact(url: &Url) -> Url {
+    let mut url = url.clone();
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    url.set_query(None);
+    url.set_fragment(None);
+    url
+}
+
+/// Returns the message of `err` with the URL it mentions redacted.
+pub fn redact_error(err: &wreq::Error) -> String {
+    let message = err.to_string();
+    match err.url() {
+        Some(url) => message.replace(url.as_str(), redact(url).as_str()),
+        None => message,
+    }
+}
+
+fn parse_level(level: &str) -> PyResult<LevelFilter> {
+    if level.eq_ignore_ascii_case("warning") {
+        return Ok(LevelFilter::Warn);
+    }
+    level.parse().map_err(|_| {
+        PyValueError::new_err(format!(
+            "invalid log level {level:?}; expected off, error, warn, info, debug or trace"
+        ))
+    })
+}
+
+/// Installs the logger, at the level in the `RNET_LOG` environment variable or off.
+pub fn init() {
+    if log::set_logger(&LOGGER).is_err() {
+        return;
+    }
+    let level = std::env::var(LOG_ENV)
+        .ok()
+        .and_then(|level| parse_level(&level).ok())
+        .unwrap_or(LevelFilter::Off);
+    log::set_max_level(level);
+}
+
+/// Sets the level of the logs rnet sends to Python's `logging`, or turns them off.
+///
+/// Records below the level are dropped before they reach Python, so logging stays free
+/// while it's off; the `rnet` loggers still filter what they are sent.
+#[pyfunction]
+#[pyo3(signature = (level = None))]
+pub fn set_log_level(level: Option<&str>) -> PyResult<()> {
+    let level = match level {
+        Some(level) => parse_level(level)?,
+        None => LevelFilter::Off,
+    };
+    log::set_max_level(level);
+    Ok(())
+}
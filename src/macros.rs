@@ -92,7 +92,18 @@ macro_rules! define_enum_with_conversion {
             $($rust_variant),*
         }
 
+        #[allow(dead_code)]
         impl $enum_type {
+            /// All variants, in declaration order.
+            pub const VARIANTS: &'static [$enum_type] = &[$(<$enum_type>::$rust_variant),*];
+
+            /// Returns the variant name as declared.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(<$enum_type>::$rust_variant => stringify!($rust_variant),)*
+                }
+            }
+
             pub const fn into_ffi(self) -> $ffi_type {
                 match self {
                     $(<$enum_type>::$rust_variant => <$ffi_type>::$ffi_variant,)*
@@ -116,4 +127,58 @@ macro_rules! extract_option {
             $params.$field = value.extract()?;
         }
     };
+    ($ob:expr, $params:expr, $field:ident, alias = $alias:ident) => {
+        if let Some(value) = $ob.get_aliased(stringify!($field), stringify!($alias))? {
+            $params.$field = Some(value);
+        }
+    };
+}
+
+macro_rules! apply_local_address {
+    ($builder:expr, $option:expr, $interface:expr) => {
+        if let Some(addr) = $option.take() {
+            // A scoped IPv6 address can only be bound on the interface its zone names,
+            // unless an interface is set explicitly.
+            if let Some(zone) = addr.zone.filter(|_| $interface.is_none()) {
+                $interface = Some(crate::interface::zone_interface(&zone)?);
+            }
+            $builder = $builder.local_addresses(addr.ipv4, addr.ipv6);
+        }
+    };
+}
+
+macro_rules! apply_interface {
+    ($builder:expr, $option:expr) => {
+        if let Some(interface) = $option.take() {
+            // Bind to the device itself where the OS supports it.
+            #[cfg(any(
+                target_os = "android",
+                target_os = "fuchsia",
+                target_os = "linux",
+                target_os = "ios",
+                target_os = "visionos",
+                target_os = "macos",
+                target_os = "tvos",
+                target_os = "watchos"
+            ))]
+            {
+                $builder = $builder.interface(interface);
+            }
+            // Otherwise bind to the interface's primary addresses.
+            #[cfg(not(any(
+                target_os = "android",
+                target_os = "fuchsia",
+                target_os = "linux",
+                target_os = "ios",
+                target_os = "visionos",
+                target_os = "macos",
+                target_os = "tvos",
+                target_os = "watchos"
+            )))]
+            {
+                let (ipv4, ipv6) = crate::interface::resolve(&interface)?;
+                $builder = $builder.local_addresses(ipv4, ipv6);
+            }
+        }
+    };
 }
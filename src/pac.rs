@@ -0,0 +1,975 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    net::{IpAddr, Ipv4Addr, ToSocketAddrs, UdpSocket},
+};
+use wreq::Url;
+
+/// The deepest the functions of a script may call each other, so that recursion ends.
+const MAX_DEPTH: usize = 64;
+
+/// Names that can't be used as variables.
+const KEYWORDS: &[&str] = &[
+    "function",
+    "if",
+    "else",
+    "return",
+    "var",
+    "let",
+    "const",
+    "true",
+    "false",
+    "null",
+    "undefined",
+];
+
+/// JavaScript that is valid in a PAC script but not understood here.
+const UNSUPPORTED: &[&str] = &[
+    "for", "while", "do", "switch", "try", "throw", "new", "break", "continue", "typeof",
+];
+
+/// The standard PAC helpers, apart from the date and time ones.
+const BUILTINS: &[&str] = &[
+    "isPlainHostName",
+    "dnsDomainIs",
+    "localHostOrDomainIs",
+    "isResolvable",
+    "isInNet",
+    "dnsResolve",
+    "myIpAddress",
+    "dnsDomainLevels",
+    "shExpMatch",
+    "convert_addr",
+    "alert",
+];
+
+/// The string methods scripts may call.
+const METHODS: &[&str] = &[
+    "toLowerCase",
+    "toUpperCase",
+    "trim",
+    "indexOf",
+    "lastIndexOf",
+    "includes",
+    "startsWith",
+    "endsWith",
+    "charAt",
+    "substring",
+    "substr",
+    "slice",
+];
+
+const PUNCTUATION: &[&str] = &[
+    "===", "!==", "==", "!=", "<=", ">=", "&&", "||", "+=", "(", ")", "{", "}", ",", ";", ".", "=",
+    "!", "<", ">", "+", "-", "?", ":",
+];
+
+/// A proxy auto-config (PAC) script, which picks the proxy for each request with its
+/// `FindProxyForURL(url, host)` function.
+///
+/// Only the subset of JavaScript PAC scripts are commonly written in is understood:
+/// functions, `var`, `let` and `const`, assignments, `if`/`else` and `return`, with string,
+/// number and boolean values, the usual operators, the standard helpers other than the
+/// date and time ones, and a few string methods. Scripts using anything else, such as
+/// loops, are rejected when parsed.
+pub struct Pac {
+    functions: HashMap<String, Function>,
+    globals: HashMap<String, Value>,
+}
+
+struct Function {
+    params: Vec<String>,
+    body: Vec<Stmt>,
+}
+
+impl Pac {
+    /// Parses `source` and runs its top-level statements.
+    pub fn parse(source: &str) -> Result<Pac, String> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+            calls: Vec::new(),
+        };
+        let mut functions = HashMap::new();
+        let mut init = Vec::new();
+        while parser.peek().is_some() {
+            if parser.eat_keyword("function") {
+                let name = parser.ident()?;
+                parser.expect("(")?;
+                let mut params = Vec::new();
+                if !parser.eat(")") {
+                    loop {
+                        params.push(parser.ident()?);
+                        if parser.eat(")") {
+                            break;
+                        }
+                        parser.expect(",")?;
+                    }
+                }
+                parser.expect("{")?;
+                let body = parser.block()?;
+                functions.insert(name, Function { params, body });
+            } else {
+                init.push(parser.statement()?);
+            }
+        }
+
+        if !functions.contains_key("FindProxyForURL") {
+            return Err("the script doesn't define FindProxyForURL".to_owned());
+        }
+        if let Some(name) = parser
+            .calls
+            .iter()
+            .find(|name| !functions.contains_key(*name) && !BUILTINS.contains(&name.as_str()))
+        {
+            return Err(format!("unsupported function {name}"));
+        }
+
+        let mut pac = Pac {
+            functions,
+            globals: HashMap::new(),
+        };
+        let mut globals = HashMap::new();
+        pac.exec(&init, &mut globals, 0)?;
+        pac.globals = globals;
+        Ok(pac)
+    }
+
+    /// Returns the proxy to send a request to `url` through, or `None` to send it direct.
+    ///
+    /// The first proxy the script returns is used, since a failed proxy can't fall back to
+    /// the next one. A script that fails, or returns nothing usable, is an error rather
+    /// than a reason to go direct.
+    pub fn find_proxy(&self, url: &Url) -> Result<Option<String>, String> {
+        let Some(host) = url.host_str() else {
+            return Ok(None);
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        first_proxy(&self.evaluate(url.as_str(), host)?)
+    }
+
+    /// Returns what `FindProxyForURL(url, host)` returns, such as `"PROXY proxy:8080; DIRECT"`.
+    pub fn evaluate(&self, url: &str, host: &str) -> Result<String, String> {
+        let args = vec![Value::Str(url.to_owned()), Value::Str(host.to_owned())];
+        Ok(self.call("FindProxyForURL", args, 0)?.to_str())
+    }
+
+    fn call(&self, name: &str, args: Vec<Value>, depth: usize) -> Result<Value, String> {
+        let Some(function) = self.functions.get(name) else {
+            return builtin(name, &args);
+        };
+        if depth >= MAX_DEPTH {
+            return Err("too much recursion".to_owned());
+        }
+
+        let mut locals = function
+            .params
+            .iter()
+            .cloned()
+            .zip(args.into_iter().chain(std::iter::repeat(Value::Undefined)))
+            .collect();
+        Ok(self
+            .exec(&function.body, &mut locals, depth + 1)?
+            .unwrap_or(Value::Undefined))
+    }
+
+    /// Runs `stmts`, returning the value of the `return` that ends them, if one does.
+    fn exec(
+        &self,
+        stmts: &[Stmt],
+        locals: &mut HashMap<String, Value>,
+        depth: usize,
+    ) -> Result<Option<Value>, String> {
+        for stmt in stmts {
+            if let Some(value) = self.exec_one(stmt, locals, depth)? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    fn exec_one(
+        &self,
+        stmt: &Stmt,
+        locals: &mut HashMap<String, Value>,
+        depth: usize,
+    ) -> Result<Option<Value>, String> {
+        match stmt {
+            Stmt::Var(name, None) => {
+                locals.entry(name.clone()).or_insert(Value::Undefined);
+            }
+            Stmt::Var(name, Some(value)) => {
+                let value = self.eval(value, locals, depth)?;
+                locals.insert(name.clone(), value);
+            }
+            Stmt::Assign(name, append, value) => {
+                let mut value = self.eval(value, locals, depth)?;
+                if *append {
+                    value = add(self.lookup(name, locals), value);
+                }
+                locals.insert(name.clone(), value);
+            }
+            Stmt::Expr(expr) => {
+                self.eval(expr, locals, depth)?;
+            }
+            Stmt::If(cond, then, otherwise) => {
+                if self.eval(cond, locals, depth)?.truthy() {
+                    return self.exec_one(then, locals, depth);
+                }
+                if let Some(otherwise) = otherwise {
+                    return self.exec_one(otherwise, locals, depth);
+                }
+            }
+            Stmt::Block(stmts) => return self.exec(stmts, locals, depth),
+            Stmt::Return(value) => {
+                let value = match value {
+                    Some(value) => self.eval(value, locals, depth)?,
+                    None => Value::Undefined,
+                };
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    fn lookup(&self, name: &str, locals: &HashMap<String, Value>) -> Value {
+        locals
+            .get(name)
+            .or_else(|| self.globals.get(name))
+            .cloned()
+            .unwrap_or(Value::Undefined)
+    }
+
+    fn eval(
+        &self,
+        expr: &Expr,
+        locals: &mut HashMap<String, Value>,
+        depth: usize,
+    ) -> Result<Value, String> {
+        Ok(match expr {
+            Expr::Value(value) => value.clone(),
+            Expr::Var(name) => self.lookup(name, locals),
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg, locals, depth))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(name, args, depth)?
+            }
+            Expr::Method(object, name, args) => {
+                let object = self.eval(object, locals, depth)?;
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg, locals, depth))
+                    .collect::<Result<Vec<_>, _>>()?;
+                string_method(&object.to_str(), name, &args)
+            }
+            Expr::Length(object) => {
+                Value::Num(self.eval(object, locals, depth)?.to_str().chars().count() as f64)
+            }
+            Expr::Not(value) => Value::Bool(!self.eval(value, locals, depth)?.truthy()),
+            Expr::Neg(value) => Value::Num(-self.eval(value, locals, depth)?.to_num()),
+            Expr::And(left, right) => {
+                let left = self.eval(left, locals, depth)?;
+                if left.truthy() {
+                    self.eval(right, locals, depth)?
+                } else {
+                    left
+                }
+            }
+            Expr::Or(left, right) => {
+                let left = self.eval(left, locals, depth)?;
+                if left.truthy() {
+                    left
+                } else {
+                    self.eval(right, locals, depth)?
+                }
+            }
+            Expr::Cond(cond, then, otherwise) => {
+                if self.eval(cond, locals, depth)?.truthy() {
+                    self.eval(then, locals, depth)?
+                } else {
+                    self.eval(otherwise, locals, depth)?
+                }
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left, locals, depth)?;
+                let right = self.eval(right, locals, depth)?;
+                binary(*op, left, right)
+            }
+        })
+    }
+}
+
+/// Returns the proxy URL for the first usable directive of a `FindProxyForURL` result,
+/// such as `"PROXY proxy:8080; DIRECT"`, or `None` for `DIRECT`.
+fn first_proxy(result: &str) -> Result<Option<String>, String> {
+    for directive in result.split(';') {
+        let mut parts = directive.split_whitespace();
+        let (Some(kind), Some(address)) = (parts.next(), parts.next()) else {
+            if directive.trim().eq_ignore_ascii_case("DIRECT") {
+                return Ok(None);
+            }
+            continue;
+        };
+        let scheme = match kind.to_ascii_uppercase().as_str() {
+            "PROXY" | "HTTP" => "http",
+            "HTTPS" => "https",
+            "SOCKS" | "SOCKS5" => "socks5",
+            "SOCKS4" => "socks4",
+            _ => continue,
+        };
+        return Ok(Some(format!("{scheme}://{address}")));
+    }
+    Err(format!(
+        "FindProxyForURL returned {result:?}, which is neither a proxy nor DIRECT"
+    ))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    Null,
+    Undefined,
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Str(s) => !s.is_empty(),
+            Value::Num(n) => *n != 0.0 && !n.is_nan(),
+            Value::Bool(b) => *b,
+            Value::Null | Value::Undefined => false,
+        }
+    }
+
+    fn to_str(&self) -> String {
+        match self {
+            Value::Str(s) => s.clone(),
+            Value::Num(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", *n as i64),
+            Value::Num(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Null => "null".to_owned(),
+            Value::Undefined => "undefined".to_owned(),
+        }
+    }
+
+    fn to_num(&self) -> f64 {
+        match self {
+            Value::Str(s) if s.trim().is_empty() => 0.0,
+            Value::Str(s) => s.trim().parse().unwrap_or(f64::NAN),
+            Value::Num(n) => *n,
+            Value::Bool(b) => f64::from(u8::from(*b)),
+            Value::Null => 0.0,
+            Value::Undefined => f64::NAN,
+        }
+    }
+
+    /// Compares like `==`, converting to numbers unless both sides are strings.
+    fn loose_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Null | Value::Undefined, Value::Null | Value::Undefined) => true,
+            (Value::Null | Value::Undefined, _) | (_, Value::Null | Value::Undefined) => false,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            _ => self.to_num() == other.to_num(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum BinOp {
+    Add,
+    Sub,
+    Eq,
+    Ne,
+    StrictEq,
+    StrictNe,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+}
+
+fn add(left: Value, right: Value) -> Value {
+    match (&left, &right) {
+        (Value::Str(_), _) | (_, Value::Str(_)) => Value::Str(left.to_str() + &right.to_str()),
+        _ => Value::Num(left.to_num() + right.to_num()),
+    }
+}
+
+fn binary(op: BinOp, left: Value, right: Value) -> Value {
+    // Strings compare by their characters, anything else as numbers.
+    let ordering = || match (&left, &right) {
+        (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+        _ => left.to_num().partial_cmp(&right.to_num()),
+    };
+    match op {
+        BinOp::Add => add(left, right),
+        BinOp::Sub => Value::Num(left.to_num() - right.to_num()),
+        BinOp::Eq => Value::Bool(left.loose_eq(&right)),
+        BinOp::Ne => Value::Bool(!left.loose_eq(&right)),
+        BinOp::StrictEq => Value::Bool(left == right),
+        BinOp::StrictNe => Value::Bool(left != right),
+        BinOp::Lt => Value::Bool(ordering() == Some(Ordering::Less)),
+        BinOp::Gt => Value::Bool(ordering() == Some(Ordering::Greater)),
+        BinOp::Le => Value::Bool(matches!(ordering(), Some(Ordering::Less | Ordering::Equal))),
+        BinOp::Ge => Value::Bool(matches!(
+            ordering(),
+            Some(Ordering::Greater | Ordering::Equal)
+        )),
+    }
+}
+
+fn builtin(name: &str, args: &[Value]) -> Result<Value, String> {
+    let arg = |i: usize| {
+        args.get(i)
+            .map_or_else(|| "undefined".to_owned(), Value::to_str)
+    };
+    Ok(match name {
+        "isPlainHostName" => Value::Bool(!arg(0).contains('.')),
+        "dnsDomainIs" => Value::Bool(
+            arg(0)
+                .to_ascii_lowercase()
+                .ends_with(&arg(1).to_ascii_lowercase()),
+        ),
+        "localHostOrDomainIs" => {
+            let (host, domain) = (arg(0).to_ascii_lowercase(), arg(1).to_ascii_lowercase());
+            let matches = host == domain
+                || (!host.contains('.')
+                    && domain
+                        .strip_prefix(&host)
+                        .is_some_and(|rest| rest.starts_with('.')));
+            Value::Bool(matches)
+        }
+        "dnsDomainLevels" => Value::Num(arg(0).matches('.').count() as f64),
+        "shExpMatch" => Value::Bool(sh_exp_match(arg(1).as_bytes(), arg(0).as_bytes())),
+        "isResolvable" => Value::Bool(resolve(&arg(0)).is_some()),
+        "dnsResolve" => resolve(&arg(0)).map_or(Value::Null, |ip| Value::Str(ip.to_string())),
+        "isInNet" => Value::Bool(is_in_net(&arg(0), &arg(1), &arg(2))),
+        "myIpAddress" => Value::Str(my_ip_address().to_string()),
+        "convert_addr" => arg(0)
+            .parse::<Ipv4Addr>()
+            .map_or(Value::Null, |ip| Value::Num(f64::from(u32::from(ip)))),
+        "alert" => Value::Undefined,
+        _ => return Err(format!("unsupported function {name}")),
+    })
+}
+
+fn string_method(s: &str, name: &str, args: &[Value]) -> Value {
+    let chars = s.chars().collect::<Vec<_>>();
+    let len = chars.len();
+    let text = |i: usize| {
+        args.get(i)
+            .map_or_else(|| "undefined".to_owned(), Value::to_str)
+    };
+    // A position clamped to the string, counted from its end if negative and `relative`.
+    let position = |i: usize, default: usize, relative: bool| {
+        let Some(n) = args.get(i).map(Value::to_num).filter(|n| !n.is_nan()) else {
+            return default;
+        };
+        let n = if relative && n < 0.0 {
+            len as f64 + n
+        } else {
+            n
+        };
+        n.trunc().clamp(0.0, len as f64) as usize
+    };
+    let slice =
+        |start: usize, end: usize| Value::Str(chars[start..end.max(start)].iter().collect());
+    let index_of =
+        |found: Option<usize>| Value::Num(found.map_or(-1.0, |i| s[..i].chars().count() as f64));
+
+    match name {
+        "toLowerCase" => Value::Str(s.to_lowercase()),
+        "toUpperCase" => Value::Str(s.to_uppercase()),
+        "trim" => Value::Str(s.trim().to_owned()),
+        "indexOf" => index_of(s.find(text(0).as_str())),
+        "lastIndexOf" => index_of(s.rfind(text(0).as_str())),
+        "includes" => Value::Bool(s.contains(text(0).as_str())),
+        "startsWith" => Value::Bool(s.starts_with(text(0).as_str())),
+        "endsWith" => Value::Bool(s.ends_with(text(0).as_str())),
+        "charAt" => {
+            let i = position(0, 0, false);
+            slice(i, (i + 1).min(len))
+        }
+        "substring" => {
+            let (start, end) = (position(0, 0, false), position(1, len, false));
+            slice(start.min(end), start.max(end))
+        }
+        "substr" => {
+            let start = position(0, 0, true);
+            let count = args
+                .get(1)
+                .map(Value::to_num)
+                .filter(|n| !n.is_nan())
+                .map_or(len, |n| n.max(0.0) as usize);
+            slice(start, start.saturating_add(count).min(len))
+        }
+        "slice" => slice(position(0, 0, true), position(1, len, true)),
+        _ => Value::Undefined,
+    }
+}
+
+/// Matches `text` against a shell expression, where `*` matches any run of characters and
+/// `?` any single one.
+fn sh_exp_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Where to resume after the last `*` if the rest doesn't match.
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, start)) => {
+                    p = star + 1;
+                    t = start + 1;
+                    backtrack = Some((star, start + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&b| b == b'*')
+}
+
+/// Resolves `host` to an address, preferring IPv4 as PAC scripts expect.
+///
+/// The lookup blocks, so on a runtime worker the other tasks are moved off it first.
+fn resolve(host: &str) -> Option<IpAddr> {
+    if let Ok(ip) = host.parse() {
+        return Some(ip);
+    }
+    let lookup = || {
+        let addrs = (host, 0).to_socket_addrs().ok()?.collect::<Vec<_>>();
+        addrs
+            .iter()
+            .find(|addr| addr.is_ipv4())
+            .or(addrs.first())
+            .map(|addr| addr.ip())
+    };
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(lookup)
+        }
+        _ => lookup(),
+    }
+}
+
+fn is_in_net(host: &str, pattern: &str, mask: &str) -> bool {
+    let (Some(IpAddr::V4(ip)), Ok(pattern), Ok(mask)) = (
+        resolve(host),
+        pattern.parse::<Ipv4Addr>(),
+        mask.parse::<Ipv4Addr>(),
+    ) else {
+        return false;
+    };
+    let mask = u32::from(mask);
+    u32::from(ip) & mask == u32::from(pattern) & mask
+}
+
+/// Returns the address of the interface the default route goes through, found by
+/// connecting a UDP socket, which sends nothing.
+fn my_ip_address() -> IpAddr {
+    UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .and_then(|socket| {
+            socket.connect((Ipv4Addr::new(192, 0, 2, 1), 53))?;
+            socket.local_addr()
+        })
+        .map_or(IpAddr::V4(Ipv4Addr::LOCALHOST), |addr| addr.ip())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Punct(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "`{name}`"),
+            Token::Str(_) => f.write_str("a string"),
+            Token::Num(_) => f.write_str("a number"),
+            Token::Punct(punct) => write!(f, "`{punct}`"),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    loop {
+        rest = rest.trim_start();
+        let Some(c) = rest.chars().next() else {
+            return Ok(tokens);
+        };
+
+        if let Some(comment) = rest.strip_prefix("//") {
+            rest = comment.find('\n').map_or("", |end| &comment[end..]);
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment.find("*/").ok_or("unterminated comment")?;
+            rest = &comment[end + 2..];
+        } else if c == '"' || c == '\'' {
+            let (value, len) = read_string(rest, c)?;
+            tokens.push(Token::Str(value));
+            rest = &rest[len..];
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.'))
+                .unwrap_or(rest.len());
+            let number = rest[..len]
+                .parse()
+                .map_err(|_| format!("invalid number {:?}", &rest[..len]))?;
+            tokens.push(Token::Num(number));
+            rest = &rest[len..];
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..len].to_owned()));
+            rest = &rest[len..];
+        } else {
+            let punct = PUNCTUATION
+                .iter()
+                .find(|punct| rest.starts_with(**punct))
+                .ok_or_else(|| format!("unexpected character {c:?}"))?;
+            tokens.push(Token::Punct(punct));
+            rest = &rest[punct.len()..];
+        }
+    }
+}
+
+/// Reads the string literal at the start of `source`, returning its value and length.
+fn read_string(source: &str, quote: char) -> Result<(String, usize), String> {
+    let mut value = String::new();
+    let mut chars = source.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            _ if c == quote => return Ok((value, i + c.len_utf8())),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, 'r')) => value.push('\r'),
+                Some((_, c)) => value.push(c),
+                None => break,
+            },
+            '\n' => break,
+            c => value.push(c),
+        }
+    }
+    Err("unterminated string".to_owned())
+}
+
+enum Stmt {
+    Var(String, Option<Expr>),
+    /// An assignment, which appends with `+=`.
+    Assign(String, bool, Expr),
+    Expr(Expr),
+    If(Expr, Box<Stmt>, Option<Box<Stmt>>),
+    Block(Vec<Stmt>),
+    Return(Option<Expr>),
+}
+
+enum Expr {
+    Value(Value),
+    Var(String),
+    Call(String, Vec<Expr>),
+    Method(Box<Expr>, String, Vec<Expr>),
+    Length(Box<Expr>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Cond(Box<Expr>, Box<Expr>, Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// The functions called, checked once all of them are defined.
+    calls: Vec<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Punct(p)) if *p == punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{punct}`")))
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Ident(name)) if name == keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Ident(name)) if !KEYWORDS.contains(&name.as_str()) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(self.unexpected("a name")),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> String {
+        match self.peek() {
+            Some(token) => format!("expected {expected}, found {token}"),
+            None => format!("expected {expected}, found the end of the script"),
+        }
+    }
+
+    /// Parses the statements of a block up to its closing `}`.
+    fn block(&mut self) -> Result<Vec<Stmt>, String> {
+        let mut stmts = Vec::new();
+        while !self.eat("}") {
+            if self.peek().is_none() {
+                return Err(self.unexpected("`}`"));
+            }
+            stmts.push(self.statement()?);
+        }
+        Ok(stmts)
+    }
+
+    fn statement(&mut self) -> Result<Stmt, String> {
+        if let Some(Token::Ident(name)) = self.peek() {
+            if UNSUPPORTED.contains(&name.as_str()) || name == "function" {
+                return Err(format!("`{name}` is not supported in PAC scripts"));
+            }
+        }
+
+        if self.eat("{") {
+            return self.block().map(Stmt::Block);
+        }
+        if self.eat(";") {
+            return Ok(Stmt::Block(Vec::new()));
+        }
+        if self.eat_keyword("if") {
+            self.expect("(")?;
+            let cond = self.expression()?;
+            self.expect(")")?;
+            let then = Box::new(self.statement()?);
+            let otherwise = if self.eat_keyword("else") {
+                Some(Box::new(self.statement()?))
+            } else {
+                None
+            };
+            return Ok(Stmt::If(cond, then, otherwise));
+        }
+        if self.eat_keyword("return") {
+            let value = match self.peek() {
+                None | Some(Token::Punct(";" | "}")) => None,
+                Some(_) => Some(self.expression()?),
+            };
+            self.eat(";");
+            return Ok(Stmt::Return(value));
+        }
+        if self.eat_keyword("var") || self.eat_keyword("let") || self.eat_keyword("const") {
+            let mut decls = Vec::new();
+            loop {
+                let name = self.ident()?;
+                let value = if self.eat("=") {
+                    Some(self.expression()?)
+                } else {
+                    None
+                };
+                decls.push(Stmt::Var(name, value));
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.eat(";");
+            return Ok(Stmt::Block(decls));
+        }
+
+        let assignment = match self.tokens.get(self.pos + 1) {
+            Some(Token::Punct("=")) => Some(false),
+            Some(Token::Punct("+=")) => Some(true),
+            _ => None,
+        };
+        let stmt = match assignment {
+            Some(append) => {
+                let name = self.ident()?;
+                self.pos += 1;
+                Stmt::Assign(name, append, self.expression()?)
+            }
+            None => Stmt::Expr(self.expression()?),
+        };
+        self.eat(";");
+        Ok(stmt)
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        let cond = self.or()?;
+        if !self.eat("?") {
+            return Ok(cond);
+        }
+        let then = self.expression()?;
+        self.expect(":")?;
+        let otherwise = self.expression()?;
+        Ok(Expr::Cond(
+            Box::new(cond),
+            Box::new(then),
+            Box::new(otherwise),
+        ))
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat("||") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.binary(0)?;
+        while self.eat("&&") {
+            left = Expr::And(Box::new(left), Box::new(self.binary(0)?));
+        }
+        Ok(left)
+    }
+
+    /// Parses the binary operators from precedence `level` up: equality, then relational,
+    /// then additive ones.
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        const LEVELS: &[&[(&str, BinOp)]] = &[
+            &[
+                ("===", BinOp::StrictEq),
+                ("!==", BinOp::StrictNe),
+                ("==", BinOp::Eq),
+                ("!=", BinOp::Ne),
+            ],
+            &[
+                ("<=", BinOp::Le),
+                (">=", BinOp::Ge),
+                ("<", BinOp::Lt),
+                (">", BinOp::Gt),
+            ],
+            &[("+", BinOp::Add), ("-", BinOp::Sub)],
+        ];
+        let Some(ops) = LEVELS.get(level) else {
+            return self.unary();
+        };
+
+        let mut left = self.binary(level + 1)?;
+        'outer: loop {
+            for (punct, op) in *ops {
+                if self.eat(punct) {
+                    let right = self.binary(level + 1)?;
+                    left = Expr::Binary(*op, Box::new(left), Box::new(right));
+                    continue 'outer;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+
+        let mut expr = self.primary()?;
+        while self.eat(".") {
+            let name = self.ident()?;
+            if self.eat("(") {
+                if !METHODS.contains(&name.as_str()) {
+                    return Err(format!("unsupported method {name}"));
+                }
+                expr = Expr::Method(Box::new(expr), name, self.arguments()?);
+            } else if name == "length" {
+                expr = Expr::Length(Box::new(expr));
+            } else {
+                return Err(format!("unsupported property {name}"));
+            }
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.unexpected("an expression"));
+        };
+        let expr = match token {
+            Token::Str(s) => Expr::Value(Value::Str(s)),
+            Token::Num(n) => Expr::Value(Value::Num(n)),
+            Token::Punct("(") => {
+                self.pos += 1;
+                let expr = self.expression()?;
+                self.expect(")")?;
+                return Ok(expr);
+            }
+            Token::Ident(name) => match name.as_str() {
+                "true" => Expr::Value(Value::Bool(true)),
+                "false" => Expr::Value(Value::Bool(false)),
+                "null" => Expr::Value(Value::Null),
+                "undefined" => Expr::Value(Value::Undefined),
+                _ if UNSUPPORTED.contains(&name.as_str()) || name == "function" => {
+                    return Err(format!("`{name}` is not supported in PAC scripts"));
+                }
+                _ if KEYWORDS.contains(&name.as_str()) => {
+                    return Err(self.unexpected("an expression"));
+                }
+                _ if matches!(self.tokens.get(self.pos + 1), Some(Token::Punct("("))) => {
+                    self.pos += 2;
+                    self.calls.push(name.clone());
+                    return Ok(Expr::Call(name, self.arguments()?));
+                }
+                _ => Expr::Var(name),
+            },
+            Token::Punct(_) => return Err(self.unexpected("an expression")),
+        };
+        self.pos += 1;
+        Ok(expr)
+    }
+
+    /// Parses the arguments of a call after its opening `(`.
+    fn arguments(&mut self) -> Result<Vec<Expr>, String> {
+        let mut args = Vec::new();
+        if self.eat(")") {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.expression()?);
+            if self.eat(")") {
+                return Ok(args);
+            }
+            self.expect(",")?;
+        }
+    }
+}
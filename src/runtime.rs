@@ -0,0 +1,121 @@
+use crate::error::Error;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        OnceLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+use tokio::runtime::{Builder, Handle, Runtime};
+
+/// The runtime connections are driven by, recorded once the first one is made.
+static HANDLE: OnceLock<Handle> = OnceLock::new();
+
+/// Configures the global tokio runtime shared by the async and blocking APIs.
+///
+/// Must be called before the first request is made; the runtime is created lazily
+/// on first use and cannot be reconfigured afterwards.
+#[pyfunction]
+#[pyo3(signature = (worker_threads = None, max_blocking_threads = None, thread_name_prefix = None))]
+pub fn configure_runtime(
+    py: Python,
+    worker_threads: Option<usize>,
+    max_blocking_threads: Option<usize>,
+    thread_name_prefix: Option<String>,
+) -> PyResult<()> {
+    if worker_threads == Some(0) {
+        return Err(PyValueError::new_err(
+            "worker_threads must be greater than 0",
+        ));
+    }
+    if max_blocking_threads == Some(0) {
+        return Err(PyValueError::new_err(
+            "max_blocking_threads must be greater than 0",
+        ));
+    }
+
+    py.allow_threads(|| {
+        let mut builder = Builder::new_multi_thread();
+        builder.enable_all();
+        if let Some(worker_threads) = worker_threads {
+            builder.worker_threads(worker_threads);
+        }
+        if let Some(max_blocking_threads) = max_blocking_threads {
+            builder.max_blocking_threads(max_blocking_threads);
+        }
+        if let Some(prefix) = thread_name_prefix {
+            let id = AtomicUsize::new(0);
+            builder.thread_name_fn(move || {
+                format!("{}-{}", prefix, id.fetch_add(1, Ordering::Relaxed))
+            });
+        }
+
+        install(builder.build().map_err(Error::from)?)
+    })
+}
+
+fn install(runtime: Runtime) -> PyResult<()> {
+    let handle = runtime.handle().clone();
+    let runtime = Box::into_raw(Box::new(runtime));
+
+    // SAFETY: the pointer comes from `Box::into_raw` above and is never freed once installed.
+    if pyo3_async_runtimes::tokio::init_with_runtime(unsafe { &*runtime }).is_err() {
+        // SAFETY: the runtime was rejected, so no other reference to it exists.
+        drop(unsafe { Box::from_raw(runtime) });
+        return Err(Error::RuntimeInitialized.into());
+    }
+
+    let _ = HANDLE.set(handle);
+    Ok(())
+}
+
+/// Records the runtime the current task runs on, so objects holding its connections can be
+/// dropped within it later. Called where those objects are made.
+pub fn remember_current() {
+    if HANDLE.get().is_none() {
+        let _ = Handle::try_current().map(|handle| HANDLE.set(handle));
+    }
+}
+
+/// Runs `f` with the GIL, unless the interpreter has been finalized or is finalizing.
+///
+/// Runtime threads may call into Python while the interpreter shuts down, or after, where
+/// taking the GIL would fail or hang.
+pub fn with_live_gil(f: impl FnOnce(Python<'_>)) {
+    // SAFETY: `Py_IsInitialized` may be called at any time.
+    if unsafe { pyo3::ffi::Py_IsInitialized() } == 0 {
+        return;
+    }
+    Python::with_gil(|py| {
+        let finalizing = py
+            .import("sys")
+            .and_then(|sys| sys.call_method0("is_finalizing"))
+            .and_then(|finalizing| finalizing.is_truthy());
+        if !finalizing.unwrap_or(true) {
+            f(py);
+        }
+    });
+}
+
+/// Drops `value`, which may hold connections, within the tokio runtime.
+///
+/// Python objects are often collected outside of it, e.g. after the event loop has closed or
+/// at interpreter shutdown, where dropping a connection may need a runtime to spawn on. The
+/// drop is then handed to the runtime instead; if the runtime has shut down, it happens right
+/// away but still within its context. This never blocks, and a panic is contained so it
+/// can't escape into the garbage collector.
+pub fn drop_in_runtime<T: Send + 'static>(value: T) {
+    if Handle::try_current().is_ok() {
+        drop(value);
+        return;
+    }
+    let Some(handle) = HANDLE.get() else {
+        drop(value);
+        return;
+    };
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _guard = handle.enter();
+        drop(handle.spawn(async move { drop(value) }));
+    }));
+}
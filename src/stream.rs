@@ -1,10 +1,20 @@
-use bytes::Bytes;
-use futures_util::Stream;
+use bytes::{Bytes, BytesMut};
+use futures_util::{Stream, stream};
 use pyo3::{
     PyObject, PyResult, Python,
+    exceptions::PyRuntimeError,
     pybacked::{PyBackedBytes, PyBackedStr},
 };
-use std::{pin::Pin, task::Context};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+};
+use tokio::{io::AsyncReadExt, task::JoinHandle};
+
+/// The number of bytes requested from a file-like object per `read` call.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
 
 pub struct SyncStream {
     iter: PyObject,
@@ -14,6 +24,12 @@ pub struct AsyncStream {
     stream: Pin<Box<dyn Stream<Item = PyObject> + Send + Sync + 'static>>,
 }
 
+/// A stream over a file-like object, calling its `read(size)` on the blocking thread pool.
+pub struct ReadStream {
+    reader: Arc<PyObject>,
+    pending: Option<JoinHandle<PyResult<Option<Bytes>>>>,
+}
+
 impl SyncStream {
     #[inline]
     pub fn new(iter: PyObject) -> Self {
@@ -30,6 +46,54 @@ impl AsyncStream {
     }
 }
 
+impl ReadStream {
+    #[inline]
+    pub fn new(reader: PyObject) -> Self {
+        ReadStream {
+            reader: Arc::new(reader),
+            pending: None,
+        }
+    }
+}
+
+/// Returns a stream over the rest of `file`, read in chunks of [`READ_CHUNK_SIZE`].
+pub fn file_stream(file: tokio::fs::File) -> impl Stream<Item = std::io::Result<Bytes>> {
+    stream::try_unfold(file, |mut file| async move {
+        let mut chunk = BytesMut::with_capacity(READ_CHUNK_SIZE);
+        let read = file.read_buf(&mut chunk).await?;
+        Ok((read > 0).then(|| (chunk.freeze(), file)))
+    })
+}
+
+impl Stream for ReadStream {
+    type Item = PyResult<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.pending.is_none() {
+            let reader = self.reader.clone();
+            self.pending = Some(tokio::task::spawn_blocking(move || {
+                Python::with_gil(|py| {
+                    let chunk = reader.call_method1(py, "read", (READ_CHUNK_SIZE,))?;
+                    // An empty chunk signals end of file.
+                    extract_bytes(py, chunk).map(|chunk| (!chunk.is_empty()).then_some(chunk))
+                })
+            }));
+        }
+
+        let pending = self.pending.as_mut().expect("read task was just spawned");
+        let result = ready!(Pin::new(pending).poll(cx));
+        self.pending = None;
+
+        Poll::Ready(match result {
+            Ok(chunk) => chunk.transpose(),
+            Err(err) => Some(Err(PyRuntimeError::new_err(format!(
+                "Reading the body failed: {}",
+                err
+            )))),
+        })
+    }
+}
+
 impl Stream for SyncStream {
     type Item = PyResult<Bytes>;
 
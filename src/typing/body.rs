@@ -1,8 +1,15 @@
-use crate::stream::{AsyncStream, SyncStream};
+use crate::stream::{AsyncStream, ReadStream, SyncStream, file_stream};
 use bytes::Bytes;
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 use pyo3::pybacked::{PyBackedBytes, PyBackedStr};
 use pyo3::{FromPyObject, PyAny};
+use std::{
+    path::PathBuf,
+    pin::Pin,
+    task::{Context, Poll},
+};
 use wreq::Body;
 
 /// The body to use for the request.
@@ -11,6 +18,10 @@ pub enum BodyExtractor {
     Bytes(Bytes),
     SyncStream(SyncStream),
     AsyncStream(AsyncStream),
+    /// A file-like object, with the number of bytes left in it if it's seekable.
+    ReadStream(ReadStream, Option<u64>),
+    /// An opened file path, with its size if it's a regular file.
+    File(tokio::fs::File, Option<u64>),
 }
 
 impl From<BodyExtractor> for Body {
@@ -19,10 +30,73 @@ impl From<BodyExtractor> for Body {
             BodyExtractor::Text(bytes) | BodyExtractor::Bytes(bytes) => Body::from(bytes),
             BodyExtractor::SyncStream(stream) => Body::wrap_stream(stream),
             BodyExtractor::AsyncStream(stream) => Body::wrap_stream(stream),
+            BodyExtractor::ReadStream(stream, len) => {
+                SizedBody::wrap(Body::wrap_stream(stream), len)
+            }
+            BodyExtractor::File(file, len) => {
+                SizedBody::wrap(Body::wrap_stream(file_stream(file)), len)
+            }
         }
     }
 }
 
+/// A streaming body of a known length, which is sent with a `Content-Length` instead of
+/// chunked.
+struct SizedBody {
+    inner: Body,
+    len: u64,
+}
+
+impl SizedBody {
+    /// Wraps `inner` if its length is known.
+    fn wrap(inner: Body, len: Option<u64>) -> Body {
+        match len {
+            Some(len) => Body::wrap(SizedBody { inner, len }),
+            None => inner,
+        }
+    }
+}
+
+impl HttpBody for SizedBody {
+    type Data = Bytes;
+    type Error = wreq::Error;
+
+    #[inline]
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_frame(cx)
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::with_exact(self.len)
+    }
+}
+
+/// Returns how many bytes are left in a seekable binary file-like object, restoring its
+/// position afterwards, or `None` if that can't be told.
+fn remaining_len(ob: &Bound<'_, PyAny>) -> PyResult<Option<u64>> {
+    // Text files seek by opaque cookies rather than byte offsets.
+    let text_io = ob.py().import("io")?.getattr("TextIOBase")?;
+    if ob.is_instance(&text_io)? || !ob.hasattr("seekable")? || !ob.hasattr("tell")? {
+        return Ok(None);
+    }
+    if !ob.call_method0("seekable")?.is_truthy()? {
+        return Ok(None);
+    }
+    let pos = ob.call_method0("tell")?.extract::<u64>()?;
+    let end = ob.call_method1("seek", (0, 2))?.extract::<u64>()?;
+    ob.call_method1("seek", (pos,))?;
+    Ok(end.checked_sub(pos))
+}
+
 impl FromPyObject<'_> for BodyExtractor {
     fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
         if let Ok(text) = ob.extract::<PyBackedStr>() {
@@ -33,14 +107,33 @@ impl FromPyObject<'_> for BodyExtractor {
             return Ok(Self::Bytes(Bytes::from_owner(bytes)));
         }
 
+        if ob.is_instance(&ob.py().import("os")?.getattr("PathLike")?)? {
+            let path = ob.extract::<PathBuf>()?;
+            let file = std::fs::File::open(&path)?;
+            let metadata = file.metadata()?;
+            let len = metadata.is_file().then(|| metadata.len());
+            return Ok(Self::File(tokio::fs::File::from_std(file), len));
+        }
+
         if ob.hasattr("asend")? {
-            pyo3_async_runtimes::tokio::into_stream_v2(ob.to_owned())
+            return pyo3_async_runtimes::tokio::into_stream_v2(ob.to_owned())
                 .map(AsyncStream::new)
-                .map(Self::AsyncStream)
-        } else {
-            ob.extract::<PyObject>()
-                .map(SyncStream::new)
-                .map(Self::SyncStream)
+                .map(Self::AsyncStream);
+        }
+
+        if ob.hasattr("read")? {
+            let len = remaining_len(ob)?;
+            return Ok(Self::ReadStream(ReadStream::new(ob.clone().unbind()), len));
         }
+
+        if ob.hasattr("__next__")? {
+            return Ok(Self::SyncStream(SyncStream::new(ob.clone().unbind())));
+        }
+
+        Err(PyTypeError::new_err(format!(
+            "body: expected str, bytes, a path, a file-like object with read(), or a sync or \
+             async iterator of bytes, got {}",
+            ob.get_type().name()?
+        )))
     }
 }
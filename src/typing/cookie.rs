@@ -1,10 +1,14 @@
-use crate::error::Error;
+use crate::{
+    buffer::{HeaderValueBuffer, PyBufferProtocol},
+    error::Error,
+};
 use bytes::Bytes;
 use pyo3::FromPyObject;
 use pyo3::pybacked::PyBackedStr;
 use pyo3::{prelude::*, types::PyDict};
-use std::time::SystemTime;
-use wreq::cookie::{self, Expiration};
+use std::{sync::Arc, time::SystemTime};
+use wreq::Url;
+use wreq::cookie::{self, CookieStore, Expiration, Jar};
 use wreq::header::{self, HeaderMap, HeaderValue};
 
 /// A cookie.
@@ -13,13 +17,22 @@ use wreq::header::{self, HeaderMap, HeaderValue};
 pub struct Cookie(pub cookie::Cookie<'static>);
 
 impl Cookie {
+    /// Parses the `Set-Cookie` headers, decoding values that aren't valid UTF-8 as Latin-1.
     pub(crate) fn extract_cookies(headers: &HeaderMap) -> Vec<Self> {
         headers
             .get_all(header::SET_COOKIE)
             .iter()
-            .map(cookie::Cookie::parse)
-            .flat_map(Result::ok)
-            .map(cookie::Cookie::into_owned)
+            .filter_map(|value| {
+                cookie::Cookie::parse(value)
+                    .map(cookie::Cookie::into_owned)
+                    .or_else(|_| {
+                        // Latin-1 maps every byte to the code point of the same value.
+                        let latin1: String =
+                            value.as_bytes().iter().map(|&b| char::from(b)).collect();
+                        cookie::Cookie::parse(&latin1).map(cookie::Cookie::into_owned)
+                    })
+                    .ok()
+            })
             .map(Cookie)
             .collect()
     }
@@ -165,6 +178,64 @@ impl Cookie {
     }
 }
 
+/// A cookie store that can be shared between clients.
+///
+/// Clients created with the same jar as their `cookie_provider` send and store the same
+/// cookies. The jar is locked internally, so it can be used from any thread.
+#[pyclass]
+#[derive(Clone, Default)]
+pub struct CookieJar(pub Arc<Jar>);
+
+#[pymethods]
+impl CookieJar {
+    /// Creates a new, empty cookie jar.
+    #[new]
+    pub fn new() -> CookieJar {
+        CookieJar::default()
+    }
+
+    /// Returns the cookies for the given URL.
+    #[pyo3(signature = (url))]
+    pub fn get_cookies<'py>(
+        &self,
+        py: Python<'py>,
+        url: PyBackedStr,
+    ) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let cookies = py.allow_threads(|| {
+            let url = Url::parse(url.as_ref()).map_err(Error::from)?;
+            Ok::<_, PyErr>(self.0.cookies(&url).map(HeaderValueBuffer::new))
+        })?;
+
+        cookies.map(|buffer| buffer.into_bytes_ref(py)).transpose()
+    }
+
+    /// Sets the cookie for the given URL.
+    #[pyo3(signature = (url, cookie))]
+    pub fn set_cookie(&self, py: Python, url: PyBackedStr, cookie: Cookie) -> PyResult<()> {
+        py.allow_threads(|| {
+            let url = Url::parse(url.as_ref()).map_err(Error::from)?;
+            self.0.set_cookie(&url, &cookie.0);
+            Ok(())
+        })
+    }
+
+    /// Removes the cookie with the given name for the given URL.
+    #[pyo3(signature = (url, name))]
+    pub fn remove_cookie(&self, py: Python, url: PyBackedStr, name: PyBackedStr) -> PyResult<()> {
+        py.allow_threads(|| {
+            let url = Url::parse(url.as_ref()).map_err(Error::from)?;
+            self.0.remove(&url, &name);
+            Ok(())
+        })
+    }
+
+    /// Clears all the cookies.
+    pub fn clear(&self, py: Python) {
+        py.allow_threads(|| self.0.clear())
+    }
+}
+
+#[derive(Clone)]
 pub struct CookieExtractor(pub HeaderValue);
 
 impl FromPyObject<'_> for CookieExtractor {
@@ -1,4 +1,8 @@
-use pyo3::prelude::*;
+use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    pybacked::PyBackedStr,
+};
 
 define_enum_with_conversion!(
     /// An HTTP version.
@@ -12,10 +16,49 @@ define_enum_with_conversion!(
     HTTP_3,
 );
 
-define_enum_with_conversion!(
-    /// An HTTP method.
-    Method,
-    wreq::Method,
+impl Version {
+    /// Returns the version as it appears on the wire, e.g. `HTTP/1.1`.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Version::HTTP_09 => "HTTP/0.9",
+            Version::HTTP_10 => "HTTP/1.0",
+            Version::HTTP_11 => "HTTP/1.1",
+            Version::HTTP_2 => "HTTP/2",
+            Version::HTTP_3 => "HTTP/3",
+        }
+    }
+}
+
+#[pymethods]
+impl Version {
+    /// Parses a version string such as `HTTP/1.1` or `HTTP/2` (case-insensitive).
+    #[staticmethod]
+    fn from_str(value: &str) -> PyResult<Self> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "HTTP/0.9" => Ok(Version::HTTP_09),
+            "HTTP/1.0" => Ok(Version::HTTP_10),
+            "HTTP/1.1" => Ok(Version::HTTP_11),
+            "HTTP/2" | "HTTP/2.0" => Ok(Version::HTTP_2),
+            "HTTP/3" | "HTTP/3.0" => Ok(Version::HTTP_3),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid HTTP version: {:?}",
+                value
+            ))),
+        }
+    }
+
+    fn __str__(&self) -> &'static str {
+        self.as_str()
+    }
+}
+
+/// An HTTP method, including the common WebDAV ones.
+///
+/// Requests also take a method as a string, for verbs that aren't listed here.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum Method {
     GET,
     HEAD,
     POST,
@@ -24,7 +67,122 @@ define_enum_with_conversion!(
     OPTIONS,
     TRACE,
     PATCH,
-);
+    PROPFIND,
+    PROPPATCH,
+    MKCOL,
+    COPY,
+    MOVE,
+    LOCK,
+    UNLOCK,
+    REPORT,
+}
+
+impl Method {
+    /// All variants, in declaration order.
+    const VARIANTS: &'static [Method] = &[
+        Method::GET,
+        Method::HEAD,
+        Method::POST,
+        Method::PUT,
+        Method::DELETE,
+        Method::OPTIONS,
+        Method::TRACE,
+        Method::PATCH,
+        Method::PROPFIND,
+        Method::PROPPATCH,
+        Method::MKCOL,
+        Method::COPY,
+        Method::MOVE,
+        Method::LOCK,
+        Method::UNLOCK,
+        Method::REPORT,
+    ];
+
+    /// Returns the method as it's sent on the wire.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Method::GET => "GET",
+            Method::HEAD => "HEAD",
+            Method::POST => "POST",
+            Method::PUT => "PUT",
+            Method::DELETE => "DELETE",
+            Method::OPTIONS => "OPTIONS",
+            Method::TRACE => "TRACE",
+            Method::PATCH => "PATCH",
+            Method::PROPFIND => "PROPFIND",
+            Method::PROPPATCH => "PROPPATCH",
+            Method::MKCOL => "MKCOL",
+            Method::COPY => "COPY",
+            Method::MOVE => "MOVE",
+            Method::LOCK => "LOCK",
+            Method::UNLOCK => "UNLOCK",
+            Method::REPORT => "REPORT",
+        }
+    }
+
+    pub fn into_ffi(self) -> wreq::Method {
+        match self {
+            Method::GET => wreq::Method::GET,
+            Method::HEAD => wreq::Method::HEAD,
+            Method::POST => wreq::Method::POST,
+            Method::PUT => wreq::Method::PUT,
+            Method::DELETE => wreq::Method::DELETE,
+            Method::OPTIONS => wreq::Method::OPTIONS,
+            Method::TRACE => wreq::Method::TRACE,
+            Method::PATCH => wreq::Method::PATCH,
+            method => wreq::Method::from_bytes(method.as_str().as_bytes())
+                .expect("WebDAV method names are valid tokens"),
+        }
+    }
+}
+
+#[pymethods]
+impl Method {
+    /// Parses a method name such as `GET` or `PROPFIND` (case-insensitive).
+    ///
+    /// Extension methods aren't members, so they raise; requests take them as strings.
+    #[staticmethod]
+    fn from_str(value: &str) -> PyResult<Self> {
+        let value = value.trim();
+        Method::VARIANTS
+            .iter()
+            .copied()
+            .find(|method| method.as_str().eq_ignore_ascii_case(value))
+            .ok_or_else(|| PyValueError::new_err(format!("Invalid HTTP method: {:?}", value)))
+    }
+
+    fn __str__(&self) -> &'static str {
+        self.as_str()
+    }
+}
+
+/// A method given to a request, as a `Method` or as a string for any other verb.
+pub struct MethodExtractor(pub wreq::Method);
+
+impl From<Method> for MethodExtractor {
+    fn from(method: Method) -> Self {
+        Self(method.into_ffi())
+    }
+}
+
+impl FromPyObject<'_> for MethodExtractor {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(method) = ob.downcast::<Method>() {
+            return Ok(Self::from(*method.borrow()));
+        }
+
+        let name = ob
+            .extract::<PyBackedStr>()
+            .map_err(|_| PyTypeError::new_err("method: expected a Method or a str"))?;
+        parse_method(&name).map(Self)
+    }
+}
+
+/// Parses a method name to be sent verbatim, whatever its case.
+fn parse_method(name: &str) -> PyResult<wreq::Method> {
+    wreq::Method::from_bytes(name.as_bytes())
+        .map_err(|_| PyValueError::new_err(format!("method: {:?} is not a valid token", name)))
+}
 
 define_enum_with_conversion!(
     /// An impersonate.
@@ -105,6 +263,99 @@ define_enum_with_conversion!(
     Opera119
 );
 
+impl Impersonate {
+    /// Returns the browser family of the profile, e.g. `chrome` or `safari`.
+    pub fn browser(self) -> &'static str {
+        const BROWSERS: &[(&str, &str)] = &[
+            ("Chrome", "chrome"),
+            ("Edge", "edge"),
+            ("Firefox", "firefox"),
+            ("Safari", "safari"),
+            ("OkHttp", "okhttp"),
+            ("Opera", "opera"),
+        ];
+
+        let name = self.name();
+        BROWSERS
+            .iter()
+            .find(|(prefix, _)| name.starts_with(prefix))
+            .map(|(_, browser)| *browser)
+            .unwrap_or_default()
+    }
+
+    /// Returns the operating systems the profile's browser plausibly runs on.
+    pub fn supported_os(self) -> &'static [ImpersonateOS] {
+        const DESKTOP: &[ImpersonateOS] = &[
+            ImpersonateOS::Windows,
+            ImpersonateOS::MacOS,
+            ImpersonateOS::Linux,
+        ];
+        const DESKTOP_AND_ANDROID: &[ImpersonateOS] = &[
+            ImpersonateOS::Windows,
+            ImpersonateOS::MacOS,
+            ImpersonateOS::Linux,
+            ImpersonateOS::Android,
+        ];
+
+        let name = self.name();
+        match self.browser() {
+            "chrome" => DESKTOP_AND_ANDROID,
+            "firefox" if name.starts_with("FirefoxAndroid") => &[ImpersonateOS::Android],
+            "safari" if name.starts_with("SafariIos") || name.starts_with("SafariIPad") => {
+                &[ImpersonateOS::IOS]
+            }
+            "safari" => &[ImpersonateOS::MacOS],
+            "okhttp" => &[ImpersonateOS::Android],
+            _ => DESKTOP,
+        }
+    }
+
+    /// Returns the browser version of the profile, e.g. `136` or `17.4.1`.
+    pub fn version(self) -> String {
+        let name = self.name();
+        name.find(|c: char| c.is_ascii_digit())
+            .map(|start| name[start..].replace('_', "."))
+            .unwrap_or_default()
+    }
+}
+
+#[pymethods]
+impl Impersonate {
+    /// Returns all available impersonation profiles.
+    #[staticmethod]
+    fn all() -> Vec<Self> {
+        Self::VARIANTS.to_vec()
+    }
+
+    /// The name of the profile, e.g. `Chrome136`.
+    #[getter(name)]
+    fn py_name(&self) -> &'static str {
+        self.name()
+    }
+
+    /// The browser family of the profile.
+    #[getter(browser)]
+    fn py_browser(&self) -> &'static str {
+        self.browser()
+    }
+
+    /// The browser version of the profile.
+    #[getter(version)]
+    fn py_version(&self) -> String {
+        self.version()
+    }
+
+    /// The operating systems the profile's browser runs on.
+    #[getter(supported_os)]
+    fn py_supported_os(&self) -> Vec<ImpersonateOS> {
+        self.supported_os().to_vec()
+    }
+
+    fn __str__(&self) -> &'static str {
+        self.name()
+    }
+}
+
 define_enum_with_conversion!(
     /// An impersonate operating system.
     const,
@@ -149,3 +400,14 @@ define_enum_with_conversion!(
     (Lax, Lax),
     (Empty, None),
 );
+
+/// The type of a WebSocket message.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageType {
+    Text,
+    Binary,
+    Ping,
+    Pong,
+    Close,
+}
@@ -5,7 +5,7 @@ use crate::{
 use pyo3::{
     prelude::*,
     pybacked::PyBackedStr,
-    types::{PyDict, PyList},
+    types::{PyBytes, PyDict, PyList},
 };
 use wreq::header::{self, HeaderName, HeaderValue};
 
@@ -51,6 +51,23 @@ impl HeaderMap {
         buffer.into_bytes_ref(py).ok()
     }
 
+    /// Returns the first value associated with the key decoded as UTF-8, with `errors`
+    /// handling invalid bytes as in `bytes.decode` (`"strict"`, `"replace"`, `"ignore"`, ...).
+    #[pyo3(signature = (key, errors = "replace"))]
+    fn get_str<'py>(
+        &self,
+        py: Python<'py>,
+        key: PyBackedStr,
+        errors: &str,
+    ) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let Some(value) = self.0.get::<&str>(key.as_ref()) else {
+            return Ok(None);
+        };
+        PyBytes::new(py, value.as_bytes())
+            .call_method1("decode", ("utf-8", errors))
+            .map(Some)
+    }
+
     /// Insert a key-value pair into the header map.
     #[inline]
     fn insert(&mut self, py: Python, key: PyBackedStr, value: PyBackedStr) {
@@ -225,7 +242,9 @@ impl HeaderMapItemsIter {
     }
 }
 
-/// A HTTP header map.
+/// A HTTP header map, from a `HeaderMap`, a dict, or a sequence of `(name, value)` pairs
+/// that may repeat a name.
+#[derive(Clone)]
 pub struct HeaderMapExtractor(pub header::HeaderMap);
 
 /// A list of header names in order.
@@ -237,17 +256,35 @@ impl FromPyObject<'_> for HeaderMapExtractor {
             return Ok(Self(headers.borrow().0.clone()));
         }
 
-        let dict = ob.downcast::<PyDict>()?;
-        dict.iter()
+        if let Ok(dict) = ob.downcast::<PyDict>() {
+            return dict
+                .iter()
+                .try_fold(
+                    header::HeaderMap::with_capacity(dict.len()),
+                    |mut headers, (name, value)| {
+                        let name = name.extract::<PyBackedStr>()?;
+                        let name = HeaderName::from_bytes(name.as_bytes()).map_err(Error::from)?;
+                        let value = value.extract::<PyBackedStr>()?;
+                        let value =
+                            HeaderValue::from_bytes(value.as_bytes()).map_err(Error::from)?;
+                        headers.insert(name, value);
+                        Ok(headers)
+                    },
+                )
+                .map(Self);
+        }
+
+        // Pairs may repeat a name, each of whose values is kept.
+        let pairs = ob.extract::<Vec<(PyBackedStr, PyBackedStr)>>()?;
+        pairs
+            .iter()
             .try_fold(
-                header::HeaderMap::with_capacity(dict.len()),
+                header::HeaderMap::with_capacity(pairs.len()),
                 |mut headers, (name, value)| {
-                    let name = name.extract::<PyBackedStr>()?;
                     let name = HeaderName::from_bytes(name.as_bytes()).map_err(Error::from)?;
-                    let value = value.extract::<PyBackedStr>()?;
                     let value = HeaderValue::from_bytes(value.as_bytes()).map_err(Error::from)?;
-                    headers.insert(name, value);
-                    Ok(headers)
+                    headers.append(name, value);
+                    Ok::<_, PyErr>(headers)
                 },
             )
             .map(Self)
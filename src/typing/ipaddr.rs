@@ -1,12 +1,114 @@
-use pyo3::{IntoPyObjectExt, prelude::*};
+use pyo3::{
+    IntoPyObjectExt,
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    pybacked::PyBackedStr,
+    types::PyString,
+};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
-/// An IP address.
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub struct IpAddrExtractor(pub std::net::IpAddr);
+/// The local addresses to bind to: a single IP address, or an IPv4 and an IPv6 address
+/// of which the one matching the family of the resolved target is used.
+///
+/// The IPv6 address may carry a zone, as in `fe80::1%eth0`, naming the interface (or its
+/// index) a link-local address belongs to. Since the zone binds the socket to that
+/// interface whatever the family of the target, it can't be paired with an IPv4 address.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct LocalAddressExtractor {
+    pub ipv4: Option<Ipv4Addr>,
+    pub ipv6: Option<Ipv6Addr>,
+    pub zone: Option<String>,
+}
+
+impl LocalAddressExtractor {
+    fn add(&mut self, (addr, zone): (IpAddr, Option<String>)) -> PyResult<()> {
+        let duplicate = match addr {
+            IpAddr::V4(addr) => self.ipv4.replace(addr).is_some(),
+            IpAddr::V6(addr) => {
+                self.zone = zone;
+                self.ipv6.replace(addr).is_some()
+            }
+        };
+        if duplicate {
+            return Err(PyValueError::new_err(
+                "local_address: expected at most one IPv4 and one IPv6 address",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Extracts an IP address along with the zone of a scoped IPv6 address, from either a
+/// string or an `ipaddress` object.
+fn extract_scoped(ob: &Bound<'_, PyAny>) -> PyResult<(IpAddr, Option<String>)> {
+    if ob.is_instance_of::<PyString>() {
+        let text = ob.extract::<PyBackedStr>()?;
+        let (addr, zone) = match text.split_once('%') {
+            Some((addr, zone)) => (addr, Some(zone)),
+            None => (&*text, None),
+        };
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|_| PyValueError::new_err(format!("invalid IP address: {:?}", &*text)))?;
+        return match zone {
+            Some(zone) if addr.is_ipv4() || zone.is_empty() => Err(PyValueError::new_err(format!(
+                "invalid IP address: {:?}",
+                &*text
+            ))),
+            zone => Ok((addr, zone.map(str::to_owned))),
+        };
+    }
+
+    let addr = ob.extract::<IpAddr>()?;
+    // `ipaddress.IPv6Address` keeps the zone in `scope_id` since Python 3.9.
+    let zone = match ob.getattr_opt("scope_id")? {
+        Some(zone) if addr.is_ipv6() => zone.extract()?,
+        _ => None,
+    };
+    Ok((addr, zone))
+}
 
-impl FromPyObject<'_> for IpAddrExtractor {
+impl FromPyObject<'_> for LocalAddressExtractor {
     fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
-        ob.extract().map(IpAddrExtractor)
+        let mut addresses = LocalAddressExtractor {
+            ipv4: None,
+            ipv6: None,
+            zone: None,
+        };
+
+        match extract_scoped(ob) {
+            Ok(addr) => {
+                addresses.add(addr)?;
+                return Ok(addresses);
+            }
+            Err(err) if ob.is_instance_of::<PyString>() => return Err(err),
+            Err(_) => {}
+        }
+
+        let pair = ob
+            .extract::<(Option<Bound<'_, PyAny>>, Option<Bound<'_, PyAny>>)>()
+            .and_then(|(first, second)| {
+                [first, second]
+                    .into_iter()
+                    .flatten()
+                    .map(|addr| extract_scoped(&addr))
+                    .collect::<PyResult<Vec<_>>>()
+            })
+            .map_err(|_| {
+                PyTypeError::new_err(
+                    "local_address: expected an IP address or a pair of IPv4 and IPv6 addresses",
+                )
+            })?;
+        for addr in pair {
+            addresses.add(addr)?;
+        }
+        if addresses.zone.is_some() && addresses.ipv4.is_some() {
+            return Err(PyValueError::new_err(
+                "local_address: an IPv6 address with a zone can't be paired with an IPv4 \
+                 address, since the zone binds every connection to its interface",
+            ));
+        }
+        Ok(addresses)
     }
 }
 
@@ -17,9 +119,18 @@ pub struct SocketAddr(pub std::net::SocketAddr);
 
 #[pymethods]
 impl SocketAddr {
-    /// Returns the IP address of the socket address.
+    /// Returns the IP address of the socket address, with the scope id of a scoped IPv6
+    /// address as its zone.
     fn ip<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.0.ip().into_bound_py_any(py)
+        match self.0 {
+            std::net::SocketAddr::V6(addr) if addr.scope_id() != 0 => {
+                py.import("ipaddress")?.call_method1(
+                    "IPv6Address",
+                    (format!("{}%{}", addr.ip(), addr.scope_id()),),
+                )
+            }
+            addr => addr.ip().into_bound_py_any(py),
+        }
     }
 
     /// Returns the port number of the socket address.
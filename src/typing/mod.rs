@@ -9,29 +9,43 @@ pub mod param;
 mod proxy;
 mod ssl;
 mod status;
+mod url;
 
 pub use self::{
     body::BodyExtractor,
-    cookie::{Cookie, CookieExtractor},
-    enums::{Impersonate, ImpersonateOS, LookupIpStrategy, Method, SameSite, TlsVersion, Version},
+    cookie::{Cookie, CookieExtractor, CookieJar},
+    enums::{
+        Impersonate, ImpersonateOS, LookupIpStrategy, MessageType, Method, MethodExtractor,
+        SameSite, TlsVersion, Version,
+    },
     header::{
         HeaderMap, HeaderMapExtractor, HeaderMapItemsIter, HeaderMapKeysIter, HeaderMapValuesIter,
         HeadersOrderExtractor,
     },
-    ipaddr::{IpAddrExtractor, SocketAddr},
+    ipaddr::{LocalAddressExtractor, SocketAddr},
     json::Json,
     multipart::{Multipart, Part},
-    proxy::{Proxy, ProxyExtractor},
-    ssl::SslVerify,
+    proxy::{Proxy, ProxyExtractor, check_pac, check_pac_future},
+    ssl::{ClientCert, SslVerify, TlsConfigExtractor},
     status::StatusCode,
+    url::{Url, UrlExtractor},
 };
-use pyo3::{prelude::*, pybacked::PyBackedStr};
+use pyo3::{exceptions::PyValueError, prelude::*, pybacked::PyBackedStr, types::PyDict};
 use serde::ser::{Serialize, SerializeSeq, Serializer};
 use wreq_util::EmulationOption;
 
 /// A struct to represent the `ImpersonateOption` class.
 #[pyclass(subclass)]
-pub struct ImpersonateOption(EmulationOption);
+pub struct ImpersonateOption(EmulationOption, Option<ImpersonateKey>);
+
+/// What an impersonation option was built from, which determines its fingerprint.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ImpersonateKey {
+    impersonate: Impersonate,
+    impersonate_os: Option<ImpersonateOS>,
+    skip_http2: bool,
+    skip_headers: bool,
+}
 
 #[pymethods]
 impl ImpersonateOption {
@@ -49,24 +63,75 @@ impl ImpersonateOption {
         skip_http2: Option<bool>,
         skip_headers: Option<bool>,
     ) -> Self {
+        let key = ImpersonateKey {
+            impersonate,
+            impersonate_os,
+            skip_http2: skip_http2.unwrap_or(false),
+            skip_headers: skip_headers.unwrap_or(false),
+        };
         let emulation = EmulationOption::builder()
             .emulation(impersonate.into_ffi())
             .emulation_os(impersonate_os.map(|os| os.into_ffi()).unwrap_or_default())
-            .skip_http2(skip_http2.unwrap_or(false))
-            .skip_headers(skip_headers.unwrap_or(false))
+            .skip_http2(key.skip_http2)
+            .skip_headers(key.skip_headers)
             .build();
 
-        Self(emulation)
+        Self(emulation, Some(key))
     }
 
     /// Creates a new random impersonation option instance.
     #[staticmethod]
     fn random() -> Self {
-        Self(wreq_util::Emulation::random())
+        Self(wreq_util::Emulation::random(), None)
+    }
+
+    /// Creates an impersonation option for `browser` paired with an operating system that
+    /// browser actually runs on.
+    ///
+    /// `browser` is either a profile, or a browser family such as `"chrome"` to pick a random
+    /// profile of that family.
+    #[staticmethod]
+    fn random_for(browser: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let impersonate = match browser.extract::<Impersonate>() {
+            Ok(impersonate) => impersonate,
+            Err(_) => {
+                let family = browser.extract::<PyBackedStr>()?.to_ascii_lowercase();
+                let profiles = Impersonate::VARIANTS
+                    .iter()
+                    .copied()
+                    .filter(|profile| profile.browser() == family)
+                    .collect::<Vec<_>>();
+                random_choice(&profiles).ok_or_else(|| {
+                    PyValueError::new_err(format!("Unknown browser family: {:?}", family))
+                })?
+            }
+        };
+
+        let impersonate_os = random_choice(impersonate.supported_os());
+        Ok(Self::new(impersonate, impersonate_os, None, None))
+    }
+}
+
+#[pymethods]
+impl ImpersonateOS {
+    /// Returns a random operating system.
+    #[staticmethod]
+    fn random() -> Self {
+        random_choice(Self::VARIANTS).expect("ImpersonateOS has variants")
     }
 }
 
-pub struct UrlEncodedValuesExtractor(Vec<(PyBackedStr, PyBackedStr)>);
+/// Picks a random element of `items`, or `None` if it is empty.
+fn random_choice<T: Copy>(items: &[T]) -> Option<T> {
+    use std::hash::{BuildHasher, RandomState};
+
+    // Every `RandomState` is seeded differently, which is plenty for picking a profile.
+    let seed = RandomState::new().hash_one(0u8) as usize;
+    (!items.is_empty()).then(|| items[seed % items.len()])
+}
+
+#[derive(Clone)]
+pub struct UrlEncodedValuesExtractor(Vec<(String, String)>);
 
 impl Serialize for UrlEncodedValuesExtractor {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -75,7 +140,7 @@ impl Serialize for UrlEncodedValuesExtractor {
     {
         let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
         for (key, value) in &self.0 {
-            seq.serialize_element::<(&str, &str)>(&(key.as_ref(), value.as_ref()))?;
+            seq.serialize_element::<(&str, &str)>(&(key.as_str(), value.as_str()))?;
         }
         seq.end()
     }
@@ -83,23 +148,36 @@ impl Serialize for UrlEncodedValuesExtractor {
 
 impl FromPyObject<'_> for UrlEncodedValuesExtractor {
     fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        // A dict, as requests takes them, in the order of its items.
+        if let Ok(dict) = ob.downcast::<PyDict>() {
+            return dict.items().extract().map(Self);
+        }
         ob.extract().map(Self)
     }
 }
 
-pub struct ImpersonateExtractor(pub EmulationOption);
+/// An impersonation option, with what it was built from if that's known.
+pub struct ImpersonateExtractor(pub EmulationOption, pub Option<ImpersonateKey>);
 
 impl FromPyObject<'_> for ImpersonateExtractor {
     fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
-        if let Ok(impersonate) = ob.downcast::<Impersonate>() {
-            let emulation = EmulationOption::builder()
-                .emulation(impersonate.borrow().into_ffi())
-                .build();
+        // `"random"` picks a fresh profile every time it is extracted.
+        if let Ok(value) = ob.extract::<PyBackedStr>() {
+            if value.eq_ignore_ascii_case("random") {
+                return Ok(Self(wreq_util::Emulation::random(), None));
+            }
+            return Err(PyValueError::new_err(format!(
+                "Invalid impersonate value: {:?}",
+                &*value
+            )));
+        }
 
-            return Ok(Self(emulation));
+        if let Ok(impersonate) = ob.downcast::<Impersonate>() {
+            let option = ImpersonateOption::new(*impersonate.borrow(), None, None, None);
+            return Ok(Self(option.0, option.1));
         }
 
         let option = ob.downcast::<ImpersonateOption>()?.borrow();
-        Ok(Self(option.0.clone()))
+        Ok(Self(option.0.clone(), option.1))
     }
 }
@@ -1,18 +1,35 @@
 use super::part::Part;
 use crate::error::Error;
-use pyo3::{prelude::*, types::PyTuple};
-use wreq::multipart::Form;
+use bytes::Bytes;
+use futures_util::{StreamExt, stream};
+use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    pybacked::{PyBackedBytes, PyBackedStr},
+    types::{PyDict, PyTuple, PyType},
+};
+use wreq::{Body, RequestBuilder, header::CONTENT_TYPE, multipart::Form};
 
 /// A multipart form for a request.
 #[pyclass(subclass)]
-pub struct Multipart(pub Option<Form>);
+pub struct Multipart {
+    form: Option<Form>,
+    content_type: Option<String>,
+    boundary: Option<String>,
+}
 
 #[pymethods]
 impl Multipart {
     /// Creates a new multipart form.
     #[new]
-    #[pyo3(signature = (*parts))]
-    pub fn new(parts: &Bound<PyTuple>) -> PyResult<Multipart> {
+    #[pyo3(signature = (*parts, content_type = None, boundary = None))]
+    pub fn new(
+        parts: &Bound<PyTuple>,
+        content_type: Option<String>,
+        boundary: Option<String>,
+    ) -> PyResult<Multipart> {
+        validate_options(content_type.as_deref(), boundary.as_deref())?;
+
         let mut new_form = Form::new();
         for part in parts {
             let part = part.downcast::<Part>()?;
@@ -24,20 +41,228 @@ impl Multipart {
                 .map(|(name, inner)| new_form.part(name, inner))
                 .ok_or_else(|| Error::Memory)?;
         }
-        Ok(Multipart(Some(new_form)))
+
+        Ok(Multipart::with_form(new_form, content_type, boundary))
+    }
+
+    /// Creates a multipart form from a dict of field names to values.
+    ///
+    /// Strings become text parts and bytes become binary parts; `Part` objects are added as
+    /// they are, under the name of their key.
+    #[classmethod]
+    #[pyo3(signature = (fields, content_type = None, boundary = None))]
+    pub fn from_dict(
+        _cls: &Bound<'_, PyType>,
+        fields: &Bound<'_, PyDict>,
+        content_type: Option<String>,
+        boundary: Option<String>,
+    ) -> PyResult<Multipart> {
+        validate_options(content_type.as_deref(), boundary.as_deref())?;
+
+        let mut new_form = Form::new();
+        for (name, value) in fields {
+            let name = name.extract::<String>()?;
+            let part = if let Ok(part) = value.downcast::<Part>() {
+                part.borrow_mut()
+                    .inner
+                    .take()
+                    .ok_or_else(|| Error::Memory)?
+            } else if let Ok(text) = value.extract::<PyBackedStr>() {
+                wreq::multipart::Part::text(text.to_string())
+            } else if let Ok(bytes) = value.extract::<PyBackedBytes>() {
+                wreq::multipart::Part::bytes(bytes.to_vec())
+            } else {
+                return Err(PyTypeError::new_err(format!(
+                    "multipart field {name:?}: expected str, bytes or Part, got {}",
+                    value.get_type().name()?
+                )));
+            };
+            new_form = new_form.part(name, part);
+        }
+
+        Ok(Multipart::with_form(new_form, content_type, boundary))
+    }
+}
+
+impl Multipart {
+    /// Wraps `form` with its custom `content_type` and `boundary`, if it has any.
+    fn with_form(form: Form, content_type: Option<String>, boundary: Option<String>) -> Multipart {
+        Multipart {
+            form: Some(form),
+            content_type,
+            boundary,
+        }
+    }
+}
+
+pub struct MultipartExtractor {
+    form: Form,
+    content_type: Option<String>,
+    boundary: Option<String>,
+}
+
+impl MultipartExtractor {
+    /// Returns the `Content-Type` of the form, falling back to `default` for the media type.
+    pub fn content_type(&self, default: &str) -> String {
+        let boundary = self
+            .boundary
+            .as_deref()
+            .unwrap_or_else(|| self.form.boundary());
+        let media_type = self.content_type.as_deref().unwrap_or(default);
+
+        // Boundaries may contain characters that are only legal in a quoted parameter.
+        if boundary
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"'+_-.".contains(&b))
+        {
+            format!("{}; boundary={}", media_type, boundary)
+        } else {
+            format!("{}; boundary=\"{}\"", media_type, boundary)
+        }
+    }
+
+    /// Consumes the form and returns its encoded body.
+    pub fn into_body(self) -> Body {
+        let Some(boundary) = self.boundary else {
+            return Body::wrap_stream(self.form.into_stream());
+        };
+
+        // wreq always picks a random boundary, so replace it with the requested one
+        // wherever it occurs, including across chunks.
+        let from = Bytes::from(self.form.boundary().to_owned());
+        let to = Bytes::from(boundary);
+        let stream = Box::pin(self.form.into_stream());
+        Body::wrap_stream(stream::unfold(
+            (stream, Vec::new(), false),
+            move |(mut stream, mut pending, mut finished)| {
+                let (from, to) = (from.clone(), to.clone());
+                async move {
+                    loop {
+                        if finished {
+                            if pending.is_empty() {
+                                return None;
+                            }
+                            let rest = Bytes::from(std::mem::take(&mut pending));
+                            return Some((Ok(rest), (stream, pending, finished)));
+                        }
+                        match stream.next().await {
+                            Some(Ok(chunk)) => {
+                                pending.extend_from_slice(&chunk);
+                                let (out, kept) = replace_boundary(&pending, &from, &to);
+                                pending.drain(..pending.len() - kept);
+                                if !out.is_empty() {
+                                    return Some((
+                                        Ok(Bytes::from(out)),
+                                        (stream, pending, finished),
+                                    ));
+                                }
+                            }
+                            Some(Err(err)) => return Some((Err(err), (stream, pending, true))),
+                            None => finished = true,
+                        }
+                    }
+                }
+            },
+        ))
+    }
+
+    /// Attaches the form to `builder` as the request body.
+    pub fn apply(self, builder: RequestBuilder) -> RequestBuilder {
+        let content_type = self.content_type("multipart/form-data");
+
+        // With the generated boundary wreq can compute the `Content-Length` up front;
+        // a custom boundary changes the encoded length, so that body is streamed.
+        let builder = if self.boundary.is_none() {
+            builder.multipart(self.form)
+        } else {
+            builder.body(self.into_body())
+        };
+
+        builder.header(CONTENT_TYPE, content_type)
     }
 }
 
-pub struct MultipartExtractor(pub Form);
+/// Replaces each `from` in `buf` with `to`. The tail of `buf` that could still start a
+/// match is left out of the output; returns the output and the length of that tail.
+fn replace_boundary(buf: &[u8], from: &[u8], to: &[u8]) -> (Vec<u8>, usize) {
+    let mut out = Vec::with_capacity(buf.len());
+    let mut i = 0;
+    while i + from.len() <= buf.len() {
+        if buf[i..].starts_with(from) {
+            out.extend_from_slice(to);
+            i += from.len();
+        } else {
+            out.push(buf[i]);
+            i += 1;
+        }
+    }
+    (out, buf.len() - i)
+}
 
 impl FromPyObject<'_> for MultipartExtractor {
     fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
         let form = ob.downcast::<Multipart>()?;
-        form.borrow_mut()
-            .0
+        let mut form = form.borrow_mut();
+        let content_type = form.content_type.clone();
+        let boundary = form.boundary.clone();
+        form.form
             .take()
-            .map(Self)
+            .map(|form| MultipartExtractor {
+                form,
+                content_type,
+                boundary,
+            })
             .ok_or_else(|| Error::Memory)
             .map_err(Into::into)
     }
 }
+
+/// Checks the custom `content_type` and `boundary` of a form, if it has any.
+fn validate_options(content_type: Option<&str>, boundary: Option<&str>) -> PyResult<()> {
+    if let Some(content_type) = content_type {
+        validate_content_type(content_type)?;
+    }
+    if let Some(boundary) = boundary {
+        validate_boundary(boundary)?;
+    }
+    Ok(())
+}
+
+/// Checks that `content_type` is a bare `multipart/*` media type.
+fn validate_content_type(content_type: &str) -> PyResult<()> {
+    let valid = content_type
+        .get(..10)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("multipart/"))
+        && content_type.len() > 10
+        && !content_type.contains(';');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(format!(
+            "Invalid multipart content type {:?}: expected a media type such as \"multipart/related\"",
+            content_type
+        )))
+    }
+}
+
+/// Checks `boundary` against the grammar of RFC 2046, section 5.1.1.
+fn validate_boundary(boundary: &str) -> PyResult<()> {
+    const SPECIALS: &[u8] = b"'()+_,-./:=? ";
+
+    let valid = (1..=70).contains(&boundary.len())
+        && !boundary.ends_with(' ')
+        && boundary
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || SPECIALS.contains(&b));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(format!(
+            "Invalid multipart boundary {:?}: expected 1 to 70 letters, digits or any of \
+             \"'()+_,-./:=? \", not ending with a space",
+            boundary
+        )))
+    }
+}
@@ -1,14 +1,20 @@
+use super::form::{Multipart, MultipartExtractor};
 use crate::{
     error::{Error, MIMEParseError},
-    stream::{AsyncStream, SyncStream},
+    stream::{AsyncStream, ReadStream, SyncStream},
+    typing::HeaderMapExtractor,
 };
 use bytes::Bytes;
 use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
     prelude::*,
     pybacked::{PyBackedBytes, PyBackedStr},
 };
 use std::path::PathBuf;
-use wreq::Body;
+use wreq::{
+    Body,
+    header::{self, HeaderMap},
+};
 
 /// A part of a multipart form.
 #[pyclass(subclass)]
@@ -24,23 +30,76 @@ pub enum PartExtractor {
     File(PathBuf),
     SyncStream(SyncStream),
     AsyncStream(AsyncStream),
+    ReadStream(ReadStream),
+    Multipart(MultipartExtractor),
+}
+
+/// A file-like object, or a sync or async iterator of byte chunks.
+pub enum StreamExtractor {
+    SyncStream(SyncStream),
+    AsyncStream(AsyncStream),
+    ReadStream(ReadStream),
+}
+
+impl Part {
+    /// Applies the optional part metadata and wraps `inner` into a new part.
+    fn build(
+        name: String,
+        mut inner: wreq::multipart::Part,
+        filename: Option<String>,
+        mime: Option<&str>,
+        headers: Option<HeaderMapExtractor>,
+    ) -> PyResult<Part> {
+        // Set the filename if provided
+        if let Some(filename) = filename {
+            inner = inner.file_name(filename);
+        }
+
+        // Set the MIME type if provided
+        if let Some(mime) = mime {
+            inner = inner
+                .mime_str(mime)
+                .map_err(|e| MIMEParseError::new_err(format!("Cannot parse MIME type: {:?}", e)))?;
+        }
+
+        // Set the custom part headers if provided
+        if let Some(headers) = headers {
+            check_headers(&headers.0, mime.is_some())?;
+            inner = inner.headers(headers.0);
+        }
+
+        Ok(Part {
+            name: Some(name),
+            inner: Some(inner),
+        })
+    }
 }
 
 #[pymethods]
 impl Part {
     /// Creates a new part.
     #[new]
-    #[pyo3(signature = (name, value, filename = None, mime = None))]
+    #[pyo3(signature = (name, value, filename = None, mime = None, headers = None))]
     pub fn new(
         py: Python,
         name: String,
         value: PartExtractor,
         filename: Option<String>,
         mime: Option<&str>,
+        headers: Option<HeaderMapExtractor>,
     ) -> PyResult<Part> {
         py.allow_threads(|| {
+            // A nested form is sent as `multipart/mixed` unless another MIME type is given.
+            let nested_mime = match &value {
+                PartExtractor::Multipart(form) if mime.is_none() => {
+                    Some(form.content_type("multipart/mixed"))
+                }
+                _ => None,
+            };
+            let mime = mime.or(nested_mime.as_deref());
+
             // Create the inner part
-            let mut inner = match value {
+            let inner = match value {
                 PartExtractor::Text(bytes) | PartExtractor::Bytes(bytes) => {
                     wreq::multipart::Part::stream(Body::from(bytes))
                 }
@@ -53,24 +112,43 @@ impl Part {
                 PartExtractor::AsyncStream(stream) => {
                     wreq::multipart::Part::stream(Body::wrap_stream(stream))
                 }
+                PartExtractor::ReadStream(stream) => {
+                    wreq::multipart::Part::stream(Body::wrap_stream(stream))
+                }
+                PartExtractor::Multipart(form) => wreq::multipart::Part::stream(form.into_body()),
+            };
+
+            Part::build(name, inner, filename, mime, headers)
+        })
+    }
+
+    /// Creates a new part whose value is read from a sync or async iterator of bytes.
+    ///
+    /// Passing the total `length` lets the form be sent with a `Content-Length`.
+    #[staticmethod]
+    #[pyo3(signature = (name, stream, length = None, filename = None, mime = None, headers = None))]
+    pub fn from_stream(
+        py: Python,
+        name: String,
+        stream: StreamExtractor,
+        length: Option<u64>,
+        filename: Option<String>,
+        mime: Option<&str>,
+        headers: Option<HeaderMapExtractor>,
+    ) -> PyResult<Part> {
+        py.allow_threads(|| {
+            let body = match stream {
+                StreamExtractor::SyncStream(stream) => Body::wrap_stream(stream),
+                StreamExtractor::AsyncStream(stream) => Body::wrap_stream(stream),
+                StreamExtractor::ReadStream(stream) => Body::wrap_stream(stream),
             };
 
-            // Set the filename and MIME type if provided
-            if let Some(filename) = filename {
-                inner = inner.file_name(filename);
-            }
-
-            // Set the MIME type if provided
-            if let Some(mime) = mime {
-                inner = inner.mime_str(mime).map_err(|e| {
-                    MIMEParseError::new_err(format!("Cannot parse MIME type: {:?}", e))
-                })?;
-            }
-
-            Ok(Part {
-                name: Some(name),
-                inner: Some(inner),
-            })
+            let inner = match length {
+                Some(length) => wreq::multipart::Part::stream_with_length(body, length),
+                None => wreq::multipart::Part::stream(body),
+            };
+
+            Part::build(name, inner, filename, mime, headers)
         })
     }
 }
@@ -89,14 +167,75 @@ impl FromPyObject<'_> for PartExtractor {
             return Ok(Self::File(path));
         }
 
+        if ob.is_instance_of::<Multipart>() {
+            return ob.extract().map(Self::Multipart);
+        }
+
+        match StreamExtractor::try_extract(ob)? {
+            Some(StreamExtractor::SyncStream(stream)) => Ok(Self::SyncStream(stream)),
+            Some(StreamExtractor::AsyncStream(stream)) => Ok(Self::AsyncStream(stream)),
+            Some(StreamExtractor::ReadStream(stream)) => Ok(Self::ReadStream(stream)),
+            None => Err(PyTypeError::new_err(format!(
+                "expected str, bytes, os.PathLike, Multipart, a file-like object with read(), \
+                 or a sync or async iterator of bytes, got {}",
+                ob.get_type().name()?
+            ))),
+        }
+    }
+}
+
+impl StreamExtractor {
+    /// Extracts a stream from `ob`, or returns `None` if it is not a supported stream type.
+    fn try_extract(ob: &Bound<'_, PyAny>) -> PyResult<Option<Self>> {
         if ob.hasattr("asend")? {
-            pyo3_async_runtimes::tokio::into_stream_v2(ob.to_owned())
+            return pyo3_async_runtimes::tokio::into_stream_v2(ob.to_owned())
                 .map(AsyncStream::new)
                 .map(Self::AsyncStream)
-        } else {
-            ob.extract::<PyObject>()
-                .map(SyncStream::new)
-                .map(Self::SyncStream)
+                .map(Some);
         }
+
+        if ob.hasattr("read")? {
+            return Ok(Some(Self::ReadStream(ReadStream::new(ob.clone().unbind()))));
+        }
+
+        if ob.hasattr("__next__")? {
+            return Ok(Some(Self::SyncStream(SyncStream::new(ob.clone().unbind()))));
+        }
+
+        Ok(None)
+    }
+}
+
+impl FromPyObject<'_> for StreamExtractor {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Self::try_extract(ob)?.ok_or_else(|| {
+            PyTypeError::new_err(format!(
+                "expected a file-like object with read(), or a sync or async iterator of bytes, \
+                 got {}",
+                ob.get_type()
+                    .name()
+                    .map(|name| name.to_string())
+                    .unwrap_or_default()
+            ))
+        })
+    }
+}
+
+/// Checks that the custom `headers` of a part don't repeat the ones written for it.
+///
+/// `Content-Disposition` is always written from the name and filename of the part, so it
+/// can't carry parameters of its own, and `Content-Type` from its MIME type if it has one.
+fn check_headers(headers: &HeaderMap, has_mime: bool) -> PyResult<()> {
+    if headers.contains_key(header::CONTENT_DISPOSITION) {
+        return Err(PyValueError::new_err(
+            "the content-disposition of a part is written from its name and filename, and \
+             custom parameters for it aren't supported",
+        ));
+    }
+    if has_mime && headers.contains_key(header::CONTENT_TYPE) {
+        return Err(PyValueError::new_err(
+            "the content-type of a part is given both with mime and in its headers",
+        ));
     }
+    Ok(())
 }
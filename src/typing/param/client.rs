@@ -1,8 +1,12 @@
+use super::{Kwargs, Seconds, Timeout, WebSocketParams};
+use crate::async_impl::{CacheOption, MockTransport, OAuth2ClientCredentials};
 use crate::typing::{
-    HeaderMapExtractor, HeadersOrderExtractor, ImpersonateExtractor, IpAddrExtractor,
-    LookupIpStrategy, SslVerify, TlsVersion, proxy::ProxyListExtractor,
+    ClientCert, CookieJar, HeaderMapExtractor, HeadersOrderExtractor, ImpersonateExtractor,
+    LocalAddressExtractor, LookupIpStrategy, SslVerify, TlsConfigExtractor, TlsVersion,
+    proxy::ProxyListExtractor,
 };
 use pyo3::{prelude::*, pybacked::PyBackedStr};
+use std::path::PathBuf;
 
 /// The parameters for a request.
 #[derive(Default)]
@@ -31,18 +35,21 @@ pub struct ClientParams {
     /// Whether to use cookie store.
     pub cookie_store: Option<bool>,
 
+    /// The cookie jar to store cookies in, which may be shared with other clients.
+    pub cookie_provider: Option<CookieJar>,
+
     /// The lookup ip strategy
     pub lookup_ip_strategy: Option<LookupIpStrategy>,
 
     // ========= Timeout options =========
-    /// The timeout to use for the request. (in seconds)
-    pub timeout: Option<u64>,
+    /// The timeout to use for the request, or a `(connect, read)` pair. (in seconds)
+    pub timeout: Option<Timeout>,
 
     /// The connect timeout to use for the request. (in seconds)
-    pub connect_timeout: Option<u64>,
+    pub connect_timeout: Option<Seconds>,
 
     /// The read timeout to use for the request. (in seconds)
-    pub read_timeout: Option<u64>,
+    pub read_timeout: Option<Seconds>,
 
     /// Disable keep-alive for the client.
     pub no_keepalive: Option<bool>,
@@ -51,7 +58,7 @@ pub struct ClientParams {
     pub tcp_keepalive: Option<u64>,
 
     /// Set an optional timeout for idle sockets being kept-alive. (in seconds)
-    pub pool_idle_timeout: Option<u64>,
+    pub pool_idle_timeout: Option<Seconds>,
 
     /// Sets the maximum idle connection per host allowed in the pool.
     pub pool_max_idle_per_host: Option<usize>,
@@ -69,6 +76,9 @@ pub struct ClientParams {
     /// Whether to use HTTPS only.
     pub https_only: Option<bool>,
 
+    /// Whether to send HTTP/1 header names as-is instead of title-casing them.
+    pub preserve_header_case: Option<bool>,
+
     /// Set whether sockets have `TCP_NODELAY` enabled.
     pub tcp_nodelay: Option<bool>,
 
@@ -79,6 +89,9 @@ pub struct ClientParams {
     /// Whether to verify the SSL certificate or root certificate file path.
     pub verify: Option<SslVerify>,
 
+    /// The client certificate to present, as in requests.
+    pub cert: Option<ClientCert>,
+
     /// Add TLS information as `TlsInfo` extension to responses.
     pub tls_info: Option<bool>,
 
@@ -88,17 +101,55 @@ pub struct ClientParams {
     /// The maximum TLS version to use for the request.
     pub max_tls_version: Option<TlsVersion>,
 
+    /// Low-level TLS settings that replace the TLS layer of the impersonation.
+    pub tls_config: Option<TlsConfigExtractor>,
+
+    /// The HTTP cache for the client's requests.
+    pub cache: Option<CacheOption>,
+
+    /// The directory to persist the HTTP cache in.
+    pub cache_dir: Option<PathBuf>,
+
+    /// A mock transport that answers the client's requests instead of the network.
+    pub transport: Option<MockTransport>,
+
+    /// The cassette file to record the client's responses in.
+    pub record: Option<PathBuf>,
+
+    /// The cassette file to replay the client's responses from, instead of the network.
+    pub replay: Option<PathBuf>,
+
+    /// The defaults for the client's WebSocket requests, overridden by per-call options.
+    pub websocket_defaults: Option<WebSocketParams>,
+
+    /// The header to send a generated UUIDv4 request id in.
+    pub request_id_header: Option<String>,
+
+    /// The maximum size of decompressed response bodies, in bytes.
+    pub max_response_size: Option<u64>,
+
+    /// The maximum size of HTTP/1 response heads, in bytes.
+    pub max_header_size: Option<usize>,
+
+    /// The OAuth2 credentials to authenticate the client's requests with.
+    pub auth: Option<OAuth2ClientCredentials>,
+
     // ========= Network options =========
     /// Whether to disable the proxy for the request.
     pub no_proxy: Option<bool>,
 
+    /// Whether to read proxies from the `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY`
+    /// environment variables.
+    pub trust_env: Option<bool>,
+
     /// The proxy to use for the request.
     pub proxies: Option<ProxyListExtractor>,
 
-    /// Bind to a local IP Address.
-    pub local_address: Option<IpAddrExtractor>,
+    /// Bind to a local IP address, or to an IPv4 and an IPv6 address picked by the target's family.
+    pub local_address: Option<LocalAddressExtractor>,
 
-    /// Bind to an interface by `SO_BINDTODEVICE`.
+    /// Bind to an interface by `SO_BINDTODEVICE`, or by its primary IP addresses
+    /// where device binding is unavailable.
     pub interface: Option<String>,
 
     // ========= Compression options =========
@@ -131,15 +182,87 @@ pub struct UpdateClientParams {
     /// The proxy to use for the request.
     pub proxies: Option<ProxyListExtractor>,
 
-    /// Bind to a local IP Address.
-    pub local_address: Option<IpAddrExtractor>,
+    /// Bind to a local IP address, or to an IPv4 and an IPv6 address picked by the target's family.
+    pub local_address: Option<LocalAddressExtractor>,
 
-    /// Bind to an interface by `SO_BINDTODEVICE`.
+    /// Bind to an interface by `SO_BINDTODEVICE`, or by its primary IP addresses
+    /// where device binding is unavailable.
     pub interface: Option<String>,
+
+    // ========= Redirect options =========
+    /// Whether to allow redirects.
+    pub allow_redirects: Option<bool>,
+
+    /// The maximum number of redirects to follow.
+    pub max_redirects: Option<usize>,
+
+    // ========= Options that rebuild the client =========
+    /// Whether to use cookie store.
+    pub cookie_store: Option<bool>,
+
+    /// The cookie jar to store cookies in, which may be shared with other clients.
+    pub cookie_provider: Option<CookieJar>,
+
+    /// The timeout to use for the request, or a `(connect, read)` pair. (in seconds)
+    pub timeout: Option<Timeout>,
+
+    /// The connect timeout to use for the request. (in seconds)
+    pub connect_timeout: Option<Seconds>,
+
+    /// The read timeout to use for the request. (in seconds)
+    pub read_timeout: Option<Seconds>,
+
+    /// Set an optional timeout for idle sockets being kept-alive. (in seconds)
+    pub pool_idle_timeout: Option<Seconds>,
+
+    /// Sets the maximum idle connection per host allowed in the pool.
+    pub pool_max_idle_per_host: Option<usize>,
+
+    /// Sets the maximum number of connections in the pool.
+    pub pool_max_size: Option<usize>,
+
+    /// Sets gzip as an accepted encoding.
+    pub gzip: Option<bool>,
+
+    /// Sets brotli as an accepted encoding.
+    pub brotli: Option<bool>,
+
+    /// Sets deflate as an accepted encoding.
+    pub deflate: Option<bool>,
+
+    /// Sets zstd as an accepted encoding.
+    pub zstd: Option<bool>,
+}
+
+impl ClientParams {
+    /// Whether the client stores cookies: by default, only if it's given a cookie jar.
+    pub fn stores_cookies(&self) -> bool {
+        self.cookie_store.unwrap_or(self.cookie_provider.is_some())
+    }
+}
+
+impl UpdateClientParams {
+    /// Whether the update changes settings that wreq can't update in place, so the client
+    /// has to be rebuilt.
+    pub fn needs_rebuild(&self) -> bool {
+        self.cookie_store.is_some()
+            || self.cookie_provider.is_some()
+            || self.timeout.is_some()
+            || self.connect_timeout.is_some()
+            || self.read_timeout.is_some()
+            || self.pool_idle_timeout.is_some()
+            || self.pool_max_idle_per_host.is_some()
+            || self.pool_max_size.is_some()
+            || self.gzip.is_some()
+            || self.brotli.is_some()
+            || self.deflate.is_some()
+            || self.zstd.is_some()
+    }
 }
 
 impl<'py> FromPyObject<'py> for ClientParams {
     fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let ob = Kwargs::new(ob);
         let mut params = Self::default();
         extract_option!(ob, params, impersonate);
 
@@ -148,7 +271,9 @@ impl<'py> FromPyObject<'py> for ClientParams {
         extract_option!(ob, params, headers_order);
         extract_option!(ob, params, referer);
         extract_option!(ob, params, allow_redirects);
+        extract_option!(ob, params, max_redirects);
         extract_option!(ob, params, cookie_store);
+        extract_option!(ob, params, cookie_provider);
         extract_option!(ob, params, lookup_ip_strategy);
 
         extract_option!(ob, params, timeout);
@@ -161,6 +286,7 @@ impl<'py> FromPyObject<'py> for ClientParams {
         extract_option!(ob, params, tcp_keepalive);
 
         extract_option!(ob, params, no_proxy);
+        extract_option!(ob, params, trust_env);
         extract_option!(ob, params, proxies);
         extract_option!(ob, params, local_address);
         extract_option!(ob, params, interface);
@@ -168,23 +294,38 @@ impl<'py> FromPyObject<'py> for ClientParams {
         extract_option!(ob, params, http1_only);
         extract_option!(ob, params, http2_only);
         extract_option!(ob, params, https_only);
+        extract_option!(ob, params, preserve_header_case);
         extract_option!(ob, params, tcp_nodelay);
         extract_option!(ob, params, verify);
+        extract_option!(ob, params, cert);
         extract_option!(ob, params, http2_max_retry_count);
         extract_option!(ob, params, tls_info);
         extract_option!(ob, params, min_tls_version);
         extract_option!(ob, params, max_tls_version);
+        extract_option!(ob, params, tls_config);
+        extract_option!(ob, params, cache);
+        extract_option!(ob, params, cache_dir);
+        extract_option!(ob, params, transport);
+        extract_option!(ob, params, record);
+        extract_option!(ob, params, replay);
+        extract_option!(ob, params, websocket_defaults);
+        extract_option!(ob, params, request_id_header);
+        extract_option!(ob, params, max_response_size);
+        extract_option!(ob, params, max_header_size);
+        extract_option!(ob, params, auth);
 
         extract_option!(ob, params, gzip);
         extract_option!(ob, params, brotli);
         extract_option!(ob, params, deflate);
         extract_option!(ob, params, zstd);
+        ob.finish()?;
         Ok(params)
     }
 }
 
 impl<'py> FromPyObject<'py> for UpdateClientParams {
     fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let ob = Kwargs::new(ob);
         let mut params = Self::default();
         extract_option!(ob, params, impersonate);
         extract_option!(ob, params, headers);
@@ -192,6 +333,23 @@ impl<'py> FromPyObject<'py> for UpdateClientParams {
         extract_option!(ob, params, proxies);
         extract_option!(ob, params, local_address);
         extract_option!(ob, params, interface);
+
+        extract_option!(ob, params, allow_redirects);
+        extract_option!(ob, params, max_redirects);
+
+        extract_option!(ob, params, cookie_store);
+        extract_option!(ob, params, cookie_provider);
+        extract_option!(ob, params, timeout);
+        extract_option!(ob, params, connect_timeout);
+        extract_option!(ob, params, read_timeout);
+        extract_option!(ob, params, pool_idle_timeout);
+        extract_option!(ob, params, pool_max_idle_per_host);
+        extract_option!(ob, params, pool_max_size);
+        extract_option!(ob, params, gzip);
+        extract_option!(ob, params, brotli);
+        extract_option!(ob, params, deflate);
+        extract_option!(ob, params, zstd);
+        ob.finish()?;
         Ok(params)
     }
 }
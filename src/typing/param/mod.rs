@@ -1,7 +1,140 @@
 mod client;
 mod request;
+mod timeout;
 mod ws;
 
 pub use self::client::{ClientParams, UpdateClientParams};
 pub use self::request::RequestParams;
-pub use self::ws::WebSocketParams;
+pub use self::timeout::{Seconds, Timeout};
+pub use self::ws::{ReconnectCallback, WebSocketParams};
+use crate::typing::{BodyExtractor, UrlEncodedValuesExtractor};
+use pyo3::{exceptions::PyTypeError, prelude::*, pybacked::PyBackedStr, types::PyDict};
+use std::{cell::RefCell, sync::LazyLock};
+
+/// Set `RNET_ALLOW_UNKNOWN_KWARGS=1` to ignore unknown keyword arguments instead of raising.
+static ALLOW_UNKNOWN_KWARGS: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("RNET_ALLOW_UNKNOWN_KWARGS").is_ok_and(|value| value != "0" && !value.is_empty())
+});
+
+/// Keyword arguments that remember which keys have been looked up.
+///
+/// Used with `extract_option!` so that leftover, unknown keys can be reported by [`Kwargs::finish`].
+pub struct Kwargs<'a, 'py> {
+    ob: &'a Bound<'py, PyAny>,
+    consumed: RefCell<Vec<&'static str>>,
+}
+
+impl<'a, 'py> Kwargs<'a, 'py> {
+    pub fn new(ob: &'a Bound<'py, PyAny>) -> Self {
+        Kwargs {
+            ob,
+            consumed: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn get_item(&self, key: &'static str) -> PyResult<Bound<'py, PyAny>> {
+        self.consumed.borrow_mut().push(key);
+        self.ob.get_item(key)
+    }
+
+    /// Extracts `key`, or `alias`, its name in `requests`, if `key` isn't given. `None`
+    /// counts as not given, and giving both raises `TypeError` unless they extract to the
+    /// same value.
+    pub fn get_aliased<T>(&self, key: &'static str, alias: &'static str) -> PyResult<Option<T>>
+    where
+        T: FromPyObject<'py> + SameValue,
+    {
+        let given = |key| self.get_item(key).ok().filter(|value| !value.is_none());
+        match (given(key), given(alias)) {
+            (Some(value), Some(aliased)) => {
+                let extracted = value.extract::<T>()?;
+                if !value.is(&aliased) && !extracted.same_value(&aliased.extract()?) {
+                    return Err(PyTypeError::new_err(format!(
+                        "conflicting values for '{key}' and its alias '{alias}'"
+                    )));
+                }
+                Ok(Some(extracted))
+            }
+            (value, aliased) => value.or(aliased).map(|value| value.extract()).transpose(),
+        }
+    }
+
+    /// Raises `TypeError` for the first key that was never looked up.
+    pub fn finish(self) -> PyResult<()> {
+        if *ALLOW_UNKNOWN_KWARGS {
+            return Ok(());
+        }
+
+        let Ok(dict) = self.ob.downcast::<PyDict>() else {
+            return Ok(());
+        };
+
+        let consumed = self.consumed.into_inner();
+        for key in dict.keys() {
+            let key = key.extract::<PyBackedStr>()?;
+            if consumed.iter().any(|known| *known == &*key) {
+                continue;
+            }
+
+            let suggestion = consumed
+                .iter()
+                .map(|known| (edit_distance(&key, known), known))
+                .filter(|(distance, _)| *distance <= 2.max(key.len() / 3))
+                .min_by_key(|(distance, _)| *distance)
+                .map(|(_, known)| format!(" (did you mean '{}'?)", known))
+                .unwrap_or_default();
+
+            return Err(PyTypeError::new_err(format!(
+                "unexpected keyword argument '{}'{}",
+                &*key, suggestion
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether an option and its alias, given together, ask for the same thing.
+pub trait SameValue {
+    fn same_value(&self, other: &Self) -> bool;
+}
+
+impl SameValue for UrlEncodedValuesExtractor {
+    fn same_value(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl SameValue for BodyExtractor {
+    /// Bodies are the same if they hold the same bytes. Streams and files only match
+    /// themselves, which is checked before extraction.
+    fn same_value(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                BodyExtractor::Text(a) | BodyExtractor::Bytes(a),
+                BodyExtractor::Text(b) | BodyExtractor::Bytes(b),
+            ) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Levenshtein distance between two ASCII-ish identifiers.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.bytes().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(current)
+            };
+            prev = current;
+        }
+    }
+    row[b.len()]
+}
@@ -1,33 +1,56 @@
+use super::{Kwargs, Seconds, Timeout};
+use crate::async_impl::UploadProgress;
 use crate::typing::{
-    BodyExtractor, CookieExtractor, HeaderMapExtractor, IpAddrExtractor, Json, ProxyExtractor,
-    UrlEncodedValuesExtractor, Version, multipart::MultipartExtractor,
+    BodyExtractor, CookieExtractor, HeaderMapExtractor, HeadersOrderExtractor,
+    ImpersonateExtractor, Json, LocalAddressExtractor, ProxyExtractor, UrlEncodedValuesExtractor,
+    Version, multipart::MultipartExtractor,
 };
 use pyo3::{prelude::*, pybacked::PyBackedStr};
 
 /// The parameters for a request.
 #[derive(Default)]
 pub struct RequestParams {
+    /// The impersonation settings for the request, overriding the client's.
+    pub impersonate: Option<ImpersonateExtractor>,
+
     /// The proxy to use for the request.
     pub proxy: Option<ProxyExtractor>,
 
-    /// Bind to a local IP Address.
-    pub local_address: Option<IpAddrExtractor>,
+    /// Bind to a local IP address, or to an IPv4 and an IPv6 address picked by the target's family.
+    pub local_address: Option<LocalAddressExtractor>,
 
-    /// Bind to an interface by `SO_BINDTODEVICE`.
+    /// Bind to an interface by `SO_BINDTODEVICE`, or by its primary IP addresses
+    /// where device binding is unavailable.
     pub interface: Option<String>,
 
-    /// The timeout to use for the request.
-    pub timeout: Option<u64>,
+    /// The timeout to use for the request, or a `(connect, read)` pair. A connect timeout
+    /// can only be set on the client, so the pair's must be `None`.
+    pub timeout: Option<Timeout>,
 
     /// The read timeout to use for the request.
-    pub read_timeout: Option<u64>,
+    pub read_timeout: Option<Seconds>,
 
     /// The HTTP version to use for the request.
     pub version: Option<Version>,
 
+    /// Whether to retry over HTTP/1.1 if the requested version can't be negotiated.
+    pub version_fallback: Option<bool>,
+
+    /// Whether to decode the body. When `false`, the body is returned exactly as sent, with
+    /// its `Content-Encoding`, and the request asks for `Accept-Encoding: identity` unless
+    /// `headers` sets one.
+    pub decompress: Option<bool>,
+
+    /// The byte range to request as `(start, end)`, both inclusive; `end` may be `None`
+    /// to request everything from `start` on.
+    pub range: Option<(u64, Option<u64>)>,
+
     /// The headers to use for the request.
     pub headers: Option<HeaderMapExtractor>,
 
+    /// The order to send the headers in, overriding the client's.
+    pub headers_order: Option<HeadersOrderExtractor>,
+
     /// The cookies to use for the request.
     pub cookies: Option<CookieExtractor>,
 
@@ -37,6 +60,23 @@ pub struct RequestParams {
     /// The maximum number of redirects to follow.
     pub max_redirects: Option<usize>,
 
+    /// Whether to follow `<meta http-equiv="refresh">` redirects in HTML responses, within
+    /// the same limit as HTTP redirects.
+    pub follow_meta_refresh: Option<bool>,
+
+    /// The maximum size of the decompressed response body, in bytes.
+    pub max_response_size: Option<u64>,
+
+    /// Whether the body may be read before the response is returned, to store it in the
+    /// cache. When `false`, the response is returned as soon as its headers arrive.
+    pub prefetch_body: Option<bool>,
+
+    /// Whether the request and its redirects must use `https`.
+    pub https_only: Option<bool>,
+
+    /// Whether to follow redirects from `https` to `http`.
+    pub allow_insecure_redirects: Option<bool>,
+
     /// The authentication to use for the request.
     pub auth: Option<PyBackedStr>,
 
@@ -46,25 +86,30 @@ pub struct RequestParams {
     /// The basic authentication to use for the request.
     pub basic_auth: Option<(PyBackedStr, Option<PyBackedStr>)>,
 
-    /// The query parameters to use for the request.
+    /// The query parameters to use for the request, also accepted as `params`.
     pub query: Option<UrlEncodedValuesExtractor>,
 
-    /// The form parameters to use for the request.
+    /// The form parameters to use for the request, also accepted as `data`.
     pub form: Option<UrlEncodedValuesExtractor>,
 
     /// The JSON body to use for the request.
     pub json: Option<Json>,
 
-    /// The body to use for the request.
+    /// The body to use for the request, also accepted as `data` if it isn't a form.
     pub body: Option<BodyExtractor>,
 
     /// The multipart form to use for the request.
     pub multipart: Option<MultipartExtractor>,
+
+    /// Called with `(sent, total)` as the body of the request is sent.
+    pub on_upload_progress: Option<UploadProgress>,
 }
 
 impl<'py> FromPyObject<'py> for RequestParams {
     fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<RequestParams> {
+        let ob = Kwargs::new(ob);
         let mut params = Self::default();
+        extract_option!(ob, params, impersonate);
         extract_option!(ob, params, proxy);
         extract_option!(ob, params, local_address);
         extract_option!(ob, params, interface);
@@ -72,18 +117,39 @@ impl<'py> FromPyObject<'py> for RequestParams {
         extract_option!(ob, params, read_timeout);
 
         extract_option!(ob, params, version);
+        extract_option!(ob, params, version_fallback);
+        extract_option!(ob, params, decompress);
+        extract_option!(ob, params, range);
         extract_option!(ob, params, headers);
+        extract_option!(ob, params, headers_order);
         extract_option!(ob, params, cookies);
         extract_option!(ob, params, allow_redirects);
         extract_option!(ob, params, max_redirects);
+        extract_option!(ob, params, follow_meta_refresh);
+        extract_option!(ob, params, max_response_size);
+        extract_option!(ob, params, prefetch_body);
+        extract_option!(ob, params, https_only);
+        extract_option!(ob, params, allow_insecure_redirects);
         extract_option!(ob, params, auth);
         extract_option!(ob, params, bearer_auth);
         extract_option!(ob, params, basic_auth);
-        extract_option!(ob, params, query);
-        extract_option!(ob, params, form);
+        extract_option!(ob, params, query, alias = params);
+        // `data` is a form if it's a dict or pairs, and the body otherwise, as in requests.
+        let data_is_form = ob
+            .get_item("data")
+            .is_ok_and(|data| data.extract::<UrlEncodedValuesExtractor>().is_ok());
+        if data_is_form {
+            extract_option!(ob, params, form, alias = data);
+            extract_option!(ob, params, body);
+        } else {
+            extract_option!(ob, params, form);
+            extract_option!(ob, params, body, alias = data);
+        }
         extract_option!(ob, params, json);
-        extract_option!(ob, params, body);
         extract_option!(ob, params, multipart);
+        extract_option!(ob, params, on_upload_progress);
+
+        ob.finish()?;
 
         Ok(params)
     }
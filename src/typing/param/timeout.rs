@@ -0,0 +1,36 @@
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyTuple};
+use std::time::Duration;
+
+/// A duration in seconds, given as an integer or as a float such as `0.5`.
+#[derive(Clone, Copy)]
+pub struct Seconds(pub Duration);
+
+impl FromPyObject<'_> for Seconds {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(secs) = ob.extract::<u64>() {
+            return Ok(Seconds(Duration::from_secs(secs)));
+        }
+        let secs = ob.extract::<f64>()?;
+        Duration::try_from_secs_f64(secs).map(Seconds).map_err(|_| {
+            PyValueError::new_err(format!(
+                "timeouts must be a non-negative number of seconds, got {secs}"
+            ))
+        })
+    }
+}
+
+/// A timeout for the whole request, or a `(connect, read)` pair where either may be `None`.
+pub enum Timeout {
+    Total(Duration),
+    Split(Option<Seconds>, Option<Seconds>),
+}
+
+impl FromPyObject<'_> for Timeout {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if ob.downcast::<PyTuple>().is_ok() {
+            let (connect, read) = ob.extract()?;
+            return Ok(Timeout::Split(connect, read));
+        }
+        ob.extract::<Seconds>().map(|secs| Timeout::Total(secs.0))
+    }
+}
@@ -1,18 +1,25 @@
+use super::Kwargs;
 use crate::typing::{
-    CookieExtractor, HeaderMapExtractor, IpAddrExtractor, ProxyExtractor, UrlEncodedValuesExtractor,
+    CookieExtractor, HeaderMapExtractor, LocalAddressExtractor, ProxyExtractor,
+    UrlEncodedValuesExtractor,
 };
-use pyo3::{prelude::*, pybacked::PyBackedStr};
+use pyo3::{exceptions::PyTypeError, prelude::*};
+use std::sync::Arc;
 
 /// The parameters for a WebSocket request.
-#[derive(Default)]
+///
+/// Strings are stored as owned `String`s so the parameters can be cloned without the GIL
+/// to replay the handshake.
+#[derive(Default, Clone)]
 pub struct WebSocketParams {
     /// The proxy to use for the request.
     pub proxy: Option<ProxyExtractor>,
 
-    /// Bind to a local IP Address.
-    pub local_address: Option<IpAddrExtractor>,
+    /// Bind to a local IP address, or to an IPv4 and an IPv6 address picked by the target's family.
+    pub local_address: Option<LocalAddressExtractor>,
 
-    /// Bind to an interface by `SO_BINDTODEVICE`.
+    /// Bind to an interface by `SO_BINDTODEVICE`, or by its primary IP addresses
+    /// where device binding is unavailable.
     pub interface: Option<String>,
 
     /// The headers to use for the request.
@@ -22,19 +29,25 @@ pub struct WebSocketParams {
     pub cookies: Option<CookieExtractor>,
 
     /// The protocols to use for the request.
-    pub protocols: Option<Vec<String>>,
+    pub protocols: Option<ProtocolsExtractor>,
+
+    /// The `Origin` header to send with the handshake.
+    pub origin: Option<String>,
+
+    /// The `User-Agent` header to send with the handshake, overriding the client's.
+    pub user_agent: Option<String>,
 
     /// Whether to use HTTP/2 for the websocket.
     pub use_http2: Option<bool>,
 
     /// The authentication to use for the request.
-    pub auth: Option<PyBackedStr>,
+    pub auth: Option<String>,
 
     /// The bearer authentication to use for the request.
-    pub bearer_auth: Option<PyBackedStr>,
+    pub bearer_auth: Option<String>,
 
     /// The basic authentication to use for the request.
-    pub basic_auth: Option<(PyBackedStr, Option<PyBackedStr>)>,
+    pub basic_auth: Option<(String, Option<String>)>,
 
     /// The query parameters to use for the request.
     pub query: Option<UrlEncodedValuesExtractor>,
@@ -90,10 +103,148 @@ pub struct WebSocketParams {
     /// some popular libraries that are sending unmasked frames, ignoring the RFC.
     /// By default this option is set to `false`, i.e. according to RFC 6455.
     pub accept_unmasked_frames: Option<bool>,
+
+    /// The number of received messages to read ahead and buffer. When the queue is full,
+    /// the connection isn't read until messages are received, applying TCP backpressure.
+    /// By default, messages are only read from the connection when received.
+    pub max_recv_queue: Option<usize>,
+
+    // ========= Reconnect options =========
+    /// Whether to re-establish the connection when receiving hits a network error.
+    pub auto_reconnect: Option<bool>,
+
+    /// The number of reconnect attempts before the error is raised. The default value is 5.
+    pub reconnect_max_attempts: Option<usize>,
+
+    /// The delay before the first reconnect attempt, doubled after each failed one.
+    /// (in seconds, the default value is 1.0)
+    pub reconnect_backoff: Option<f64>,
+
+    /// Called with the attempt number once the connection has been re-established.
+    pub on_reconnect: Option<ReconnectCallback>,
+
+    /// Whether messages sent while reconnecting wait for the new connection instead of
+    /// failing. The default value is `true`.
+    pub reconnect_queue_sends: Option<bool>,
+}
+
+impl WebSocketParams {
+    /// Fills the options that weren't given from `defaults`.
+    ///
+    /// Headers are merged by name, so a default header is only replaced by a per-call
+    /// header of the same name.
+    pub fn merge(mut self, defaults: &WebSocketParams) -> WebSocketParams {
+        let defaults = defaults.clone();
+        self.headers = match (defaults.headers, self.headers) {
+            (Some(mut headers), Some(overrides)) => {
+                for name in overrides.0.keys() {
+                    headers.0.remove(name);
+                }
+                headers.0.extend(overrides.0);
+                Some(headers)
+            }
+            (headers, overrides) => overrides.or(headers),
+        };
+
+        macro_rules! fill {
+            ($($field:ident),* $(,)?) => {
+                $(self.$field = self.$field.or(defaults.$field);)*
+            };
+        }
+        fill!(
+            proxy,
+            local_address,
+            interface,
+            cookies,
+            protocols,
+            origin,
+            user_agent,
+            use_http2,
+            auth,
+            bearer_auth,
+            basic_auth,
+            query,
+            read_buffer_size,
+            write_buffer_size,
+            max_write_buffer_size,
+            max_message_size,
+            max_frame_size,
+            accept_unmasked_frames,
+            max_recv_queue,
+            auto_reconnect,
+            reconnect_max_attempts,
+            reconnect_backoff,
+            on_reconnect,
+            reconnect_queue_sends,
+        );
+        self
+    }
+}
+
+/// The subprotocols to offer: a single string or any iterable of strings.
+#[derive(Clone)]
+pub struct ProtocolsExtractor(pub Vec<String>);
+
+impl FromPyObject<'_> for ProtocolsExtractor {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(protocol) = ob.extract::<String>() {
+            return Ok(ProtocolsExtractor(vec![protocol]));
+        }
+
+        let Ok(protocols) = ob.try_iter() else {
+            return Err(PyTypeError::new_err(format!(
+                "protocols: expected str or an iterable of str, got {}",
+                ob.get_type().name()?
+            )));
+        };
+        protocols
+            .map(|item| item?.extract::<String>())
+            .collect::<PyResult<_>>()
+            .map(ProtocolsExtractor)
+    }
+}
+
+/// A Python callable invoked after a WebSocket reconnects.
+#[derive(Clone)]
+pub struct ReconnectCallback(pub Arc<PyObject>);
+
+impl FromPyObject<'_> for ReconnectCallback {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if !ob.is_callable() {
+            return Err(PyTypeError::new_err(format!(
+                "on_reconnect must be callable, got {}",
+                ob.get_type().name()?
+            )));
+        }
+        Ok(ReconnectCallback(Arc::new(ob.clone().unbind())))
+    }
+}
+
+impl ReconnectCallback {
+    /// Fails if the callback is a coroutine function, which a blocking WebSocket can't await.
+    pub fn check_blocking(&self, py: Python) -> PyResult<()> {
+        let inspect = py.import("inspect")?;
+        if inspect
+            .call_method1("iscoroutinefunction", (self.0.bind(py),))?
+            .is_truthy()?
+        {
+            return Err(Self::coroutine_error());
+        }
+        Ok(())
+    }
+
+    /// The error for a coroutine `on_reconnect` on a blocking WebSocket.
+    pub fn coroutine_error() -> PyErr {
+        PyTypeError::new_err(
+            "on_reconnect can't be a coroutine function on a blocking WebSocket, which has \
+             no event loop to await it; pass a plain function",
+        )
+    }
 }
 
 impl<'py> FromPyObject<'py> for WebSocketParams {
     fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let ob = Kwargs::new(ob);
         let mut params = Self::default();
         extract_option!(ob, params, proxy);
         extract_option!(ob, params, local_address);
@@ -103,6 +254,8 @@ impl<'py> FromPyObject<'py> for WebSocketParams {
         extract_option!(ob, params, headers);
         extract_option!(ob, params, cookies);
         extract_option!(ob, params, protocols);
+        extract_option!(ob, params, origin);
+        extract_option!(ob, params, user_agent);
         extract_option!(ob, params, auth);
         extract_option!(ob, params, bearer_auth);
         extract_option!(ob, params, basic_auth);
@@ -114,6 +267,14 @@ impl<'py> FromPyObject<'py> for WebSocketParams {
         extract_option!(ob, params, max_message_size);
         extract_option!(ob, params, max_frame_size);
         extract_option!(ob, params, accept_unmasked_frames);
+        extract_option!(ob, params, max_recv_queue);
+
+        extract_option!(ob, params, auto_reconnect);
+        extract_option!(ob, params, reconnect_max_attempts);
+        extract_option!(ob, params, reconnect_backoff);
+        extract_option!(ob, params, on_reconnect);
+        extract_option!(ob, params, reconnect_queue_sends);
+        ob.finish()?;
         Ok(params)
     }
 }
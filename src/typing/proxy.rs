@@ -1,10 +1,26 @@
 use super::HeaderMapExtractor;
-use crate::error::Error;
-use pyo3::{prelude::*, pybacked::PyBackedStr, types::PyList};
-use wreq::header::HeaderValue;
+use crate::{
+    error::{Error, ProxyError},
+    pac::Pac,
+};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyTypeError, PyValueError},
+    prelude::*,
+    pybacked::PyBackedStr,
+    types::PyList,
+};
+use std::{
+    cell::RefCell,
+    future::{Future, poll_fn},
+    path::PathBuf,
+    pin::pin,
+    sync::Arc,
+    task::Poll,
+};
+use wreq::{Url, header::HeaderValue};
 
 macro_rules! proxy_method {
-    ( $( { $(#[$meta:meta])* $name:ident, $proxy_fn:path} ),* ) => {
+    ( $( { $(#[$meta:meta])* $name:ident, $proxy_fn:path, $scheme:expr } ),* ) => {
         #[pymethods]
         impl Proxy {
             $(
@@ -17,7 +33,9 @@ macro_rules! proxy_method {
                     custom_http_auth = None,
                     custom_http_headers = None,
                     exclusion = None,
+                    matches = None,
                 ))]
+                #[allow(clippy::too_many_arguments)]
                 #[inline]
                 fn $name(
                     py: Python,
@@ -27,16 +45,19 @@ macro_rules! proxy_method {
                     custom_http_auth: Option<&str>,
                     custom_http_headers: Option<HeaderMapExtractor>,
                     exclusion: Option<&str>,
+                    matches: Option<HostMatcher>,
                 ) -> PyResult<Self> {
                     py.allow_threads(|| {
                         Self::create_proxy(
                             $proxy_fn,
+                            $scheme,
                             url,
                             username,
                             password,
                             custom_http_auth,
                             custom_http_headers,
                             exclusion,
+                            matches,
                         )
                     })
                 }
@@ -56,37 +77,219 @@ proxy_method! {
         ///
         /// This method sets up a proxy server for HTTP requests.
         http,
-        wreq::Proxy::http
+        wreq::Proxy::http,
+        Some("http")
     },
     {
         /// Creates a new HTTPS proxy.
         ///
         /// This method sets up a proxy server for HTTPS requests.
         https,
-        wreq::Proxy::https
+        wreq::Proxy::https,
+        Some("https")
     },
     {
         /// Creates a new proxy for all protocols.
         ///
         /// This method sets up a proxy server for all types of requests (HTTP, HTTPS, etc.).
         all,
-        wreq::Proxy::all
+        wreq::Proxy::all,
+        None
     }
 }
 
+/// Where requests go when a PAC script fails outside of [`check_pac`]: a host that can't
+/// resolve, so that they fail rather than leave without the proxy the script was meant to
+/// pick.
+const PAC_FAILED_PROXY: &str = "http://pac-script-failed.invalid:9";
+
+tokio::task_local! {
+    /// The error of the PAC script that failed while [`check_pac`] was running.
+    static PAC_FAILURE: RefCell<Option<String>>;
+}
+
+/// Runs `f`, failing with `ProxyError` if a PAC script fails to pick a proxy while it runs.
+///
+/// wreq asks the proxies of a request for its proxy as the request is built or sent, so
+/// this wraps those calls.
+pub fn check_pac<T>(f: impl FnOnce() -> T) -> PyResult<T> {
+    let (output, failure) = PAC_FAILURE.sync_scope(RefCell::new(None), || {
+        let output = f();
+        (output, PAC_FAILURE.with(|failure| failure.take()))
+    });
+    match failure {
+        Some(err) => Err(ProxyError::new_err(format!("PAC script failed: {err}"))),
+        None => Ok(output),
+    }
+}
+
+/// Polls `future` within [`check_pac`], dropping it as soon as a PAC script fails.
+pub async fn check_pac_future<F: Future>(future: F) -> PyResult<F::Output> {
+    let mut future = pin!(future);
+    poll_fn(|cx| match check_pac(|| future.as_mut().poll(cx)) {
+        Ok(poll) => poll.map(Ok),
+        Err(err) => Poll::Ready(Err(err)),
+    })
+    .await
+}
+
+#[pymethods]
+impl Proxy {
+    /// Creates a proxy chosen per request by the `FindProxyForURL` function of a proxy
+    /// auto-config (PAC) script, read from a file or fetched from an `http`, `https` or
+    /// `file` URL, with `client` if given.
+    #[staticmethod]
+    #[pyo3(signature = (source, client = None))]
+    fn from_pac(
+        py: Python,
+        source: PacSource,
+        client: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        let script = match source {
+            PacSource::Path(path) => py
+                .allow_threads(|| std::fs::read_to_string(path))
+                .map_err(Error::IO)?,
+            PacSource::Url(url) => {
+                let running_loop = py
+                    .import("asyncio")?
+                    .getattr("_get_running_loop")?
+                    .call0()?;
+                if !running_loop.is_none() {
+                    return Err(PyRuntimeError::new_err(
+                        "can't fetch a PAC script while an event loop is running; fetch it \
+                         with the client and use Proxy.from_pac_script",
+                    ));
+                }
+                let client = match client {
+                    Some(client) => pac_client(client)?,
+                    None => wreq::Client::new(),
+                };
+                py.allow_threads(|| {
+                    pyo3_async_runtimes::tokio::get_runtime().block_on(async {
+                        client
+                            .get(url)
+                            .send()
+                            .await?
+                            .error_for_status()?
+                            .text()
+                            .await
+                    })
+                })
+                .map_err(Error::Request)?
+            }
+        };
+        Self::from_pac_script(&script)
+    }
+
+    /// Creates a proxy chosen per request by the `FindProxyForURL` function of the PAC
+    /// script `script`.
+    #[staticmethod]
+    fn from_pac_script(script: &str) -> PyResult<Self> {
+        let pac = parse_pac(script)?;
+        Ok(Proxy(wreq::Proxy::custom(move |target: &Url| {
+            pac.find_proxy(target).unwrap_or_else(|err| {
+                let reported = PAC_FAILURE
+                    .try_with(|failure| *failure.borrow_mut() = Some(err.clone()))
+                    .is_ok();
+                if !reported {
+                    log::warn!(
+                        "PAC script failed for {}: {err}",
+                        target.origin().ascii_serialization()
+                    );
+                }
+                Some(PAC_FAILED_PROXY.to_owned())
+            })
+        })))
+    }
+
+    /// Returns what the `FindProxyForURL` function of the PAC script `script` returns for
+    /// `url`.
+    #[staticmethod]
+    fn find_proxy_for_url(py: Python, script: &str, url: &str) -> PyResult<String> {
+        let pac = parse_pac(script)?;
+        let target = Url::parse(url).map_err(|err| PyValueError::new_err(err.to_string()))?;
+        let host = target
+            .host_str()
+            .unwrap_or_default()
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        py.allow_threads(|| pac.evaluate(url, host))
+            .map_err(|err| PyValueError::new_err(format!("PAC script failed: {err}")))
+    }
+}
+
+fn parse_pac(script: &str) -> PyResult<Pac> {
+    Pac::parse(script).map_err(|err| PyValueError::new_err(format!("invalid PAC script: {err}")))
+}
+
+/// Returns the inner client of `client`, a `Client` or `BlockingClient`.
+fn pac_client(client: &Bound<'_, PyAny>) -> PyResult<wreq::Client> {
+    if let Ok(client) = client.downcast::<crate::async_impl::Client>() {
+        return client.borrow().inner();
+    }
+    if let Ok(client) = client.downcast::<crate::blocking::BlockingClient>() {
+        return client.borrow().0.inner();
+    }
+    Err(PyTypeError::new_err(
+        "client must be a Client or BlockingClient",
+    ))
+}
+
 impl Proxy {
+    /// Creates a proxy with `proxy_fn`, or one that's only used for the hosts `matches`
+    /// allows, among the requests to `scheme` (any scheme if `None`).
+    #[allow(clippy::too_many_arguments)]
     fn create_proxy<'py>(
         proxy_fn: impl Fn(&'py str) -> wreq::Result<wreq::Proxy>,
+        scheme: Option<&'static str>,
         url: &'py str,
         username: Option<&'py str>,
         password: Option<&'py str>,
         custom_http_auth: Option<&'py str>,
         custom_http_headers: Option<HeaderMapExtractor>,
         exclusion: Option<&'py str>,
+        matches: Option<HostMatcher>,
     ) -> PyResult<Self> {
         let mut proxy = proxy_fn(url).map_err(Error::Request)?;
+
+        // Credentials passed apart from the URL are used as they are, without percent-encoding,
+        // and a username may go without a password.
+        let credentials = match (username, password) {
+            (Some(username), password) => Some((username, password.unwrap_or_default())),
+            (None, Some(_)) => {
+                return Err(PyValueError::new_err("password requires a username"));
+            }
+            (None, None) => None,
+        };
+        if credentials.is_some()
+            && Url::parse(url).is_ok_and(|url| url.scheme().starts_with("socks4"))
+        {
+            return Err(PyValueError::new_err(
+                "SOCKS4 proxies don't support a username and password",
+            ));
+        }
+
+        // A proxy limited to some hosts is a custom one, which wreq sends no headers through.
+        if let Some(matches) = matches {
+            if custom_http_headers.is_some() {
+                return Err(PyValueError::new_err(
+                    "custom_http_headers can't be combined with matches",
+                ));
+            }
+            // Only HTTP proxies get the credentials of a custom one, so SOCKS5 ones need them
+            // in the URL.
+            let url = match credentials {
+                Some((username, password)) => with_credentials(url, username, password)?,
+                None => url.to_owned(),
+            };
+            proxy = wreq::Proxy::custom(move |target: &Url| {
+                let scheme_matches = scheme.is_none_or(|scheme| target.scheme() == scheme);
+                (scheme_matches && matches.matches(target)).then(|| url.clone())
+            });
+        }
+
         // Convert the username and password to a basic auth header value.
-        if let (Some(username), Some(password)) = (username, password) {
+        if let Some((username, password)) = credentials {
             proxy = proxy.basic_auth(username, password)
         }
 
@@ -109,6 +312,98 @@ impl Proxy {
     }
 }
 
+/// Returns the proxy `url` with `username` and `password` percent-encoded into it.
+fn with_credentials(url: &str, username: &str, password: &str) -> PyResult<String> {
+    // wreq reads a proxy URL without a scheme as an `http` one.
+    let mut url = Url::parse(url)
+        .ok()
+        .filter(Url::has_host)
+        .map_or_else(|| Url::parse(&format!("http://{url}")), Ok)
+        .map_err(Error::UrlParse)?;
+    url.set_username(username)
+        .and_then(|_| url.set_password(Some(password)))
+        .map_err(|_| PyValueError::new_err(format!("proxy URL can't have credentials: {url}")))?;
+    Ok(url.into())
+}
+
+/// The hosts a proxy is used for, as patterns in which `*` matches any run of characters,
+/// so `*.google.com` matches `www.google.com` but not `google.com`.
+pub struct HostMatcher(Arc<[String]>);
+
+impl HostMatcher {
+    /// Returns whether the host of `url` matches one of the patterns, ignoring case.
+    fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        self.0
+            .iter()
+            .any(|pattern| glob_match(pattern.as_bytes(), host.as_bytes()))
+    }
+}
+
+impl FromPyObject<'_> for HostMatcher {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let patterns = match ob.extract::<PyBackedStr>() {
+            Ok(pattern) => vec![pattern.to_ascii_lowercase()],
+            Err(_) => ob
+                .extract::<Vec<PyBackedStr>>()?
+                .iter()
+                .map(|pattern| pattern.to_ascii_lowercase())
+                .collect(),
+        };
+        Ok(HostMatcher(patterns.into()))
+    }
+}
+
+/// Matches `text` against `pattern`, where `*` matches any run of characters, ignoring ASCII
+/// case in `text`.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Where to resume after the last `*` if the rest doesn't match.
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t].to_ascii_lowercase() {
+            p += 1;
+            t += 1;
+        } else if let Some((star, start)) = backtrack {
+            p = star + 1;
+            t = start + 1;
+            backtrack = Some((star, start + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&b| b == b'*')
+}
+
+/// Where a PAC script is loaded from: a path, or an `http`, `https` or `file` URL.
+pub enum PacSource {
+    Url(Url),
+    Path(PathBuf),
+}
+
+impl FromPyObject<'_> for PacSource {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let Ok(source) = ob.extract::<PyBackedStr>() else {
+            return ob.extract().map(PacSource::Path);
+        };
+        match Url::parse(&source) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(PacSource::Url(url)),
+            Ok(url) if url.scheme() == "file" => url
+                .to_file_path()
+                .map(PacSource::Path)
+                .map_err(|_| PyValueError::new_err(format!("invalid file URL: {url}"))),
+            _ => Ok(PacSource::Path(PathBuf::from(&*source))),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct ProxyExtractor(pub wreq::Proxy);
 
 impl FromPyObject<'_> for ProxyExtractor {
@@ -1,7 +1,9 @@
-use pyo3::prelude::*;
+use pyo3::{IntoPyObjectExt, basic::CompareOp, prelude::*};
 
 /// HTTP status code.
-#[pyclass(eq)]
+///
+/// Compares and hashes like its integer value, so `status == 404` works.
+#[pyclass]
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct StatusCode(wreq::StatusCode);
 
@@ -13,6 +15,20 @@ impl StatusCode {
         self.0.as_u16()
     }
 
+    /// Returns the standard reason phrase, such as `"Not Found"`, if the status has one.
+    #[getter]
+    #[inline]
+    pub fn reason(&self) -> Option<&'static str> {
+        self.0.canonical_reason()
+    }
+
+    /// Alias of `reason`.
+    #[getter]
+    #[inline]
+    pub fn reason_phrase(&self) -> Option<&'static str> {
+        self.0.canonical_reason()
+    }
+
     /// Check if status is within 100-199.
     #[inline]
     pub fn is_informational(&self) -> bool {
@@ -31,6 +47,18 @@ impl StatusCode {
         self.0.is_redirection()
     }
 
+    /// Check if status is one that redirects: 301, 302, 303, 307 or 308.
+    #[inline]
+    pub fn is_redirect(&self) -> bool {
+        matches!(self.0.as_u16(), 301 | 302 | 303 | 307 | 308)
+    }
+
+    /// Check if status is a permanent redirect: 301 or 308.
+    #[inline]
+    pub fn is_permanent_redirect(&self) -> bool {
+        matches!(self.0.as_u16(), 301 | 308)
+    }
+
     /// Check if status is within 400-499.
     #[inline]
     pub fn is_client_error(&self) -> bool {
@@ -46,17 +74,128 @@ impl StatusCode {
 
 #[pymethods]
 impl StatusCode {
-    fn __str__(&self) -> &str {
-        self.0.as_str()
+    fn __str__(&self) -> String {
+        match self.0.canonical_reason() {
+            Some(reason) => format!("{} {reason}", self.0.as_str()),
+            None => self.0.as_str().to_owned(),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("StatusCode({})", self.0.as_u16())
+    }
+
+    fn __int__(&self) -> u16 {
+        self.0.as_u16()
+    }
+
+    fn __index__(&self) -> u16 {
+        self.0.as_u16()
+    }
+
+    fn __hash__(&self) -> u64 {
+        self.0.as_u16().into()
     }
 
-    fn __repr__(&self) -> &str {
-        self.__str__()
+    fn __richcmp__(&self, other: &Bound<'_, PyAny>, op: CompareOp) -> PyResult<PyObject> {
+        let py = other.py();
+        // Both ints and status codes, through `__index__`, extract as integers.
+        let Ok(other) = other.extract::<i64>() else {
+            return Ok(py.NotImplemented());
+        };
+        op.matches(i64::from(self.0.as_u16()).cmp(&other))
+            .into_py_any(py)
     }
 }
 
+/// Defines the standard status codes as class attributes, such as `StatusCode.NOT_FOUND`.
+macro_rules! define_status_constants {
+    ($($name:ident),* $(,)?) => {
+        #[pymethods]
+        impl StatusCode {
+            $(
+                #[classattr]
+                const $name: StatusCode = StatusCode(wreq::StatusCode::$name);
+            )*
+        }
+    };
+}
+
+define_status_constants!(
+    CONTINUE,
+    SWITCHING_PROTOCOLS,
+    PROCESSING,
+    EARLY_HINTS,
+    OK,
+    CREATED,
+    ACCEPTED,
+    NON_AUTHORITATIVE_INFORMATION,
+    NO_CONTENT,
+    RESET_CONTENT,
+    PARTIAL_CONTENT,
+    MULTI_STATUS,
+    ALREADY_REPORTED,
+    IM_USED,
+    MULTIPLE_CHOICES,
+    MOVED_PERMANENTLY,
+    FOUND,
+    SEE_OTHER,
+    NOT_MODIFIED,
+    USE_PROXY,
+    TEMPORARY_REDIRECT,
+    PERMANENT_REDIRECT,
+    BAD_REQUEST,
+    UNAUTHORIZED,
+    PAYMENT_REQUIRED,
+    FORBIDDEN,
+    NOT_FOUND,
+    METHOD_NOT_ALLOWED,
+    NOT_ACCEPTABLE,
+    PROXY_AUTHENTICATION_REQUIRED,
+    REQUEST_TIMEOUT,
+    CONFLICT,
+    GONE,
+    LENGTH_REQUIRED,
+    PRECONDITION_FAILED,
+    PAYLOAD_TOO_LARGE,
+    URI_TOO_LONG,
+    UNSUPPORTED_MEDIA_TYPE,
+    RANGE_NOT_SATISFIABLE,
+    EXPECTATION_FAILED,
+    IM_A_TEAPOT,
+    MISDIRECTED_REQUEST,
+    UNPROCESSABLE_ENTITY,
+    LOCKED,
+    FAILED_DEPENDENCY,
+    TOO_EARLY,
+    UPGRADE_REQUIRED,
+    PRECONDITION_REQUIRED,
+    TOO_MANY_REQUESTS,
+    REQUEST_HEADER_FIELDS_TOO_LARGE,
+    UNAVAILABLE_FOR_LEGAL_REASONS,
+    INTERNAL_SERVER_ERROR,
+    NOT_IMPLEMENTED,
+    BAD_GATEWAY,
+    SERVICE_UNAVAILABLE,
+    GATEWAY_TIMEOUT,
+    HTTP_VERSION_NOT_SUPPORTED,
+    VARIANT_ALSO_NEGOTIATES,
+    INSUFFICIENT_STORAGE,
+    LOOP_DETECTED,
+    NOT_EXTENDED,
+    NETWORK_AUTHENTICATION_REQUIRED,
+);
+
 impl From<wreq::StatusCode> for StatusCode {
     fn from(status_code: wreq::StatusCode) -> Self {
         Self(status_code)
     }
 }
+
+impl StatusCode {
+    /// Returns the underlying `wreq` status code.
+    #[inline]
+    pub const fn into_ffi(self) -> wreq::StatusCode {
+        self.0
+    }
+}
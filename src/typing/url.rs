@@ -0,0 +1,169 @@
+use crate::error::Error;
+use pyo3::{
+    IntoPyObjectExt,
+    basic::CompareOp,
+    prelude::*,
+    pybacked::PyBackedStr,
+    types::{PyDict, PyString},
+};
+
+/// A parsed URL.
+///
+/// Compares and hashes like its string form, so `response.url == "https://..."` works.
+#[pyclass(frozen)]
+#[derive(Clone, PartialEq, Eq)]
+pub struct Url(pub wreq::Url);
+
+impl Url {
+    fn with(&self, f: impl FnOnce(&mut wreq::Url)) -> Url {
+        let mut url = self.0.clone();
+        f(&mut url);
+        Url(url)
+    }
+}
+
+#[pymethods]
+impl Url {
+    /// Parses `url`, which must be absolute.
+    #[new]
+    fn new(url: &str) -> PyResult<Url> {
+        wreq::Url::parse(url)
+            .map(Url)
+            .map_err(|err| Error::UrlParse(err).into())
+    }
+
+    /// Returns the scheme, such as `"https"`.
+    #[getter]
+    fn scheme(&self) -> &str {
+        self.0.scheme()
+    }
+
+    /// Returns the host, if the URL has one.
+    #[getter]
+    fn host(&self) -> Option<&str> {
+        self.0.host_str()
+    }
+
+    /// Returns the port written in the URL, or `None` if it is omitted or the default port
+    /// of the scheme.
+    #[getter]
+    fn port(&self) -> Option<u16> {
+        self.0.port()
+    }
+
+    /// Returns the path, which is `"/"` for an empty path of an `http` URL.
+    #[getter]
+    fn path(&self) -> &str {
+        self.0.path()
+    }
+
+    /// Returns the query without its leading `?`, if the URL has one.
+    #[getter]
+    fn query(&self) -> Option<&str> {
+        self.0.query()
+    }
+
+    /// Returns the fragment without its leading `#`, if the URL has one.
+    #[getter]
+    fn fragment(&self) -> Option<&str> {
+        self.0.fragment()
+    }
+
+    /// Returns the decoded query parameters as a dict, in order. When a name is repeated,
+    /// its last value is kept.
+    fn query_params<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let params = PyDict::new(py);
+        for (name, value) in self.0.query_pairs() {
+            params.set_item(name, value)?;
+        }
+        Ok(params)
+    }
+
+    /// Resolves `relative` against this URL, as a link on its page would be.
+    fn join(&self, relative: &str) -> PyResult<Url> {
+        self.0
+            .join(relative)
+            .map(Url)
+            .map_err(|err| Error::UrlParse(err).into())
+    }
+
+    /// Returns a copy of this URL with its query replaced by `params`, a dict or a list of
+    /// pairs. Values are converted with `str()`, and no params removes the query.
+    fn with_query(&self, params: &Bound<'_, PyAny>) -> PyResult<Url> {
+        let pairs = match params.downcast::<PyDict>() {
+            Ok(dict) => dict.items().into_any(),
+            Err(_) => params.clone(),
+        };
+        let pairs = pairs
+            .try_iter()?
+            .map(|pair| {
+                let (name, value) = pair?.extract::<(PyBackedStr, Bound<'_, PyAny>)>()?;
+                Ok((name, value.str()?.to_string()))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        Ok(self.with(|url| {
+            if pairs.is_empty() {
+                url.set_query(None);
+            } else {
+                url.query_pairs_mut()
+                    .clear()
+                    .extend_pairs(pairs.iter().map(|(name, value)| (&**name, value)));
+            }
+        }))
+    }
+
+    /// Returns a copy of this URL with its fragment replaced, or removed if `None`.
+    #[pyo3(signature = (fragment = None))]
+    fn with_fragment(&self, fragment: Option<&str>) -> Url {
+        self.with(|url| url.set_fragment(fragment))
+    }
+
+    fn __str__(&self) -> &str {
+        self.0.as_str()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Url({:?})", self.0.as_str())
+    }
+
+    fn __hash__(&self, py: Python) -> PyResult<isize> {
+        PyString::new(py, self.0.as_str()).hash()
+    }
+
+    fn __richcmp__(&self, other: &Bound<'_, PyAny>, op: CompareOp) -> PyResult<PyObject> {
+        let py = other.py();
+        let equal = if let Ok(other) = other.downcast::<Url>() {
+            self.0 == other.get().0
+        } else if let Ok(other) = other.extract::<PyBackedStr>() {
+            self.0.as_str() == &*other
+        } else {
+            return Ok(py.NotImplemented());
+        };
+        match op {
+            CompareOp::Eq => equal.into_py_any(py),
+            CompareOp::Ne => (!equal).into_py_any(py),
+            _ => Ok(py.NotImplemented()),
+        }
+    }
+}
+
+/// A URL passed as either a `str` or a `Url`.
+#[derive(Clone)]
+pub struct UrlExtractor(String);
+
+impl AsRef<str> for UrlExtractor {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromPyObject<'_> for UrlExtractor {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(url) = ob.downcast::<Url>() {
+            return Ok(Self(url.get().0.to_string()));
+        }
+        ob.extract::<PyBackedStr>()
+            .map(|url| Self((*url).to_owned()))
+    }
+}
@@ -12,3 +12,38 @@ async def test_badssl():
     client.update(impersonate=rnet.Impersonate.Chrome100)
     resp = await client.get("https://self-signed.badssl.com/")
     assert resp.status == 200
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+@pytest.mark.parametrize(
+    "url",
+    [
+        "https://expired.badssl.com/",
+        "https://self-signed.badssl.com/",
+        "https://wrong.host.badssl.com/",
+        "https://untrusted-root.badssl.com/",
+    ],
+)
+async def test_badssl_certificate_error(url):
+    client = rnet.Client()
+    with pytest.raises(rnet.CertificateError) as exc_info:
+        await client.get(url)
+    assert isinstance(exc_info.value, rnet.TlsError)
+    assert exc_info.value.url == url
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+@pytest.mark.parametrize(
+    "url",
+    [
+        "https://rc4.badssl.com/",
+        "https://dh480.badssl.com/",
+    ],
+)
+async def test_badssl_tls_error(url):
+    client = rnet.Client()
+    with pytest.raises(rnet.TlsError) as exc_info:
+        await client.get(url)
+    assert not isinstance(exc_info.value, rnet.CertificateError)
@@ -0,0 +1,90 @@
+import asyncio
+import pytest
+import rnet
+from conftest import url_of, start_slow_server, start_trickle_server
+
+
+@pytest.mark.asyncio
+async def test_cancel_request_closes_connection():
+    disconnected = asyncio.Event()
+    server = await start_slow_server(disconnected, respond=False)
+    port = server.sockets[0].getsockname()[1]
+
+    async with server:
+        client = rnet.Client()
+        with pytest.raises(asyncio.TimeoutError):
+            await asyncio.wait_for(client.get(f"http://127.0.0.1:{port}/"), 0.5)
+
+        await asyncio.wait_for(disconnected.wait(), 5)
+
+
+@pytest.mark.asyncio
+async def test_cancel_download_closes_connection():
+    disconnected = asyncio.Event()
+    server = await start_slow_server(disconnected)
+    port = server.sockets[0].getsockname()[1]
+
+    async with server:
+        client = rnet.Client()
+        response = await client.get(f"http://127.0.0.1:{port}/")
+        streamer = response.stream()
+
+        async def download():
+            async for _ in streamer:
+                pass
+
+        with pytest.raises(asyncio.TimeoutError):
+            await asyncio.wait_for(download(), 0.5)
+
+        # The response and stream are still referenced here, so the disconnect
+        # must come from the cancellation itself rather than garbage collection.
+        await asyncio.wait_for(disconnected.wait(), 5)
+        assert response.status == 200
+
+
+@pytest.mark.asyncio
+async def test_read_after_cancelled_read_raises():
+    disconnected = asyncio.Event()
+    server = await start_slow_server(disconnected)
+    port = server.sockets[0].getsockname()[1]
+
+    async with server:
+        response = await rnet.Client().get(f"http://127.0.0.1:{port}/")
+        streamer = response.stream()
+        with pytest.raises(asyncio.TimeoutError):
+            await asyncio.wait_for(streamer.readexactly(4096), 0.5)
+        await asyncio.wait_for(disconnected.wait(), 5)
+
+        # The bytes the cancelled read took went with the stream, so reading on would
+        # silently return a truncated body.
+        with pytest.raises(rnet.BodyError, match="cancelled"):
+            await streamer.read(10)
+        with pytest.raises(rnet.BodyError, match="cancelled"):
+            await streamer.__anext__()
+
+
+@pytest.mark.asyncio
+async def test_read_timeout_between_chunks():
+    # The body takes longer than the read timeout, but no single chunk does.
+    server = await start_trickle_server(chunks=4, interval=0.4)
+    url = f"{url_of(server)}/"
+
+    async with server:
+        response = await rnet.Client().get(url, read_timeout=1)
+        received = []
+        with pytest.raises(rnet.TimeoutError) as exc_info:
+            async for chunk in response.stream():
+                received.append(chunk)
+        assert received == [b"x"] * 4
+        assert exc_info.value.phase == "read"
+
+        def consume():
+            response = rnet.BlockingClient(read_timeout=1).get(url)
+            received = []
+            with pytest.raises(rnet.TimeoutError):
+                for chunk in response.stream():
+                    received.append(chunk)
+            return received
+
+        loop = asyncio.get_event_loop()
+        assert await loop.run_in_executor(None, consume) == [b"x"] * 4
@@ -1,6 +1,20 @@
+import asyncio
+import base64
+import ipaddress
 import pytest
 import rnet
+import sys
+from concurrent.futures import ThreadPoolExecutor
 from rnet import Cookie, Impersonate, ImpersonateOS, ImpersonateOption, HeaderMap
+from conftest import (
+    serve,
+    url_of,
+    start_redirecting_server,
+    start_header_echo_server,
+    start_keepalive_server,
+    start_named_server,
+    start_token_server,
+)
 
 
 @pytest.mark.asyncio
@@ -88,3 +102,798 @@ async def test_alps_new_endpoint():
     response = await client.get(url)
     text = await response.text()
     assert text is not None
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_close_client():
+    client = rnet.Client()
+    response = await client.get("https://httpbin.org/anything")
+    assert response.status == 200
+
+    client.close()
+    with pytest.raises(RuntimeError):
+        await client.get("https://httpbin.org/anything")
+    with pytest.raises(RuntimeError):
+        client.headers
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_trust_env_proxy(monkeypatch):
+    url = "http://httpbin.org/anything"
+    monkeypatch.setenv("HTTP_PROXY", "http://127.0.0.1:1")
+    monkeypatch.delenv("NO_PROXY", raising=False)
+    monkeypatch.delenv("no_proxy", raising=False)
+
+    with pytest.raises(Exception):
+        await rnet.Client().get(url)
+
+    response = await rnet.Client(trust_env=False).get(url)
+    assert response.status == 200
+
+    monkeypatch.setenv("NO_PROXY", "httpbin.org")
+    response = await rnet.Client().get(url)
+    assert response.status == 200
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_trust_env_default(monkeypatch):
+    url = "http://httpbin.org/anything"
+    monkeypatch.setenv("HTTP_PROXY", "http://127.0.0.1:1")
+    monkeypatch.delenv("NO_PROXY", raising=False)
+    monkeypatch.delenv("no_proxy", raising=False)
+
+    rnet.set_trust_env_default(False)
+    try:
+        response = await rnet.Client().get(url)
+        assert response.status == 200
+        with pytest.raises(rnet.ConnectionError):
+            await rnet.Client(trust_env=True).get(url)
+    finally:
+        rnet.set_trust_env_default(True)
+
+
+def test_unknown_kwargs():
+    with pytest.raises(TypeError, match="did you mean 'allow_redirects'"):
+        rnet.Client(allow_redirect=True)
+
+    client = rnet.Client()
+    with pytest.raises(TypeError, match="unexpected keyword argument 'timeout_seconds'"):
+        client.update(timeout_seconds=5)
+
+
+@pytest.mark.asyncio
+async def test_update_rebuild():
+    server = await start_redirecting_server()
+    base = url_of(server)
+
+    async with server:
+        client = rnet.Client(cookie_store=True)
+        await client.get(f"{base}/set")
+        assert client.get_cookies(base) == b"a=1"
+        assert (await client.get(f"{base}/redirect")).status == 302
+
+        client.update(allow_redirects=True, headers={"x-test": "1"})
+        response = await client.get(f"{base}/redirect")
+        assert response.url == f"{base}/end"
+
+        # Rebuilding keeps the cookies, headers and redirect settings.
+        client.update(timeout=5, pool_idle_timeout=1, gzip=False)
+        assert client.headers["x-test"] == b"1"
+        assert client.get_cookies(base) == b"a=1"
+        assert (await client.get(f"{base}/redirect")).url == f"{base}/end"
+
+        client.update(cookie_store=False, max_redirects=1)
+        assert client.get_cookies(base) is None
+        with pytest.raises(rnet.RedirectError):
+            await client.get(f"{base}/redirect")
+        client.update(cookie_store=True, allow_redirects=False)
+        assert client.get_cookies(base) == b"a=1"
+        assert (await client.get(f"{base}/redirect")).status == 302
+
+    with pytest.raises(TypeError):
+        client.update(timeout="soon")
+
+
+def test_update_concurrent_rebuild():
+    client = rnet.Client()
+
+    # A headers update racing a rebuild must land on the client that's kept.
+    with ThreadPoolExecutor(max_workers=2) as pool:
+        for i in range(20):
+            rebuild = pool.submit(client.update, timeout=5 + i)
+            headers = pool.submit(client.update, headers={f"x-test-{i}": "1"})
+            rebuild.result()
+            headers.result()
+
+    for i in range(20):
+        assert client.headers[f"x-test-{i}"] == b"1"
+
+
+def test_impersonate_all():
+    profiles = Impersonate.all()
+    assert Impersonate.Chrome136 in profiles
+    assert len(profiles) == len(set(profiles))
+
+    assert Impersonate.Chrome136.name == "Chrome136"
+    assert str(Impersonate.Chrome136) == "Chrome136"
+    assert Impersonate.Chrome136.browser == "chrome"
+    assert Impersonate.Chrome136.version == "136"
+    assert Impersonate.SafariIos17_4_1.browser == "safari"
+    assert Impersonate.SafariIos17_4_1.version == "17.4.1"
+    assert Impersonate.SafariIos17_4_1.supported_os == [ImpersonateOS.IOS]
+    assert ImpersonateOS.Android in Impersonate.Chrome136.supported_os
+    assert Impersonate.OkHttp5.supported_os == [ImpersonateOS.Android]
+    assert all(profile.supported_os for profile in profiles)
+    assert all(profile.browser for profile in profiles)
+
+
+def test_impersonate_random_for():
+    assert ImpersonateOS.random() in list(ImpersonateOS)
+
+    for browser in ["chrome", "Safari", "okhttp", Impersonate.FirefoxAndroid135]:
+        assert isinstance(ImpersonateOption.random_for(browser), ImpersonateOption)
+
+    with pytest.raises(ValueError):
+        ImpersonateOption.random_for("netscape")
+
+
+@pytest.mark.asyncio
+async def test_preserve_header_case():
+    server = await start_header_echo_server()
+    url = f"{url_of(server)}/"
+
+    async with server:
+        response = await rnet.Client().get(url, headers={"x-test": "1"})
+        assert b"X-Test: 1" in await response.bytes()
+
+        client = rnet.Client(preserve_header_case=True)
+        response = await client.get(url, headers={"x-test": "1"})
+        assert b"x-test: 1" in await response.bytes()
+
+
+@pytest.mark.asyncio
+async def test_local_address_pair():
+    server = await start_header_echo_server()
+    url = f"{url_of(server)}/"
+
+    async with server:
+        client = rnet.Client(local_address=("127.0.0.1", "::1"))
+        response = await client.get(url)
+        assert response.status == 200
+
+        client.update(local_address=(None, "::1"))
+        response = await client.get(url, local_address="127.0.0.1")
+        assert response.status == 200
+
+    with pytest.raises(ValueError):
+        rnet.Client(local_address=("127.0.0.1", "127.0.0.2"))
+    with pytest.raises(TypeError):
+        rnet.Client(local_address=["127.0.0.1"])
+
+
+@pytest.mark.asyncio
+async def test_tls_fingerprint():
+    chrome = await rnet.Client(impersonate=Impersonate.Chrome133).tls_fingerprint()
+    firefox = await rnet.Client(impersonate=Impersonate.Firefox136).tls_fingerprint()
+
+    for fingerprint in (chrome, firefox):
+        version, ciphers, extensions, curves, point_formats = fingerprint.ja3.split(",")
+        assert version == "771"
+        assert ciphers and extensions and curves
+        assert len(fingerprint.ja3_hash) == 32
+
+    # Chrome shuffles its extensions, so compare the cipher lists only.
+    assert chrome.ja3.split(",")[1] != firefox.ja3.split(",")[1]
+
+
+@pytest.mark.asyncio
+async def test_tls_fingerprint_bypasses_proxies():
+    # Nothing listens on the proxy, so the capture only succeeds if it skips it.
+    client = rnet.Client(
+        impersonate=Impersonate.Firefox136,
+        proxies=[rnet.Proxy.all("http://127.0.0.1:1")],
+    )
+    fingerprint = await client.tls_fingerprint()
+    assert len(fingerprint.ja3_hash) == 32
+
+
+@pytest.mark.asyncio
+async def test_tls_config():
+    client = rnet.Client(
+        tls_config={"curves": ["X25519", "P-256"], "grease_enabled": False},
+    )
+    fingerprint = await client.tls_fingerprint()
+    assert fingerprint.ja3.split(",")[3] == "29-23"
+
+    # A profile's TLS settings can't be merged with, so the combination is refused.
+    with pytest.raises(ValueError, match="impersonate"):
+        rnet.Client(impersonate=Impersonate.Chrome133, tls_config={"curves": ["X25519"]})
+    with pytest.raises(ValueError, match="impersonate"):
+        client.update(impersonate=Impersonate.Chrome133)
+    with pytest.raises(ValueError, match="impersonate"):
+        await client.get("https://localhost:1/", impersonate=Impersonate.Chrome133)
+
+    with pytest.raises(ValueError):
+        rnet.Client(tls_config={"curve": ["X25519"]})
+    with pytest.raises(ValueError):
+        rnet.Client(tls_config={"curves": ["P-999"]})
+
+
+@pytest.mark.asyncio
+async def test_connection_reuse():
+    server = await start_keepalive_server()
+    url = f"{url_of(server)}/"
+
+    async with server:
+        client = rnet.Client()
+        first = await client.get(url)
+        await first.bytes()
+        second = await client.get(url)
+        await second.bytes()
+        assert first.connection_reused is False
+        assert second.connection_reused is True
+        assert first.connection_id == second.connection_id
+
+        client = rnet.Client(no_keepalive=True)
+        first = await client.get(url)
+        await first.bytes()
+        second = await client.get(url)
+        assert second.connection_reused is False
+        assert first.connection_id != second.connection_id
+
+
+@pytest.mark.asyncio
+async def test_client_connection():
+    server = await start_keepalive_server()
+    url = f"{url_of(server)}/"
+
+    async with server:
+        client = rnet.Client()
+        # An idle connection of the client itself isn't the one that's pinned.
+        await (await client.get(url)).bytes()
+
+        ids = []
+        async with client.connection() as conn:
+            for _ in range(3):
+                other = asyncio.ensure_future(client.get(url))
+                response = await conn.get(url)
+                assert await response.text() == "ok"
+                ids.append(response.connection_id)
+                await (await other).bytes()
+        assert ids[0] is not None
+        assert ids == [ids[0]] * 3
+
+        async with client.connection() as conn:
+            first = await conn.get(url)
+            second = asyncio.ensure_future(conn.get(url))
+            await asyncio.sleep(0.1)
+            assert not second.done()
+            await first.bytes()
+            assert (await second).connection_id == first.connection_id
+
+            with pytest.raises(ValueError, match="impersonate"):
+                await conn.get(url, impersonate=Impersonate.Chrome133)
+            with pytest.raises(ValueError, match="decompress"):
+                await conn.get(url, decompress=False)
+
+        # Leaving the block drops the pinned connection.
+        with pytest.raises(RuntimeError, match="closed"):
+            await conn.get(url)
+
+
+@pytest.mark.asyncio
+async def test_request_impersonate_pools():
+    server = await start_keepalive_server()
+    url = f"{url_of(server)}/"
+
+    async def connection_id(**kwargs):
+        response = await client.get(url, **kwargs)
+        await response.bytes()
+        return response.connection_id
+
+    async with server:
+        client = rnet.Client(impersonate=Impersonate.Safari18)
+        default = await connection_id()
+        chrome = await connection_id(impersonate=Impersonate.Chrome133)
+        firefox = await connection_id(impersonate=Impersonate.Firefox136)
+        assert len({default, chrome, firefox}) == 3
+
+        # Each profile keeps reusing the connections it made.
+        assert await connection_id(impersonate=Impersonate.Chrome133) == chrome
+        assert await connection_id(impersonate=Impersonate.Firefox136) == firefox
+        assert await connection_id() == default
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_resolve():
+    client = rnet.Client()
+    assert await client.resolve("127.0.0.1") == [ipaddress.ip_address("127.0.0.1")]
+    assert await client.resolve("[::1]") == [ipaddress.ip_address("::1")]
+
+    v4_client = rnet.Client(lookup_ip_strategy=rnet.LookupIpStrategy.Ipv4Only)
+    addrs = await v4_client.resolve("example.com")
+    assert addrs and all(addr.version == 4 for addr in addrs)
+
+    with pytest.raises(rnet.DNSResolverError):
+        await client.resolve("nonexistent.invalid")
+
+    blocking = rnet.BlockingClient()
+    assert blocking.resolve("127.0.0.1") == [ipaddress.ip_address("127.0.0.1")]
+
+
+@pytest.mark.asyncio
+async def test_shared_cookie_jar():
+    server = await start_redirecting_server()
+    base = url_of(server)
+
+    async with server:
+        first = rnet.Client(impersonate=Impersonate.Chrome133, cookie_store=True)
+        second = rnet.Client(
+            impersonate=Impersonate.Firefox135, cookie_provider=first.cookie_jar
+        )
+        await first.get(f"{base}/set")
+        assert second.get_cookies(base) == b"a=1"
+
+        jar = rnet.CookieJar()
+        jar.set_cookie(base, Cookie(name="b", value="2"))
+        second.cookie_jar = jar
+        assert second.get_cookies(base) == b"b=2"
+        assert first.get_cookies(base) == b"a=1"
+
+        second.cookie_jar = None
+        assert second.cookie_jar is None
+        assert second.get_cookies(base) is None
+        assert jar.get_cookies(base) == b"b=2"
+        jar.clear()
+        assert jar.get_cookies(base) is None
+
+    assert rnet.Client().cookie_jar is None
+    assert rnet.Client(cookie_provider=jar, cookie_store=False).cookie_jar is None
+
+
+@pytest.mark.asyncio
+async def test_proxy_matches():
+    first, first_url = await start_named_server(b"first")
+    second, second_url = await start_named_server(b"second")
+    direct, direct_url = await start_named_server(b"direct")
+
+    async with first, second, direct:
+        client = rnet.Client(
+            trust_env=False,
+            proxies=[rnet.Proxy.http(first_url, matches="*.example.invalid")],
+        )
+        response = await client.get("http://www.EXAMPLE.invalid/a")
+        assert await response.text() == "first http://www.example.invalid/a"
+        # Hosts that don't match go direct without a catch-all.
+        response = await client.get(f"{direct_url}/b")
+        assert await response.text() == "direct /b"
+
+        client = rnet.Client(
+            trust_env=False,
+            proxies=[
+                rnet.Proxy.all(first_url, matches=["a.invalid", "b.invalid"]),
+                rnet.Proxy.all(second_url, exclusion="127.0.0.1"),
+            ],
+        )
+        response = await client.get("http://b.invalid/")
+        assert await response.text() == "first http://b.invalid/"
+        response = await client.get("http://example.invalid/")
+        assert await response.text() == "second http://example.invalid/"
+        response = await client.get(f"{direct_url}/c")
+        assert await response.text() == "direct /c"
+
+    with pytest.raises(ValueError):
+        rnet.Proxy.all(first_url, matches="*", custom_http_headers={"x-a": "1"})
+
+
+@pytest.mark.asyncio
+async def test_proxy_credentials():
+    server = await start_header_echo_server()
+    url = url_of(server)
+
+    async def proxy_authorization(response):
+        lines = (await response.text()).split("\r\n")[1:]
+        headers = dict(line.split(": ", 1) for line in lines if line)
+        return {name.lower(): value for name, value in headers.items()}["proxy-authorization"]
+
+    async with server:
+        for proxy, credentials in [
+            (rnet.Proxy.http(url, username="us:er", password="p@ss/w%rd"), b"us:er:p@ss/w%rd"),
+            (rnet.Proxy.all(url, username="user"), b"user:"),
+            (
+                rnet.Proxy.all(url, username="us:er", password="p@ss", matches="*.invalid"),
+                b"us:er:p@ss",
+            ),
+        ]:
+            client = rnet.Client(trust_env=False, proxies=[proxy])
+            response = await client.get("http://example.invalid/")
+            assert await proxy_authorization(response) == "Basic %s" % (
+                base64.b64encode(credentials).decode()
+            )
+
+    with pytest.raises(ValueError):
+        rnet.Proxy.all(url, password="secret")
+    with pytest.raises(ValueError):
+        rnet.Proxy.all("socks4://127.0.0.1:1080", username="user", password="secret")
+
+
+@pytest.mark.asyncio
+async def test_proxy_from_pac(tmp_path):
+    proxy, proxy_url = await start_named_server(b"proxy")
+    direct, direct_url = await start_named_server(b"direct")
+
+    pac = tmp_path / "proxy.pac"
+    pac.write_text(
+        """
+        // Only the example hosts go through the proxy.
+        var proxy = "PROXY %s";
+        function FindProxyForURL(url, host) {
+            if (shExpMatch(host, "*.example.invalid") || dnsDomainIs(host, ".test.invalid"))
+                return proxy + "; DIRECT";
+            return "DIRECT";
+        }
+        """
+        % proxy_url[len("http://") :]
+    )
+
+    async with proxy, direct:
+        client = rnet.Client(trust_env=False, proxies=[rnet.Proxy.from_pac(pac)])
+        response = await client.get("http://www.example.invalid/a")
+        assert await response.text() == "proxy http://www.example.invalid/a"
+        response = await client.get(f"{direct_url}/b")
+        assert await response.text() == "direct /b"
+
+    pac.write_text("function FindProxyForURL(url, host) { while (true) {} }")
+    with pytest.raises(ValueError):
+        rnet.Proxy.from_pac(str(pac))
+    pac.write_text('function FindProxy(url, host) { return "DIRECT"; }')
+    with pytest.raises(ValueError):
+        rnet.Proxy.from_pac(pac)
+
+
+@pytest.mark.asyncio
+async def test_proxy_from_pac_failing_script():
+    direct, direct_url = await start_named_server(b"direct")
+    # A result naming neither a proxy nor DIRECT mustn't send the request direct.
+    pac = rnet.Proxy.from_pac_script(
+        'function FindProxyForURL(url, host) { return "BOGUS " + host; }'
+    )
+    async with direct:
+        client = rnet.Client(trust_env=False, proxies=[pac])
+        with pytest.raises(rnet.ProxyError, match="neither a proxy nor DIRECT"):
+            await client.get(f"{direct_url}/b")
+        with pytest.raises(rnet.ProxyError, match="neither a proxy nor DIRECT"):
+            await rnet.Client(trust_env=False).get(f"{direct_url}/b", proxy=pac)
+
+
+@pytest.mark.parametrize(
+    "expr, expected",
+    [
+        ("1 + 2 - 4", "-1"),
+        ("'a' + 1 + 2", "a12"),
+        ("1 + 2 + 'a'", "3a"),
+        ("2 < 10", "true"),
+        ("'2' < '10'", "false"),
+        ("1 == '1'", "true"),
+        ("1 === '1'", "false"),
+        ("null == undefined", "true"),
+        ("!0 && 'x'", "x"),
+        ("'' || 0 || 'y'", "y"),
+        ("true || false && false", "true"),
+        ("(true || false) && false", "false"),
+        ("1 < 2 == true", "true"),
+        ("0 ? 'a' : 1 ? 'b' : 'c'", "b"),
+        ("-'3' + 1", "-2"),
+        ("'Host'.toLowerCase().indexOf('o')", "1"),
+        ("'example.com'.substring(8, 3)", "mple."),
+        ("'example.com'.slice(-3)", "com"),
+        ("isInNet('10.1.2.3', '10.1.0.0', '255.255.0.0')", "true"),
+        ("isInNet('10.2.2.3', '10.1.0.0', '255.255.0.0')", "false"),
+        ("isInNet('10.1.2.3', '10.1.2.3', '255.255.255.255')", "true"),
+        ("shExpMatch('www.example.com', '*.example.com')", "true"),
+        ("shExpMatch('example.com', '*.example.com')", "false"),
+        ("shExpMatch('a.b.c', 'a?b*')", "true"),
+        ("shExpMatch('ab', 'a?b')", "false"),
+        ("dnsDomainLevels('a.b.c')", "2"),
+        ("localHostOrDomainIs('www', 'www.example.com')", "true"),
+    ],
+)
+def test_pac_evaluation(expr, expected):
+    script = "function FindProxyForURL(url, host) { return %s; }" % expr
+    assert rnet.Proxy.find_proxy_for_url(script, "http://example.com/") == expected
+
+
+def test_pac_evaluation_arguments():
+    script = """
+    var suffix = ".example.com";
+    function FindProxyForURL(url, host) {
+        var direct = "DIRECT";
+        if (dnsDomainIs(host, suffix)) {
+            return "PROXY " + host + ":" + url.length;
+        } else if (isPlainHostName(host))
+            return direct;
+        return url;
+    }
+    """
+    assert (
+        rnet.Proxy.find_proxy_for_url(script, "http://www.example.com/")
+        == "PROXY www.example.com:23"
+    )
+    assert rnet.Proxy.find_proxy_for_url(script, "http://intranet/") == "DIRECT"
+    assert rnet.Proxy.find_proxy_for_url(script, "http://[::1]:8080/") == (
+        "http://[::1]:8080/"
+    )
+
+
+def test_pac_evaluation_errors():
+    with pytest.raises(ValueError, match="too much recursion"):
+        rnet.Proxy.find_proxy_for_url(
+            "function FindProxyForURL(url, host) { return FindProxyForURL(url, host); }",
+            "http://example.com/",
+        )
+    with pytest.raises(ValueError, match="unsupported function"):
+        rnet.Proxy.find_proxy_for_url(
+            "function FindProxyForURL(url, host) { return eval(host); }",
+            "http://example.com/",
+        )
+
+
+def test_pac_fetch_with_running_loop_raises():
+    async def fetch():
+        rnet.Proxy.from_pac("http://127.0.0.1:9/proxy.pac")
+
+    with pytest.raises(RuntimeError, match="from_pac_script"):
+        asyncio.run(fetch())
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_head_ok():
+    client = rnet.Client()
+    assert await client.head_ok("https://httpbin.org/redirect/2")
+    assert not await client.head_ok("https://httpbin.org/status/404")
+    assert not await client.head_ok(
+        "https://httpbin.org/redirect/1", allow_redirects=False
+    )
+
+    blocking = rnet.BlockingClient()
+    assert blocking.head_ok("https://httpbin.org/get")
+    assert not blocking.head_ok("https://httpbin.org/status/500")
+
+
+@pytest.mark.skipif(sys.platform != "linux", reason="binds to a device by its zone")
+def test_local_address_zone():
+    rnet.Client(local_address="fe80::1%lo")
+    rnet.Client(local_address=ipaddress.IPv6Address("fe80::1%1"))
+    rnet.Client(local_address=(None, "fe80::1%lo"))
+
+    with pytest.raises(ValueError, match="IPv4"):
+        rnet.Client(local_address=("127.0.0.1", "fe80::1%lo"))
+    with pytest.raises(ValueError, match="not found"):
+        rnet.Client(local_address="fe80::1%no-such-if0")
+    with pytest.raises(ValueError):
+        rnet.Client(local_address="127.0.0.1%lo")
+
+
+def link_local_address():
+    """Returns a link-local IPv6 address of this host with its interface, if it has one."""
+    try:
+        with open("/proc/net/if_inet6") as f:
+            lines = f.read().splitlines()
+    except OSError:
+        return None
+    for line in lines:
+        addr, index, _, scope, _, name = line.split()
+        if int(scope, 16) == 0x20:
+            addr = ipaddress.IPv6Address(int(addr, 16))
+            return str(addr), int(index, 16), name
+    return None
+
+
+@pytest.mark.asyncio
+@pytest.mark.skipif(sys.platform != "linux", reason="binds to a device by its zone")
+async def test_remote_addr_keeps_scope():
+    found = link_local_address()
+    if found is None:
+        pytest.skip("no link-local IPv6 address")
+    addr, index, name = found
+
+    async def handle(reader, writer):
+        while (await reader.readline()) not in (b"\r\n", b""):
+            pass
+        writer.write(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+        await writer.drain()
+        writer.close()
+
+    server = await serve(handle, host=f"{addr}%{name}")
+    port = server.sockets[0].getsockname()[1]
+
+    async with server:
+        # The socket is bound to the interface, which scopes the link-local address.
+        response = await rnet.Client(interface=name).get(f"http://[{addr}]:{port}/")
+        ip = response.remote_addr.ip()
+        assert ip == ipaddress.IPv6Address(f"{addr}%{index}")
+        assert ip.scope_id == str(index)
+
+
+def sized_site(method, url, headers, body):
+    path = url.split("example.invalid", 1)[-1]
+    if path == "/sized":
+        return (200, {"content-length": "5"}, b"" if method == "HEAD" else b"hello")
+    if path == "/no-head" and method == "HEAD":
+        raise ConnectionResetError("HEAD dropped")
+    if method == "HEAD":
+        return (405, {}, b"")
+    if path == "/ranged":
+        assert headers.get("range") == b"bytes=0-0"
+        return (206, {"content-range": "bytes 0-0/1234"}, b"x")
+    if path in ("/whole", "/no-head"):
+        return (200, {"content-length": "42"}, b"y" * 42)
+    return (404, {}, b"")
+
+
+@pytest.mark.asyncio
+async def test_content_length():
+    transport = rnet.MockTransport(sized_site)
+    client = rnet.Client(transport=transport)
+    base = "https://example.invalid"
+
+    assert await client.content_length(f"{base}/sized") == 5
+    assert await client.content_length(f"{base}/ranged") == 1234
+    assert await client.content_length(f"{base}/whole") == 42
+    assert await client.content_length(f"{base}/no-head") == 42
+    assert await client.content_length(f"{base}/missing") is None
+    assert rnet.BlockingClient(transport=transport).content_length(f"{base}/ranged") == 1234
+
+    response = await client.get(f"{base}/whole", prefetch_body=False)
+    assert await response.bytes() == b"y" * 42
+    with pytest.raises(rnet.BuilderError):
+        await client.get(f"{base}/whole", prefetch_body=False, follow_meta_refresh=True)
+
+
+@pytest.mark.asyncio
+async def test_oauth2_client_credentials():
+    server, url, state = await start_token_server()
+
+    async with server:
+        auth = rnet.OAuth2ClientCredentials(f"{url}/token", "id", "secret", scope="read")
+        client = rnet.Client(auth=auth)
+        assert client.token_info() is None
+
+        # Concurrent requests wait for a single token.
+        responses = await asyncio.gather(*(client.get(f"{url}/api") for _ in range(5)))
+        assert [await r.text() for r in responses] == ["t1"] * 5
+        assert state["issued"] == ["t1"]
+        assert state["forms"] == [b"grant_type=client_credentials&scope=read"]
+        assert state["credentials"] == ["Basic aWQ6c2VjcmV0"]
+        info = client.token_info()
+        assert info["access_token"] == "t1"
+        assert info["token_type"] == "bearer"
+        assert info["scope"] == "read"
+        assert 3500 < info["expires_in"] <= 3600
+
+        # A revoked token is replaced, and the request retried with the new one.
+        state["revoked"].add("t1")
+        response = await client.post(f"{url}/api", body=b"payload")
+        assert response.status == 200
+        assert await response.text() == "t2"
+        assert client.token_info()["access_token"] == "t2"
+
+        # A request's own authorization is left alone.
+        response = await client.get(f"{url}/api", bearer_auth="t1")
+        assert response.status == 401
+        assert state["issued"] == ["t1", "t2"]
+
+        blocking = rnet.BlockingClient(
+            auth=rnet.OAuth2ClientCredentials(f"{url}/token", "id", "secret")
+        )
+        loop = asyncio.get_running_loop()
+        response = await loop.run_in_executor(None, blocking.get, f"{url}/api")
+        assert response.text() == "t3"
+        assert blocking.token_info()["access_token"] == "t3"
+
+        bad = rnet.Client(auth=rnet.OAuth2ClientCredentials(f"{url}/api", "id", "secret"))
+        with pytest.raises(rnet.StatusError):
+            await bad.get(f"{url}/api")
+
+
+@pytest.mark.asyncio
+async def test_oauth2_token_scope():
+    server, url, state = await start_token_server()
+    echo = await start_header_echo_server()
+    echo_url = url_of(echo)
+
+    async with server, echo:
+        # Another host never sees the token.
+        client = rnet.Client(auth=rnet.OAuth2ClientCredentials(f"{url}/token", "id", "secret"))
+        response = await client.get(f"{echo_url}/api")
+        assert b"authorization" not in (await response.bytes()).lower()
+        assert state["issued"] == []
+        assert await (await client.get(f"{url}/api")).text() == "t1"
+
+        # Listed base URLs do, below their path only.
+        auth = rnet.OAuth2ClientCredentials(
+            f"{url}/token", "id", "secret", base_urls=[f"{echo_url}/api/"]
+        )
+        client = rnet.Client(auth=auth)
+        response = await client.get(f"{echo_url}/api/items")
+        assert b"authorization: bearer t2" in (await response.bytes()).lower()
+        response = await client.get(f"{echo_url}/apix")
+        assert b"authorization" not in (await response.bytes()).lower()
+        assert (await client.get(f"{url}/api")).status == 401
+
+
+@pytest.mark.asyncio
+async def test_oauth2_credentials_are_form_urlencoded():
+    server, url, state = await start_token_server()
+
+    async with server:
+        auth = rnet.OAuth2ClientCredentials(f"{url}/token", "my id:1", "s&c=\u00fc")
+        await rnet.Client(auth=auth).get(f"{url}/api")
+        expected = base64.b64encode(b"my+id%3A1:s%26c%3D%C3%BC").decode()
+        assert state["credentials"] == [f"Basic {expected}"]
+
+
+@pytest.mark.asyncio
+async def test_record_and_replay(tmp_path):
+    cassette = tmp_path / "cassette.json"
+    server, url = await start_named_server(b"live")
+
+    async with server:
+        client = rnet.Client(record=cassette)
+        response = await client.get(f"{url}/a")
+        assert await response.text() == "live /a"
+        response = await client.post(f"{url}/b", body=b"\xff\x00")
+        assert await response.text() == "live /b"
+
+    # The server is gone, so only the cassette can answer.
+    assert "interactions" in cassette.read_text()
+    client = rnet.Client(replay=cassette)
+    for _ in range(2):
+        response = await client.get(f"{url}/a")
+        assert response.status == 200
+        assert response.url == f"{url}/a"
+        assert await response.text() == "live /a"
+    response = await client.post(f"{url}/b", body=b"\xff\x00")
+    assert await response.text() == "live /b"
+
+    # Requests are matched by their body too.
+    with pytest.raises(rnet.RequestError):
+        await client.post(f"{url}/b", body=b"other")
+    with pytest.raises(rnet.RequestError):
+        await client.get(f"{url}/c")
+
+    with pytest.raises(ValueError):
+        rnet.Client(record=cassette, replay=cassette)
+
+
+def test_repr():
+    proxy = rnet.Proxy.all("http://127.0.0.1:8080", username="user", password="secret")
+    client = rnet.Client(
+        user_agent="rnet-test",
+        impersonate=rnet.Impersonate.Firefox135,
+        allow_redirects=True,
+        gzip=False,
+        proxies=[proxy],
+    )
+    text = repr(client)
+    assert text.startswith("Client(")
+    assert "user_agent='rnet-test'" in text
+    assert "Firefox135" in text
+    assert "allow_redirects=True" in text
+    assert "compression=['brotli', 'deflate', 'zstd']" in text
+    assert "proxies=1" in text
+    assert "secret" not in text and "8080" not in text
+
+    client.update(allow_redirects=False)
+    assert "allow_redirects=False" in repr(client)
+    client.close()
+    assert repr(client) == "Client(closed)"
+
+    text = repr(rnet.BlockingClient())
+    assert text.startswith("BlockingClient(")
+    assert "proxies=0" in text
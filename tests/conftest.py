@@ -0,0 +1,386 @@
+"""Local servers the tests talk to, written directly on asyncio streams."""
+
+import asyncio
+import base64
+import hashlib
+
+
+async def serve(handle, ssl=None, host="127.0.0.1"):
+    """Starts a server on a free port that runs `handle(reader, writer)` per connection."""
+    return await asyncio.start_server(handle, host, 0, ssl=ssl)
+
+
+def url_of(server, scheme="http"):
+    """Returns the base URL of a server started on 127.0.0.1, without a trailing slash."""
+    return f"{scheme}://127.0.0.1:{server.sockets[0].getsockname()[1]}"
+
+
+def header_names(head):
+    """Returns the names of the headers in an echoed request head, joined by commas."""
+    lines = head.split("\r\n")[1:]
+    return ",".join(line.split(":")[0].lower() for line in lines if line)
+
+
+async def start_cache_server(requests, cache_control):
+    async def handle(reader, writer):
+        while True:
+            head = []
+            while (line := await reader.readline()) not in (b"\r\n", b""):
+                head.append(line.decode().strip().lower())
+            if not head:
+                break
+            requests.append(head)
+
+            if 'if-none-match: "v1"' in head:
+                writer.write(b'HTTP/1.1 304 Not Modified\r\nETag: "v1"\r\n\r\n')
+            else:
+                writer.write(
+                    b"HTTP/1.1 200 OK\r\nCache-Control: %s\r\nETag: \"v1\"\r\n"
+                    b"Content-Length: 5\r\n\r\nhello" % cache_control
+                )
+            await writer.drain()
+        writer.close()
+
+    return await serve(handle)
+
+
+async def start_vary_server(requests):
+    async def handle(reader, writer):
+        while True:
+            head = []
+            while (line := await reader.readline()) not in (b"\r\n", b""):
+                head.append(line.decode().strip().lower())
+            if not head:
+                break
+            requests.append(head)
+
+            language = next(
+                (h.split(": ", 1)[1] for h in head if h.startswith("accept-language:")),
+                "none",
+            ).encode()
+            writer.write(
+                b"HTTP/1.1 200 OK\r\nCache-Control: max-age=60\r\nVary: Accept-Language\r\n"
+                b"Content-Length: %d\r\n\r\n%s" % (len(language), language)
+            )
+            await writer.drain()
+        writer.close()
+
+    return await serve(handle)
+
+
+async def start_slow_server(disconnected, respond=True):
+    async def handle(reader, writer):
+        while (await reader.readline()) not in (b"\r\n", b""):
+            pass
+
+        try:
+            if respond:
+                writer.write(
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 1048576\r\n\r\n" + b"x" * 1024
+                )
+                await writer.drain()
+            # Hold the connection open until the client goes away.
+            while await reader.read(1024):
+                pass
+        except ConnectionError:
+            pass
+        finally:
+            disconnected.set()
+            writer.close()
+
+    return await serve(handle)
+
+
+async def start_trickle_server(chunks, interval):
+    """Sends `chunks` of the body `interval` seconds apart, then stalls."""
+
+    async def handle(reader, writer):
+        while (await reader.readline()) not in (b"\r\n", b""):
+            pass
+        try:
+            writer.write(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n")
+            for _ in range(chunks):
+                await asyncio.sleep(interval)
+                writer.write(b"1\r\nx\r\n")
+                await writer.drain()
+            while await reader.read(1024):
+                pass
+        except ConnectionError:
+            pass
+        finally:
+            writer.close()
+
+    return await serve(handle)
+
+
+async def start_redirecting_server():
+    async def handle(reader, writer):
+        path = (await reader.readline()).split()[1]
+        while await reader.readline() not in (b"\r\n", b""):
+            pass
+        if path == b"/redirect":
+            head = b"HTTP/1.1 302 Found\r\nLocation: /end\r\n"
+        elif path == b"/set":
+            head = b"HTTP/1.1 200 OK\r\nSet-Cookie: a=1\r\n"
+        else:
+            head = b"HTTP/1.1 200 OK\r\n"
+        writer.write(head + b"Content-Length: 0\r\nConnection: close\r\n\r\n")
+        await writer.drain()
+        writer.close()
+
+    return await serve(handle)
+
+
+async def start_header_echo_server():
+    async def handle(reader, writer):
+        head = b""
+        while (line := await reader.readline()) not in (b"\r\n", b""):
+            head += line
+        writer.write(
+            b"HTTP/1.1 200 OK\r\nContent-Length: %d\r\nConnection: close\r\n\r\n%s"
+            % (len(head), head)
+        )
+        await writer.drain()
+        writer.close()
+
+    return await serve(handle)
+
+
+async def start_keepalive_server():
+    async def handle(reader, writer):
+        while True:
+            head = []
+            while (line := await reader.readline()) not in (b"\r\n", b""):
+                head.append(line)
+            if not head:
+                break
+            writer.write(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+            await writer.drain()
+        writer.close()
+
+    return await serve(handle)
+
+
+async def start_named_server(name):
+    async def handle(reader, writer):
+        target = (await reader.readline()).split()[1]
+        while await reader.readline() not in (b"\r\n", b""):
+            pass
+        body = name + b" " + target
+        writer.write(
+            b"HTTP/1.1 200 OK\r\nContent-Length: %d\r\nConnection: close\r\n\r\n%s"
+            % (len(body), body)
+        )
+        await writer.drain()
+        writer.close()
+
+    server = await serve(handle)
+    return server, url_of(server)
+
+
+async def start_token_server():
+    """Serves tokens at `/token` and checks them at `/api`, which refuses revoked ones."""
+    state = {"issued": [], "revoked": set(), "forms": [], "credentials": []}
+
+    async def handle(reader, writer):
+        target = (await reader.readline()).split()[1]
+        headers = {}
+        while (line := await reader.readline()) not in (b"\r\n", b""):
+            name, _, value = line.decode().partition(":")
+            headers[name.strip().lower()] = value.strip()
+        body = await reader.readexactly(int(headers.get("content-length", 0)))
+
+        if target == b"/token":
+            state["credentials"].append(headers["authorization"])
+            state["forms"].append(body)
+            token = "t%d" % (len(state["issued"]) + 1)
+            state["issued"].append(token)
+            await asyncio.sleep(0.1)
+            status = b"200 OK"
+            body = b'{"access_token": "%s", "token_type": "bearer", "expires_in": 3600}' % (
+                token.encode()
+            )
+        else:
+            token = headers.get("authorization", "")[len("Bearer ") :]
+            if token in state["issued"] and token not in state["revoked"]:
+                status, body = b"200 OK", token.encode()
+            else:
+                status, body = b"401 Unauthorized", b""
+        writer.write(
+            b"HTTP/1.1 %s\r\nContent-Length: %d\r\nConnection: close\r\n\r\n%s"
+            % (status, len(body), body)
+        )
+        await writer.drain()
+        writer.close()
+
+    server = await serve(handle)
+    return server, url_of(server), state
+
+
+async def start_range_server(requests, content):
+    """Serves `content`, or the byte range of it a request asks for."""
+
+    async def handle(reader, writer):
+        head = []
+        while (line := await reader.readline()) not in (b"\r\n", b""):
+            head.append(line.decode().strip().lower())
+        requests.append(head)
+
+        ranges = [h.split("bytes=")[1] for h in head if h.startswith("range:")]
+        if not ranges:
+            status, extra, body = b"200 OK", b"", content
+        else:
+            start, end = ranges[0].split("-")
+            start, end = int(start), int(end) if end else len(content) - 1
+            if start >= len(content):
+                status, extra, body = (
+                    b"416 Range Not Satisfiable",
+                    b"Content-Range: bytes */%d\r\n" % len(content),
+                    b"",
+                )
+            else:
+                status, body = b"206 Partial Content", content[start : end + 1]
+                extra = b"Content-Range: bytes %d-%d/%d\r\n" % (start, end, len(content))
+        writer.write(
+            b"HTTP/1.1 %s\r\n%sContent-Length: %d\r\nConnection: close\r\n\r\n%s"
+            % (status, extra, len(body), body)
+        )
+        await writer.drain()
+        writer.close()
+
+    return await serve(handle)
+
+
+async def start_h2_reset_server(error_code):
+    async def handle(reader, writer):
+        await reader.readexactly(24)  # The client connection preface.
+        writer.write(b"\x00\x00\x00\x04\x00\x00\x00\x00\x00")
+        while True:
+            try:
+                head = await reader.readexactly(9)
+            except asyncio.IncompleteReadError:
+                break
+            length = int.from_bytes(head[:3], "big")
+            kind, flags = head[3], head[4]
+            stream = int.from_bytes(head[5:9], "big") & 0x7FFFFFFF
+            await reader.readexactly(length)
+            if kind == 0x4 and not flags & 0x1:
+                writer.write(b"\x00\x00\x00\x04\x01\x00\x00\x00\x00")
+            elif kind == 0x1:
+                writer.write(
+                    b"\x00\x00\x04\x03\x00"
+                    + stream.to_bytes(4, "big")
+                    + error_code.to_bytes(4, "big")
+                )
+            await writer.drain()
+        writer.close()
+
+    return await serve(handle)
+
+
+async def start_redirect_server(location, ssl=None):
+    async def handle(reader, writer):
+        while (await reader.readline()) not in (b"\r\n", b""):
+            pass
+        if location:
+            writer.write(
+                f"HTTP/1.1 302 Found\r\nLocation: {location}\r\n".encode()
+                + b"Content-Length: 0\r\nConnection: close\r\n\r\n"
+            )
+        else:
+            writer.write(
+                b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok"
+            )
+        await writer.drain()
+        writer.close()
+
+    return await serve(handle, ssl=ssl)
+
+
+async def start_dropping_websocket_server(clean_close=False):
+    connections = []
+
+    async def handle(reader, writer):
+        key = b""
+        while (line := await reader.readline()) not in (b"\r\n", b""):
+            if line.lower().startswith(b"sec-websocket-key:"):
+                key = line.split(b":", 1)[1].strip()
+        accept = base64.b64encode(
+            hashlib.sha1(key + b"258EAFA5-E914-47DA-95CA-C5AB0DC85B11").digest()
+        )
+        writer.write(
+            b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n"
+            b"Connection: Upgrade\r\nSec-WebSocket-Accept: %s\r\n\r\n" % accept
+        )
+        connections.append(writer)
+        payload = b"connection %d" % len(connections)
+        writer.write(b"\x81" + bytes([len(payload)]) + payload)
+        await writer.drain()
+        if len(connections) == 1 and clean_close:
+            # Close the first connection with a normal closure frame.
+            writer.write(b"\x88\x02\x03\xe8")
+            await writer.drain()
+            writer.close()
+        elif len(connections) == 1:
+            # Drop the first connection without a close handshake.
+            writer.transport.abort()
+        else:
+            await reader.read()
+
+    server = await serve(handle)
+    return server, connections
+
+
+async def start_hello_server():
+    async def handle(reader, writer):
+        try:
+            while await reader.readline():
+                while (await reader.readline()) not in (b"\r\n", b""):
+                    pass
+                writer.write(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello")
+                await writer.drain()
+        except ConnectionError:
+            pass
+        finally:
+            writer.close()
+
+    return await serve(handle)
+
+
+async def start_echo_server():
+    async def handle(reader, writer):
+        while data := await reader.read(1024):
+            writer.write(data)
+            await writer.drain()
+        writer.close()
+
+    return await serve(handle)
+
+
+async def start_connect_proxy():
+    async def pipe(reader, writer):
+        while data := await reader.read(1024):
+            writer.write(data)
+            await writer.drain()
+        writer.close()
+
+    async def handle(reader, writer):
+        request_line = await reader.readline()
+        while (await reader.readline()) not in (b"\r\n", b""):
+            pass
+
+        method, target, _ = request_line.decode().split(" ", 2)
+        if method != "CONNECT":
+            writer.write(b"HTTP/1.1 405 Method Not Allowed\r\n\r\n")
+            writer.close()
+            return
+
+        host, port = target.rsplit(":", 1)
+        upstream_reader, upstream_writer = await asyncio.open_connection(host, int(port))
+        writer.write(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+        await writer.drain()
+        await asyncio.gather(
+            pipe(reader, upstream_writer), pipe(upstream_reader, writer)
+        )
+
+    return await serve(handle)
@@ -1,5 +1,8 @@
+import asyncio
+import gzip
 import pytest
 import rnet
+from conftest import serve, url_of
 
 client = rnet.Client(tls_info=True)
 
@@ -32,3 +35,146 @@ async def test_brotli():
     text = await response.text()
     assert text is not None
     assert "brotli" in text
+
+
+@pytest.mark.asyncio
+async def test_decompress_disabled():
+    requests = []
+
+    async def handle(reader, writer):
+        head = []
+        while (line := await reader.readline()) not in (b"\r\n", b""):
+            head.append(line.decode().strip().lower())
+        requests.append(head)
+        body = gzip.compress(b"hello")
+        writer.write(
+            b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: %d\r\n"
+            b"Connection: close\r\n\r\n%s" % (len(body), body)
+        )
+        await writer.drain()
+        writer.close()
+
+    server = await serve(handle)
+    url = f"{url_of(server)}/"
+
+    async with server:
+        response = await rnet.Client(impersonate=rnet.Impersonate.Chrome133).get(
+            url, decompress=False
+        )
+        assert "accept-encoding: identity" in requests[0]
+        await response.bytes()
+
+        # Encoded bodies pass through untouched when the client doesn't decode them.
+        raw = rnet.Client(gzip=False, brotli=False, deflate=False, zstd=False)
+        response = await raw.get(url, headers={"accept-encoding": "gzip"})
+        assert response.headers["content-encoding"] == b"gzip"
+        assert gzip.decompress(await response.bytes()) == b"hello"
+
+        # A decoding client leaves the body encoded for a request that asks it to, and
+        # `content_length` is then the encoded length.
+        client = rnet.Client()
+        response = await client.get(
+            url, decompress=False, headers={"accept-encoding": "gzip"}
+        )
+        assert response.content_encoding == "gzip"
+        assert response.content_length == len(gzip.compress(b"hello"))
+        assert gzip.decompress(await response.bytes()) == b"hello"
+
+        response = await client.get(url)
+        assert response.content_encoding is None
+        assert response.content_length == 0
+        assert await response.bytes() == b"hello"
+
+        # So does the blocking client, keeping the Content-Encoding header.
+        def fetch():
+            response = rnet.BlockingClient().get(
+                url, decompress=False, headers={"accept-encoding": "gzip"}
+            )
+            return response.headers["content-encoding"], response.bytes()
+
+        loop = asyncio.get_event_loop()
+        encoding, body = await loop.run_in_executor(None, fetch)
+        assert encoding == b"gzip"
+        assert gzip.decompress(body) == b"hello"
+
+
+@pytest.mark.asyncio
+async def test_decompress_disabled_cache_and_cassette(tmp_path):
+    hits = []
+
+    async def handle(reader, writer):
+        await reader.readuntil(b"\r\n\r\n")
+        hits.append(1)
+        body = gzip.compress(b"hello")
+        writer.write(
+            b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: %d\r\n"
+            b"Cache-Control: max-age=60\r\nConnection: close\r\n\r\n%s"
+            % (len(body), body)
+        )
+        await writer.drain()
+        writer.close()
+
+    server = await serve(handle)
+    url = f"{url_of(server)}/"
+    encoded = {"accept-encoding": "gzip"}
+
+    async with server:
+        # An encoded body isn't stored, nor served to a request that decodes.
+        client = rnet.Client(cache=rnet.Cache())
+        response = await client.get(url, decompress=False, headers=encoded)
+        assert gzip.decompress(await response.bytes()) == b"hello"
+        response = await client.get(url)
+        assert await response.bytes() == b"hello"
+        response = await client.get(url, decompress=False, headers=encoded)
+        assert response.content_encoding == "gzip"
+        assert gzip.decompress(await response.bytes()) == b"hello"
+        assert len(hits) == 3
+
+        # A recorded encoded body keeps its Content-Encoding.
+        cassette = tmp_path / "cassette.json"
+        client = rnet.Client(record=cassette)
+        response = await client.get(url, decompress=False, headers=encoded)
+        await response.bytes()
+
+    response = await rnet.Client(replay=cassette).get(
+        url, decompress=False, headers=encoded
+    )
+    assert response.content_encoding == "gzip"
+    assert gzip.decompress(await response.bytes()) == b"hello"
+
+
+@pytest.mark.asyncio
+async def test_max_response_size():
+    bomb = gzip.compress(b"\0" * (10 * 1024 * 1024))
+
+    async def handle(reader, writer):
+        await reader.readuntil(b"\r\n\r\n")
+        writer.write(
+            b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: %d\r\n\r\n%s"
+            % (len(bomb), bomb)
+        )
+        await writer.drain()
+        writer.close()
+
+    server = await serve(handle)
+    url = f"{url_of(server)}/"
+
+    async with server:
+        # The compressed body is small; the limit applies to what it inflates to.
+        assert len(bomb) < 64 * 1024
+        limited = rnet.Client(max_response_size=1024 * 1024)
+        response = await limited.get(url)
+        with pytest.raises(rnet.ResponseTooLargeError):
+            await response.bytes()
+
+        response = await limited.get(url)
+        with pytest.raises(rnet.BodyError):
+            async for _ in response.stream():
+                pass
+
+        # A request can raise the client's limit.
+        response = await limited.get(url, max_response_size=16 * 1024 * 1024)
+        assert len(await response.bytes()) == 10 * 1024 * 1024
+
+    with pytest.raises(ValueError):
+        rnet.Client(max_header_size=1024)
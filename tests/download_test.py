@@ -0,0 +1,47 @@
+import pytest
+import rnet
+from conftest import url_of, start_range_server
+
+BODY = bytes(range(256)) * 4
+
+
+@pytest.mark.asyncio
+async def test_range_request():
+    requests = []
+    server = await start_range_server(requests, BODY)
+    url = f"{url_of(server)}/"
+
+    async with server:
+        response = await rnet.Client().get(url, range=(10, 19))
+        assert response.status == 206
+        assert response.content_range == (10, 19, len(BODY))
+        assert await response.bytes() == BODY[10:20]
+        assert "range: bytes=10-19" in requests[0]
+
+        response = await rnet.Client().get(url)
+        assert response.content_range is None
+
+    with pytest.raises(ValueError):
+        await rnet.Client().get(url, range=(5, 1))
+
+
+@pytest.mark.asyncio
+async def test_resume_download(tmp_path):
+    requests = []
+    server = await start_range_server(requests, BODY)
+    url = f"{url_of(server)}/"
+    path = tmp_path / "body.bin"
+    path.write_bytes(BODY[:100])
+
+    async with server:
+        client = rnet.Client()
+        assert await client.download(url, path) == len(BODY)
+        assert path.read_bytes() == BODY
+        assert "range: bytes=100-" in requests[0]
+
+        # Already complete: the server answers 416 and the file is left alone.
+        assert await client.download(url, path) == len(BODY)
+        assert path.read_bytes() == BODY
+
+        assert await client.download(url, path, resume=False) == len(BODY)
+        assert not any(h.startswith("range:") for h in requests[-1])
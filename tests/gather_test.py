@@ -0,0 +1,79 @@
+import pytest
+import rnet
+from rnet import Method
+
+
+def handler(method, url, headers, body):
+    if url.endswith("/fail"):
+        raise RuntimeError("boom")
+    return (200, {}, url)
+
+
+@pytest.mark.asyncio
+async def test_gather_keeps_order():
+    client = rnet.Client(transport=rnet.MockTransport(handler))
+    progress = []
+    requests = [(Method.GET, f"https://example.invalid/{i}") for i in range(20)]
+    requests.append((Method.POST, "https://example.invalid/last", {"body": b"x"}))
+
+    responses = await client.gather(
+        requests, concurrency=4, on_progress=lambda done, total: progress.append(done)
+    )
+    assert [await r.text() for r in responses] == [url for _, url, *_ in requests]
+    assert progress == list(range(1, 22))
+
+
+@pytest.mark.asyncio
+async def test_gather_exceptions():
+    client = rnet.Client(transport=rnet.MockTransport(handler))
+    requests = [
+        (Method.GET, "https://example.invalid/ok"),
+        (Method.GET, "https://example.invalid/fail"),
+    ]
+
+    with pytest.raises(RuntimeError):
+        await client.gather(requests)
+
+    ok, failed = await client.gather(requests, return_exceptions=True)
+    assert ok.status == 200
+    assert isinstance(failed, RuntimeError)
+
+    with pytest.raises(ValueError):
+        await client.gather(requests, concurrency=0)
+
+
+def test_blocking_map():
+    client = rnet.BlockingClient(transport=rnet.MockTransport(handler))
+    urls = [f"https://example.invalid/{i}" for i in range(10)]
+    responses = client.map([(Method.GET, url) for url in urls], concurrency=3)
+    assert [r.text() for r in responses] == urls
+
+
+@pytest.mark.asyncio
+async def test_get_many():
+    client = rnet.Client(transport=rnet.MockTransport(handler))
+    urls = [f"https://example.invalid/{i}" for i in range(10)]
+    urls.insert(3, "https://example.invalid/fail")
+
+    results = await client.get_many(urls, concurrency=2, timeout=10)
+    assert isinstance(results[3], RuntimeError)
+    del results[3], urls[3]
+    assert [await r.text() for r in results] == urls
+
+    ok, failed = await client.request_many(
+        [
+            (Method.GET, "https://example.invalid/ok"),
+            (Method.GET, "https://example.invalid/fail"),
+        ]
+    )
+    assert ok.status == 200
+    assert isinstance(failed, RuntimeError)
+
+
+def test_blocking_get_many():
+    client = rnet.BlockingClient(transport=rnet.MockTransport(handler))
+    results = client.get_many(
+        ["https://example.invalid/ok", "https://example.invalid/fail"]
+    )
+    assert results[0].text() == "https://example.invalid/ok"
+    assert isinstance(results[1], RuntimeError)
@@ -1,6 +1,17 @@
+import asyncio
 import pytest
+import ssl
 import rnet
+from pathlib import Path
 from rnet import Version, HeaderMap
+from conftest import (
+    header_names,
+    serve,
+    url_of,
+    start_header_echo_server,
+    start_h2_reset_server,
+    start_redirect_server,
+)
 
 client = rnet.Client(tls_info=True)
 
@@ -104,3 +115,564 @@ async def test_send_sync_bytes_stream():
     response = await client.post(url, body=file_to_bytes_stream("README.md"))
     json = await response.json()
     assert json["data"] in open("README.md").read()
+
+
+def test_version_from_str():
+    for version in [
+        Version.HTTP_09,
+        Version.HTTP_10,
+        Version.HTTP_11,
+        Version.HTTP_2,
+        Version.HTTP_3,
+    ]:
+        assert Version.from_str(str(version)) == version
+
+    assert str(Version.HTTP_11) == "HTTP/1.1"
+    assert Version.from_str("http/2.0") == Version.HTTP_2
+    with pytest.raises(ValueError):
+        Version.from_str("HTTP/4")
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_version_fallback():
+    # Cleartext HTTP/2 isn't negotiated by httpbin, so HTTP/2 over http:// fails.
+    url = "http://httpbin.org/anything"
+    client = rnet.Client()
+    with pytest.raises(Exception):
+        await client.get(url, version=Version.HTTP_2)
+
+    response = await client.get(url, version=Version.HTTP_2, version_fallback=True)
+    assert response.status == 200
+    assert response.version == Version.HTTP_11
+
+
+@pytest.mark.asyncio
+async def test_connection_error_fields():
+    client = rnet.Client()
+    with pytest.raises(rnet.ConnectionError) as exc_info:
+        await client.get("http://127.0.0.1:1/")
+
+    err = exc_info.value
+    assert err.url == "http://127.0.0.1:1/"
+    assert err.method == "GET"
+    assert err.is_connect
+    assert not err.is_timeout
+    assert err.host == "127.0.0.1"
+    assert err.port == 1
+    assert err.os_error is not None
+    assert err.phase is None
+    assert err.status is None
+
+
+@pytest.mark.asyncio
+async def test_http2_error_fields():
+    server = await start_h2_reset_server(0x2)
+    url = f"{url_of(server)}/"
+
+    async with server:
+        client = rnet.Client(http2_only=True)
+        with pytest.raises(rnet.Http2Error) as exc_info:
+            await client.get(url)
+
+    err = exc_info.value
+    assert isinstance(err, rnet.RequestError)
+    assert err.error_code == 2
+    assert err.reason == "INTERNAL_ERROR"
+    assert err.is_reset
+    assert err.is_remote
+    assert not err.is_goaway
+    assert not err.never_processed
+    assert err.url == url
+    assert err.method == "GET"
+
+
+def test_error_fields_default_to_none():
+    err = rnet.TimeoutError("timed out")
+    assert (err.url, err.method, err.is_timeout, err.phase) == (None, None, None, None)
+    assert rnet.ResponseTooLargeError("too large").status is None
+    assert rnet.Http2Error("reset").never_processed is None
+    assert rnet.CertificateError("untrusted").os_error is None
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_timeout_error_fields():
+    client = rnet.Client()
+    with pytest.raises(rnet.TimeoutError) as exc_info:
+        await client.post("https://httpbin.org/delay/5", timeout=1)
+
+    err = exc_info.value
+    assert err.is_timeout
+    assert err.method == "POST"
+    assert err.phase in ("connect", "read", "total")
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_request_impersonate():
+    url = "https://httpbin.org/headers"
+    client = rnet.Client(impersonate=rnet.Impersonate.Chrome133)
+
+    response = await client.get(url, impersonate=rnet.Impersonate.Firefox135)
+    json = await response.json()
+    assert "Firefox/135.0" in json["headers"]["User-Agent"]
+
+    # The override does not stick to the client.
+    response = await client.get(url)
+    json = await response.json()
+    assert "Chrome/133" in json["headers"]["User-Agent"]
+
+    response = await client.get(url, impersonate="random")
+    assert response.status == 200
+
+    with pytest.raises(ValueError):
+        await client.get(url, impersonate="chrome")
+
+
+@pytest.mark.asyncio
+async def test_request_headers_order():
+    server = await start_header_echo_server()
+    url = f"{url_of(server)}/"
+    headers = {"x-a": "1", "x-b": "2", "x-c": "3"}
+
+    async with server:
+        client = rnet.Client(headers_order=["x-a", "x-b", "x-c"])
+        response = await client.get(url, headers=headers)
+        assert header_names(await response.text()).startswith("x-a,x-b,x-c")
+
+        response = await client.get(
+            url, headers=headers, headers_order=["x-c", "x-a", "x-b"]
+        )
+        assert header_names(await response.text()).startswith("x-c,x-a,x-b")
+
+        # The override does not stick to the client.
+        response = await client.get(url, headers=headers)
+        assert header_names(await response.text()).startswith("x-a,x-b,x-c")
+
+        response = await client.get(
+            url,
+            headers=headers,
+            impersonate=rnet.Impersonate.Firefox135,
+            headers_order=["x-b", "x-c", "x-a"],
+        )
+        assert header_names(await response.text()).startswith("x-b,x-c,x-a")
+
+
+@pytest.mark.asyncio
+async def test_request_header_pairs():
+    server = await start_header_echo_server()
+    url = f"{url_of(server)}/"
+    headers = [("x-c", "1"), ("x-a", "2"), ("x-c", "3"), ("x-b", "4")]
+
+    async with server:
+        # The client's order places the names it lists, the pairs order the rest.
+        client = rnet.Client(headers_order=["x-b"])
+        response = await client.get(url, headers=headers)
+        assert header_names(await response.text()).startswith("x-b,x-c,x-c,x-a")
+
+        client = rnet.Client(headers_order=["x-a", "x-b", "x-c"])
+        response = await client.get(url, headers=headers)
+        assert header_names(await response.text()).startswith("x-a,x-b,x-c,x-c")
+
+        response = await client.get(
+            url, headers=headers, headers_order=["x-b", "x-a", "x-c"]
+        )
+        assert header_names(await response.text()).startswith("x-b,x-a,x-c,x-c")
+
+        # The client's order still applies to other requests.
+        response = await client.get(url, headers=dict(headers))
+        assert header_names(await response.text()).startswith("x-a,x-b,x-c")
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_send_file_object():
+    client = rnet.Client()
+    with open("README.md", "rb") as f:
+        response = await client.post("https://httpbin.org/anything", body=f)
+    json = await response.json()
+    assert json["data"] == open("README.md").read()
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_send_sized_body():
+    import io
+
+    size = Path("README.md").stat().st_size
+    client = rnet.Client()
+
+    response = await client.post("https://httpbin.org/anything", body=Path("README.md"))
+    json = await response.json()
+    assert json["headers"]["Content-Length"] == str(size)
+    assert json["data"] == open("README.md").read()
+
+    buffer = io.BytesIO(b"skipped:sent")
+    buffer.read(8)
+    response = await client.post("https://httpbin.org/anything", body=buffer)
+    json = await response.json()
+    assert json["headers"]["Content-Length"] == "4"
+    assert json["data"] == "sent"
+
+    with pytest.raises(FileNotFoundError):
+        await client.post("https://httpbin.org/anything", body=Path("missing.bin"))
+
+
+def test_path_and_type_errors():
+    # A path-like is accepted; loading a missing file fails later, not with a TypeError.
+    with pytest.raises(Exception) as exc_info:
+        rnet.Client(verify=Path("missing-ca.pem"))
+    assert not isinstance(exc_info.value, TypeError)
+
+    with pytest.raises(TypeError, match="verify: expected bool, str or os.PathLike"):
+        rnet.Client(verify=1.5)
+
+    with pytest.raises(TypeError, match="body: expected str, bytes"):
+        rnet.Client().get("https://httpbin.org/anything", body=1)
+
+
+def meta_refresh_site(method, url, headers, body):
+    pages = {
+        "/start": b'<html><head><META http-equiv="Refresh" content="0; URL=\'/next?a=1&amp;b=2\'">',
+        "/next?a=1&b=2": b"<meta http-equiv=refresh content=0;url=https://other.invalid/end>",
+        "/plain": b'<meta http-equiv="refresh" content="0; url=/next?a=1&b=2">',
+    }
+    path = url.split("example.invalid", 1)[-1]
+    if path == "/plain":
+        return (200, {"content-type": "text/plain"}, pages[path])
+    if path in pages:
+        return (200, {"content-type": "text/html; charset=utf-8"}, pages[path])
+    return (200, {"x-auth": headers.get("authorization") or b""}, b"done")
+
+
+@pytest.mark.asyncio
+async def test_follow_meta_refresh():
+    client = rnet.Client(
+        allow_redirects=True, transport=rnet.MockTransport(meta_refresh_site)
+    )
+    url = "https://example.invalid/start"
+
+    response = await client.get(url, follow_meta_refresh=True, bearer_auth="secret")
+    assert response.url == "https://other.invalid/end"
+    assert response.history == [url, "https://example.invalid/next?a=1&b=2"]
+    assert response.headers["x-auth"] == b""
+    assert await response.text() == "done"
+
+    # Off by default, and bounded by the redirect policy.
+    response = await client.get(url)
+    assert response.url == url and response.history == []
+    assert b"Refresh" in await response.bytes()
+    response = await client.get(url, follow_meta_refresh=True, allow_redirects=False)
+    assert response.url == url
+    with pytest.raises(rnet.RedirectError):
+        await client.get(
+            url, follow_meta_refresh=True, allow_redirects=True, max_redirects=2
+        )
+
+    # Non-HTML bodies aren't scanned.
+    response = await client.get(
+        "https://example.invalid/plain", follow_meta_refresh=True
+    )
+    assert response.url == "https://example.invalid/plain"
+    assert (await response.text()).startswith("<meta")
+
+
+def downgrade_site(method, url, headers, body):
+    if url == "https://example.invalid/start":
+        return (
+            200,
+            {"content-type": "text/html"},
+            b'<meta http-equiv="refresh" content="0; url=http://example.invalid/end">',
+        )
+    return (200, {}, b"done")
+
+
+@pytest.mark.asyncio
+async def test_https_downgrade():
+    client = rnet.Client(
+        allow_redirects=True, transport=rnet.MockTransport(downgrade_site)
+    )
+    url = "https://example.invalid/start"
+
+    with pytest.raises(rnet.RedirectError):
+        await client.get(url, follow_meta_refresh=True)
+    response = await client.get(
+        url, follow_meta_refresh=True, allow_insecure_redirects=True
+    )
+    assert response.url == "http://example.invalid/end"
+    with pytest.raises(rnet.RedirectError):
+        await client.get(
+            url,
+            follow_meta_refresh=True,
+            allow_insecure_redirects=True,
+            https_only=True,
+        )
+
+    with pytest.raises(rnet.BuilderError):
+        await client.get("http://example.invalid/end", https_only=True)
+    response = await client.get("http://example.invalid/end")
+    assert await response.text() == "done"
+
+
+@pytest.mark.asyncio
+async def test_https_downgrade_redirect(tmp_path):
+    trustme = pytest.importorskip("trustme")
+    ca = trustme.CA()
+    ca.cert_pem.write_to_path(str(tmp_path / "ca.pem"))
+    context = ssl.create_default_context(ssl.Purpose.CLIENT_AUTH)
+    ca.issue_cert("127.0.0.1").configure_cert(context)
+
+    plain = await start_redirect_server(None)
+    target = f"{url_of(plain)}/get"
+    secure = await start_redirect_server(target, ssl=context)
+    url = f"{url_of(secure, 'https')}/redirect"
+
+    async with plain, secure:
+        client = rnet.Client(allow_redirects=True, verify=tmp_path / "ca.pem")
+        with pytest.raises(rnet.RedirectError):
+            await client.get(url)
+        response = await client.get(url, allow_insecure_redirects=True)
+        assert response.url == target
+        assert await response.text() == "ok"
+
+
+def write_client_cert(ca, tmp_path):
+    """Issues a client certificate from `ca`, returning its (cert, PKCS #8 key) paths."""
+    from cryptography.hazmat.primitives import serialization
+
+    issued = ca.issue_cert("client.example")
+    cert, key = tmp_path / "client.pem", tmp_path / "client.key"
+    cert.write_bytes(b"".join(blob.bytes() for blob in issued.cert_chain_pems))
+    private_key = serialization.load_pem_private_key(
+        issued.private_key_pem.bytes(), password=None
+    )
+    key.write_bytes(
+        private_key.private_bytes(
+            serialization.Encoding.PEM,
+            serialization.PrivateFormat.PKCS8,
+            serialization.NoEncryption(),
+        )
+    )
+    return cert, key
+
+
+@pytest.mark.asyncio
+async def test_client_cert(tmp_path):
+    trustme = pytest.importorskip("trustme")
+    ca = trustme.CA()
+    ca.cert_pem.write_to_path(str(tmp_path / "ca.pem"))
+    context = ssl.create_default_context(ssl.Purpose.CLIENT_AUTH)
+    ca.issue_cert("127.0.0.1").configure_cert(context)
+    ca.configure_trust(context)
+    context.verify_mode = ssl.CERT_REQUIRED
+    cert = write_client_cert(ca, tmp_path)
+
+    server = await start_redirect_server(None, ssl=context)
+    url = f"{url_of(server, 'https')}/"
+
+    async with server:
+        client = rnet.Client(verify=tmp_path / "ca.pem", cert=cert)
+        response = await client.get(url)
+        assert await response.text() == "ok"
+
+        # The server refuses a client without one, during or right after the handshake.
+        refused = (
+            rnet.TlsError,
+            rnet.ConnectionError,
+            rnet.ConnectionResetError,
+            rnet.RequestError,
+            rnet.BodyError,
+        )
+        with pytest.raises(refused):
+            response = await rnet.Client(verify=tmp_path / "ca.pem").get(url)
+            await response.text()
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_client_cert_keeps_default_roots(tmp_path):
+    trustme = pytest.importorskip("trustme")
+    # A public server is still trusted with a client certificate and no `verify`.
+    client = rnet.Client(cert=write_client_cert(trustme.CA(), tmp_path))
+    response = await client.get("https://httpbin.org/get")
+    assert response.status == 200
+
+
+@pytest.mark.asyncio
+async def test_upload_progress():
+    received = []
+
+    def echo_size(method, url, headers, body):
+        received.append(len(body))
+        return (200, {}, b"")
+
+    client = rnet.Client(transport=rnet.MockTransport(echo_size))
+    url = "https://example.invalid/upload"
+
+    calls = []
+    await client.post(
+        url,
+        body=b"x" * 200_000,
+        on_upload_progress=lambda sent, total: calls.append((sent, total)),
+    )
+    assert calls == [
+        (65536, 200_000),
+        (131072, 200_000),
+        (196608, 200_000),
+        (200_000, 200_000),
+    ]
+
+    calls.clear()
+    await client.post(
+        url,
+        body=iter([b"a" * 10, b"b" * 10, b"c" * 10]),
+        on_upload_progress=lambda sent, total: calls.append((sent, total)),
+    )
+    assert calls == [(30, None)]
+    assert received == [200_000, 30]
+
+    def fail(sent, total):
+        raise RuntimeError("stop")
+
+    with pytest.raises(RuntimeError, match="stop"):
+        await client.post(url, body=b"data", on_upload_progress=fail)
+    with pytest.raises(TypeError, match="on_upload_progress must be callable"):
+        await client.post(url, body=b"data", on_upload_progress=1)
+
+
+@pytest.mark.asyncio
+async def test_mount():
+    server = await start_header_echo_server()
+    url = f"{url_of(server)}/"
+    calls = []
+
+    def users(method, url, headers, body):
+        calls.append((method, url, body))
+        return 201, [("x-mock", "users")], b'{"id": 1}'
+
+    async with server:
+        client = rnet.Client()
+        client.mount("https://api.example.invalid/users", users)
+        client.mount(
+            lambda method, url: method == "DELETE",
+            lambda *_: (403, {}, "forbidden"),
+        )
+
+        response = await client.post(
+            "https://api.example.invalid/users/new", body=b"alice"
+        )
+        assert response.status == 201
+        assert response.headers["x-mock"] == b"users"
+        assert await response.json() == {"id": 1}
+        assert calls == [("POST", "https://api.example.invalid/users/new", b"alice")]
+
+        response = await client.delete("https://api.example.invalid/users/1")
+        assert response.status == 201
+        response = await client.delete("https://api.example.invalid/posts/1")
+        assert response.status == 403
+        assert await response.text() == "forbidden"
+
+        # Requests no mount matches go over the network.
+        response = await client.get(url)
+        assert response.status == 200
+
+    transport = rnet.MockTransport(lambda *_: (200, {}, b"transport"))
+    client = rnet.BlockingClient(transport=transport)
+    client.mount("https://example.invalid/mounted", lambda *_: (200, {}, b"mount"))
+    assert client.get("https://example.invalid/mounted").text() == "mount"
+    assert client.get("https://example.invalid/other").text() == "transport"
+
+    with pytest.raises(TypeError):
+        client.mount(1, lambda *_: (200, {}, b""))
+    with pytest.raises(TypeError):
+        client.mount("https://example.invalid/", None)
+
+
+@pytest.mark.asyncio
+async def test_requests_aliases(tmp_path):
+    def echo(method, url, headers, body):
+        content_type = (headers.get("content-type") or b"").decode()
+        return 200, {"x-url": url, "x-type": content_type}, body
+
+    client = rnet.Client(transport=rnet.MockTransport(echo))
+    url = "https://example.invalid/"
+
+    response = await client.get(url, params={"a": "1", "b": "2"})
+    assert response.headers["x-url"] == b"https://example.invalid/?a=1&b=2"
+
+    response = await client.post(url, data={"x": "1", "y": "2"})
+    assert await response.text() == "x=1&y=2"
+    assert response.headers["x-type"] == b"application/x-www-form-urlencoded"
+    response = await client.post(url, data=b"raw", form=None)
+    assert await response.text() == "raw"
+    response = await client.post(url, data="text")
+    assert await response.text() == "text"
+
+    # Both spellings are accepted when they give the same value.
+    response = await client.get(url, query={"a": "1"}, params=[("a", "1")])
+    assert response.headers["x-url"] == b"https://example.invalid/?a=1"
+    response = await client.post(url, data=[("x", "1")], form={"x": "1"})
+    assert await response.text() == "x=1"
+    response = await client.post(url, data="raw", body=b"raw")
+    assert await response.text() == "raw"
+
+    with pytest.raises(TypeError, match="'query' and its alias 'params'"):
+        await client.get(url, query={"a": "1"}, params={"a": "2"})
+    with pytest.raises(TypeError, match="'form' and its alias 'data'"):
+        await client.post(url, form={"x": "1"}, data={"x": "2"})
+    with pytest.raises(TypeError, match="'body' and its alias 'data'"):
+        await client.post(url, body=b"a", data=b"b")
+
+    with pytest.raises(TypeError, match="cert"):
+        rnet.Client(cert=1)
+    with pytest.raises(RuntimeError):
+        rnet.Client(cert=tmp_path / "missing.pem")
+    (tmp_path / "empty.pem").write_text("")
+    with pytest.raises(ValueError, match="cert"):
+        rnet.Client(cert=(tmp_path / "empty.pem", tmp_path / "empty.pem"))
+
+
+@pytest.mark.asyncio
+async def test_conflicting_body_options():
+    transport = rnet.MockTransport(lambda *_: (200, {}, b""))
+    client = rnet.Client(transport=transport)
+    url = "https://example.invalid/"
+
+    with pytest.raises(rnet.BuilderError, match="json, body"):
+        await client.post(url, json={"a": 1}, body=b"x")
+    with pytest.raises(rnet.BuilderError, match="form, multipart"):
+        await client.post(
+            url, form=[("a", "1")], multipart=rnet.Multipart(rnet.Part("a", "1"))
+        )
+    with pytest.raises(rnet.BuilderError):
+        rnet.BlockingClient(transport=transport).post(url, json={}, form=[])
+
+    assert (await client.post(url, json={"a": 1})).status == 200
+
+
+@pytest.mark.asyncio
+async def test_fractional_and_split_timeouts():
+    async def handle(reader, writer):
+        await reader.readline()
+        await asyncio.sleep(2)
+        writer.write(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+        await writer.drain()
+        writer.close()
+
+    server = await serve(handle)
+    url = f"{url_of(server)}/"
+
+    async with server:
+        client = rnet.Client(timeout=(5, 10.5))
+        with pytest.raises(rnet.TimeoutError):
+            await client.get(url, timeout=0.3)
+        with pytest.raises(rnet.TimeoutError):
+            await client.get(url, timeout=(None, 0.3))
+
+        with pytest.raises(ValueError, match="connect"):
+            await client.get(url, timeout=(1, 1))
+        with pytest.raises(ValueError):
+            await client.get(url, timeout=-1)
+        with pytest.raises(ValueError):
+            rnet.Client(read_timeout=-0.5)
@@ -1,7 +1,15 @@
+import asyncio
+import base64
+import gc
+import hashlib
 import pytest
 import rnet
+import sys
+import threading
+import warnings
 from pathlib import Path
 from rnet import Version, Multipart, Part, WebSocket, Message
+from conftest import serve, url_of, start_dropping_websocket_server, start_hello_server
 
 client = rnet.Client(tls_info=True)
 
@@ -17,6 +25,58 @@ async def test_websocket():
     await ws.close()
 
 
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_websocket_send_all():
+    ws: WebSocket = await client.websocket("wss://echo.websocket.org")
+    await ws.recv()
+    sent = await ws.send_all(Message.from_text(str(i)) for i in range(3))
+    assert sent == 3
+    assert [(await ws.recv()).text for _ in range(3)] == ["0", "1", "2"]
+    await ws.close()
+
+    with pytest.raises(RuntimeError) as exc:
+        await ws.send_all([Message.from_text("late")])
+    assert exc.value.sent == 0
+
+
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+def test_blocking_websocket():
+    with rnet.BlockingClient().websocket("wss://echo.websocket.org") as ws:
+        ws.recv(timeout=10)
+        with pytest.raises(rnet.TimeoutError):
+            ws.recv(timeout=0.2)
+        with pytest.raises(ValueError):
+            ws.recv(timeout=-1)
+
+        ws.send(Message.from_text("Hello, World!"))
+        assert next(iter(ws)).data == b"Hello, World!"
+    assert list(ws) == []
+
+
+def test_message_type():
+    assert Message.from_text("hi").type == rnet.MessageType.Text
+    assert Message.from_binary(b"hi").type == rnet.MessageType.Binary
+    assert Message.from_ping(b"").type == rnet.MessageType.Ping
+    assert Message.from_pong(b"").type == rnet.MessageType.Pong
+    assert Message.from_close(1000).type == rnet.MessageType.Close
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_websocket_close_while_receiving():
+    ws: WebSocket = await client.websocket("wss://echo.websocket.org")
+    await ws.recv()
+
+    # Nothing is sent, so the reader blocks until the socket is closed.
+    reader = asyncio.create_task(ws.recv())
+    await asyncio.sleep(0.5)
+    await asyncio.wait_for(ws.close(), timeout=5)
+
+    with pytest.raises(RuntimeError):
+        await asyncio.wait_for(reader, timeout=5)
+
+
 @pytest.mark.asyncio
 @pytest.mark.flaky(reruns=3, reruns_delay=2)
 async def test_multiple_requests():
@@ -52,6 +112,101 @@ async def test_multiple_requests():
     assert "rnet" in text
 
 
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_multipart_related():
+    async def chunks():
+        yield b"hello "
+        yield b"world"
+
+    resp = await client.post(
+        "https://httpbin.org/anything",
+        multipart=Multipart(
+            Part(
+                "metadata",
+                '{"name": "a.txt"}',
+                mime="application/json",
+                headers={"Content-ID": "<meta>"},
+            ),
+            Part.from_stream("file", chunks(), length=11, mime="text/plain"),
+            content_type="multipart/related",
+            boundary="rnet-boundary",
+        ),
+    )
+    json = await resp.json()
+    assert json["headers"]["Content-Type"] == "multipart/related; boundary=rnet-boundary"
+    assert json["data"].startswith("--rnet-boundary\r\n")
+    assert json["data"].endswith("--rnet-boundary--\r\n")
+    assert "content-id: <meta>" in json["data"].lower()
+    assert "hello world" in json["data"]
+
+
+@pytest.mark.asyncio
+async def test_multipart_content_type_conflict():
+    client = rnet.Client()
+    with pytest.raises(rnet.BuilderError, match="Content-Type"):
+        await client.post(
+            "https://httpbin.org/anything",
+            headers={"Content-Type": "multipart/form-data; boundary=other"},
+            multipart=Multipart(Part("field", "value")),
+        )
+
+
+def test_multipart_invalid_boundary():
+    with pytest.raises(ValueError, match="boundary"):
+        Multipart(boundary="not;valid")
+    with pytest.raises(ValueError, match="boundary"):
+        Multipart(boundary="x" * 71)
+    with pytest.raises(ValueError, match="content type"):
+        Multipart(content_type="text/plain")
+
+
+def test_part_invalid_headers():
+    with pytest.raises(ValueError, match="content-disposition"):
+        Part("file", b"x", headers={"Content-Disposition": 'form-data; name="other"'})
+    with pytest.raises(ValueError, match="content-type"):
+        Part("file", b"x", mime="text/plain", headers={"Content-Type": "text/plain"})
+    with pytest.raises(RuntimeError, match="header name"):
+        Part("file", b"x", headers={"Bad Name": "x"})
+    with pytest.raises(RuntimeError, match="header value"):
+        Part("file", b"x", headers={"X-Test": "a\r\nInjected: b"})
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_part_content_type_header():
+    resp = await rnet.Client().post(
+        "https://httpbin.org/anything",
+        multipart=Multipart(
+            Part("meta", b"{}", headers={"Content-Type": "application/json"}),
+            content_type="multipart/related",
+        ),
+    )
+    data = (await resp.json())["data"].lower()
+    assert data.count("content-type: application/json") == 1
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_multipart_from_dict():
+    resp = await client.post(
+        "https://httpbin.org/anything",
+        multipart=Multipart.from_dict(
+            {
+                "name": "rnet",
+                "data": b"raw",
+                "file": Part("ignored", b"hello", filename="a.txt", mime="text/plain"),
+            }
+        ),
+    )
+    json = await resp.json()
+    assert json["form"] == {"name": "rnet", "data": "raw"}
+    assert json["files"] == {"file": "hello"}
+
+    with pytest.raises(TypeError, match="count"):
+        Multipart.from_dict({"count": 1})
+
+
 @pytest.mark.asyncio
 @pytest.mark.flaky(reruns=3, reruns_delay=2)
 async def test_get_cookies():
@@ -117,8 +272,836 @@ async def test_get_stream():
             assert bytes is not None
 
 
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_read_buffer():
+    url = "https://httpbin.org/bytes/4096?seed=1"
+    expected = await (await client.get(url)).bytes()
+
+    buffer = await (await client.get(url)).read_buffer()
+    view = memoryview(buffer)
+    assert view.readonly
+    assert len(buffer) == view.nbytes == 4096
+    assert view.tobytes() == bytes(buffer) == expected
+
+    response = await client.get("https://httpbin.org/stream-bytes/4096?seed=1")
+    chunks = [chunk async for chunk in response.stream(zero_copy=True)]
+    assert all(isinstance(chunk, rnet.BytesBuffer) for chunk in chunks)
+    assert b"".join(memoryview(chunk) for chunk in chunks) == expected
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_stream_read():
+    url = "https://httpbin.org/stream-bytes/4096?seed=1&chunk_size=1000"
+    expected = await (await client.get(url)).bytes()
+
+    streamer = (await client.get(url)).stream()
+    first = await streamer.__anext__()
+    assert await streamer.read(10) == expected[len(first) : len(first) + 10]
+    assert await streamer.read(1500) == expected[len(first) + 10 : len(first) + 1510]
+    assert await streamer.read_all() == expected[len(first) + 1510 :]
+    assert await streamer.read(10) == b""
+    assert await streamer.read_all() == b""
+
+    # Each fetch may split the body into chunks differently, so only the bytes are compared.
+    streamer = rnet.BlockingClient().get(url).stream()
+    head = streamer.read(5)
+    assert len(head) == 5
+    chunk = next(streamer)
+    assert head + chunk + streamer.read_all() == expected
+
+
+@pytest.mark.asyncio
+async def test_stream_readexactly():
+    streamer = rnet.Response.build(body=b"abcdef").stream()
+    assert await streamer.readexactly(4) == b"abcd"
+    with pytest.raises(rnet.BodyError):
+        await streamer.readexactly(4)
+    assert await streamer.read_all() == b"ef"
+    with pytest.raises(rnet.BodyError):
+        await streamer.readexactly(1)
+
+
+@pytest.mark.asyncio
+async def test_stream_framed():
+    frames = [b"", b"a", b"hello", b"x" * 300]
+    body = b"".join(len(frame).to_bytes(4, "big") + frame for frame in frames)
+    response = rnet.Response.build(body=b"head" + body)
+
+    streamer = response.stream()
+    assert await streamer.read(4) == b"head"
+    assert [frame async for frame in streamer.framed()] == frames
+
+    body = b"".join(len(frame).to_bytes(2, "little") + frame for frame in frames)
+    streamer = rnet.Response.build(body=body + b"\x05\x00abc").stream()
+    received = []
+    with pytest.raises(rnet.DecodingError):
+        async for frame in streamer.framed(prefix_len=2, big_endian=False):
+            received.append(frame)
+    assert received == frames
+
+    with pytest.raises(ValueError):
+        streamer.framed(prefix_len=9)
+
+
+@pytest.mark.asyncio
+async def test_events():
+    body = (
+        "\ufeff: a comment\r\n"
+        "retry: 3000\r\n"
+        "data: first\r\n"
+        "data:  second\r\n"
+        "\r\n"
+        "event: update\n"
+        "id: 7\n"
+        'data: {"n": 1}\n'
+        "\n"
+        "event: empty\r"
+        "\r"
+        "data\r"
+        "\r"
+        "data: unterminated"
+    ).encode()
+    response = rnet.Response.build(headers={"content-type": "text/event-stream"}, body=body)
+    events = [event async for event in response.events()]
+
+    assert [(e.event, e.data, e.id, e.retry) for e in events] == [
+        ("message", "first\n second", None, 3000),
+        ("update", '{"n": 1}', "7", None),
+        ("message", "", "7", None),
+    ]
+    assert events[1].json() == {"n": 1}
+    with pytest.raises(rnet.DecodingError):
+        events[0].json()
+
+
+@pytest.mark.asyncio
+async def test_parts():
+    body = (
+        b"preamble --sep is not a boundary\r\n"
+        b"--sep\r\n"
+        b"Content-Type: text/plain\r\n"
+        b"Content-Range: bytes 0-4/20\r\n"
+        b"\r\n"
+        b"hello\r\n"
+        b"--sep  \n"
+        b"Content-Range: bytes 10-14/20\n"
+        b"\n"
+        b"wor\nld\n"
+        b"--sep--\r\n"
+        b"epilogue\r\n"
+    )
+    headers = {"content-type": "multipart/byteranges; boundary=sep"}
+    response = rnet.Response.build(headers=headers, body=body)
+    parts = [part async for part in response.parts()]
+
+    assert [(h["content-range"], b) for h, b in parts] == [
+        (b"bytes 0-4/20", b"hello"),
+        (b"bytes 10-14/20", b"wor\nld"),
+    ]
+    assert parts[0][0]["content-type"] == b"text/plain"
+
+    blocking = rnet.BlockingResponse.build(headers=headers, body=body)
+    assert [b for _, b in blocking.parts()] == [b"hello", b"wor\nld"]
+
+    with pytest.raises(rnet.DecodingError):
+        rnet.Response.build(body=body).parts()
+
+    truncated = rnet.Response.build(headers=headers, body=body[:60])
+    with pytest.raises(rnet.DecodingError):
+        async for _ in truncated.parts():
+            pass
+
+
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+def test_blocking_stream_concurrent_consumers():
+    size = 64 * 1024
+    client = rnet.BlockingClient()
+    response = client.get(f"https://httpbin.org/stream-bytes/{size}?chunk_size=512")
+    streamer = response.stream()
+    received = []
+    lock = threading.Lock()
+
+    def consume():
+        while True:
+            try:
+                chunk = next(streamer)
+            except StopIteration:
+                return
+            except RuntimeError:
+                # Another thread currently owns the stream.
+                continue
+            with lock:
+                received.append(chunk)
+
+    threads = [threading.Thread(target=consume) for _ in range(8)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join(timeout=30)
+        assert not thread.is_alive()
+
+    assert sum(len(chunk) for chunk in received) == size
+
+
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+def test_blocking_stream_parallel_streams():
+    size = 16 * 1024
+    client = rnet.BlockingClient()
+    results = [0] * 4
+
+    def consume(index):
+        response = client.get(f"https://httpbin.org/stream-bytes/{size}")
+        with response.stream() as streamer:
+            for chunk in streamer:
+                results[index] += len(chunk)
+
+    threads = [threading.Thread(target=consume, args=(i,)) for i in range(len(results))]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join(timeout=30)
+        assert not thread.is_alive()
+
+    assert results == [size] * len(results)
+
+
 @pytest.mark.asyncio
 @pytest.mark.flaky(reruns=3, reruns_delay=2)
 async def test_peer_certificate():
     resp = await client.get("https://httpbin.org/anything")
     assert resp.peer_certificate() is not None
+
+
+@pytest.mark.asyncio
+@pytest.mark.flaky(reruns=3, reruns_delay=2)
+async def test_bytes_accounting():
+    body = b"x" * 1024
+    response = await client.post("https://httpbin.org/anything", body=body)
+    assert response.bytes_sent > len(body)
+    head = response.bytes_received
+    assert head > 0
+
+    content = await response.bytes()
+    assert response.bytes_received == head + len(content)
+
+
+@pytest.mark.asyncio
+async def test_websocket_auto_reconnect():
+    server, connections = await start_dropping_websocket_server()
+    url = f"{url_of(server, 'ws')}/"
+    attempts = []
+
+    async def on_reconnect(attempt):
+        attempts.append(attempt)
+
+    async with server:
+        ws = await rnet.Client().websocket(
+            url,
+            auto_reconnect=True,
+            reconnect_backoff=0.1,
+            on_reconnect=on_reconnect,
+        )
+        assert (await ws.recv()).data == b"connection 1"
+        assert (await ws.recv()).data == b"connection 2"
+        assert attempts == [1]
+        assert len(connections) == 2
+        await ws.close()
+
+
+@pytest.mark.asyncio
+async def test_websocket_reconnect_after_close_frame():
+    server, connections = await start_dropping_websocket_server(clean_close=True)
+    url = f"{url_of(server, 'ws')}/"
+
+    async with server:
+        ws = await rnet.Client().websocket(url, auto_reconnect=True, reconnect_backoff=0.1)
+        assert (await ws.recv()).data == b"connection 1"
+        assert (await ws.recv()).data == b"connection 2"
+        assert len(connections) == 2
+        await ws.close()
+
+
+def test_blocking_websocket_rejects_coroutine_on_reconnect():
+    async def on_reconnect(attempt):
+        pass
+
+    with pytest.raises(TypeError, match="coroutine"):
+        rnet.BlockingClient().websocket(
+            "ws://127.0.0.1:1/", auto_reconnect=True, on_reconnect=on_reconnect
+        )
+
+
+@pytest.mark.asyncio
+async def test_websocket_max_recv_queue():
+    async def handle(reader, writer):
+        key = b""
+        while (line := await reader.readline()) not in (b"\r\n", b""):
+            if line.lower().startswith(b"sec-websocket-key:"):
+                key = line.split(b":", 1)[1].strip()
+        accept = base64.b64encode(
+            hashlib.sha1(key + b"258EAFA5-E914-47DA-95CA-C5AB0DC85B11").digest()
+        )
+        writer.write(
+            b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n"
+            b"Connection: Upgrade\r\nSec-WebSocket-Accept: %s\r\n\r\n" % accept
+        )
+        for i in range(10):
+            payload = b"message %d" % i
+            writer.write(b"\x81" + bytes([len(payload)]) + payload)
+        await writer.drain()
+        await reader.read()
+
+    server = await serve(handle)
+    url = f"{url_of(server, 'ws')}/"
+
+    async with server:
+        ws = await rnet.Client().websocket(url, max_recv_queue=2)
+        await asyncio.sleep(0.1)
+        for i in range(10):
+            assert (await ws.recv()).data == b"message %d" % i
+        await ws.close()
+
+
+@pytest.mark.asyncio
+async def test_websocket_origin_and_protocols():
+    handshakes = []
+
+    async def handle(reader, writer):
+        head = {}
+        while (line := await reader.readline()) not in (b"\r\n", b""):
+            name, _, value = line.decode().partition(":")
+            head[name.strip().lower()] = value.strip()
+        handshakes.append(head)
+        accept = base64.b64encode(
+            hashlib.sha1(
+                head["sec-websocket-key"].encode() + b"258EAFA5-E914-47DA-95CA-C5AB0DC85B11"
+            ).digest()
+        )
+        protocol = head["sec-websocket-protocol"].split(",")[0].strip()
+        writer.write(
+            b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n"
+            b"Connection: Upgrade\r\nSec-WebSocket-Accept: %s\r\n"
+            b"Sec-WebSocket-Protocol: %s\r\n\r\n" % (accept, protocol.encode())
+        )
+        await writer.drain()
+        await reader.read()
+
+    server = await serve(handle)
+    url = f"{url_of(server, 'ws')}/"
+
+    async with server:
+        ws = await rnet.Client().websocket(
+            url,
+            protocols=(p for p in ["chat", "superchat"]),
+            origin="https://example.com",
+            user_agent="rnet-test",
+        )
+        assert ws.protocol == "chat"
+        assert ws.origin == "https://example.com"
+        assert ws.user_agent == "rnet-test"
+        assert handshakes[0]["origin"] == "https://example.com"
+        assert handshakes[0]["user-agent"] == "rnet-test"
+        await ws.close()
+
+        ws = await rnet.Client().websocket(url, protocols="chat")
+        assert ws.protocol == "chat"
+        assert ws.origin is None
+        await ws.close()
+
+
+@pytest.mark.asyncio
+async def test_websocket_client_defaults():
+    handshakes = []
+
+    async def handle(reader, writer):
+        head = {}
+        while (line := await reader.readline()) not in (b"\r\n", b""):
+            name, _, value = line.decode().partition(":")
+            head[name.strip().lower()] = value.strip()
+        handshakes.append(head)
+        accept = base64.b64encode(
+            hashlib.sha1(
+                head["sec-websocket-key"].encode() + b"258EAFA5-E914-47DA-95CA-C5AB0DC85B11"
+            ).digest()
+        )
+        protocol = head["sec-websocket-protocol"].split(",")[0].strip()
+        writer.write(
+            b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n"
+            b"Connection: Upgrade\r\nSec-WebSocket-Accept: %s\r\n"
+            b"Sec-WebSocket-Protocol: %s\r\n\r\n" % (accept, protocol.encode())
+        )
+        await writer.drain()
+        await reader.read()
+
+    server = await serve(handle)
+    url = f"{url_of(server, 'ws')}/"
+
+    async with server:
+        client = rnet.Client(
+            websocket_defaults={
+                "protocols": ["chat"],
+                "origin": "https://example.com",
+                "headers": {"x-a": "default", "x-b": "default"},
+            }
+        )
+        ws = await client.websocket(url)
+        assert ws.protocol == "chat"
+        assert ws.origin == "https://example.com"
+        assert handshakes[0]["x-a"] == "default"
+        await ws.close()
+
+        ws = await client.websocket(
+            url, protocols="superchat", headers={"x-b": "override"}
+        )
+        assert ws.protocol == "superchat"
+        assert ws.origin == "https://example.com"
+        assert handshakes[1]["x-a"] == "default"
+        assert handshakes[1]["x-b"] == "override"
+        await ws.close()
+
+    with pytest.raises(TypeError):
+        rnet.Client(websocket_defaults={"max_mesage_size": 1024})
+
+
+@pytest.mark.asyncio
+async def test_invalid_utf8_headers():
+    async def handle(reader, writer):
+        while (await reader.readline()) not in (b"\r\n", b""):
+            pass
+        writer.write(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=latin-1; x=\xff\r\n"
+            b"Set-Cookie: name=caf\xe9\r\nX-Raw: a\xffb\r\n"
+            b"Content-Length: 4\r\nConnection: close\r\n\r\ncaf\xe9"
+        )
+        await writer.drain()
+        writer.close()
+
+    server = await serve(handle)
+    url = f"{url_of(server)}/"
+
+    async with server:
+        response = await rnet.Client().get(url)
+        assert response.encoding == "latin-1"
+        # The body is decoded with the charset the header declares, as `encoding` reports.
+        assert await response.text() == "café"
+        response = await rnet.Client().get(url)
+        assert [(c.name, c.value) for c in response.cookies] == [("name", "café")]
+        assert response.headers["x-raw"] == b"a\xffb"
+        assert response.headers.get_str("x-raw") == "a�b"
+        assert response.headers.get_str("x-raw", errors="ignore") == "ab"
+        assert response.headers.get_str("missing") is None
+        with pytest.raises(UnicodeDecodeError):
+            response.headers.get_str("x-raw", errors="strict")
+
+
+@pytest.mark.asyncio
+async def test_build_response():
+    response = rnet.Response.build(
+        status=201,
+        headers={"content-type": "application/json", "set-cookie": "a=1; Path=/"},
+        body='{"ok": true}',
+        url="https://example.invalid/item",
+        version=Version.HTTP_2,
+    )
+    assert response.status == 201
+    assert response.url == "https://example.invalid/item"
+    assert response.version == Version.HTTP_2
+    assert response.content_length == 12
+    assert response.headers["content-type"] == b"application/json"
+    assert [(c.name, c.value) for c in response.cookies] == [("a", "1")]
+    assert await response.json() == {"ok": True}
+
+    response = rnet.Response.build(body=b"chunk")
+    assert response.status == 200 and response.url == "http://test/"
+    assert b"".join([chunk async for chunk in response.stream()]) == b"chunk"
+
+    with pytest.raises(ValueError):
+        rnet.Response.build(status=1000)
+
+    blocking = rnet.BlockingResponse.build(status=404, body="missing")
+    assert blocking.status == 404
+    assert blocking.text() == "missing"
+
+
+@pytest.mark.asyncio
+async def test_json_items():
+    document = b'[1, "a,]\\"}", {"b": [2, 3]}, null, [], 4.5]'
+
+    async def handle(reader, writer):
+        await reader.readuntil(b"\r\n\r\n")
+        writer.write(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n")
+        # Split the array at every few bytes so items span chunks.
+        for i in range(0, len(document), 3):
+            chunk = document[i : i + 3]
+            writer.write(b"%x\r\n%s\r\n" % (len(chunk), chunk))
+            await writer.drain()
+        writer.write(b"0\r\n\r\n")
+        await writer.drain()
+        writer.close()
+
+    server = await serve(handle)
+    port = server.sockets[0].getsockname()[1]
+    async with server:
+        response = await client.get(f"http://127.0.0.1:{port}/")
+        items = [item async for item in response.json_items()]
+        assert items == [1, 'a,]"}', {"b": [2, 3]}, None, [], 4.5]
+
+    response = rnet.Response.build(body='{"not": "an array"}')
+    with pytest.raises(rnet.DecodingError):
+        await response.json_items().__anext__()
+
+    response = rnet.Response.build(body="[1, 2")
+    items = response.json_items()
+    assert await items.__anext__() == 1
+    with pytest.raises(rnet.DecodingError):
+        await items.__anext__()
+
+
+@pytest.mark.asyncio
+async def test_text_errors():
+    def build(body, charset="utf-8"):
+        headers = {"content-type": f"text/html; charset={charset}"}
+        return rnet.Response.build(headers=headers, body=body)
+
+    body = b"caf\xc3\xa9 \xff!"
+    assert await build(body).text() == "café �!"
+    assert await build(body).text(errors="ignore") == "café !"
+    with pytest.raises(rnet.DecodingError):
+        await build(body).text(errors="strict")
+    with pytest.raises(ValueError):
+        build(body).text(errors="backslashreplace")
+
+    # A wrong declared charset is overridden by the fallback only when unrecognised.
+    latin = "café".encode("latin-1")
+    assert await build(latin, "bogus").text_with_charset("latin-1") == "café"
+    assert await build(latin).text_with_charset("latin-1", errors="ignore") == "caf"
+
+    blocking = rnet.BlockingResponse.build(body=b"ok \xff")
+    assert blocking.text(errors="ignore") == "ok "
+
+
+@pytest.mark.asyncio
+async def test_text_detect_encoding():
+    body = "Привет, как дела? Это страница на русском языке.".encode("windows-1251")
+    headers = {"content-type": "text/html; charset=utf-8"}
+
+    response = rnet.Response.build(headers=headers, body=body)
+    assert response.encoding == "utf-8"
+    assert await response.text(detect_encoding=True) == body.decode("windows-1251")
+    assert response.encoding == "windows-1251"
+
+    response = rnet.Response.build(headers=headers, body=body)
+    assert await response.text(encoding="detect") == body.decode("windows-1251")
+
+    response = rnet.Response.build(body="\ufeffhello".encode("utf-16-le"))
+    assert await response.text(detect_encoding=True) == "hello"
+    assert response.encoding == "utf-16le"
+
+    blocking = rnet.BlockingResponse.build(headers=headers, body=body)
+    assert blocking.text(detect_encoding=True) == body.decode("windows-1251")
+    assert blocking.encoding == "windows-1251"
+
+
+@pytest.mark.asyncio
+async def test_text_autodetect():
+    body = "Привет, как дела? Это страница на русском языке.".encode("windows-1251")
+
+    response = rnet.Response.build(headers={"content-type": "text/csv"}, body=body)
+    assert await response.detect_encoding() == ("windows-1251", True)
+    assert await response.text(autodetect=True) == body.decode("windows-1251")
+    response = rnet.Response.build(headers={"content-type": "text/csv"}, body=body)
+    assert await response.text(encoding="auto") == body.decode("windows-1251")
+
+    # A declared charset is trusted.
+    headers = {"content-type": "text/csv; charset=koi8-r"}
+    response = rnet.Response.build(headers=headers, body=body)
+    assert await response.text(autodetect=True) == body.decode("koi8-r")
+
+    blocking = rnet.BlockingResponse.build(body=b"\xef\xbb\xbfhello")
+    assert blocking.detect_encoding() == ("utf-8", True)
+    assert blocking.text(autodetect=True) == "hello"
+
+    with pytest.raises(ValueError, match="text_with_charset"):
+        await rnet.Response.build(body=body).text(encoding="koi8-r")
+
+
+def test_status_predicates():
+    moved = rnet.Response.build(status=308, headers={"location": "/new"})
+    assert moved.is_redirect and moved.is_permanent_redirect
+    assert moved.status_code.is_redirect() and moved.status_code.is_permanent_redirect()
+
+    # A redirect status is only a redirect when it says where to.
+    found = rnet.Response.build(status=302)
+    assert found.status_code.is_redirect()
+    assert not found.is_redirect and not found.is_permanent_redirect
+    assert not rnet.Response.build(status=304, headers={"location": "/"}).is_redirect
+
+    assert rnet.Response.build(status=103).is_informational
+    assert rnet.Response.build(status=404).is_client_error
+    assert rnet.BlockingResponse.build(status=503).is_server_error
+    assert not rnet.BlockingResponse.build(status=200).is_server_error
+
+
+def test_status_code():
+    status = rnet.Response.build(status=404).status_code
+    assert status.reason == "Not Found"
+    assert str(status) == "404 Not Found"
+    assert repr(status) == "StatusCode(404)"
+    assert int(status) == 404
+    assert status == 404 and status != 200 and 200 < status <= 404
+    assert status == rnet.Response.build(status=404).status_code
+    assert status in {404} and hash(status) == hash(404)
+    assert status != "404"
+
+    unknown = rnet.Response.build(status=599).status_code
+    assert unknown.reason is None
+    assert str(unknown) == "599"
+    with pytest.raises(TypeError):
+        unknown < "600"
+
+
+def test_status_code_equality():
+    for code in [*range(100, 600), 999]:
+        status = rnet.Response.build(status=code).status_code
+        other = rnet.Response.build(status=code).status_code
+        assert status == code and code == status and status == other
+        assert not status != code and not status != other
+        assert status != code + 1
+        assert (status == rnet.StatusCode.OK) == (code == 200)
+        assert hash(status) == hash(code) == hash(other)
+        assert int(status) == status.as_int() == code
+        assert status.reason_phrase == status.reason
+        assert status.is_informational() == (100 <= code < 200)
+        assert status.is_success() == (200 <= code < 300)
+        assert status.is_redirection() == (300 <= code < 400)
+        assert status.is_client_error() == (400 <= code < 500)
+        assert status.is_server_error() == (500 <= code < 600)
+
+    assert rnet.StatusCode.NOT_FOUND == 404
+    assert rnet.StatusCode.NOT_FOUND.reason_phrase == "Not Found"
+    assert rnet.StatusCode.IM_A_TEAPOT == 418
+    assert rnet.StatusCode.OK == rnet.Response.build().status_code
+    assert {rnet.StatusCode.OK: "ok"}[200] == "ok"
+    assert rnet.StatusCode.PERMANENT_REDIRECT.is_permanent_redirect()
+
+
+@pytest.mark.asyncio
+async def test_response_url():
+    transport = rnet.MockTransport(lambda *_: (200, {}, b""))
+    client = rnet.Client(transport=transport)
+    base = rnet.Url("https://example.invalid/a/b?x=1&y=2&x=3#top")
+
+    assert (base.scheme, base.host, base.port) == ("https", "example.invalid", None)
+    assert (base.path, base.query, base.fragment) == ("/a/b", "x=1&y=2&x=3", "top")
+    assert base.query_params() == {"x": "3", "y": "2"}
+    assert str(base.join("../c")) == "https://example.invalid/c"
+    assert str(base.with_query({"q": "a b", "n": 1})).endswith("/a/b?q=a+b&n=1#top")
+    assert base.with_query([]).query is None
+    assert base.with_fragment().fragment is None
+    assert rnet.Url("http://example.invalid:8080/").port == 8080
+
+    response = await client.get(base.with_fragment())
+    assert isinstance(response.url, rnet.Url)
+    assert response.url == "https://example.invalid/a/b?x=1&y=2&x=3"
+    assert response.url == base.with_fragment()
+    assert str(response.url) == "https://example.invalid/a/b?x=1&y=2&x=3"
+    assert hash(response.url) == hash(str(response.url))
+
+    with pytest.raises(rnet.URLParseError):
+        rnet.Url("/relative")
+
+
+@pytest.mark.asyncio
+async def test_unread_response_warns():
+    server = await start_hello_server()
+    url = f"{url_of(server)}/"
+    client = rnet.Client()
+
+    async with server:
+        with pytest.warns(ResourceWarning, match="127.0.0.1"):
+            response = await client.get(url)
+            del response
+            gc.collect()
+
+        # Credentials and the query are left out of the warning.
+        secret_url = url.replace("http://", "http://user:secret@") + "?token=secret"
+        with pytest.warns(ResourceWarning) as record:
+            response = await client.get(secret_url)
+            del response
+            gc.collect()
+        assert all("secret" not in str(warning.message) for warning in record)
+
+        with warnings.catch_warnings():
+            warnings.simplefilter("error", ResourceWarning)
+            response = await client.get(url)
+            assert await response.text() == "hello"
+            del response
+            response = await client.get(url)
+            await response.close()
+            del response
+            async with await client.get(url):
+                pass
+            gc.collect()
+
+
+@pytest.mark.asyncio
+@pytest.mark.skipif(sys.version_info < (3, 8), reason="needs sys.unraisablehook")
+async def test_strict_resources():
+    server = await start_hello_server()
+    url = f"{url_of(server)}/"
+    client = rnet.Client()
+
+    async with server:
+        unraisable = []
+        hook, sys.unraisablehook = sys.unraisablehook, unraisable.append
+        rnet.strict_resources(True)
+        try:
+            response = await client.get(url)
+            del response
+            gc.collect()
+        finally:
+            rnet.strict_resources(False)
+            sys.unraisablehook = hook
+        assert [type(u.exc_value) for u in unraisable] == [ResourceWarning]
+
+
+@pytest.mark.asyncio
+@pytest.mark.skipif(sys.version_info < (3, 8), reason="needs sys.unraisablehook")
+async def test_internal_responses_dont_warn():
+    server = await start_hello_server()
+    url = f"{url_of(server)}/"
+    client = rnet.Client()
+
+    async with server:
+        unraisable = []
+        hook, sys.unraisablehook = sys.unraisablehook, unraisable.append
+        rnet.strict_resources(True)
+        try:
+            # Responses rnet reads the headers of itself are never handed out to be closed.
+            assert await client.content_length(url) == 5
+            assert await client.head_ok(url)
+            gc.collect()
+        finally:
+            rnet.strict_resources(False)
+            sys.unraisablehook = hook
+        assert unraisable == []
+
+
+@pytest.mark.asyncio
+async def test_timing():
+    server = await start_hello_server()
+    url = f"{url_of(server)}/"
+    client = rnet.Client()
+
+    async with server:
+        response = await client.get(url)
+        timing = response.timing
+        assert set(timing) == {"dns", "connect", "tls", "time_to_first_byte", "total"}
+        # The host is an IP address, so it isn't resolved.
+        assert timing["dns"] is None and timing["tls"] is None
+        assert 0 < timing["connect"] <= timing["time_to_first_byte"]
+        assert timing["total"] is None
+
+        assert await response.text() == "hello"
+        assert response.timing["total"] >= timing["time_to_first_byte"]
+
+        response = await client.get(url)
+        assert response.connection_reused
+        assert response.timing["connect"] is None
+        await response.close()
+
+    transport = rnet.MockTransport(lambda *_: (200, {}, b""))
+    assert (await rnet.Client(transport=transport).get(url)).timing is None
+
+
+@pytest.mark.asyncio
+async def test_save(tmp_path):
+    def handler(method, url, headers, body):
+        if url.endswith("/json"):
+            return 200, {"Content-Type": "application/json"}, b'{"b": [1, 2], "a": null}'
+        return 200, {}, b"\x00binary"
+
+    client = rnet.Client(transport=rnet.MockTransport(handler))
+
+    response = await client.get("https://example.invalid/file")
+    assert await response.save(tmp_path / "file") == 7
+    assert (tmp_path / "file").read_bytes() == b"\x00binary"
+
+    response = await client.get("https://example.invalid/json")
+    size = await response.save_json(str(tmp_path / "data.json"))
+    text = (tmp_path / "data.json").read_text()
+    assert text == '{\n  "b": [\n    1,\n    2\n  ],\n  "a": null\n}\n'
+    assert size == len(text)
+
+    def save():
+        response = rnet.BlockingClient(transport=rnet.MockTransport(handler)).get(
+            "https://example.invalid/file"
+        )
+        return response.save(tmp_path / "blocking")
+
+    loop = asyncio.get_event_loop()
+    assert await loop.run_in_executor(None, save) == 7
+    assert (tmp_path / "blocking").read_bytes() == b"\x00binary"
+
+
+@pytest.mark.asyncio
+async def test_save_failure_keeps_file(tmp_path):
+    async def handle(reader, writer):
+        while (await reader.readline()) not in (b"\r\n", b""):
+            pass
+        # The connection closes well short of the promised length.
+        writer.write(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\npartial")
+        await writer.drain()
+        writer.close()
+
+    server = await serve(handle)
+    url = f"{url_of(server)}/"
+    target = tmp_path / "file"
+    target.write_bytes(b"old")
+
+    async with server:
+        response = await rnet.Client().get(url)
+        with pytest.raises(rnet.BodyError):
+            await response.save(target)
+    assert target.read_bytes() == b"old"
+    assert [p.name for p in tmp_path.iterdir()] == ["file"]
+
+
+@pytest.mark.asyncio
+async def test_websocket_counters():
+    async def handle(reader, writer):
+        key = b""
+        while (line := await reader.readline()) not in (b"\r\n", b""):
+            if line.lower().startswith(b"sec-websocket-key:"):
+                key = line.split(b":", 1)[1].strip()
+        accept = base64.b64encode(
+            hashlib.sha1(key + b"258EAFA5-E914-47DA-95CA-C5AB0DC85B11").digest()
+        )
+        writer.write(
+            b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n"
+            b"Connection: Upgrade\r\nSec-WebSocket-Accept: %s\r\n\r\n" % accept
+        )
+        for payload in (b"one", b"three"):
+            writer.write(b"\x81" + bytes([len(payload)]) + payload)
+        await writer.drain()
+        while await reader.read(1024):
+            pass
+
+    server = await serve(handle)
+    url = f"{url_of(server, 'ws')}/"
+
+    async with server:
+        ws = await rnet.Client().websocket(url, max_message_size=1024)
+        assert (ws.max_message_size, ws.max_frame_size) == (1024, 16 << 20)
+        assert (ws.messages_sent, ws.messages_received, ws.pending_bytes) == (0, 0, 0)
+
+        await ws.send(Message.from_text("hello"))
+        await ws.send_all([Message.from_binary(b"ab"), Message.from_text("c")])
+        assert (ws.messages_sent, ws.bytes_sent, ws.pending_bytes) == (3, 8, 0)
+
+        await ws.recv()
+        await ws.recv()
+        assert (ws.messages_received, ws.bytes_received) == (2, 8)
+        await ws.close()
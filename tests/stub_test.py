@@ -0,0 +1,135 @@
+import os
+import subprocess
+import sys
+import textwrap
+
+import pytest
+
+pytest.importorskip("mypy")
+
+ROOT = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+
+SAMPLE = """
+import rnet
+from rnet import Impersonate, Method, Multipart, Part, Proxy, Version
+
+
+async def run() -> None:
+    client = rnet.Client(
+        impersonate=Impersonate.Firefox136,
+        user_agent="rnet",
+        default_headers={"x-client": "rnet"},
+        headers_order=["user-agent", "accept"],
+        referer=False,
+        allow_redirects=True,
+        max_redirects=5,
+        cookie_store=True,
+        timeout=10,
+        connect_timeout=5,
+        read_timeout=5,
+        pool_idle_timeout=30,
+        pool_max_idle_per_host=4,
+        pool_max_size=16,
+        http1_only=False,
+        https_only=False,
+        verify=True,
+        trust_env=False,
+        proxies=[Proxy.all("http://127.0.0.1:8080", username="u", password="p")],
+        gzip=True,
+        brotli=True,
+        deflate=True,
+        zstd=True,
+    )
+    client.update(
+        impersonate=Impersonate.Chrome133,
+        headers={"x-client": "rnet"},
+        allow_redirects=False,
+        timeout=(1, 2),
+        gzip=False,
+    )
+    response = await client.get(
+        "https://example.com",
+        headers={"accept": "*/*"},
+        cookies={"session": "1"},
+        allow_redirects=False,
+        max_redirects=1,
+        timeout=5,
+        read_timeout=5,
+        version=Version.HTTP_11,
+        query=[("q", "rnet")],
+        auth="token",
+        bearer_auth="token",
+        basic_auth=("user", None),
+    )
+    print(response.status)
+    await client.post("https://example.com", json={"a": 1})
+    await client.post("https://example.com", form=[("a", "1")])
+    await client.post(
+        "https://example.com",
+        multipart=Multipart(Part(name="field", value="value")),
+    )
+    await client.request(Method.PUT, "https://example.com", body=b"data")
+    await rnet.get("https://example.com", timeout=5)
+    ws = await client.websocket(
+        "wss://example.com",
+        protocols=["chat"],
+        read_buffer_size=1024,
+        max_message_size=1 << 20,
+    )
+    await ws.close()
+
+    blocking = rnet.BlockingClient(impersonate=Impersonate.Safari18, timeout=10)
+    blocking.update(cookie_store=False)
+    blocking.get("https://example.com", query=[("q", "rnet")]).close()
+"""
+
+
+def run_mypy(tmp_path, source):
+    path = tmp_path / "sample.py"
+    path.write_text(textwrap.dedent(source))
+    env = dict(os.environ, MYPYPATH=ROOT)
+    result = subprocess.run(
+        [
+            sys.executable,
+            "-m",
+            "mypy",
+            "--python-version",
+            "3.13",
+            "--no-incremental",
+            "--follow-imports",
+            "silent",
+            str(path),
+        ],
+        cwd=str(tmp_path),
+        env=env,
+        stdout=subprocess.PIPE,
+        stderr=subprocess.STDOUT,
+        universal_newlines=True,
+    )
+    # Only the diagnostics for the sample matter, not those for the stubs themselves.
+    return [
+        line
+        for line in result.stdout.splitlines()
+        if line.startswith("sample.py:") and ": error:" in line
+    ]
+
+
+def test_stub_kwargs_typecheck(tmp_path):
+    assert run_mypy(tmp_path, SAMPLE) == []
+
+
+def test_stub_kwargs_reject_unknown(tmp_path):
+    errors = run_mypy(
+        tmp_path,
+        """
+        import rnet
+
+        rnet.Client(allow_redirect=True)
+        rnet.Client().update(timeout="soon")
+        rnet.BlockingClient().get("https://example.com", header={})
+        """,
+    )
+    assert len(errors) == 3
+    assert '"allow_redirect"' in errors[0]
+    assert '"timeout"' in errors[1]
+    assert '"header"' in errors[2]
@@ -0,0 +1,103 @@
+import pytest
+import rnet
+import uuid
+
+
+def echo(method, url, headers, body):
+    return (
+        201,
+        {"content-type": "application/json", "x-method": method},
+        b'{"url": "%s", "body": "%s", "agent": "%s"}'
+        % (url.encode(), body, headers["user-agent"]),
+    )
+
+
+@pytest.mark.asyncio
+async def test_mock_transport():
+    client = rnet.Client(user_agent="rnet", transport=rnet.MockTransport(echo))
+    response = await client.post("https://example.invalid/path", body=b"hi")
+    assert response.status == 201
+    assert response.headers["x-method"] == b"POST"
+    assert not response.from_cache
+    assert await response.json() == {
+        "url": "https://example.invalid/path",
+        "body": "hi",
+        "agent": "rnet",
+    }
+
+    response = await client.get("https://example.invalid/", body=b"chunk")
+    chunks = [chunk async for chunk in response.stream()]
+    assert b"".join(chunks).endswith(b'"agent": "rnet"}')
+
+
+def test_mock_transport_blocking():
+    transport = rnet.MockTransport(lambda *_: (404, {}, "missing"))
+    response = rnet.BlockingClient(transport=transport).get("https://example.invalid/")
+    assert response.status == 404
+    assert response.text() == "missing"
+
+
+@pytest.mark.asyncio
+async def test_mock_transport_errors():
+    def fail(*_):
+        raise KeyError("boom")
+
+    with pytest.raises(TypeError):
+        rnet.MockTransport("not callable")
+
+    client = rnet.Client(transport=rnet.MockTransport(fail))
+    with pytest.raises(KeyError):
+        await client.get("https://example.invalid/")
+
+
+@pytest.mark.asyncio
+async def test_request_id_header():
+    sent = []
+
+    def handler(method, url, headers, body):
+        sent.append(headers["x-request-id"].decode())
+        if url.endswith("/echo"):
+            return (200, {"x-request-id": "server-id"}, b"")
+        return (200, {}, b"")
+
+    client = rnet.Client(
+        request_id_header="x-request-id", transport=rnet.MockTransport(handler)
+    )
+    response = await client.get("https://example.invalid/")
+    assert response.request_id == response.client_request_id == sent[-1]
+    assert uuid.UUID(sent[-1]).version == 4
+
+    response = await client.get("https://example.invalid/echo")
+    assert response.request_id == "server-id"
+    assert response.client_request_id == sent[-1] != sent[-2]
+
+    response = await client.get(
+        "https://example.invalid/", headers={"x-request-id": "mine"}
+    )
+    assert response.request_id == "mine"
+
+    response = await rnet.Client(transport=rnet.MockTransport(echo)).get(
+        "https://example.invalid/"
+    )
+    assert response.request_id is None
+
+
+@pytest.mark.asyncio
+async def test_custom_method():
+    client = rnet.Client(user_agent="rnet", transport=rnet.MockTransport(echo))
+    response = await client.request(rnet.Method.PROPFIND, "https://example.invalid/dav/")
+    assert response.headers["x-method"] == b"PROPFIND"
+
+    response = await client.request("SEARCH", "https://example.invalid/dav/")
+    assert response.headers["x-method"] == b"SEARCH"
+
+    # Strings are sent verbatim, case included.
+    response = await client.request("get", "https://example.invalid/")
+    assert response.headers["x-method"] == b"get"
+
+    assert rnet.Method.from_str("mkcol") == rnet.Method.MKCOL
+    assert str(rnet.Method.REPORT) == "REPORT"
+    with pytest.raises(ValueError):
+        rnet.Method.from_str("SEARCH")
+    with pytest.raises(ValueError):
+        await client.request("BAD VERB", "https://example.invalid/")
@@ -0,0 +1,27 @@
+import pytest
+import rnet
+from conftest import start_connect_proxy, start_echo_server
+
+
+@pytest.mark.asyncio
+async def test_connect_tunnel():
+    echo = await start_echo_server()
+    proxy = await start_connect_proxy()
+    echo_port = echo.sockets[0].getsockname()[1]
+    proxy_port = proxy.sockets[0].getsockname()[1]
+
+    client = rnet.Client()
+    async with await client.connect_tunnel(
+        f"http://127.0.0.1:{proxy_port}", "127.0.0.1", echo_port
+    ) as tunnel:
+        await tunnel.write(b"hello tunnel")
+        received = b""
+        while len(received) < len(b"hello tunnel"):
+            received += await tunnel.read()
+        assert received == b"hello tunnel"
+
+    with pytest.raises(RuntimeError):
+        await tunnel.read()
+
+    proxy.close()
+    echo.close()